@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use perflite_wasm::parse_stack;
+use perflite_wasm::{parse, parse_chrome_fast};
 
 fn stack_parsing_benchmark(c: &mut Criterion) {
     let sample_stack = r#"Error: Something went wrong
@@ -9,7 +9,7 @@ fn stack_parsing_benchmark(c: &mut Criterion) {
     at async HTMLFormElement.submitForm (app.js:20:30)"#;
 
     c.bench_function("parse_stack", |b| {
-        b.iter(|| parse_stack(black_box(sample_stack)))
+        b.iter(|| parse(black_box(sample_stack)))
     });
 
     // 测试复杂调用栈
@@ -26,7 +26,12 @@ fn stack_parsing_benchmark(c: &mut Criterion) {
     at Compilation.nextStepInChainModule (/node_modules/webpack/lib/Compilation.js:1037:10)"#;
 
     c.bench_function("parse_complex_stack", |b| {
-        b.iter(|| parse_stack(black_box(complex_stack)))
+        b.iter(|| parse(black_box(complex_stack)))
+    });
+
+    // Chrome专用快速路径与完整流程对比
+    c.bench_function("parse_complex_stack_chrome_fast", |b| {
+        b.iter(|| parse_chrome_fast(black_box(complex_stack)))
     });
 
     // SIMD加速版本测试（如果可用）
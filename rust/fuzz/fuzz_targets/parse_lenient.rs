@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use perflite_wasm::parse_lenient;
+
+// 任意字节输入都不能panic，输出必须是合法的JSON数组
+fuzz_target!(|data: &[u8]| {
+    let json = parse_lenient(data);
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse_lenient输出的JSON非法");
+    assert!(value.is_array());
+});
@@ -0,0 +1,637 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::mem::size_of;
+use crate::naming;
+use crate::utils::now_ms;
+
+// 未flush时最多保留的已关闭时间桶数量
+const MAX_CLOSED_BUCKETS: usize = 48;
+// 被淘汰分组在当前时间桶中的计数合并到这个键下
+const EVICTED_KEY: &str = "<evicted>";
+
+/// 聚合数据的保留策略，反序列化时同样校验
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "RetentionFields")]
+pub struct RetentionPolicy {
+    /// 时间桶长度（毫秒），默认一小时
+    bucket_ms: f64,
+    /// 分组多久未出现即过期（毫秒），默认30分钟
+    group_ttl_ms: f64,
+}
+
+#[derive(Deserialize)]
+struct RetentionFields {
+    bucket_ms: f64,
+    group_ttl_ms: f64,
+}
+
+impl TryFrom<RetentionFields> for RetentionPolicy {
+    type Error = String;
+
+    fn try_from(fields: RetentionFields) -> Result<Self, String> {
+        RetentionPolicy::new(fields.bucket_ms, fields.group_ttl_ms)
+    }
+}
+
+impl RetentionPolicy {
+    /// 桶长度与过期时间必须是正的有限值
+    pub fn new(bucket_ms: f64, group_ttl_ms: f64) -> Result<Self, String> {
+        if !(bucket_ms.is_finite() && bucket_ms > 0.0) {
+            return Err(format!("时间桶长度必须为正数: {}", bucket_ms));
+        }
+        if !(group_ttl_ms.is_finite() && group_ttl_ms > 0.0) {
+            return Err(format!("分组过期时间必须为正数: {}", group_ttl_ms));
+        }
+        Ok(RetentionPolicy { bucket_ms, group_ttl_ms })
+    }
+
+    pub fn bucket_ms(&self) -> f64 {
+        self.bucket_ms
+    }
+
+    pub fn group_ttl_ms(&self) -> f64 {
+        self.group_ttl_ms
+    }
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            bucket_ms: 60.0 * 60.0 * 1000.0,
+            group_ttl_ms: 30.0 * 60.0 * 1000.0,
+        }
+    }
+}
+
+/// 一个已关闭的时间桶
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AggregateBucket {
+    pub start: f64,
+    pub end: f64,
+    pub counts: BTreeMap<String, u64>,
+}
+
+/// 过期的分组
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExpiredGroup {
+    pub key: String,
+    pub first_seen: f64,
+    pub last_seen: f64,
+    pub total: u64,
+}
+
+/// `flush_expired` 上报给后端的窗口数据
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AggregatePayload {
+    pub buckets: Vec<AggregateBucket>,
+    pub expired_groups: Vec<ExpiredGroup>,
+    /// 超出保留上限被丢弃的时间桶数量
+    pub dropped_buckets: u32,
+    /// 超出内存上限被淘汰的分组数量
+    #[serde(default)]
+    pub evicted_groups: u32,
+}
+
+/// 淘汰或丢弃的聚合数据，逐条通知淘汰回调
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AggregateEviction {
+    /// 超出内存上限被淘汰的分组，其当前时间桶中的计数并入 `<evicted>`
+    Group { key: String, total: u64 },
+    /// 超出保留数量或内存上限被丢弃的已关闭时间桶
+    Bucket { start: f64, end: f64, events: u64 },
+    /// 淘汰全部分组后仍超出内存上限，当前时间桶中被丢弃的计数
+    Counts { events: u64 },
+}
+
+/// 聚合数据的内存占用与淘汰统计
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct AggregateStats {
+    pub groups: usize,
+    pub closed_buckets: usize,
+    /// 估算占用的字节数
+    pub approx_bytes: usize,
+    /// 内存上限，未设置时为null
+    pub max_bytes: Option<usize>,
+    /// 累计淘汰的分组数量
+    pub evicted_groups: u64,
+    /// 累计丢弃的时间桶数量
+    pub dropped_buckets: u64,
+    /// 累计从当前时间桶中丢弃的事件数
+    pub dropped_events: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct GroupStats {
+    first_seen: f64,
+    last_seen: f64,
+    total: u64,
+    // 最近一次记录的序号，即在 `lru` 中的键
+    #[serde(skip)]
+    touched: u64,
+}
+
+/// 按时间桶滚动的分组计数，配合TTL淘汰长期未出现的分组
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Aggregates {
+    policy: RetentionPolicy,
+    bucket_start: Option<f64>,
+    current: BTreeMap<String, u64>,
+    closed: Vec<AggregateBucket>,
+    dropped_buckets: u32,
+    evicted_groups: u32,
+    groups: BTreeMap<String, GroupStats>,
+    // 按最近记录顺序排列的分组键，淘汰时取最前面的，不必扫描全部分组
+    #[serde(skip)]
+    lru: BTreeMap<u64, String>,
+    #[serde(skip)]
+    clock: u64,
+    // 持久化时不保存，恢复后重新统计
+    #[serde(skip)]
+    bytes: usize,
+    max_bytes: Option<usize>,
+    total_evicted_groups: u64,
+    total_dropped_buckets: u64,
+    #[serde(default)]
+    total_dropped_events: u64,
+    // 尚未通知回调的淘汰
+    #[serde(skip)]
+    evictions: Vec<AggregateEviction>,
+}
+
+/// 键的估算占用：String头部加内容
+fn key_bytes(key: &str) -> usize {
+    size_of::<String>() + key.len()
+}
+
+fn count_bytes(key: &str) -> usize {
+    key_bytes(key) + size_of::<u64>()
+}
+
+/// 分组的估算占用：分组表与 `lru` 索引各保存一份键
+fn group_bytes(key: &str) -> usize {
+    key_bytes(key) * 2 + size_of::<GroupStats>() + size_of::<u64>()
+}
+
+fn bucket_bytes(counts: &BTreeMap<String, u64>) -> usize {
+    size_of::<AggregateBucket>() + counts.keys().map(|key| count_bytes(key)).sum::<usize>()
+}
+
+impl Aggregates {
+    pub fn new(policy: RetentionPolicy) -> Self {
+        Aggregates {
+            policy,
+            bucket_start: None,
+            current: BTreeMap::new(),
+            closed: Vec::new(),
+            dropped_buckets: 0,
+            evicted_groups: 0,
+            groups: BTreeMap::new(),
+            lru: BTreeMap::new(),
+            clock: 0,
+            bytes: 0,
+            max_bytes: None,
+            total_evicted_groups: 0,
+            total_dropped_buckets: 0,
+            total_dropped_events: 0,
+            evictions: Vec::new(),
+        }
+    }
+
+    /// 设置内存上限（None表示不限制）并立即执行一次淘汰
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.max_bytes = max_bytes;
+        self.enforce_limit(None);
+    }
+
+    pub fn set_policy(&mut self, policy: RetentionPolicy) {
+        self.policy = policy;
+    }
+
+    /// 时间桶起点按桶长度对齐
+    fn align(&self, now: f64) -> f64 {
+        (now / self.policy.bucket_ms).floor() * self.policy.bucket_ms
+    }
+
+    /// 当前时间越过桶边界时关闭当前桶
+    fn roll(&mut self, now: f64) {
+        let start = match self.bucket_start {
+            Some(start) => start,
+            None => {
+                self.bucket_start = Some(self.align(now));
+                return;
+            }
+        };
+
+        let end = start + self.policy.bucket_ms;
+        if now < end {
+            return;
+        }
+
+        if !self.current.is_empty() {
+            self.closed.push(AggregateBucket {
+                start,
+                end,
+                counts: std::mem::take(&mut self.current),
+            });
+            self.bytes += size_of::<AggregateBucket>();
+            if self.closed.len() > MAX_CLOSED_BUCKETS {
+                self.drop_oldest_bucket();
+            }
+        }
+        self.bucket_start = Some(self.align(now));
+    }
+
+    fn drop_oldest_bucket(&mut self) {
+        let bucket = self.closed.remove(0);
+        self.bytes -= bucket_bytes(&bucket.counts);
+        self.dropped_buckets += 1;
+        self.total_dropped_buckets += 1;
+        self.evictions.push(AggregateEviction::Bucket {
+            start: bucket.start,
+            end: bucket.end,
+            events: bucket.counts.values().sum(),
+        });
+    }
+
+    /// 当前时间桶中某个键的计数加 `count`
+    fn add_count(&mut self, key: &str, count: u64) {
+        match self.current.get_mut(key) {
+            Some(current) => *current += count,
+            None => {
+                self.bytes += count_bytes(key);
+                self.current.insert(key.to_string(), count);
+            }
+        }
+    }
+
+    /// 从分组表与 `lru` 索引中移除分组
+    fn remove_group(&mut self, key: &str) -> Option<GroupStats> {
+        let stats = self.groups.remove(key)?;
+        self.lru.remove(&stats.touched);
+        self.bytes -= group_bytes(key);
+        Some(stats)
+    }
+
+    /// 把当前时间桶中某个键的计数并入 `<evicted>`
+    fn fold_count(&mut self, key: &str) {
+        if let Some(count) = self.current.remove(key) {
+            self.bytes -= count_bytes(key);
+            self.add_count(EVICTED_KEY, count);
+        }
+    }
+
+    /// 淘汰分组，它在当前时间桶中的计数并入 `<evicted>`
+    fn evict_group(&mut self, key: &str) {
+        let total = self.remove_group(key).map_or(0, |stats| stats.total);
+        self.fold_count(key);
+        self.evicted_groups += 1;
+        self.total_evicted_groups += 1;
+        self.evictions.push(AggregateEviction::Group { key: key.to_string(), total });
+    }
+
+    /// 超出内存上限时依次回收：丢弃最旧的已关闭时间桶，淘汰最久未出现的分组，
+    /// 把已过期分组留在当前时间桶中的计数并入 `<evicted>`，最后丢弃 `<evicted>` 本身。
+    /// `keep` 为刚记录的分组，它的统计与计数总是保留，其余数据都计入上限
+    fn enforce_limit(&mut self, keep: Option<&str>) {
+        let Some(limit) = self.max_bytes else {
+            return;
+        };
+        while self.bytes > limit && !self.closed.is_empty() {
+            self.drop_oldest_bucket();
+        }
+        while self.bytes > limit {
+            // 刚记录的分组总在最后，最多跳过一个
+            let oldest = self.lru.values().find(|key| Some(key.as_str()) != keep).cloned();
+            match oldest {
+                Some(key) => self.evict_group(&key),
+                None => break,
+            }
+        }
+        if self.bytes > limit {
+            let orphans: Vec<String> = self.current.keys()
+                .filter(|key| key.as_str() != EVICTED_KEY && Some(key.as_str()) != keep)
+                .cloned()
+                .collect();
+            for key in orphans {
+                self.fold_count(&key);
+            }
+        }
+        if self.bytes > limit {
+            if let Some(events) = self.current.remove(EVICTED_KEY) {
+                self.bytes -= count_bytes(EVICTED_KEY);
+                self.total_dropped_events += events;
+                self.evictions.push(AggregateEviction::Counts { events });
+            }
+        }
+    }
+
+    /// 在指定时间记录一次分组出现
+    pub fn record_at(&mut self, key: &str, now: f64) {
+        self.roll(now);
+        self.add_count(key, 1);
+
+        if !self.groups.contains_key(key) {
+            self.bytes += group_bytes(key);
+        }
+        let stats = self.groups.entry(key.to_string()).or_insert(GroupStats {
+            first_seen: now,
+            last_seen: now,
+            total: 0,
+            touched: 0,
+        });
+        stats.last_seen = now;
+        stats.total += 1;
+        self.lru.remove(&stats.touched);
+        self.clock += 1;
+        stats.touched = self.clock;
+        self.lru.insert(self.clock, key.to_string());
+        self.enforce_limit(Some(key));
+    }
+
+    /// 取出已关闭的时间桶与过期分组，并从内存中移除
+    pub fn flush_expired_at(&mut self, now: f64) -> AggregatePayload {
+        self.roll(now);
+
+        let ttl = self.policy.group_ttl_ms;
+        let expired: Vec<String> = self.groups.iter()
+            .filter(|(_, stats)| stats.last_seen + ttl <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let expired_groups = expired.into_iter()
+            .filter_map(|key| {
+                let stats = self.remove_group(&key)?;
+                Some(ExpiredGroup {
+                    key,
+                    first_seen: stats.first_seen,
+                    last_seen: stats.last_seen,
+                    total: stats.total,
+                })
+            })
+            .collect();
+
+        let buckets = std::mem::take(&mut self.closed);
+        self.bytes -= buckets.iter().map(|bucket| bucket_bytes(&bucket.counts)).sum::<usize>();
+        AggregatePayload {
+            buckets,
+            expired_groups,
+            dropped_buckets: std::mem::take(&mut self.dropped_buckets),
+            evicted_groups: std::mem::take(&mut self.evicted_groups),
+        }
+    }
+
+    /// 仍在保留期内的分组数量
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// 估算占用的内存字节数
+    pub fn approx_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn stats(&self) -> AggregateStats {
+        AggregateStats {
+            groups: self.groups.len(),
+            closed_buckets: self.closed.len(),
+            approx_bytes: self.bytes,
+            max_bytes: self.max_bytes,
+            evicted_groups: self.total_evicted_groups,
+            dropped_buckets: self.total_dropped_buckets,
+            dropped_events: self.total_dropped_events,
+        }
+    }
+
+    /// 取出尚未通知的淘汰
+    pub fn take_evictions(&mut self) -> Vec<AggregateEviction> {
+        std::mem::take(&mut self.evictions)
+    }
+
+    /// 按最后出现时间重建 `lru` 索引，用于恢复持久化的数据
+    fn reindex(&mut self) {
+        let mut order: Vec<(&String, &mut GroupStats)> = self.groups.iter_mut().collect();
+        order.sort_by(|a, b| a.1.last_seen.total_cmp(&b.1.last_seen));
+        self.lru.clear();
+        self.clock = 0;
+        for (key, stats) in order {
+            self.clock += 1;
+            stats.touched = self.clock;
+            self.lru.insert(self.clock, key.clone());
+        }
+    }
+
+    /// 重新统计估算的内存占用
+    fn recount_bytes(&mut self) {
+        self.bytes = self.current.keys().map(|key| count_bytes(key)).sum::<usize>()
+            + self.closed.iter().map(|bucket| bucket_bytes(&bucket.counts)).sum::<usize>()
+            + self.groups.keys().map(|key| group_bytes(key)).sum::<usize>();
+    }
+
+    /// 重建内部表并释放多余容量
+    pub fn compact(&mut self) {
+        self.groups = std::mem::take(&mut self.groups).into_iter().collect();
+        self.lru = std::mem::take(&mut self.lru).into_iter().collect();
+        self.current = std::mem::take(&mut self.current).into_iter().collect();
+        self.closed.shrink_to_fit();
+    }
+}
+
+impl Default for Aggregates {
+    fn default() -> Self {
+        Aggregates::new(RetentionPolicy::default())
+    }
+}
+
+thread_local! {
+    static AGGREGATES: RefCell<Aggregates> = RefCell::new(Aggregates::default());
+    // 注入的淘汰回调，参数为单条淘汰的JSON
+    static EVICTION_CALLBACK: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
+}
+
+/// 注入淘汰回调，传None取消
+pub fn set_eviction_callback(callback: Option<js_sys::Function>) {
+    EVICTION_CALLBACK.with(|current| *current.borrow_mut() = callback);
+}
+
+/// 修改全局聚合数据，释放借用后再通知淘汰回调，回调中可以再次调用本模块
+fn update<T>(f: impl FnOnce(&mut Aggregates) -> T) -> T {
+    let (result, evictions) = AGGREGATES.with(|aggregates| {
+        let mut aggregates = aggregates.borrow_mut();
+        let result = f(&mut aggregates);
+        (result, aggregates.take_evictions())
+    });
+    if !evictions.is_empty() {
+        let callback = EVICTION_CALLBACK.with(|callback| callback.borrow().clone());
+        if let Some(callback) = callback {
+            for eviction in &evictions {
+                if let Ok(json) = naming::to_json(eviction, naming::field_naming()) {
+                    // 回调抛出的异常不影响记录
+                    let _ = callback.call1(&wasm_bindgen::JsValue::NULL, &wasm_bindgen::JsValue::from_str(&json));
+                }
+            }
+        }
+    }
+    result
+}
+
+/// 设置全局保留策略
+pub fn set_policy(policy: RetentionPolicy) {
+    AGGREGATES.with(|aggregates| aggregates.borrow_mut().set_policy(policy));
+}
+
+/// 以当前时间记录一次分组出现
+pub fn record(key: &str) {
+    update(|aggregates| aggregates.record_at(key, now_ms()));
+}
+
+/// 以当前时间flush过期数据
+pub fn flush_expired() -> AggregatePayload {
+    update(|aggregates| aggregates.flush_expired_at(now_ms()))
+}
+
+/// 设置全局聚合数据的内存上限
+pub fn set_max_bytes(max_bytes: Option<usize>) {
+    update(|aggregates| aggregates.set_max_bytes(max_bytes));
+}
+
+pub fn stats() -> AggregateStats {
+    AGGREGATES.with(|aggregates| aggregates.borrow().stats())
+}
+
+pub fn compact() {
+    AGGREGATES.with(|aggregates| aggregates.borrow_mut().compact());
+}
+
+/// 当前聚合数据的副本，用于持久化
+pub fn snapshot() -> Aggregates {
+    AGGREGATES.with(|aggregates| aggregates.borrow().clone())
+}
+
+/// 用持久化的数据替换当前聚合数据，按其中的内存上限重新淘汰
+pub fn restore(mut state: Aggregates) {
+    state.reindex();
+    state.recount_bytes();
+    update(|aggregates| {
+        *aggregates = state;
+        aggregates.enforce_limit(None);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetentionPolicy {
+        RetentionPolicy { bucket_ms: 1000.0, group_ttl_ms: 5000.0 }
+    }
+
+    #[test]
+    fn test_hourly_rollover() {
+        let mut aggregates = Aggregates::new(policy());
+        aggregates.record_at("a", 100.0);
+        aggregates.record_at("a", 900.0);
+        aggregates.record_at("b", 1500.0);
+
+        let payload = aggregates.flush_expired_at(2100.0);
+        assert_eq!(payload.buckets.len(), 2);
+        assert_eq!(payload.buckets[0].start, 0.0);
+        assert_eq!(payload.buckets[0].counts.get("a"), Some(&2));
+        assert_eq!(payload.buckets[1].counts.get("b"), Some(&1));
+
+        // 已flush的桶不会重复上报
+        assert!(aggregates.flush_expired_at(2200.0).buckets.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_invalid_policy() {
+        assert!(RetentionPolicy::new(0.0, 5000.0).is_err());
+        assert!(RetentionPolicy::new(1000.0, -1.0).is_err());
+        assert!(RetentionPolicy::new(f64::NAN, 5000.0).is_err());
+        assert_eq!(RetentionPolicy::new(1000.0, 5000.0), Ok(policy()));
+        // 持久化的策略同样校验
+        assert!(serde_json::from_str::<RetentionPolicy>(r#"{"bucket_ms": 0, "group_ttl_ms": 5000}"#).is_err());
+    }
+
+    #[test]
+    fn test_group_ttl() {
+        let mut aggregates = Aggregates::new(policy());
+        aggregates.record_at("stale", 0.0);
+        aggregates.record_at("fresh", 4000.0);
+
+        let payload = aggregates.flush_expired_at(5000.0);
+        assert_eq!(payload.expired_groups.len(), 1);
+        assert_eq!(payload.expired_groups[0].key, "stale");
+        assert_eq!(aggregates.group_count(), 1);
+    }
+
+    #[test]
+    fn test_bounded_buckets() {
+        let mut aggregates = Aggregates::new(policy());
+        for i in 0..(MAX_CLOSED_BUCKETS + 5) {
+            aggregates.record_at("a", i as f64 * 1000.0);
+        }
+        let payload = aggregates.flush_expired_at(1.0e9);
+        assert_eq!(payload.buckets.len(), MAX_CLOSED_BUCKETS);
+        assert_eq!(payload.dropped_buckets, 5);
+    }
+
+    #[test]
+    fn test_memory_ceiling() {
+        let mut aggregates = Aggregates::new(policy());
+        for i in 0..10 {
+            aggregates.record_at(&format!("group-{}", i), i as f64 * 100.0);
+        }
+        let unlimited = aggregates.approx_bytes();
+        aggregates.set_max_bytes(Some(unlimited / 2));
+        assert!(aggregates.approx_bytes() <= unlimited / 2);
+
+        // 最久未出现的分组先被淘汰，计数保留在 `<evicted>` 下
+        let stats = aggregates.stats();
+        assert!(stats.evicted_groups > 0 && stats.groups < 10);
+        let evictions = aggregates.take_evictions();
+        assert_eq!(evictions.len() as u64, stats.evicted_groups);
+        assert_eq!(evictions[0], AggregateEviction::Group { key: "group-0".to_string(), total: 1 });
+        aggregates.record_at("group-9", 950.0);
+        let payload = aggregates.flush_expired_at(1000.0);
+        assert_eq!(payload.evicted_groups as u64, stats.evicted_groups);
+        let counts = &payload.buckets[0].counts;
+        assert_eq!(counts.values().sum::<u64>(), 11);
+        assert!(counts.get("group-0").is_none() && counts.get(EVICTED_KEY).is_some());
+        assert_eq!(counts.get("group-9"), Some(&2));
+
+        // flush之后只剩分组的占用
+        aggregates.compact();
+        let remaining: usize = aggregates.groups.keys().map(|key| group_bytes(key)).sum();
+        assert_eq!(aggregates.approx_bytes(), remaining);
+    }
+
+    #[test]
+    fn test_hard_limit_covers_current_counts() {
+        let mut aggregates = Aggregates::new(policy());
+        for i in 0..10 {
+            aggregates.record_at(&format!("group-{}", i), i as f64);
+        }
+        // 分组按TTL过期后，当前时间桶中仍保留它们的计数
+        aggregates.policy.group_ttl_ms = 1.0;
+        aggregates.flush_expired_at(100.0);
+        assert_eq!((aggregates.group_count(), aggregates.current.len()), (0, 10));
+
+        // 上限只够刚记录的分组：其余计数先并入 `<evicted>`，仍超出时整体丢弃
+        let minimum = group_bytes("fresh") + count_bytes("fresh");
+        aggregates.max_bytes = Some(minimum);
+        aggregates.record_at("fresh", 200.0);
+        assert_eq!(aggregates.approx_bytes(), minimum);
+        assert_eq!(aggregates.stats().dropped_events, 10);
+        assert_eq!(aggregates.take_evictions(), vec![AggregateEviction::Counts { events: 10 }]);
+        assert_eq!(aggregates.current.get("fresh"), Some(&1));
+
+        // 记录新分组时淘汰的是最久未记录的分组
+        aggregates.set_max_bytes(None);
+        aggregates.record_at("other", 300.0);
+        aggregates.record_at("fresh", 400.0);
+        aggregates.set_max_bytes(Some(aggregates.approx_bytes() - 1));
+        assert_eq!(aggregates.take_evictions()[0], AggregateEviction::Group { key: "other".to_string(), total: 1 });
+    }
+}
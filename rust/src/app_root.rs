@@ -0,0 +1,51 @@
+use std::cell::RefCell;
+
+thread_local! {
+    // 应用根路径前缀，如 `https://cdn.example.com/assets/`、`/var/www/app/`
+    static APP_ROOTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 替换全局应用根路径前缀，空字符串会被忽略
+pub fn set_roots(roots: Vec<String>) {
+    let roots = roots.into_iter().filter(|root| !root.is_empty()).collect();
+    APP_ROOTS.with(|current| *current.borrow_mut() = roots);
+}
+
+/// 文件是否在根路径下：前缀必须止于路径分隔处，`/var/www/app` 不匹配 `/var/www/apple/x.js`
+fn is_under(file: &str, root: &str) -> bool {
+    file.strip_prefix(root).is_some_and(|rest| root.ends_with('/') || rest.is_empty() || rest.starts_with('/'))
+}
+
+/// 在根路径列表中去掉文件的根路径前缀（最长的匹配优先）与随后的 `/`，
+/// 得到不随部署主机与CDN变化的项目相对路径；不在任何根路径下或去掉后为空时原样返回
+pub fn relativize_with<'a>(file: &'a str, roots: &[String]) -> &'a str {
+    let relative = roots.iter()
+        .filter(|root| is_under(file, root))
+        .max_by_key(|root| root.len())
+        .map_or(file, |root| file[root.len()..].trim_start_matches('/'));
+    if relative.is_empty() { file } else { relative }
+}
+
+/// 按全局根路径前缀取相对路径
+pub fn relativize(file: &str) -> &str {
+    APP_ROOTS.with(|roots| relativize_with(file, &roots.borrow()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relativize() {
+        let roots = vec!["https://cdn.example.com/assets/".to_string(), "/var/www/app".to_string(), "/var/www/app/static/".to_string()];
+        assert_eq!(relativize_with("https://cdn.example.com/assets/js/app.js?v=2", &roots), "js/app.js?v=2");
+        assert_eq!(relativize_with("/var/www/app/src/cart.js", &roots), "src/cart.js");
+        assert_eq!(relativize_with("/var/www/app/static/main.js", &roots), "main.js");
+        assert_eq!(relativize_with("https://other.test/app.js", &roots), "https://other.test/app.js");
+        assert_eq!(relativize_with("/var/www/app/", &roots), "/var/www/app/");
+        assert_eq!(relativize_with("/src/a.js", &[]), "/src/a.js");
+        // 根路径只按完整的路径段匹配
+        assert_eq!(relativize_with("/var/www/apple/x.js", &roots), "/var/www/apple/x.js");
+        assert_eq!(relativize_with("https://cdn.example.com/assets2/x.js", &roots), "https://cdn.example.com/assets2/x.js");
+    }
+}
@@ -0,0 +1,190 @@
+use serde::{Serialize, Deserialize};
+use crate::simd::LineIndex;
+use crate::sourcemap::{decode_vlq_segment, find_pragma, RawSourceMap};
+use crate::symbol_server::normalize_debug_id;
+
+// 最多抽样检查的生成代码行数
+const MAX_SAMPLED_LINES: usize = 64;
+
+/// 校验发现的问题类型
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactIssueKind {
+    InvalidMap,
+    UnsupportedVersion,
+    FileMismatch,
+    TooManyMappingLines,
+    InvalidMappings,
+    ColumnOutOfRange,
+    SourceOutOfRange,
+    NameOutOfRange,
+    DebugIdMismatch,
+    DebugIdMissing,
+}
+
+/// 单条校验问题
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ArtifactIssue {
+    pub kind: ArtifactIssueKind,
+    pub message: String,
+}
+
+/// 打包产物与source map的一致性校验报告
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ArtifactReport {
+    pub ok: bool,
+    pub bundle_lines: usize,
+    pub mapping_lines: usize,
+    pub checked_segments: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug_id: Option<String>,
+    pub issues: Vec<ArtifactIssue>,
+}
+
+impl ArtifactReport {
+    fn issue(&mut self, kind: ArtifactIssueKind, message: String) {
+        self.issues.push(ArtifactIssue { kind, message });
+    }
+}
+
+fn base_name(path: &str) -> &str {
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// 校验打包产物与source map是否匹配：file字段、mappings行数、抽样的生成位置以及调试ID
+pub fn verify(bundle: &str, map_json: &str) -> ArtifactReport {
+    let mut report = ArtifactReport::default();
+    let index = LineIndex::new(bundle);
+    report.bundle_lines = index.len();
+
+    let map: RawSourceMap = match serde_json::from_str(map_json) {
+        Ok(map) => map,
+        Err(e) => {
+            report.issue(ArtifactIssueKind::InvalidMap, format!("source map解析失败: {}", e));
+            return report;
+        }
+    };
+
+    if map.version != 3 {
+        report.issue(ArtifactIssueKind::UnsupportedVersion, format!("不支持的source map版本: {}", map.version));
+    }
+
+    // file字段应与产物引用的map文件名对应
+    if let (Some(file), Some(map_url)) = (map.file.as_deref(), find_pragma(bundle, "sourceMappingURL")) {
+        let expected = base_name(map_url).trim_end_matches(".map");
+        if !map_url.starts_with("data:") && !file.is_empty() && base_name(file) != expected {
+            report.issue(ArtifactIssueKind::FileMismatch, format!("map的file为 {}，产物引用的是 {}", file, map_url));
+        }
+    }
+
+    let lines: Vec<&str> = if map.mappings.is_empty() { Vec::new() } else { map.mappings.split(';').collect() };
+    report.mapping_lines = lines.len();
+    if lines.len() > report.bundle_lines {
+        report.issue(
+            ArtifactIssueKind::TooManyMappingLines,
+            format!("mappings有 {} 行，产物只有 {} 行", lines.len(), report.bundle_lines),
+        );
+    }
+
+    check_segments(&mut report, &map, &lines, &index);
+
+    let bundle_id = find_pragma(bundle, "debugId").map(|id| normalize_debug_id(id).unwrap_or_else(|| id.to_string()));
+    let map_id = map.debug_id.as_deref().or(map.legacy_debug_id.as_deref())
+        .map(|id| normalize_debug_id(id).unwrap_or_else(|| id.to_string()));
+    match (bundle_id, map_id) {
+        (Some(bundle_id), Some(map_id)) if bundle_id != map_id => {
+            report.issue(ArtifactIssueKind::DebugIdMismatch, format!("产物调试ID {} 与map调试ID {} 不一致", bundle_id, map_id));
+        }
+        (Some(id), Some(_)) => report.debug_id = Some(id),
+        (Some(_), None) | (None, Some(_)) => {
+            report.issue(ArtifactIssueKind::DebugIdMissing, "只有一方声明了调试ID".to_string());
+        }
+        (None, None) => {}
+    }
+
+    report.ok = report.issues.is_empty();
+    report
+}
+
+/// 完整解码mappings以维护相对偏移，只对抽样行检查生成位置
+fn check_segments(report: &mut ArtifactReport, map: &RawSourceMap, lines: &[&str], index: &LineIndex) {
+    let stride = (lines.len() / MAX_SAMPLED_LINES).max(1);
+    let (mut source, mut name) = (0i64, 0i64);
+
+    for (line_no, line) in lines.iter().enumerate() {
+        let sampled = line_no % stride == 0;
+        // 列号按UTF-16计算，与JS引擎一致
+        let line_len = index.line(line_no).map(|l| l.encode_utf16().count() as i64);
+        let mut column = 0i64;
+
+        for segment in line.split(',').filter(|s| !s.is_empty()) {
+            let values = match decode_vlq_segment(segment) {
+                Some(values) if matches!(values.len(), 1 | 4 | 5) => values,
+                _ => {
+                    report.issue(ArtifactIssueKind::InvalidMappings, format!("第 {} 行存在非法segment: {}", line_no + 1, segment));
+                    return;
+                }
+            };
+
+            column += values[0];
+            if values.len() >= 4 {
+                source += values[1];
+            }
+            if values.len() == 5 {
+                name += values[4];
+            }
+
+            if !sampled {
+                continue;
+            }
+            report.checked_segments += 1;
+
+            if !line_len.is_some_and(|len| (0..=len).contains(&column)) {
+                report.issue(ArtifactIssueKind::ColumnOutOfRange, format!("生成位置 {}:{} 超出产物范围", line_no + 1, column));
+            }
+            if values.len() >= 4 && (source < 0 || source as usize >= map.sources.len()) {
+                report.issue(ArtifactIssueKind::SourceOutOfRange, format!("生成位置 {}:{} 引用了不存在的source {}", line_no + 1, column, source));
+            }
+            if values.len() == 5 && (name < 0 || name as usize >= map.names.len()) {
+                report.issue(ArtifactIssueKind::NameOutOfRange, format!("生成位置 {}:{} 引用了不存在的name {}", line_no + 1, column, name));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUNDLE: &str = "function a(){throw new Error('x')}\na();\n//# sourceMappingURL=main.js.map\n//# debugId=85314830-023f-4cf1-a267-535f4e37bb17";
+
+    fn map(file: &str, mappings: &str, debug_id: &str) -> String {
+        format!(
+            r#"{{"version":3,"file":"{}","sources":["src/a.js"],"names":["a"],"mappings":"{}","debugId":"{}"}}"#,
+            file, mappings, debug_id
+        )
+    }
+
+    #[test]
+    fn test_verify_consistent_artifact() {
+        let report = verify(BUNDLE, &map("main.js", "AAAA,SAASA;AACA", "85314830023F4CF1A267535F4E37BB17"));
+        assert!(report.ok, "{:?}", report.issues);
+        assert_eq!(report.mapping_lines, 2);
+        assert_eq!(report.checked_segments, 3);
+        assert_eq!(report.debug_id.as_deref(), Some("85314830-023f-4cf1-a267-535f4e37bb17"));
+    }
+
+    #[test]
+    fn test_verify_reports_mismatches() {
+        let report = verify(BUNDLE, &map("other.js", "AAAA,+/BAAA;;;;;", "00000000-0000-0000-0000-000000000000"));
+        let kinds: Vec<ArtifactIssueKind> = report.issues.iter().map(|issue| issue.kind).collect();
+        assert!(!report.ok);
+        assert!(kinds.contains(&ArtifactIssueKind::FileMismatch));
+        assert!(kinds.contains(&ArtifactIssueKind::TooManyMappingLines));
+        assert!(kinds.contains(&ArtifactIssueKind::ColumnOutOfRange));
+        assert!(kinds.contains(&ArtifactIssueKind::DebugIdMismatch));
+
+        assert_eq!(verify(BUNDLE, "not json").issues[0].kind, ArtifactIssueKind::InvalidMap);
+    }
+}
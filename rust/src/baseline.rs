@@ -0,0 +1,252 @@
+use wasm_bindgen::prelude::*;
+use std::collections::BTreeSet;
+use crate::fingerprint;
+use crate::grouping::{Grouper, GroupingOptions, GroupingRule};
+use crate::hash::{self, xxhash64, HashAlgorithm};
+use crate::parser_options;
+
+// 持久化格式：魔数 + 版本号 + 8字节xxhash64校验和 + 负载，均为小端。
+// 版本2的负载：算法(u8) + 是否忽略列号(u8) + 行号分桶(u32) + 指纹数(u32) + 指纹 + 签名(u64)；
+// 版本1没有算法与容差，按读取时的全局设置处理
+const BASELINE_MAGIC: &[u8; 3] = b"PLB";
+const BASELINE_VERSION: u8 = 2;
+const BASELINE_HEADER_LEN: usize = BASELINE_MAGIC.len() + 1 + 8;
+const BASELINE_SETTINGS_LEN: usize = 1 + 1 + 4;
+
+/// 错误栈相对基线的状态
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BaselineStatus {
+    /// 指纹已在基线中
+    Known,
+    /// 指纹与签名都不在基线中
+    New,
+    /// 错误类型与栈顶业务代码帧已知，但完整指纹变化（调用路径或行号改变）
+    Mutated,
+}
+
+impl BaselineStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BaselineStatus::Known => "known",
+            BaselineStatus::New => "new",
+            BaselineStatus::Mutated => "mutated",
+        }
+    }
+}
+
+/// 已知崩溃的基线：记录指纹与粗粒度签名，用于标记全新的崩溃优先上报。
+/// 创建时记下全局指纹算法与行列号容差，之后始终按这些设置计算，修改全局设置不会让已知指纹都变成新的
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Baseline {
+    algorithm: HashAlgorithm,
+    ignore_columns: bool,
+    line_bucket: u32,
+    fingerprints: BTreeSet<u64>,
+    signatures: BTreeSet<u64>,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        let options = parser_options::options();
+        Baseline {
+            algorithm: hash::fingerprint_algorithm(),
+            ignore_columns: options.ignore_columns,
+            line_bucket: options.line_bucket,
+            fingerprints: BTreeSet::new(),
+            signatures: BTreeSet::new(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Baseline {
+    pub fn new() -> Self {
+        Baseline::default()
+    }
+
+    /// 从 `to_bytes` 的数据恢复，数据无效时返回undefined
+    pub fn from_bytes(bytes: &[u8]) -> Option<Baseline> {
+        Baseline::parse(bytes).ok()
+    }
+
+    /// 把错误栈记为已知，返回指纹是否为新加入的
+    pub fn add(&mut self, stack: &str) -> bool {
+        let (fingerprint, signature) = self.keys(stack);
+        self.signatures.insert(signature);
+        self.fingerprints.insert(fingerprint)
+    }
+
+    /// 相对基线分类：`known`、`new` 或 `mutated`
+    pub fn classify(&self, stack: &str) -> String {
+        self.status(stack).as_str().to_string()
+    }
+
+    /// 已知指纹数
+    pub fn len(&self) -> usize {
+        self.fingerprints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fingerprints.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.fingerprints.clear();
+        self.signatures.clear();
+    }
+
+    /// 导出为不透明的二进制数据，供宿主持久化或随版本下发
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(BASELINE_SETTINGS_LEN + 4 + 8 * (self.fingerprints.len() + self.signatures.len()));
+        payload.push(self.algorithm as u8);
+        payload.push(u8::from(self.ignore_columns));
+        payload.extend_from_slice(&self.line_bucket.to_le_bytes());
+        payload.extend_from_slice(&(self.fingerprints.len() as u32).to_le_bytes());
+        for key in self.fingerprints.iter().chain(&self.signatures) {
+            payload.extend_from_slice(&key.to_le_bytes());
+        }
+
+        let mut bytes = Vec::with_capacity(BASELINE_HEADER_LEN + payload.len());
+        bytes.extend_from_slice(BASELINE_MAGIC);
+        bytes.push(BASELINE_VERSION);
+        bytes.extend_from_slice(&xxhash64(&payload, 0).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        bytes
+    }
+
+    /// 按基线记录的算法与容差计算的指纹与签名（错误类型加栈顶业务代码帧的分组键）
+    fn keys(&self, stack: &str) -> (u64, u64) {
+        let options = parser_options::options().with_fingerprint_tolerance(self.ignore_columns, self.line_bucket);
+        let signature = Grouper::new(GroupingOptions {
+            rules: vec![GroupingRule::ErrorType, GroupingRule::TopInAppFrame],
+            in_app_frames: 1,
+        }).group_hash(stack, None, self.algorithm);
+        (fingerprint::fingerprint_hash(stack, &options, self.algorithm), signature)
+    }
+}
+
+impl Baseline {
+    /// 相对基线的状态
+    pub fn status(&self, stack: &str) -> BaselineStatus {
+        let (fingerprint, signature) = self.keys(stack);
+        if self.fingerprints.contains(&fingerprint) {
+            BaselineStatus::Known
+        } else if self.signatures.contains(&signature) {
+            BaselineStatus::Mutated
+        } else {
+            BaselineStatus::New
+        }
+    }
+
+    /// 解析 `to_bytes` 的数据，校验魔数、版本与校验和
+    pub fn parse(bytes: &[u8]) -> Result<Baseline, String> {
+        if bytes.len() < BASELINE_HEADER_LEN || &bytes[..BASELINE_MAGIC.len()] != BASELINE_MAGIC {
+            return Err("不是PerfLite基线数据".to_string());
+        }
+        let version = bytes[BASELINE_MAGIC.len()];
+        if version != 1 && version != BASELINE_VERSION {
+            return Err(format!("不支持的基线版本: {}", version));
+        }
+        let mut checksum = [0u8; 8];
+        checksum.copy_from_slice(&bytes[BASELINE_MAGIC.len() + 1..BASELINE_HEADER_LEN]);
+        let payload = &bytes[BASELINE_HEADER_LEN..];
+        if u64::from_le_bytes(checksum) != xxhash64(payload, 0) {
+            return Err("基线数据已损坏".to_string());
+        }
+
+        let mut baseline = Baseline::new();
+        let payload = if version == 1 {
+            payload
+        } else {
+            if payload.len() < BASELINE_SETTINGS_LEN {
+                return Err("基线数据已损坏".to_string());
+            }
+            baseline.algorithm = match payload[0] {
+                0 => HashAlgorithm::XxHash64,
+                1 => HashAlgorithm::Blake3,
+                other => return Err(format!("不支持的指纹算法: {}", other)),
+            };
+            baseline.ignore_columns = payload[1] != 0;
+            let mut line_bucket = [0u8; 4];
+            line_bucket.copy_from_slice(&payload[2..BASELINE_SETTINGS_LEN]);
+            baseline.line_bucket = u32::from_le_bytes(line_bucket);
+            &payload[BASELINE_SETTINGS_LEN..]
+        };
+        if payload.len() < 4 || (payload.len() - 4) % 8 != 0 {
+            return Err("基线数据已损坏".to_string());
+        }
+
+        let mut count = [0u8; 4];
+        count.copy_from_slice(&payload[..4]);
+        let count = u32::from_le_bytes(count) as usize;
+        let mut keys = payload[4..].chunks_exact(8).map(|chunk| {
+            let mut key = [0u8; 8];
+            key.copy_from_slice(chunk);
+            u64::from_le_bytes(key)
+        });
+        if count > (payload.len() - 4) / 8 {
+            return Err("基线数据已损坏".to_string());
+        }
+        baseline.fingerprints = keys.by_ref().take(count).collect();
+        baseline.signatures = keys.collect();
+        Ok(baseline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STACK: &str = "TypeError: x is undefined\n at render (https://cdn.test/app.js:10:15)\n at update (https://cdn.test/app.js:40:2)";
+
+    #[test]
+    fn test_classify() {
+        let mut baseline = Baseline::new();
+        assert!(baseline.add(STACK));
+        assert!(!baseline.add(STACK));
+
+        assert_eq!(baseline.classify(STACK), "known");
+        // 调用方变化：栈顶帧相同
+        assert_eq!(baseline.status(&STACK.replace("update", "flush")), BaselineStatus::Mutated);
+        assert_eq!(baseline.status(&STACK.replace("render", "paint")), BaselineStatus::New);
+        assert_eq!(baseline.status(&STACK.replace("TypeError", "RangeError")), BaselineStatus::New);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let mut baseline = Baseline::new();
+        baseline.add(STACK);
+        baseline.add("Error: boom");
+        let bytes = baseline.to_bytes();
+        assert_eq!(Baseline::parse(&bytes), Ok(baseline.clone()));
+        assert_eq!(Baseline::from_bytes(&Baseline::new().to_bytes()), Some(Baseline::new()));
+
+        let mut corrupted = bytes.clone();
+        corrupted[BASELINE_HEADER_LEN + 5] ^= 1;
+        assert!(Baseline::parse(&corrupted).is_err());
+        assert!(Baseline::parse(b"PLQ").is_err());
+    }
+
+    #[test]
+    fn test_keeps_recorded_settings() {
+        let mut baseline = Baseline::new();
+        baseline.add(STACK);
+        let bytes = baseline.to_bytes();
+
+        // 修改全局算法与容差后，已记录的基线与恢复的基线仍按原设置分类
+        hash::set_fingerprint_algorithm(HashAlgorithm::Blake3);
+        parser_options::set_options(parser_options::options().with_fingerprint_tolerance(false, 10));
+        let restored = Baseline::parse(&bytes).unwrap();
+        assert_eq!(restored, baseline);
+        assert_eq!(restored.status(STACK), BaselineStatus::Known);
+        assert_eq!(baseline.status(STACK), BaselineStatus::Known);
+        // 新基线使用当前设置
+        let current = Baseline::new();
+        assert_eq!((current.algorithm, current.ignore_columns, current.line_bucket), (HashAlgorithm::Blake3, false, 10));
+        assert_ne!(Baseline::parse(&current.to_bytes()).unwrap().algorithm, baseline.algorithm);
+
+        hash::set_fingerprint_algorithm(HashAlgorithm::XxHash64);
+        parser_options::set_options(parser_options::ParserOptions::default());
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::mem::size_of;
+use crate::utils::now_ms;
+
+/// 用户行为面包屑
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Breadcrumb {
+    pub category: String,
+    pub message: String,
+    pub timestamp: f64,
+    pub level: String,
+}
+
+impl Breadcrumb {
+    /// 估算占用的内存字节数
+    pub fn approx_bytes(&self) -> usize {
+        size_of::<Breadcrumb>() + self.category.len() + self.message.len() + self.level.len()
+    }
+}
+
+/// 固定容量的面包屑环形缓冲区
+pub struct BreadcrumbBuffer {
+    entries: VecDeque<Breadcrumb>,
+    capacity: usize,
+    // 解析错误时附加的条数
+    attach_count: usize,
+}
+
+impl BreadcrumbBuffer {
+    pub fn new(capacity: usize, attach_count: usize) -> Self {
+        BreadcrumbBuffer {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            attach_count,
+        }
+    }
+
+    /// 写入一条面包屑，超出容量时丢弃最旧的记录
+    pub fn push(&mut self, breadcrumb: Breadcrumb) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(breadcrumb);
+    }
+
+    /// 调整容量与附加条数
+    pub fn configure(&mut self, capacity: usize, attach_count: usize) {
+        self.capacity = capacity;
+        self.attach_count = attach_count;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// 最近的若干条面包屑（按时间顺序）
+    pub fn recent(&self) -> Vec<Breadcrumb> {
+        let skip = self.entries.len().saturating_sub(self.attach_count);
+        self.entries.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 释放缓冲区的多余容量
+    pub fn compact(&mut self) {
+        self.entries.shrink_to_fit();
+    }
+
+    /// 估算占用的内存字节数
+    pub fn approx_bytes(&self) -> usize {
+        self.entries.iter().map(Breadcrumb::approx_bytes).sum()
+    }
+
+    /// 丢弃最旧的记录直到占用不超过 `max_bytes`，返回丢弃条数
+    pub fn shrink_to_bytes(&mut self, max_bytes: usize) -> usize {
+        let mut used = self.approx_bytes();
+        let mut dropped = 0;
+        while used > max_bytes {
+            match self.entries.pop_front() {
+                Some(breadcrumb) => used -= breadcrumb.approx_bytes(),
+                None => break,
+            }
+            dropped += 1;
+        }
+        dropped
+    }
+}
+
+impl Default for BreadcrumbBuffer {
+    fn default() -> Self {
+        BreadcrumbBuffer::new(100, 20)
+    }
+}
+
+thread_local! {
+    static BUFFER: RefCell<BreadcrumbBuffer> = RefCell::new(BreadcrumbBuffer::default());
+}
+
+/// 以当前时间记录一条面包屑
+pub fn add(category: &str, message: &str, level: &str) {
+    let breadcrumb = Breadcrumb {
+        category: category.to_string(),
+        message: message.to_string(),
+        timestamp: now_ms(),
+        level: level.to_string(),
+    };
+    BUFFER.with(|buffer| buffer.borrow_mut().push(breadcrumb));
+}
+
+/// 调整全局缓冲区容量与附加条数
+pub fn configure(capacity: usize, attach_count: usize) {
+    BUFFER.with(|buffer| buffer.borrow_mut().configure(capacity, attach_count));
+}
+
+/// 待附加到错误上的面包屑
+pub fn recent() -> Vec<Breadcrumb> {
+    BUFFER.with(|buffer| buffer.borrow().recent())
+}
+
+/// 清空全局缓冲区
+pub fn clear() {
+    BUFFER.with(|buffer| buffer.borrow_mut().clear());
+}
+
+pub fn compact() {
+    BUFFER.with(|buffer| buffer.borrow_mut().compact());
+}
+
+/// 全局缓冲区估算占用的字节数
+pub fn approx_bytes() -> usize {
+    BUFFER.with(|buffer| buffer.borrow().approx_bytes())
+}
+
+/// 收缩全局缓冲区到指定字节数以内
+pub fn shrink_to_bytes(max_bytes: usize) -> usize {
+    BUFFER.with(|buffer| buffer.borrow_mut().shrink_to_bytes(max_bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crumb(message: &str) -> Breadcrumb {
+        Breadcrumb {
+            category: "ui.click".to_string(),
+            message: message.to_string(),
+            timestamp: 0.0,
+            level: "info".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_ring_eviction() {
+        let mut buffer = BreadcrumbBuffer::new(3, 2);
+        for message in ["a", "b", "c", "d"] {
+            buffer.push(crumb(message));
+        }
+        assert_eq!(buffer.len(), 3);
+
+        let recent: Vec<String> = buffer.recent().into_iter().map(|b| b.message).collect();
+        assert_eq!(recent, vec!["c", "d"]);
+
+        buffer.configure(1, 5);
+        assert_eq!(buffer.recent().len(), 1);
+        assert_eq!(buffer.recent()[0].message, "d");
+    }
+
+    #[test]
+    fn test_shrink_to_bytes() {
+        let mut buffer = BreadcrumbBuffer::new(10, 10);
+        for message in ["a", "b", "c"] {
+            buffer.push(crumb(message));
+        }
+        let per_entry = crumb("a").approx_bytes();
+        assert_eq!(buffer.approx_bytes(), per_entry * 3);
+        assert_eq!(buffer.shrink_to_bytes(per_entry), 2);
+        assert_eq!(buffer.recent()[0].message, "c");
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut buffer = BreadcrumbBuffer::new(0, 5);
+        buffer.push(crumb("a"));
+        assert!(buffer.is_empty());
+    }
+}
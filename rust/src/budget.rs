@@ -0,0 +1,283 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use crate::{aggregates, breadcrumbs, event_buffer, remap_cache, source_context, sourcemap_registry};
+use crate::aggregates::AggregateStats;
+use crate::event_buffer::EventBufferStats;
+use crate::remap_cache::RemapCacheStats;
+use crate::utils::demangle;
+
+/// 内存预算与降级状态
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MemoryStatus {
+    /// 预算上限，未设置时为null
+    pub budget_bytes: Option<usize>,
+    /// 各缓存估算占用的字节数
+    pub used_bytes: usize,
+    pub evicted_bundles: u32,
+    pub evicted_source_maps: u32,
+    pub dropped_breadcrumbs: u32,
+    pub remap_cache_flushes: u32,
+    /// 是否仍提供上下文行
+    pub context_lines: bool,
+    /// 是否仍按解析选项保留无法识别的原始行
+    pub raw_lines: bool,
+    pub degraded: bool,
+}
+
+/// 各子系统的占用与淘汰统计，长时间运行的页面据此观察内存是否受控
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RuntimeStats {
+    pub memory: MemoryStatus,
+    pub aggregates: AggregateStats,
+    pub remap_cache: RemapCacheStats,
+    pub event_buffer: EventBufferStats,
+}
+
+/// 一个可选子系统的状态
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FeatureStatus {
+    pub name: String,
+    /// 是否编译进当前产物
+    pub compiled: bool,
+    /// 运行时是否可用（已编译且未被关闭或降级）
+    pub operational: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// 能力等级与各子系统状态，写入遥测以区分不同环境下的解析结果
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FeatureReport {
+    /// `full`：全部可选子系统可用；`standard`：未编译SIMD；`degraded`：内存预算触发了降级
+    pub tier: String,
+    pub features: Vec<FeatureStatus>,
+}
+
+fn feature(name: &str, compiled: bool, operational: bool, detail: Option<String>) -> FeatureStatus {
+    FeatureStatus { name: name.to_string(), compiled, operational: compiled && operational, detail }
+}
+
+#[derive(Default)]
+struct Budget {
+    limit: Option<usize>,
+    evicted_bundles: u32,
+    evicted_source_maps: u32,
+    dropped_breadcrumbs: u32,
+    remap_cache_flushes: u32,
+    context_disabled: bool,
+    raw_lines_disabled: bool,
+}
+
+thread_local! {
+    static BUDGET: RefCell<Budget> = RefCell::new(Budget::default());
+}
+
+/// 各缓存当前估算占用
+fn used_bytes() -> usize {
+    source_context::bundle_bytes() + breadcrumbs::approx_bytes() + remap_cache::approx_bytes() + sourcemap_registry::approx_bytes()
+}
+
+/// 设置内存预算（None表示不限制），重置降级状态并立即执行一次回收
+pub fn set_budget(limit: Option<usize>) {
+    BUDGET.with(|budget| *budget.borrow_mut() = Budget { limit, ..Budget::default() });
+    enforce();
+}
+
+/// 超出预算时关闭原始行，并按重建代价从低到高回收：先清空位置映射缓存，再淘汰最久未使用的source map，
+/// 然后淘汰打包产物源码并关闭上下文行，最后丢弃旧面包屑
+pub fn enforce() {
+    BUDGET.with(|budget| {
+        let mut budget = budget.borrow_mut();
+        let limit = match budget.limit {
+            Some(limit) => limit,
+            None => return,
+        };
+
+        // 原始行只增加输出体积，一旦超出预算最先关闭
+        if used_bytes() > limit {
+            budget.raw_lines_disabled = true;
+        }
+
+        if used_bytes() > limit && remap_cache::approx_bytes() > 0 {
+            remap_cache::clear();
+            budget.remap_cache_flushes += 1;
+        }
+
+        while used_bytes() > limit {
+            if sourcemap_registry::evict_oldest().is_none() {
+                break;
+            }
+            budget.evicted_source_maps += 1;
+        }
+
+        while used_bytes() > limit {
+            if source_context::evict_oldest_bundle().is_none() {
+                break;
+            }
+            budget.evicted_bundles += 1;
+            budget.context_disabled = true;
+        }
+
+        if used_bytes() > limit {
+            let remaining = limit.saturating_sub(source_context::bundle_bytes() + sourcemap_registry::approx_bytes());
+            budget.dropped_breadcrumbs += breadcrumbs::shrink_to_bytes(remaining) as u32;
+        }
+    });
+}
+
+/// 降级后不再注册源码、不再附加上下文行
+pub fn context_lines_enabled() -> bool {
+    BUDGET.with(|budget| !budget.borrow().context_disabled)
+}
+
+/// 降级后解析选项 `raw_lines` 不再生效
+pub fn raw_lines_enabled() -> bool {
+    BUDGET.with(|budget| !budget.borrow().raw_lines_disabled)
+}
+
+/// 当前预算与降级状态
+pub fn status() -> MemoryStatus {
+    BUDGET.with(|budget| {
+        let budget = budget.borrow();
+        MemoryStatus {
+            budget_bytes: budget.limit,
+            used_bytes: used_bytes(),
+            evicted_bundles: budget.evicted_bundles,
+            evicted_source_maps: budget.evicted_source_maps,
+            dropped_breadcrumbs: budget.dropped_breadcrumbs,
+            remap_cache_flushes: budget.remap_cache_flushes,
+            context_lines: !budget.context_disabled,
+            raw_lines: !budget.raw_lines_disabled,
+            degraded: budget.context_disabled || budget.raw_lines_disabled || budget.dropped_breadcrumbs > 0
+                || budget.remap_cache_flushes > 0 || budget.evicted_source_maps > 0,
+        }
+    })
+}
+
+/// 汇总各子系统的统计
+pub fn runtime_stats() -> RuntimeStats {
+    RuntimeStats {
+        memory: status(),
+        aggregates: aggregates::stats(),
+        remap_cache: remap_cache::stats(),
+        event_buffer: event_buffer::stats(),
+    }
+}
+
+/// 汇总可选子系统的编译与运行状态
+pub fn feature_report() -> FeatureReport {
+    let memory = status();
+    let simd = cfg!(target_feature = "simd128");
+    let features = vec![
+        feature("simd", simd, true, (!simd && cfg!(feature = "simd")).then(|| "未启用simd128目标特性，使用标量实现".to_string())),
+        feature("source_maps", true, sourcemap_registry::capacity() > 0, Some(format!("已注册 {} 个", sourcemap_registry::len()))),
+        feature("context_lines", true, memory.context_lines, (!memory.context_lines).then(|| "内存预算不足，已关闭".to_string())),
+        feature("raw_lines", true, memory.raw_lines, (!memory.raw_lines).then(|| "内存预算不足，已关闭".to_string())),
+        // 自检一个已知符号，确认原生栈符号还原可用
+        feature("demangling", true, demangle("_Z5abortv").as_deref() == Some("abort"), None),
+        feature("panic_hook", cfg!(feature = "console_error_panic_hook"), true, None),
+        feature("threads", false, false, Some("单线程，可通过worker请求把解析移出主线程".to_string())),
+        feature("compression", false, false, None),
+        feature("polyglot", cfg!(feature = "polyglot"), cfg!(feature = "polyglot"), None),
+    ];
+    let tier = if memory.degraded {
+        "degraded"
+    } else if simd {
+        "full"
+    } else {
+        "standard"
+    };
+    FeatureReport { tier: tier.to_string(), features }
+}
+
+/// 重建各子系统的内部表并释放多余容量，把淘汰后空出的内存还给WASM分配器
+pub fn compact() {
+    aggregates::compact();
+    breadcrumbs::compact();
+    event_buffer::compact();
+    remap_cache::compact();
+    source_context::compact_bundles();
+    sourcemap_registry::compact();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_by_default() {
+        source_context::register_bundle("/budget/free.js", &"x".repeat(4096));
+        enforce();
+        let report = status();
+        assert_eq!(report.budget_bytes, None);
+        assert!(!report.degraded && report.raw_lines);
+        assert!(source_context::unregister_bundle("/budget/free.js"));
+    }
+
+    #[test]
+    fn test_evicts_bundles_then_breadcrumbs() {
+        source_context::register_bundle("/budget/a.js", &"a".repeat(1000));
+        source_context::register_bundle("/budget/b.js", &"b".repeat(1000));
+        set_budget(Some(1500));
+
+        let report = status();
+        assert_eq!(report.evicted_bundles, 1);
+        assert!(!report.context_lines && !report.raw_lines);
+        assert!(report.degraded);
+        assert!(report.used_bytes <= 1500);
+        assert!(source_context::unregister_bundle("/budget/b.js"));
+
+        for i in 0..50 {
+            breadcrumbs::add("ui.click", &format!("button#{}", i), "info");
+        }
+        set_budget(Some(200));
+        let report = status();
+        assert!(report.context_lines);
+        assert!(report.dropped_breadcrumbs > 0);
+        assert!(report.used_bytes <= 200);
+
+        set_budget(None);
+        breadcrumbs::clear();
+    }
+
+    #[test]
+    fn test_evicts_source_maps_before_bundles() {
+        let map = format!(r#"{{"version":3,"sources":["src/a.js"],"names":[],"mappings":"{}"}}"#, "AAAA;".repeat(200));
+        sourcemap_registry::register("/budget/app.js", &map).unwrap();
+        source_context::register_bundle("/budget/app.js", &"a".repeat(100));
+        set_budget(Some(sourcemap_registry::approx_bytes()));
+
+        let report = status();
+        assert_eq!(report.evicted_source_maps, 1);
+        assert_eq!(report.evicted_bundles, 0);
+        assert!(report.context_lines);
+        assert!(report.degraded);
+
+        set_budget(None);
+        assert!(source_context::unregister_bundle("/budget/app.js"));
+    }
+
+    #[test]
+    fn test_runtime_stats() {
+        aggregates::set_max_bytes(Some(512));
+        for i in 0..50 {
+            aggregates::record(&format!("budget-group-{}", i));
+        }
+        compact();
+        let stats = runtime_stats();
+        assert!(stats.aggregates.approx_bytes <= 512);
+        assert!(stats.aggregates.evicted_groups > 0);
+        assert_eq!(stats.memory, status());
+        aggregates::set_max_bytes(None);
+    }
+
+    #[test]
+    fn test_feature_report() {
+        let report = feature_report();
+        let find = |name: &str| report.features.iter().find(|feature| feature.name == name).unwrap();
+        assert_eq!(report.tier, if cfg!(target_feature = "simd128") { "full" } else { "standard" });
+        assert!(find("source_maps").operational && find("demangling").operational);
+        assert!(!find("threads").compiled && !find("threads").operational);
+        assert_eq!(find("simd").compiled, find("simd").operational);
+    }
+}
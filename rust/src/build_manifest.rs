@@ -0,0 +1,117 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::fingerprint::strip_query;
+
+/// 当前构建的清单：打包产物URL -> 内容哈希，用于识别来自浏览器缓存中旧版产物的帧
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BuildManifest {
+    // 键去掉查询参数与锚点
+    hashes: HashMap<String, String>,
+}
+
+/// URL中主机之后的路径部分，不是URL时返回原值
+fn path_of(url: &str) -> &str {
+    url.split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|i| &rest[i..]))
+        .unwrap_or(url)
+}
+
+fn is_hex(value: &str) -> bool {
+    value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn is_base64(value: &str) -> bool {
+    value.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=' | b'-' | b'_'))
+}
+
+/// 参数值是否与清单哈希同长度、同字符类别
+fn looks_like(value: &str, hash: &str) -> bool {
+    value.len() == hash.len() && if is_hex(hash) { is_hex(value) } else { is_base64(value) }
+}
+
+impl BuildManifest {
+    /// 键可以是完整URL或以 `/` 开始的路径
+    pub fn new(map: HashMap<String, String>) -> Self {
+        let hashes = map.into_iter()
+            .map(|(url, hash)| (strip_query(&url).to_string(), hash.trim().to_string()))
+            .filter(|(url, hash)| !url.is_empty() && !hash.is_empty())
+            .collect();
+        BuildManifest { hashes }
+    }
+
+    fn hash_for(&self, url: &str) -> Option<&str> {
+        let base = strip_query(url);
+        self.hashes.get(base).or_else(|| self.hashes.get(path_of(base))).map(String::as_str)
+    }
+
+    /// 帧URL的查询参数带有内容哈希、且与清单中该产物的当前哈希都不相同时为旧版产物。
+    /// 只比较长度与字符类别（十六进制或base64）都和清单哈希一致的参数值，`?t=123`、`?locale=en` 等不算；
+    /// 不在清单中或没有哈希参数的URL无法判断，视为不过期
+    pub fn is_stale(&self, url: &str) -> bool {
+        let Some(current) = self.hash_for(url) else {
+            return false;
+        };
+        let Some((_, query)) = url.split('#').next().unwrap_or(url).split_once('?') else {
+            return false;
+        };
+        let mut hashes = query.split('&')
+            .map(|pair| pair.rsplit('=').next().unwrap_or(pair))
+            .filter(|value| looks_like(value, current))
+            .peekable();
+        hashes.peek().is_some() && hashes.all(|value| value != current)
+    }
+}
+
+thread_local! {
+    static MANIFEST: RefCell<BuildManifest> = RefCell::new(BuildManifest::default());
+}
+
+/// 替换全局构建清单
+pub fn set_build_manifest(manifest: BuildManifest) {
+    MANIFEST.with(|current| *current.borrow_mut() = manifest);
+}
+
+/// 按全局构建清单判断URL是否来自旧版产物
+pub fn is_stale(url: &str) -> bool {
+    MANIFEST.with(|manifest| manifest.borrow().is_stale(url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::StackFrame;
+    use crate::sourcemap_registry;
+
+    #[test]
+    fn test_is_stale() {
+        let manifest = BuildManifest::new(serde_json::from_str(r#"{
+            "https://cdn.test/static/app.js": "3f2a91bc",
+            "/static/vendor.js?ignored=1": "77aa00ff"
+        }"#).unwrap());
+
+        assert!(!manifest.is_stale("https://cdn.test/static/app.js?v=3f2a91bc"));
+        assert!(manifest.is_stale("https://cdn.test/static/app.js?v=0011aabb"));
+        assert!(!manifest.is_stale("https://cdn.test/static/app.js?3f2a91bc#L1"));
+        assert!(manifest.is_stale("https://other.test/static/vendor.js?h=1234abcd&t=1"));
+        // 不像内容哈希的缓存参数不算
+        assert!(!manifest.is_stale("https://cdn.test/static/app.js?t=123"));
+        assert!(!manifest.is_stale("https://cdn.test/static/app.js?locale=en&v=3f2a91bc"));
+        // 没有哈希参数或不在清单中时无法判断
+        assert!(!manifest.is_stale("https://cdn.test/static/app.js"));
+        assert!(!manifest.is_stale("https://cdn.test/static/other.js?v=1"));
+
+        // 旧版产物的帧被标记且不再用source map还原
+        set_build_manifest(manifest);
+        let frame = StackFrame::new("f".to_string(), "https://cdn.test/static/app.js?v=0011aabb".to_string(), 1, 1);
+        sourcemap_registry::register("https://cdn.test/static/app.js", r#"{"version": 3, "sources": ["a.ts"], "names": [], "mappings": "AAAA"}"#).unwrap();
+        assert!(frame.into_exported().stale_bundle);
+        let frame = StackFrame::new("f".to_string(), "https://cdn.test/static/app.js?v=0011aabb".to_string(), 1, 1);
+        assert!(sourcemap_registry::map_frame(&frame).is_none());
+        sourcemap_registry::clear();
+        // 传入的map同样不用于旧版产物的帧
+        let json = crate::parse_and_map("Error: x\n at f (https://cdn.test/static/app.js?v=0011aabb:1:1)", r#"{"version": 3, "sources": ["a.ts"], "names": [], "mappings": "AAAA"}"#);
+        let frames: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(frames[0]["annotations"]["symbolication_reason"].as_str(), Some("stale_bundle"));
+        set_build_manifest(BuildManifest::default());
+    }
+}
@@ -0,0 +1,148 @@
+use crate::fingerprint::strip_query;
+use crate::parser::ErrorParser;
+
+/// 生成打包产物的构建工具
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bundler {
+    Webpack,
+    Vite,
+    Rollup,
+    Parcel,
+    Esbuild,
+    Unknown,
+}
+
+impl Bundler {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bundler::Webpack => "webpack",
+            Bundler::Vite => "vite",
+            Bundler::Rollup => "rollup",
+            Bundler::Parcel => "parcel",
+            Bundler::Esbuild => "esbuild",
+            Bundler::Unknown => "unknown",
+        }
+    }
+}
+
+// 运行时辅助函数名 -> 构建工具
+const RUNTIME_FUNCTIONS: [(&str, Bundler); 10] = [
+    ("__webpack_require__", Bundler::Webpack),
+    ("__webpack_modules__", Bundler::Webpack),
+    ("webpackJsonpCallback", Bundler::Webpack),
+    ("webpackUniversalModuleDefinition", Bundler::Webpack),
+    ("parcelRequire", Bundler::Parcel),
+    ("newRequire", Bundler::Parcel),
+    ("__require", Bundler::Esbuild),
+    ("__commonJS", Bundler::Esbuild),
+    ("__toESM", Bundler::Esbuild),
+    ("__esm", Bundler::Esbuild),
+];
+
+// 明确的信号权重高于文件命名规律
+const STRONG: u32 = 3;
+const WEAK: u32 = 1;
+
+fn is_hex(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// 内容哈希的文件名规律，`name` 为去掉 `.js`/`.mjs` 后的文件名
+fn file_name_signal(name: &str) -> Option<Bundler> {
+    // esbuild的代码分割块：`chunk-` 加8位大写base32
+    if let Some(hash) = name.strip_prefix("chunk-") {
+        if hash.len() == 8 && hash.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit()) {
+            return Some(Bundler::Esbuild);
+        }
+    }
+    // webpack的 `[name].[contenthash].js`（默认20位十六进制，CRA为8位加 `.chunk`）与parcel的8位十六进制
+    let (name, cra_chunk) = match name.strip_suffix(".chunk") {
+        Some(name) => (name, true),
+        None => (name, false),
+    };
+    if let Some((_, hash)) = name.rsplit_once('.') {
+        if is_hex(hash) {
+            return match hash.len() {
+                8 if cra_chunk => Some(Bundler::Webpack),
+                8 if name.len() != hash.len() + 1 => Some(Bundler::Parcel),
+                16..=32 => Some(Bundler::Webpack),
+                _ => None,
+            };
+        }
+    }
+    // rollup的 `[name]-[hash].js`：8位base64url
+    if let Some((_, hash)) = name.rsplit_once('-') {
+        if hash.len() == 8 && hash.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') && hash.bytes().any(|b| b.is_ascii_digit()) {
+            return Some(Bundler::Rollup);
+        }
+    }
+    None
+}
+
+/// 按URL形态、分块文件命名与运行时帧名推断构建工具，信号不足或相互矛盾时为 `Unknown`
+pub fn detect_bundler(stack: &str) -> Bundler {
+    let mut scores = [0u32; 5];
+    let mut add = |bundler: Bundler, weight: u32| scores[bundler as usize] += weight;
+
+    for frame in ErrorParser::new().parse_frames(stack) {
+        let function = frame.function_name_str();
+        for (name, bundler) in RUNTIME_FUNCTIONS {
+            if function == name || function.ends_with(&format!(".{}", name)) {
+                add(bundler, STRONG);
+            }
+        }
+
+        let url = frame.file_name_str();
+        if url.starts_with("webpack://") || url.starts_with("webpack-internal://") {
+            add(Bundler::Webpack, STRONG);
+            continue;
+        }
+        if url.contains("/@vite/") || url.contains("/@fs/") || url.contains("/@id/") || url.contains("/node_modules/.vite/") {
+            add(Bundler::Vite, STRONG);
+            continue;
+        }
+
+        let path = strip_query(url);
+        let file = path.rsplit('/').next().unwrap_or(path);
+        let Some(name) = file.strip_suffix(".js").or_else(|| file.strip_suffix(".mjs")) else {
+            continue;
+        };
+        match file_name_signal(name) {
+            // vite在rollup的命名规律上默认输出到 `assets/`
+            Some(Bundler::Rollup) if path.contains("/assets/") => add(Bundler::Vite, WEAK),
+            Some(bundler) => add(bundler, WEAK),
+            None => {}
+        }
+    }
+
+    let best = scores.iter().copied().max().unwrap_or(0);
+    let leaders: Vec<usize> = (0..scores.len()).filter(|&i| scores[i] == best).collect();
+    match (best, leaders.as_slice()) {
+        (0, _) => Bundler::Unknown,
+        (_, [index]) => [Bundler::Webpack, Bundler::Vite, Bundler::Rollup, Bundler::Parcel, Bundler::Esbuild][*index],
+        _ => Bundler::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_bundler() {
+        let detect = |stack: &str| detect_bundler(stack).as_str();
+        assert_eq!(detect("TypeError: x\n at render (webpack:///./src/App.js:10:15)"), "webpack");
+        assert_eq!(detect("TypeError: x\n at a (https://cdn.test/static/js/main.3f2a91bc.chunk.js:1:100)\n at __webpack_require__ (https://cdn.test/static/js/runtime.js:1:5)"), "webpack");
+        assert_eq!(detect("TypeError: x\n at a (https://cdn.test/main.4c2f0a1b9d8e7f6a5b4c.js:1:100)"), "webpack");
+        // 只有文件名时，CRA的 `.chunk` 分块也归为webpack
+        assert_eq!(detect("TypeError: x\n at a (https://cdn.test/static/js/main.3f2a91bc.chunk.js:1:100)"), "webpack");
+        assert_eq!(detect("TypeError: x\n at a (http://localhost:5173/node_modules/.vite/deps/react.js?v=1a2b3c:10:1)"), "vite");
+        assert_eq!(detect("TypeError: x\n at a (https://cdn.test/assets/index-B7x9Kq2d.js:1:100)"), "vite");
+        assert_eq!(detect("TypeError: x\n at a (https://cdn.test/dist/index-B7x9Kq2d.js:1:100)"), "rollup");
+        assert_eq!(detect("TypeError: x\n at a (https://cdn.test/index.3d214d75.js:1:100)\n at newRequire (https://cdn.test/index.3d214d75.js:1:5)"), "parcel");
+        assert_eq!(detect("TypeError: x\n at a (https://cdn.test/out/chunk-KX4FZ3QJ.js:1:100)\n at __require (https://cdn.test/out/main.js:1:5)"), "esbuild");
+        assert_eq!(detect("TypeError: x\n at a (https://cdn.test/app.js:1:100)"), "unknown");
+        // 信号相互矛盾
+        assert_eq!(detect("TypeError: x\n at __require (https://cdn.test/a.js:1:1)\n at __webpack_require__ (https://cdn.test/b.js:1:1)"), "unknown");
+    }
+}
@@ -0,0 +1,109 @@
+use std::cell::{Cell, RefCell};
+use crate::parser::{ErrorParser, StackFrame};
+use crate::simd::LineIndex;
+use crate::utils;
+
+thread_local! {
+    // 延迟创建的完整解析器（正则编译是冷启动的主要开销）
+    static FULL_PARSER: RefCell<Option<ErrorParser>> = const { RefCell::new(None) };
+    // 首个错误已经由轻量解析器处理
+    static FIRST_HANDLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 不依赖正则的轻量解析：只识别 `at fn (file:line:col)`、`at file:line:col` 与 `fn@file:line:col`，列号可省略
+pub fn tiny_parse(stack: &str) -> Vec<StackFrame> {
+    LineIndex::new(stack).lines().filter_map(tiny_parse_line).collect()
+}
+
+fn tiny_parse_line(line: &str) -> Option<StackFrame> {
+    let line = line.trim();
+    let mut is_async = false;
+    let (name, location) = match line.strip_prefix("at ") {
+        Some(rest) => {
+            let rest = match rest.strip_prefix("async ") {
+                Some(rest) => {
+                    is_async = true;
+                    rest
+                }
+                None => rest,
+            };
+            rest.strip_suffix(')')
+                .and_then(|inner| inner.rfind(" (").map(|open| (&inner[..open], &inner[open + 2..])))
+                .unwrap_or(("<anonymous>", rest))
+        }
+        None => line.split_once('@')?,
+    };
+
+    let (file, line_number, column) = utils::split_location_with(location, |part| part.parse::<u32>().ok())
+        .filter(|(file, _, _)| !file.is_empty())?;
+    let name = match name.trim() {
+        "" => "<anonymous>",
+        name => name,
+    };
+
+    let mut frame = StackFrame::new(name.to_string(), file.to_string(), line_number, column);
+    frame.set_async(is_async);
+    Some(frame)
+}
+
+/// 冷启动配置：只重置状态，panic hook与完整解析器都推迟到升级时
+pub fn init() {
+    FIRST_HANDLED.with(|handled| handled.set(false));
+}
+
+/// 创建完整解析器并安装panic hook，可在空闲时提前调用
+pub fn upgrade() {
+    FULL_PARSER.with(|parser| {
+        let mut parser = parser.borrow_mut();
+        if parser.is_none() {
+            utils::set_panic_hook();
+            *parser = Some(ErrorParser::new());
+        }
+    });
+}
+
+/// 是否已升级到完整解析器
+pub fn is_upgraded() -> bool {
+    FULL_PARSER.with(|parser| parser.borrow().is_some())
+}
+
+/// 第一个错误走轻量解析器，之后的错误升级到完整解析器
+pub fn parse_first_error(stack: &str) -> Vec<StackFrame> {
+    if !is_upgraded() && !FIRST_HANDLED.with(|handled| handled.replace(true)) {
+        return tiny_parse(stack);
+    }
+
+    upgrade();
+    FULL_PARSER.with(|parser| parser.borrow().as_ref().map(|parser| parser.parse_frames(stack)).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STACK: &str = "TypeError: x is undefined\n\
+        at render (https://cdn.test/app.js:10:20)\n\
+        at https://cdn.test/vendor.js:1:300\n\
+        at async loadPage (/src/page.js:20:3)\n\
+        handleClick@http://localhost:3000/src/App.js:12:9";
+
+    #[test]
+    fn test_tiny_parse_matches_full_parser() {
+        let tiny = tiny_parse(STACK);
+        let full = ErrorParser::new().parse_frames(STACK);
+        assert_eq!(ErrorParser::format_frames(&tiny), ErrorParser::format_frames(&full));
+        assert!(tiny[2].is_async());
+        assert!(tiny_parse("Error: no frames").is_empty());
+    }
+
+    #[test]
+    fn test_upgrades_after_first_error() {
+        init();
+        assert!(!is_upgraded());
+        assert_eq!(parse_first_error(STACK).len(), 4);
+        assert!(!is_upgraded());
+
+        assert_eq!(parse_first_error(STACK).len(), 4);
+        assert!(is_upgraded());
+    }
+}
@@ -0,0 +1,147 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use serde_json::Value;
+
+// 上下文条目数与单条、总大小上限，避免上下文挤占事件体积
+const MAX_ENTRIES: usize = 64;
+const MAX_VALUE_BYTES: usize = 4 * 1024;
+const MAX_TOTAL_BYTES: usize = 32 * 1024;
+
+/// 附加到错误事件上的有界上下文（用户ID哈希、应用状态、功能开关等）
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ErrorContext {
+    // 值以序列化后的JSON保存，便于计算大小
+    entries: BTreeMap<String, String>,
+    bytes: usize,
+}
+
+impl ErrorContext {
+    /// 设置一个键，值必须是合法JSON；超出条目数或大小上限时拒绝
+    pub fn set(&mut self, key: &str, value_json: &str) -> Result<(), String> {
+        let value: Value = serde_json::from_str(value_json)
+            .map_err(|e| format!("上下文不是合法JSON: {}", e))?;
+        let json = serde_json::to_string(&value).map_err(|e| format!("JSON序列化错误: {}", e))?;
+        if key.is_empty() || json.len() > MAX_VALUE_BYTES {
+            return Err(format!("上下文 {} 超出单条大小上限 {} 字节", key, MAX_VALUE_BYTES));
+        }
+
+        let previous = self.entries.get(key).map_or(0, |old| key.len() + old.len());
+        let bytes = self.bytes - previous + key.len() + json.len();
+        if bytes > MAX_TOTAL_BYTES {
+            return Err(format!("上下文总大小超出上限 {} 字节", MAX_TOTAL_BYTES));
+        }
+        if previous == 0 && self.entries.len() >= MAX_ENTRIES {
+            return Err(format!("上下文条目数超出上限 {}", MAX_ENTRIES));
+        }
+
+        self.entries.insert(key.to_string(), json);
+        self.bytes = bytes;
+        Ok(())
+    }
+
+    /// 删除一个键，返回是否存在
+    pub fn remove(&mut self, key: &str) -> bool {
+        match self.entries.remove(key) {
+            Some(json) => {
+                self.bytes -= key.len() + json.len();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 当前上下文的快照
+    pub fn snapshot(&self) -> BTreeMap<String, Value> {
+        self.entries.iter()
+            .filter_map(|(key, json)| Some((key.clone(), serde_json::from_str(json).ok()?)))
+            .collect()
+    }
+}
+
+/// 发布信息，合并到每个错误事件的顶层，JSON为 `{"release": "1.4.2", "environment": "production", "build_id": "a1b2c3"}`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ReleaseInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_id: Option<String>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<ErrorContext> = RefCell::new(ErrorContext::default());
+    static RELEASE: RefCell<ReleaseInfo> = RefCell::new(ReleaseInfo::default());
+}
+
+pub fn set(key: &str, value_json: &str) -> Result<(), String> {
+    CONTEXT.with(|context| context.borrow_mut().set(key, value_json))
+}
+
+pub fn remove(key: &str) -> bool {
+    CONTEXT.with(|context| context.borrow_mut().remove(key))
+}
+
+pub fn clear() {
+    CONTEXT.with(|context| context.borrow_mut().clear());
+}
+
+/// 组装错误事件时调用，取当前上下文的快照
+pub fn snapshot() -> BTreeMap<String, Value> {
+    CONTEXT.with(|context| context.borrow().snapshot())
+}
+
+/// 替换全局发布信息，传入默认值即清除
+pub fn set_release(release: ReleaseInfo) {
+    RELEASE.with(|current| *current.borrow_mut() = release);
+}
+
+/// 组装错误事件时调用，取当前发布信息
+pub fn release() -> ReleaseInfo {
+    RELEASE.with(|release| release.borrow().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_context() {
+        let mut context = ErrorContext::default();
+        assert!(context.set("user", r#"{"id_hash": "a1b2"}"#).is_ok());
+        assert!(context.set("flags", r#"["new_checkout"]"#).is_ok());
+        assert!(context.set("broken", "{").is_err());
+        assert!(context.set("huge", &format!("\"{}\"", "x".repeat(MAX_VALUE_BYTES))).is_err());
+
+        // 覆盖同一个键不增加条目
+        assert!(context.set("flags", "[]").is_ok());
+        assert_eq!(context.len(), 2);
+        assert_eq!(context.snapshot()["user"]["id_hash"].as_str(), Some("a1b2"));
+
+        for i in 0..MAX_ENTRIES - 2 {
+            context.set(&format!("k{}", i), "1").unwrap();
+        }
+        assert!(context.set("overflow", "1").is_err());
+        assert!(context.remove("user"));
+        assert!(context.set("overflow", "1").is_ok());
+
+        context.clear();
+        assert!(context.is_empty());
+        assert_eq!(context.bytes, 0);
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use serde_json::Value;
+use crate::context;
+use crate::fingerprint;
+use crate::interactions;
+use crate::ownership;
+use crate::parser::{self, ErrorParser, ParsedError, StackFrame};
+use crate::simd::LineIndex;
+use crate::taxonomy;
+use crate::trace_context::TraceParent;
+use crate::utils::now_ms;
+use crate::uuid;
+use crate::exported_frame::ExportedStackFrame;
+
+/// 生成事件的SDK信息
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SdkInfo {
+    pub name: String,
+    pub version: String,
+}
+
+impl Default for SdkInfo {
+    fn default() -> Self {
+        SdkInfo {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// 可直接上报的完整错误事件
+#[derive(Serialize, Deserialize)]
+pub struct ErrorEnvelope {
+    pub event_id: String,
+    /// 接收时间（Unix毫秒）
+    pub received: f64,
+    pub sdk: SdkInfo,
+    /// 关联的分布式trace，来自调用方传入的traceparent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span_id: Option<String>,
+    /// 全局设置的发布版本、环境与构建ID
+    #[serde(flatten)]
+    pub release: context::ReleaseInfo,
+    /// 组装事件时的上下文快照
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub contexts: BTreeMap<String, Value>,
+    pub error: ParsedError,
+}
+
+thread_local! {
+    // 注入的JS时钟函数，返回Unix毫秒
+    static CLOCK: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
+}
+
+/// 注入时钟函数，传None恢复使用系统时间
+pub fn set_clock(clock: Option<js_sys::Function>) {
+    CLOCK.with(|current| *current.borrow_mut() = clock);
+}
+
+/// 当前接收时间：优先使用注入的时钟，调用失败时回退到系统时间
+pub fn received_now() -> f64 {
+    CLOCK.with(|clock| {
+        clock.borrow().as_ref()
+            .and_then(|clock| clock.call0(&wasm_bindgen::JsValue::NULL).ok())
+            .and_then(|value| value.as_f64())
+            .unwrap_or_else(now_ms)
+    })
+}
+
+/// 组装错误事件，`received` 为接收时间（毫秒），`trace` 为错误发生时所在的trace
+pub fn build(stack: &str, frames: Vec<StackFrame>, received: f64, trace: Option<&TraceParent>) -> ErrorEnvelope {
+    // 首行通常是错误消息，消息保留完整首行，错误类型另外拆出
+    let header = LineIndex::new(stack).line(0).unwrap_or("");
+    let (error_type, _) = ErrorParser::parse_error_header(header);
+    let message = header.trim().to_string();
+    let (strong_fingerprint, weak_fingerprint) = fingerprint::dual_fingerprints(stack, header, &frames);
+    // 最近的用户交互作为复现线索，用户已设置同名上下文时保留用户的值
+    let mut contexts = context::snapshot();
+    if !contexts.contains_key("interactions") {
+        if let Some(blob) = interactions::blob().and_then(|blob| serde_json::to_value(&blob).ok()) {
+            contexts.insert("interactions".to_string(), blob);
+        }
+    }
+
+    ErrorEnvelope {
+        event_id: uuid::v4(),
+        received,
+        sdk: SdkInfo::default(),
+        trace_id: trace.map(|trace| trace.trace_id.clone()),
+        span_id: trace.map(|trace| trace.span_id.clone()),
+        release: context::release(),
+        contexts,
+        error: ParsedError {
+            error_type,
+            message,
+            category: taxonomy::classify(header).map(|classification| classification.category),
+            owners: ownership::owners_for_frames(&frames),
+            detected_frameworks: parser::detected_frameworks(&frames),
+            strong_fingerprint,
+            weak_fingerprint,
+            frames: frames.into_iter().map(ExportedStackFrame::from).collect(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_envelope() {
+        let frames = vec![StackFrame::new("render".to_string(), "/src/App.js".to_string(), 1, 2)];
+        let envelope = build("TypeError: x is undefined\n at render (/src/App.js:1:2)", frames, 1700000000000.0, None);
+        assert_eq!(envelope.error.error_type.as_deref(), Some("TypeError"));
+        assert_eq!(envelope.error.message, "TypeError: x is undefined");
+        assert_eq!(envelope.error.frames.len(), 1);
+        assert_eq!(envelope.error.strong_fingerprint, fingerprint::fingerprint("TypeError: x is undefined\n at render (/src/App.js:1:2)"));
+        assert_eq!(envelope.received, 1700000000000.0);
+        assert_eq!(envelope.sdk.name, "perflite_wasm");
+        assert!(envelope.trace_id.is_none());
+        assert!(envelope.contexts.is_empty());
+        assert_eq!(envelope.release, context::ReleaseInfo::default());
+    }
+
+    #[test]
+    fn test_envelope_release_info() {
+        context::set_release(serde_json::from_str(r#"{"release": "1.4.2", "environment": "production"}"#).unwrap());
+        let json = serde_json::to_string(&build("Error: boom", Vec::new(), 0.0, None)).unwrap();
+        context::set_release(context::ReleaseInfo::default());
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!((value["release"].as_str(), value["environment"].as_str()), (Some("1.4.2"), Some("production")));
+        assert!(value.get("build_id").is_none());
+
+        let envelope: ErrorEnvelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(envelope.release.environment.as_deref(), Some("production"));
+    }
+
+    #[test]
+    fn test_envelope_snapshots_context() {
+        context::set("release_flags", r#"{"dark_mode": true}"#).unwrap();
+        let envelope = build("Error: boom", Vec::new(), 0.0, None);
+        context::clear();
+        // 快照不受之后的修改影响
+        assert_eq!(envelope.contexts["release_flags"]["dark_mode"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn test_build_envelope_with_trace() {
+        let trace = TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+        let envelope = build("Error: boom", Vec::new(), 0.0, Some(&trace));
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert!(json.contains(r#""trace_id":"4bf92f3577b34da6a3ce929d0e0e4736","span_id":"00f067aa0ba902b7""#));
+    }
+}
@@ -0,0 +1,63 @@
+use serde::Serialize;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// 严格解析接口允许的最大输入字节数
+pub const MAX_INPUT_BYTES: usize = 1024 * 1024;
+
+/// 解析接口的失败原因，供 `try_*` 导出区分“没有栈帧”与“解析失败”
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    /// 输入为空或只有空白
+    EmptyInput,
+    /// 既没有可识别的栈帧，也没有错误首行
+    UnrecognizedFormat,
+    /// 结果序列化失败
+    Serialization(String),
+    /// 输入超过 `MAX_INPUT_BYTES`
+    InputTooLarge { size: usize, limit: usize },
+    /// JSON栈帧数组中第 `index` 个栈帧缺少文件或位置越界
+    InvalidFrame { index: usize, reason: String },
+}
+
+impl ParseError {
+    /// 稳定的错误码，便于调用方分支处理
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::EmptyInput => "empty_input",
+            ParseError::UnrecognizedFormat => "unrecognized_format",
+            ParseError::Serialization(_) => "serialization",
+            ParseError::InputTooLarge { .. } => "input_too_large",
+            ParseError::InvalidFrame { .. } => "invalid_frame",
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::EmptyInput => write!(f, "输入为空"),
+            ParseError::UnrecognizedFormat => write!(f, "无法识别的栈格式"),
+            ParseError::Serialization(e) => write!(f, "序列化错误: {}", e),
+            ParseError::InputTooLarge { size, limit } => write!(f, "输入过大: {} > {} 字节", size, limit),
+            ParseError::InvalidFrame { index, reason } => write!(f, "第{}个栈帧无效: {}", index, reason),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// 抛给JS的错误对象
+#[derive(Serialize)]
+struct ErrorReport {
+    code: &'static str,
+    message: String,
+}
+
+/// 转为 `{code, message}` 形式的JS对象
+impl From<ParseError> for JsValue {
+    fn from(error: ParseError) -> JsValue {
+        let report = ErrorReport { code: error.code(), message: error.to_string() };
+        serde_wasm_bindgen::to_value(&report).unwrap_or_else(|_| JsValue::from_str(&report.message))
+    }
+}
@@ -0,0 +1,316 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use crate::hash::xxhash64;
+
+// 持久化格式：魔数 + 版本号 + 8字节xxhash64校验和 + JSON事件数组
+const QUEUE_MAGIC: &[u8; 3] = b"PLQ";
+const QUEUE_VERSION: u8 = 1;
+const QUEUE_HEADER_LEN: usize = QUEUE_MAGIC.len() + 1 + 8;
+
+/// 缓冲事件类型，错误的优先级高于指标
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Metric,
+    Error,
+}
+
+/// 缓冲区状态
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct EventBufferStats {
+    pub queued: usize,
+    pub queued_bytes: usize,
+    pub dropped_errors: u32,
+    pub dropped_metrics: u32,
+}
+
+#[derive(Deserialize)]
+struct StoredEvent {
+    #[serde(rename = "type")]
+    kind: EventKind,
+    payload: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct WireEvent<'a> {
+    #[serde(rename = "type")]
+    kind: EventKind,
+    payload: &'a serde_json::Value,
+}
+
+struct BufferedEvent {
+    kind: EventKind,
+    // 序列化后的 `{"type", "payload"}`，flush时直接拼接
+    json: String,
+}
+
+/// 有界事件缓冲：超出数量或字节上限时先淘汰优先级最低、最早入队的事件
+pub struct EventBuffer {
+    events: Vec<BufferedEvent>,
+    max_events: usize,
+    max_bytes: usize,
+    bytes: usize,
+    dropped_errors: u32,
+    dropped_metrics: u32,
+}
+
+impl EventBuffer {
+    pub fn new(max_events: usize, max_bytes: usize) -> Self {
+        EventBuffer {
+            events: Vec::new(),
+            max_events,
+            max_bytes,
+            bytes: 0,
+            dropped_errors: 0,
+            dropped_metrics: 0,
+        }
+    }
+
+    /// 调整上限，超出部分立即淘汰
+    pub fn configure(&mut self, max_events: usize, max_bytes: usize) {
+        self.max_events = max_events;
+        self.max_bytes = max_bytes;
+        while self.over_limit(0) {
+            if self.evict_lowest(EventKind::Error).is_none() {
+                break;
+            }
+        }
+    }
+
+    fn over_limit(&self, incoming_bytes: usize) -> bool {
+        self.events.len() + usize::from(incoming_bytes > 0) > self.max_events || self.bytes + incoming_bytes > self.max_bytes
+    }
+
+    fn record_drop(&mut self, kind: EventKind) {
+        match kind {
+            EventKind::Error => self.dropped_errors += 1,
+            EventKind::Metric => self.dropped_metrics += 1,
+        }
+    }
+
+    /// 淘汰优先级不高于 `max_kind` 的最低优先级事件中最早的一个
+    fn evict_lowest(&mut self, max_kind: EventKind) -> Option<EventKind> {
+        let index = self.events.iter().enumerate()
+            .filter(|(_, event)| event.kind <= max_kind)
+            .min_by_key(|(i, event)| (event.kind, *i))
+            .map(|(i, _)| i)?;
+        let event = self.events.remove(index);
+        self.bytes -= event.json.len();
+        self.record_drop(event.kind);
+        Some(event.kind)
+    }
+
+    /// 入队一个JSON负载，缓冲区已满且无法为其腾出空间时丢弃，返回是否入队
+    pub fn enqueue(&mut self, kind: EventKind, payload_json: &str) -> Result<bool, String> {
+        let payload: serde_json::Value = serde_json::from_str(payload_json)
+            .map_err(|e| format!("事件负载不是合法JSON: {}", e))?;
+        let json = serde_json::to_string(&WireEvent { kind, payload: &payload })
+            .map_err(|e| format!("JSON序列化错误: {}", e))?;
+
+        if json.len() > self.max_bytes || self.max_events == 0 {
+            self.record_drop(kind);
+            return Ok(false);
+        }
+        while self.over_limit(json.len()) {
+            if self.evict_lowest(kind).is_none() {
+                // 剩下的事件优先级都更高
+                self.record_drop(kind);
+                return Ok(false);
+            }
+        }
+
+        self.bytes += json.len();
+        self.events.push(BufferedEvent { kind, json });
+        Ok(true)
+    }
+
+    /// 取出不超过 `max_bytes` 的事件，输出JSON数组；优先级高的先取，同级按入队顺序，放不下的留在缓冲区
+    pub fn flush(&mut self, max_bytes: usize) -> String {
+        let mut order: Vec<usize> = (0..self.events.len()).collect();
+        order.sort_by_key(|&i| (std::cmp::Reverse(self.events[i].kind), i));
+
+        // 数组两端的括号
+        let mut size = 2;
+        let mut taken = Vec::new();
+        for i in order {
+            let extra = self.events[i].json.len() + usize::from(!taken.is_empty());
+            if size + extra <= max_bytes {
+                size += extra;
+                taken.push(i);
+            }
+        }
+
+        let jsons: Vec<&str> = taken.iter().map(|&i| self.events[i].json.as_str()).collect();
+        let output = format!("[{}]", jsons.join(","));
+
+        let mut flushed = vec![false; self.events.len()];
+        taken.iter().for_each(|&i| flushed[i] = true);
+        let mut flushed = flushed.into_iter();
+        self.events.retain(|_| !flushed.next().unwrap_or(false));
+        self.bytes = self.events.iter().map(|event| event.json.len()).sum();
+        output
+    }
+
+    /// 导出所有待发送事件为不透明的二进制数据，供宿主持久化（如IndexedDB），不清空缓冲区
+    pub fn export_queue(&self) -> Vec<u8> {
+        let jsons: Vec<&str> = self.events.iter().map(|event| event.json.as_str()).collect();
+        let payload = format!("[{}]", jsons.join(","));
+
+        let mut blob = Vec::with_capacity(QUEUE_HEADER_LEN + payload.len());
+        blob.extend_from_slice(QUEUE_MAGIC);
+        blob.push(QUEUE_VERSION);
+        blob.extend_from_slice(&xxhash64(payload.as_bytes(), 0).to_le_bytes());
+        blob.extend_from_slice(payload.as_bytes());
+        blob
+    }
+
+    /// 导入 `export_queue` 的数据，按原顺序入队并遵守当前上限，返回入队的事件数
+    pub fn import_queue(&mut self, blob: &[u8]) -> Result<usize, String> {
+        if blob.len() < QUEUE_HEADER_LEN || &blob[..QUEUE_MAGIC.len()] != QUEUE_MAGIC {
+            return Err("不是PerfLite事件队列数据".to_string());
+        }
+        if blob[QUEUE_MAGIC.len()] != QUEUE_VERSION {
+            return Err(format!("不支持的事件队列版本: {}", blob[QUEUE_MAGIC.len()]));
+        }
+        let mut checksum = [0u8; 8];
+        checksum.copy_from_slice(&blob[QUEUE_MAGIC.len() + 1..QUEUE_HEADER_LEN]);
+        let payload = &blob[QUEUE_HEADER_LEN..];
+        if u64::from_le_bytes(checksum) != xxhash64(payload, 0) {
+            return Err("事件队列数据已损坏".to_string());
+        }
+
+        let payload = std::str::from_utf8(payload).map_err(|e| format!("事件队列数据已损坏: {}", e))?;
+        let events: Vec<StoredEvent> = serde_json::from_str(payload)
+            .map_err(|e| format!("事件队列数据已损坏: {}", e))?;
+        let mut imported = 0;
+        for event in events {
+            let payload_json = serde_json::to_string(&event.payload).map_err(|e| format!("JSON序列化错误: {}", e))?;
+            if self.enqueue(event.kind, &payload_json)? {
+                imported += 1;
+            }
+        }
+        Ok(imported)
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.bytes = 0;
+    }
+
+    /// 释放队列的多余容量
+    pub fn compact(&mut self) {
+        self.events.shrink_to_fit();
+    }
+
+    pub fn stats(&self) -> EventBufferStats {
+        EventBufferStats {
+            queued: self.events.len(),
+            queued_bytes: self.bytes,
+            dropped_errors: self.dropped_errors,
+            dropped_metrics: self.dropped_metrics,
+        }
+    }
+}
+
+impl Default for EventBuffer {
+    fn default() -> Self {
+        EventBuffer::new(100, 512 * 1024)
+    }
+}
+
+thread_local! {
+    static BUFFER: RefCell<EventBuffer> = RefCell::new(EventBuffer::default());
+}
+
+pub fn enqueue(kind: EventKind, payload_json: &str) -> Result<bool, String> {
+    BUFFER.with(|buffer| buffer.borrow_mut().enqueue(kind, payload_json))
+}
+
+pub fn flush(max_bytes: usize) -> String {
+    BUFFER.with(|buffer| buffer.borrow_mut().flush(max_bytes))
+}
+
+pub fn configure(max_events: usize, max_bytes: usize) {
+    BUFFER.with(|buffer| buffer.borrow_mut().configure(max_events, max_bytes));
+}
+
+pub fn clear() {
+    BUFFER.with(|buffer| buffer.borrow_mut().clear());
+}
+
+pub fn export_queue() -> Vec<u8> {
+    BUFFER.with(|buffer| buffer.borrow().export_queue())
+}
+
+pub fn import_queue(blob: &[u8]) -> Result<usize, String> {
+    BUFFER.with(|buffer| buffer.borrow_mut().import_queue(blob))
+}
+
+pub fn stats() -> EventBufferStats {
+    BUFFER.with(|buffer| buffer.borrow().stats())
+}
+
+pub fn compact() {
+    BUFFER.with(|buffer| buffer.borrow_mut().compact());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_evict_metrics_first() {
+        let mut buffer = EventBuffer::new(3, 4096);
+        assert_eq!(buffer.enqueue(EventKind::Metric, r#"{"lcp":1200}"#), Ok(true));
+        assert_eq!(buffer.enqueue(EventKind::Error, r#"{"message":"a"}"#), Ok(true));
+        assert_eq!(buffer.enqueue(EventKind::Metric, r#"{"cls":0.1}"#), Ok(true));
+        assert_eq!(buffer.enqueue(EventKind::Error, r#"{"message":"b"}"#), Ok(true));
+
+        // 最早的指标被淘汰
+        let stats = buffer.stats();
+        assert_eq!((stats.queued, stats.dropped_metrics), (3, 1));
+
+        // 只剩错误时，新指标无法挤掉错误
+        assert_eq!(buffer.enqueue(EventKind::Error, r#"{"message":"c"}"#), Ok(true));
+        assert_eq!(buffer.enqueue(EventKind::Metric, r#"{"fid":8}"#), Ok(false));
+        assert_eq!(buffer.stats().dropped_metrics, 3);
+        assert!(buffer.enqueue(EventKind::Error, "not json").is_err());
+    }
+
+    #[test]
+    fn test_flush_respects_max_bytes() {
+        let mut buffer = EventBuffer::default();
+        buffer.enqueue(EventKind::Metric, r#"{"lcp":1200}"#).unwrap();
+        buffer.enqueue(EventKind::Error, r#"{"message":"a"}"#).unwrap();
+        buffer.enqueue(EventKind::Error, r#"{"message":"b"}"#).unwrap();
+
+        // 70字节只放得下一个事件
+        assert_eq!(buffer.flush(70), r#"[{"type":"error","payload":{"message":"a"}}]"#);
+        assert_eq!(buffer.stats().queued, 2);
+
+        let rest = buffer.flush(4096);
+        assert_eq!(rest, r#"[{"type":"error","payload":{"message":"b"}},{"type":"metric","payload":{"lcp":1200}}]"#);
+        assert_eq!(buffer.stats().queued_bytes, 0);
+        assert_eq!(buffer.flush(4096), "[]");
+    }
+
+    #[test]
+    fn test_export_import_queue() {
+        let mut buffer = EventBuffer::default();
+        buffer.enqueue(EventKind::Metric, r#"{"lcp":1200}"#).unwrap();
+        buffer.enqueue(EventKind::Error, r#"{"message":"a"}"#).unwrap();
+        let blob = buffer.export_queue();
+        assert_eq!(buffer.stats().queued, 2);
+
+        let mut restored = EventBuffer::default();
+        assert_eq!(restored.import_queue(&blob), Ok(2));
+        assert_eq!(restored.flush(4096), buffer.flush(4096));
+
+        let mut corrupted = blob.clone();
+        let last = corrupted.len() - 2;
+        corrupted[last] ^= 1;
+        assert!(restored.import_queue(&corrupted).is_err());
+        assert!(restored.import_queue(b"garbage").is_err());
+        assert_eq!(restored.import_queue(&EventBuffer::default().export_queue()), Ok(0));
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use crate::errors::ParseError;
+use crate::parser::{FrameOrigin, StackFrame};
+use crate::parser_options;
+use crate::source_context::SourceContext;
+
+/// 用于从WASM导出的栈帧结构体
+#[derive(Serialize, Deserialize)]
+pub struct ExportedStackFrame {
+    pub function_name: String,
+    /// 规范化后的函数名，与原名相同时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalized_function_name: Option<String>,
+    /// 函数名像压缩器生成的短名，此时 `display_name` 为按文件与位置合成的展示名
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub has_minified_name: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    pub file_name: String,
+    pub line_number: u32,
+    pub column_number: u32,
+    #[serde(default)]
+    pub origin: FrameOrigin,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<SourceContext>,
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub async_boundary: bool,
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub is_async: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promise_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub browser_internal: bool,
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub node_internal: bool,
+    /// 来自与构建清单不符的旧版产物
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub stale_bundle: bool,
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub minified: bool,
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub is_native: bool,
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub hermes_bytecode: bool,
+    /// 无法识别格式的原始行，`function_name` 为整行文本
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub raw: bool,
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub is_eval: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eval_origin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm_function: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm_offset: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<u64>,
+}
+
+impl From<StackFrame> for ExportedStackFrame {
+    fn from(frame: StackFrame) -> Self {
+        frame.into_exported()
+    }
+}
+
+impl From<ExportedStackFrame> for StackFrame {
+    fn from(exported: ExportedStackFrame) -> Self {
+        let positions = parser_options::options();
+        let mut frame = StackFrame::new(
+            exported.function_name,
+            exported.file_name,
+            positions.import_line(exported.line_number),
+            positions.import_column(exported.column_number),
+        );
+        frame.extend_annotations(exported.annotations);
+        if let Some(context) = exported.context {
+            frame.set_context(context);
+        }
+        frame.set_async_boundary(exported.async_boundary);
+        frame.set_async(exported.is_async);
+        frame.set_promise_index(exported.promise_index);
+        frame.set_minified(exported.minified);
+        frame.set_native(exported.is_native);
+        frame.set_hermes_bytecode(exported.hermes_bytecode);
+        frame.set_raw(exported.raw);
+        frame.set_eval(exported.is_eval, exported.eval_origin);
+        frame.set_wasm_location(exported.wasm_function, exported.wasm_offset);
+        frame.set_address(exported.address);
+        frame.set_origin(exported.origin);
+        frame.set_framework(exported.framework);
+        frame
+    }
+}
+
+// 必须出现的位置字段与可选的数值字段，取值都不能为负或超出范围
+const POSITION_FIELDS: [&str; 2] = ["line_number", "column_number"];
+const OPTIONAL_U32_FIELDS: [&str; 3] = ["promise_index", "wasm_function", "wasm_offset"];
+
+/// 校验单个JSON栈帧：文件名非空（原始行帧的文件名为空），行列号与数值字段为不超过上限的非负整数
+fn check_frame(frame: &Value) -> Result<(), String> {
+    let raw = frame.get("raw").and_then(Value::as_bool).unwrap_or(false);
+    if !frame.get("file_name").and_then(Value::as_str).is_some_and(|file| raw || !file.is_empty()) {
+        return Err("缺少文件名".to_string());
+    }
+    let in_range = |value: &Value, max: u64| value.as_u64().is_some_and(|n| n <= max);
+    for key in POSITION_FIELDS {
+        match frame.get(key) {
+            Some(value) if in_range(value, u32::MAX as u64) => {}
+            Some(value) => return Err(format!("{} 不是有效的位置: {}", key, value)),
+            None => return Err(format!("缺少 {}", key)),
+        }
+    }
+    for key in OPTIONAL_U32_FIELDS {
+        if let Some(value) = frame.get(key).filter(|value| !value.is_null() && !in_range(value, u32::MAX as u64)) {
+            return Err(format!("{} 超出范围: {}", key, value));
+        }
+    }
+    if let Some(value) = frame.get("address").filter(|value| !value.is_null() && !in_range(value, u64::MAX)) {
+        return Err(format!("address 超出范围: {}", value));
+    }
+    Ok(())
+}
+
+/// 还原snake_case键的JSON栈帧数组，任一栈帧无效时返回错误而不是填充默认值
+pub(crate) fn import_frames(frames: Vec<Value>) -> Result<Vec<StackFrame>, ParseError> {
+    frames.into_iter().enumerate()
+        .map(|(index, frame)| {
+            let invalid = |reason: String| ParseError::InvalidFrame { index, reason };
+            check_frame(&frame).map_err(invalid)?;
+            serde_json::from_value::<ExportedStackFrame>(frame).map(StackFrame::from).map_err(|e| invalid(e.to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,281 @@
+use serde::Serialize;
+use crate::hash::{self, HashAlgorithm};
+use crate::parser::{ErrorParser, StackFrame};
+use crate::grouping::message_template;
+use crate::parser_options::{self, ParserOptions, WeakStrategy};
+use crate::simd::LineIndex;
+use crate::taxonomy;
+
+/// 第三方包帧的规范化路径：`.../node_modules/@scope/pkg/dist/x.js` -> `node_modules/@scope/pkg`
+fn collapse_node_modules(file: &str) -> Option<String> {
+    let (_, rest) = file.rsplit_once("node_modules/")?;
+    let mut segments = rest.split('/');
+    let first = segments.next().filter(|s| !s.is_empty())?;
+    let package = if first.starts_with('@') {
+        format!("{}/{}", first, segments.next().unwrap_or(""))
+    } else {
+        first.to_string()
+    };
+    Some(format!("node_modules/{}", package))
+}
+
+/// 去掉查询参数与锚点，打包产物的版本号通常以 `?v=` 形式出现
+pub(crate) fn strip_query(file: &str) -> &str {
+    file.split(['?', '#']).next().unwrap_or(file)
+}
+
+/// 规范化后的栈帧文本：使用规范化的函数名，行号按 `options` 量化，默认不含列号；
+/// 第三方包帧只保留包名，连续属于同一个包的帧合并为一行；保留的原始行不计入
+pub fn normalize_frames_with(frames: &[StackFrame], options: &ParserOptions) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::with_capacity(frames.len());
+    for frame in frames.iter().filter(|frame| !frame.raw()) {
+        let file = strip_query(frame.relative_file_name());
+        let line = match collapse_node_modules(file) {
+            Some(package) => package,
+            None if options.ignore_columns => format!("{}|{}:{}", frame.normalized_function_name(), file, options.quantize_line(frame.line_number())),
+            None => format!("{}|{}:{}:{}", frame.normalized_function_name(), file, options.quantize_line(frame.line_number()), frame.fingerprint_column()),
+        };
+        if lines.last() != Some(&line) {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+/// 启用polyglot时识别Python traceback与JVM异常栈，返回其首行与栈帧
+#[cfg(feature = "polyglot")]
+fn polyglot_stack(stack: &str) -> Option<(String, Vec<StackFrame>)> {
+    crate::polyglot::parse(stack).map(|parsed| (parsed.header, parsed.frames))
+}
+
+#[cfg(not(feature = "polyglot"))]
+fn polyglot_stack(_stack: &str) -> Option<(String, Vec<StackFrame>)> {
+    None
+}
+
+/// 计算栈的64位指纹（十六进制）：错误类型加规范化后的栈帧；没有栈帧时使用错误消息
+pub fn fingerprint_with(stack: &str, algorithm: HashAlgorithm) -> String {
+    format!("{:016x}", fingerprint_hash(stack, &parser_options::options(), algorithm))
+}
+
+/// 按指定的行列号容差计算数值指纹，持久化的基线按记录时的算法与容差重算
+pub fn fingerprint_hash(stack: &str, options: &ParserOptions, algorithm: HashAlgorithm) -> u64 {
+    if let Some((header, frames)) = polyglot_stack(stack) {
+        return frames_hash(&header, &frames, options, algorithm);
+    }
+    let frames = ErrorParser::new().parse_frames(stack);
+    frames_hash(LineIndex::new(stack).line(0).unwrap_or(""), &frames, options, algorithm)
+}
+
+/// 按原始栈与调用方已解析的JS栈帧计算指纹，与 `fingerprint_with` 一致：
+/// 启用polyglot时Python/JVM栈按各自的格式重新解析，不使用传入的首行与栈帧
+pub fn fingerprint_parsed(stack: &str, header: &str, frames: &[StackFrame], algorithm: HashAlgorithm) -> String {
+    match polyglot_stack(stack) {
+        Some((header, frames)) => fingerprint_frames(&header, &frames, algorithm),
+        None => fingerprint_frames(header, frames, algorithm),
+    }
+}
+
+/// 特殊错误形态（跨域脚本错误、CSP拦截、分块加载失败）按类别与专用分组键计算的指纹
+fn category_hash(header: &str, algorithm: HashAlgorithm) -> Option<u64> {
+    let classification = taxonomy::classify(header)?;
+    let input = format!("category:{}\n{}", classification.category.as_str(), classification.key);
+    Some(algorithm.hash_u64(input.as_bytes()))
+}
+
+/// 按已解析的栈帧计算指纹，`header` 为栈的首行；特殊错误形态不使用栈帧
+pub fn fingerprint_frames(header: &str, frames: &[StackFrame], algorithm: HashAlgorithm) -> String {
+    format!("{:016x}", frames_hash(header, frames, &parser_options::options(), algorithm))
+}
+
+fn frames_hash(header: &str, frames: &[StackFrame], options: &ParserOptions, algorithm: HashAlgorithm) -> u64 {
+    if let Some(hash) = category_hash(header, algorithm) {
+        return hash;
+    }
+    let (error_type, message) = ErrorParser::parse_error_header(header);
+
+    let mut input = error_type.unwrap_or_default();
+    if frames.is_empty() {
+        input.push('\n');
+        input.push_str(&message);
+    }
+    for line in normalize_frames_with(frames, options) {
+        input.push('\n');
+        input.push_str(&line);
+    }
+    algorithm.hash_u64(input.as_bytes())
+}
+
+/// 使用全局指纹算法计算指纹
+pub fn fingerprint(stack: &str) -> String {
+    fingerprint_with(stack, hash::fingerprint_algorithm())
+}
+
+/// 弱指纹：按 `options` 的弱指纹方式只取错误类型与少量不含行列号的栈帧（或消息模板），
+/// 强指纹不同但弱指纹相同的分组可在后端合并
+pub fn weak_fingerprint_frames(header: &str, frames: &[StackFrame], options: &ParserOptions, algorithm: HashAlgorithm) -> String {
+    if let Some(hash) = category_hash(header, algorithm) {
+        return format!("{:016x}", hash);
+    }
+    let (error_type, message) = ErrorParser::parse_error_header(header);
+    let take = options.weak_frames.max(1) as usize;
+    let selected: Vec<&StackFrame> = match options.weak_strategy {
+        WeakStrategy::InAppFrames => {
+            let in_app: Vec<&StackFrame> = frames.iter().filter(|frame| frame.is_in_app()).take(take).collect();
+            if in_app.is_empty() { frames.iter().filter(|frame| !frame.raw()).take(1).collect() } else { in_app }
+        }
+        WeakStrategy::TopFrames => frames.iter().filter(|frame| !frame.raw()).take(take).collect(),
+        WeakStrategy::MessageTemplate => Vec::new(),
+    };
+
+    let mut input = format!("weak:{}", error_type.unwrap_or_default());
+    if selected.is_empty() {
+        input.push('\n');
+        input.push_str(&message_template(&message));
+    }
+    for frame in selected {
+        input.push_str(&format!("\n{}|{}", frame.normalized_function_name(), strip_query(frame.relative_file_name())));
+    }
+    format!("{:016x}", algorithm.hash_u64(input.as_bytes()))
+}
+
+/// 强指纹（与 `fingerprint` 相同）与按全局选项计算的弱指纹，`header`、`frames` 为调用方从 `stack` 解析的JS首行与栈帧，
+/// 启用polyglot时与 `fingerprint_parsed` 一样按Python/JVM栈计算
+pub fn dual_fingerprints(stack: &str, header: &str, frames: &[StackFrame]) -> (String, String) {
+    let algorithm = hash::fingerprint_algorithm();
+    let polyglot = polyglot_stack(stack);
+    let (header, frames) = match &polyglot {
+        Some((header, frames)) => (header.as_str(), frames.as_slice()),
+        None => (header, frames),
+    };
+    (fingerprint_frames(header, frames, algorithm), weak_fingerprint_frames(header, frames, &parser_options::options(), algorithm))
+}
+
+/// 回放中指纹发生变化的一条样本
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FingerprintChange {
+    /// 样本在输入中的下标
+    pub index: usize,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// 黄金样本回放结果
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ReplayReport {
+    /// 计算指纹的解析器版本
+    pub parser_version: String,
+    pub total: usize,
+    pub changed: Vec<FingerprintChange>,
+}
+
+/// 用当前解析器重算 (原始栈, 期望指纹) 样本，报告指纹发生变化的条目，
+/// 用于在升级wasm前评估分组变动
+pub fn replay(cases: &[(String, String)]) -> ReplayReport {
+    let changed = cases.iter()
+        .enumerate()
+        .filter_map(|(index, (stack, expected))| {
+            let actual = fingerprint(stack);
+            (actual != *expected).then(|| FingerprintChange { index, expected: expected.clone(), actual })
+        })
+        .collect();
+    ReplayReport {
+        parser_version: env!("CARGO_PKG_VERSION").to_string(),
+        total: cases.len(),
+        changed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_survives_bundle_changes() {
+        let a = "TypeError: x is undefined\n at render (https://cdn.test/app.js?v=1:10:15)\n at r (https://cdn.test/node_modules/react-dom/cjs/a.js:100:1)\n at c (https://cdn.test/node_modules/react-dom/cjs/b.js:5:2)";
+        let b = "TypeError: y is undefined\n at render (https://cdn.test/app.js?v=2:10:99)\n at r (https://cdn.test/node_modules/react-dom/cjs/a.js:204:1)";
+        let fingerprint_a = fingerprint_with(a, HashAlgorithm::XxHash64);
+        assert_eq!(fingerprint_a.len(), 16);
+        assert_eq!(fingerprint_a, fingerprint_with(b, HashAlgorithm::XxHash64));
+
+        // 业务代码行号或错误类型变化时指纹不同
+        assert_ne!(fingerprint_a, fingerprint_with(&a.replace(":10:15", ":11:15"), HashAlgorithm::XxHash64));
+        assert_ne!(fingerprint_a, fingerprint_with(&a.replace("TypeError", "RangeError"), HashAlgorithm::XxHash64));
+        assert_eq!(fingerprint_with(a, HashAlgorithm::Blake3).len(), 16);
+
+        // 没有栈帧时按消息区分
+        assert_ne!(fingerprint_with("Error: a", HashAlgorithm::XxHash64), fingerprint_with("Error: b", HashAlgorithm::XxHash64));
+
+        // 分块加载失败只按类别与主机分组，不受分块ID与栈帧影响
+        let chunk = |id: u32, caller: &str| fingerprint_with(&format!("ChunkLoadError: Loading chunk {} failed. (error: https://cdn.test/{}.js)\n at {} (https://cdn.test/runtime.js:1:1)", id, id, caller), HashAlgorithm::XxHash64);
+        assert_eq!(chunk(1, "a"), chunk(2, "b"));
+        assert_ne!(chunk(1, "a"), fingerprint_with("Script error.", HashAlgorithm::XxHash64));
+    }
+
+    #[test]
+    fn test_weak_fingerprint() {
+        let parser = ErrorParser::new();
+        let header = "TypeError: x is undefined";
+        let weak = |stack: &str, options: &ParserOptions| weak_fingerprint_frames(header, &parser.parse_frames(stack), options, HashAlgorithm::XxHash64);
+        let stack = "TypeError: x\n at render (https://cdn.test/app.js:10:15)\n at r (https://cdn.test/node_modules/react-dom/a.js:1:1)\n at update (https://cdn.test/app.js:40:2)\n at flush (https://cdn.test/app.js:90:2)";
+        let options = ParserOptions::default();
+
+        // 行号变化、第4个业务代码帧之后的调用方变化只影响强指纹
+        let moved = stack.replace(":10:15", ":12:1").replace(" at flush (https://cdn.test/app.js:90:2)", " at flush (https://cdn.test/app.js:90:2)\n at main (https://cdn.test/app.js:1:1)");
+        assert_eq!(weak(stack, &options), weak(&moved, &options));
+        assert_ne!(fingerprint_with(stack, HashAlgorithm::XxHash64), fingerprint_with(&moved, HashAlgorithm::XxHash64));
+        assert_ne!(weak(stack, &options), weak(&stack.replace("update", "paint"), &options));
+
+        // 只取栈顶帧时第三方帧也参与
+        let top = options.with_weak_fingerprint(WeakStrategy::TopFrames, 2);
+        assert_eq!(weak(stack, &top), weak(&stack.replace("update", "paint"), &top));
+        assert_ne!(weak(stack, &top), weak(&stack.replace("react-dom", "vue"), &top));
+
+        // 消息模板方式忽略栈帧与消息中的数字
+        let message = options.with_weak_fingerprint(WeakStrategy::MessageTemplate, 0);
+        assert_eq!(weak(stack, &message), weak("Error: y", &message));
+        assert_eq!(weak_fingerprint_frames("Error: code 42", &[], &message, HashAlgorithm::XxHash64), weak_fingerprint_frames("Error: code 7", &[], &message, HashAlgorithm::XxHash64));
+        assert_eq!(WeakStrategy::from_name("Top_Frames"), Some(WeakStrategy::TopFrames));
+
+        // 没有业务代码帧时取栈顶帧，原始行不参与，弱指纹不随 `raw_lines` 开关变化
+        let vendor = parser.parse_frames("TypeError: x\n at r (https://cdn.test/node_modules/react-dom/a.js:1:1)");
+        let mut with_raw = vendor.clone();
+        with_raw.insert(0, StackFrame::raw_line("    [truncated 3 frames]"));
+        assert_eq!(weak_fingerprint_frames(header, &with_raw, &options, HashAlgorithm::XxHash64), weak_fingerprint_frames(header, &vendor, &options, HashAlgorithm::XxHash64));
+    }
+
+    #[test]
+    fn test_normalize_frames() {
+        let frames = vec![
+            StackFrame::new("f".to_string(), "/app/node_modules/@scope/pkg/dist/index.js".to_string(), 3, 4),
+            StackFrame::new("g".to_string(), "/src/main.js#top".to_string(), 7, 8),
+        ];
+        assert_eq!(normalize_frames_with(&frames, &parser_options::options()), vec!["node_modules/@scope/pkg", "g|/src/main.js:7"]);
+
+        // 包装前缀与生成后缀不影响指纹
+        let wrapped = vec![StackFrame::new("__webpack_exports__.g$1".to_string(), "/src/main.js".to_string(), 7, 1)];
+        assert_eq!(normalize_frames_with(&wrapped, &parser_options::options()), vec!["g|/src/main.js:7"]);
+
+        // 行号量化与列号
+        let bucketed = ParserOptions::default().with_fingerprint_tolerance(true, 5);
+        assert_eq!(normalize_frames_with(&frames[1..], &bucketed), vec!["g|/src/main.js:5"]);
+        let with_columns = bucketed.with_fingerprint_tolerance(false, 5);
+        assert_eq!(normalize_frames_with(&frames[1..], &with_columns), vec!["g|/src/main.js:5:8"]);
+    }
+
+    #[test]
+    fn test_replay() {
+        let stack = "TypeError: x\n at render (https://cdn.test/app.js:10:15)".to_string();
+        let cases = vec![
+            (stack.clone(), fingerprint(&stack)),
+            ("Error: moved".to_string(), "0000000000000000".to_string()),
+        ];
+        let report = replay(&cases);
+        assert_eq!(report.total, 2);
+        assert_eq!(report.changed, vec![FingerprintChange {
+            index: 1,
+            expected: "0000000000000000".to_string(),
+            actual: fingerprint("Error: moved"),
+        }]);
+    }
+}
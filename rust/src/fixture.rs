@@ -0,0 +1,206 @@
+use crate::hash::xxhash64;
+use std::ops::Range;
+
+/// 未指定种子时使用的固定种子，同一输入总是得到同一夹具
+pub const DEFAULT_SEED: u64 = 0x504c_4649_5854_5552;
+
+// 原样保留的单词：引擎与运行时的固定写法、协议名、扩展名与打包工具的约定目录，决定方言与URL形态
+const KEPT_WORDS: [&str; 54] = [
+    "at", "async", "await", "eval", "new", "anonymous", "native", "code", "module", "global", "address",
+    "Promise", "all", "allSettled", "any", "race", "then", "catch", "finally", "index", "Array", "Object", "Function",
+    "Uncaught", "in", "promise", "http", "https", "file", "blob", "data", "webpack", "node", "internal",
+    "node_modules", "chrome", "moz", "safari", "web", "extension", "resource", "wasm", "function", "js", "mjs",
+    "cjs", "jsx", "ts", "tsx", "vue", "src", "assets", "chunk", "__webpack_require__",
+];
+
+// 原样保留的内置错误类型：JS、DOM、wasm与打包工具抛出的错误，其余 `*Error` 只保留后缀
+const BUILTIN_ERRORS: [&str; 22] = [
+    "Error", "TypeError", "RangeError", "ReferenceError", "SyntaxError", "EvalError", "URIError", "AggregateError",
+    "InternalError", "DOMException", "RuntimeError", "CompileError", "LinkError", "SecurityError", "NotFoundError",
+    "AbortError", "NetworkError", "QuotaExceededError", "InvalidStateError", "NotAllowedError", "TimeoutError", "ChunkLoadError",
+];
+
+// 自定义错误类型名保留的后缀
+const ERROR_SUFFIXES: [&str; 2] = ["Error", "Exception"];
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// 是否原样保留：固定写法、纯数字（行列号）与内置错误类型名
+fn is_kept(word: &str) -> bool {
+    KEPT_WORDS.contains(&word)
+        || word.bytes().all(|b| b.is_ascii_digit())
+        || BUILTIN_ERRORS.contains(&word)
+}
+
+/// 按单词与种子哈希生成同长度、同字符类别的随机单词；含数字的小写十六进制串（文件名哈希）仍生成十六进制
+fn scramble(word: &str, seed: u64) -> String {
+    let hex = word.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        && word.bytes().any(|b| b.is_ascii_digit())
+        && !word.bytes().all(|b| b.is_ascii_digit());
+    let mut seed = seed;
+    loop {
+        let mut state = xxhash64(word.as_bytes(), seed);
+        let mut next = |range: u64| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) % range
+        };
+        let scrambled: String = word.chars()
+            .map(|c| match c {
+                '_' | '$' => c,
+                _ if hex => char::from(b"0123456789abcdef"[next(16) as usize]),
+                '0'..='9' => char::from(b'0' + next(10) as u8),
+                'A'..='Z' => char::from(b'A' + next(26) as u8),
+                _ => char::from(b'a' + next(26) as u8),
+            })
+            .collect();
+        // 避免恰好生成固定写法而改变结构；纯数字只出现在需要抹除的主机与端口中，原样接受
+        if scrambled.bytes().all(|b| b.is_ascii_digit()) || !is_kept(&scrambled) {
+            return scrambled;
+        }
+        seed = seed.wrapping_add(1);
+    }
+}
+
+/// 替换单词：自定义错误类型（`AcmeBillingError`）只替换后缀之前的部分
+fn anonymize(word: &str, seed: u64) -> String {
+    let custom_error = ERROR_SUFFIXES.iter()
+        .find_map(|suffix| word.strip_suffix(suffix).filter(|prefix| !prefix.is_empty()).map(|prefix| (prefix, suffix)));
+    match custom_error {
+        Some((prefix, suffix)) => format!("{}{}", scramble(prefix, seed), suffix),
+        None => scramble(word, seed),
+    }
+}
+
+/// 需要抹除的数字所在区间：URL授权部分（`scheme://` 之后到路径之前，含IP与端口）与点分IPv4地址及其端口，
+/// 其余纯数字按行列号原样保留
+fn host_spans(stack: &str) -> Vec<Range<usize>> {
+    let bytes = stack.as_bytes();
+    let mut spans = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = stack[from..].find("://") {
+        let start = from + offset + 3;
+        let end = stack[start..].find(|c: char| c == '/' || c == ')' || c.is_whitespace()).map_or(stack.len(), |len| start + len);
+        spans.push(start..end);
+        from = end;
+    }
+    let mut i = 0;
+    while i < bytes.len() {
+        let boundary = i == 0 || !(is_identifier_char(char::from(bytes[i - 1])) || bytes[i - 1] == b'.');
+        match dotted_ipv4_len(&bytes[i..]).filter(|_| boundary && bytes[i].is_ascii_digit()) {
+            Some(len) => {
+                spans.push(i..i + len);
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    spans
+}
+
+/// 开头是否为点分IPv4地址（可带 `:端口`），返回其字节长度
+fn dotted_ipv4_len(bytes: &[u8]) -> Option<usize> {
+    let digits = |at: usize| bytes[at.min(bytes.len())..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut len = 0;
+    for octet in 0..4 {
+        if octet > 0 {
+            if bytes.get(len) != Some(&b'.') {
+                return None;
+            }
+            len += 1;
+        }
+        let count = digits(len);
+        if !(1..=3).contains(&count) {
+            return None;
+        }
+        len += count;
+    }
+    if bytes.get(len).is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'.') {
+        return None;
+    }
+    if bytes.get(len) == Some(&b':') && digits(len + 1) > 0 {
+        len += 1 + digits(len + 1);
+    }
+    Some(len)
+}
+
+/// 生成可分享的匿名化夹具：保留方言写法、帧数、标点、行列号与URL形态，其余标识符按单词确定性地随机替换，
+/// 同一单词在整个栈中替换为同一结果；URL中的主机、IP与端口同样替换，用户上报解析失败时不会泄露私有路径与主机。
+/// 同一种子总是得到同一夹具，便于加入测试语料；需要防止按候选单词哈希还原时由调用方传入随机种子
+pub fn make_fixture(stack: &str, seed: u64) -> String {
+    let hosts = host_spans(stack);
+    let mut fixture = String::with_capacity(stack.len());
+    let mut rest = stack;
+    while let Some(start) = rest.find(is_identifier_char) {
+        fixture.push_str(&rest[..start]);
+        let end = rest[start..].find(|c: char| !is_identifier_char(c)).map_or(rest.len(), |len| start + len);
+        let word = &rest[start..end];
+        let offset = stack.len() - rest.len() + start;
+        let host = word.bytes().all(|b| b.is_ascii_digit()) && hosts.iter().any(|span| span.contains(&offset));
+        if is_kept(word) && !host {
+            fixture.push_str(word);
+        } else {
+            fixture.push_str(&anonymize(word, seed));
+        }
+        rest = &rest[end..];
+    }
+    fixture.push_str(rest);
+    fixture
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ErrorParser;
+
+    #[test]
+    fn test_make_fixture() {
+        let stack = "TypeError: Cannot read properties of undefined (reading 'customerId')\n    at renderInvoice (https://billing.acme.test/static/js/main.3f9a2b1c.js:10:15)\n    at async Promise.all (index 0)\n    at Object.loadAccount (webpack://acme-billing/./src/account/load.ts:42:7)\n    at /home/jdoe/acme/node_modules/react-dom/index.js:1:1";
+        let fixture = make_fixture(stack, DEFAULT_SEED);
+        assert_eq!(fixture, make_fixture(stack, DEFAULT_SEED));
+        assert_ne!(fixture, make_fixture(stack, 7));
+        for secret in ["acme", "billing", "customerId", "renderInvoice", "jdoe", "loadAccount"] {
+            assert!(!fixture.contains(secret), "{}", fixture);
+        }
+        assert!(fixture.starts_with("TypeError: "));
+        assert!(fixture.contains("\n    at async Promise.all (index 0)\n"));
+        assert!(fixture.contains(".js:10:15)") && fixture.contains("/node_modules/") && fixture.contains("webpack://"));
+
+        // 结构不变：方言与帧数相同，同一单词替换结果一致
+        let parser = ErrorParser::new();
+        let (original, anonymized) = (parser.parse_frames(stack), parser.parse_frames(&fixture));
+        assert_eq!(parser.detect_dialect(&fixture), parser.detect_dialect(stack));
+        assert_eq!(anonymized.len(), original.len());
+        assert_eq!(anonymized[0].line_number(), 10);
+        assert_eq!(make_fixture("acme acme", 7), format!("{0} {0}", make_fixture("acme", 7)));
+        // 自定义错误类型只保留后缀
+        let custom = make_fixture("AcmeBillingError: x\n    at f (/a.js:1:1)", DEFAULT_SEED);
+        let error_type = custom.split(':').next().unwrap();
+        assert!(error_type.len() == "AcmeBillingError".len() && error_type.ends_with("Error") && !custom.contains("Acme"), "{}", custom);
+        // 文件名哈希保持十六进制
+        let hash = make_fixture("3f9a2b1c", DEFAULT_SEED);
+        assert!(hash.len() == 8 && hash.bytes().all(|b| b.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_make_fixture_keeps_url_shape_and_scrubs_hosts() {
+        let extension = make_fixture("    at init (safari-web-extension://3f9a2b1c/content.js:3:9)", DEFAULT_SEED);
+        assert!(extension.contains("(safari-web-extension://") && extension.ends_with(".js:3:9)"), "{}", extension);
+
+        let stack = "Error: connect ECONNREFUSED 10.20.30.40:5432\n    at load (http://192.168.1.17:8080/app.js:10:15)";
+        let fixture = make_fixture(stack, DEFAULT_SEED);
+        for secret in ["10.20.30.40", "5432", "192.168.1.17", "8080"] {
+            assert!(!fixture.contains(secret), "{}", fixture);
+        }
+        // IP与端口仍是同形态的数字，行列号原样保留
+        let url = fixture.split("(http://").nth(1).unwrap();
+        let (authority, location) = url.split_once('/').unwrap();
+        assert!(authority.len() == "192.168.1.17:8080".len() && authority.bytes().all(|b| b.is_ascii_digit() || b == b'.' || b == b':'));
+        assert!(location.ends_with(".js:10:15)"), "{}", fixture);
+        let parser = ErrorParser::new();
+        let (original, anonymized) = (parser.parse_frames(stack), parser.parse_frames(&fixture));
+        assert_eq!(anonymized.len(), original.len());
+        assert_eq!(anonymized.last().map(|frame| frame.line_number()), Some(10));
+    }
+}
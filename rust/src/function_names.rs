@@ -0,0 +1,122 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+
+// webpack模块包装产生的接收者前缀
+const WEBPACK_PREFIXES: [&str; 4] = ["__webpack_exports__.", "__webpack_require__.", "__webpack_modules__.", "__WEBPACK_IMPORTED_MODULE_"];
+
+// 内置对象的接收者前缀，如 `Array.map`、`Object.keys`
+const BUILTIN_RECEIVERS: [&str; 7] = ["Object.", "Array.", "Function.", "Promise.", "String.", "Number.", "Boolean."];
+
+/// 函数名规范化配置，JSON为 `{"strip_bound": false}`，缺省的规则均启用
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct NameNormalization {
+    /// 去掉 `__webpack_exports__.` 等webpack包装前缀
+    pub strip_webpack_wrappers: bool,
+    /// 去掉内置对象接收者与 `.prototype.`
+    pub strip_prototypes: bool,
+    /// 去掉 `bound ` 前缀
+    pub strip_bound: bool,
+    /// 去掉打包器生成的 `$1` 后缀
+    pub strip_generated_suffix: bool,
+}
+
+impl Default for NameNormalization {
+    fn default() -> Self {
+        NameNormalization {
+            strip_webpack_wrappers: true,
+            strip_prototypes: true,
+            strip_bound: true,
+            strip_generated_suffix: true,
+        }
+    }
+}
+
+impl NameNormalization {
+    /// 规范化函数名，结果为空时保留原名
+    pub fn normalize(&self, name: &str) -> String {
+        let mut name = name.trim();
+        if self.strip_bound {
+            while let Some(rest) = name.strip_prefix("bound ") {
+                name = rest.trim_start();
+            }
+        }
+
+        let mut normalized = name.to_string();
+        if self.strip_webpack_wrappers {
+            for prefix in WEBPACK_PREFIXES {
+                if let Some(rest) = normalized.strip_prefix(prefix) {
+                    // `__WEBPACK_IMPORTED_MODULE_0_react__.x` 形式还要跳过模块名
+                    normalized = if prefix.ends_with('_') {
+                        rest.split_once("__.").map_or(rest, |(_, name)| name).to_string()
+                    } else {
+                        rest.to_string()
+                    };
+                }
+            }
+        }
+        if self.strip_prototypes {
+            normalized = normalized.replace(".prototype.", ".");
+            if let Some(rest) = BUILTIN_RECEIVERS.iter().find_map(|receiver| normalized.strip_prefix(receiver)) {
+                normalized = rest.to_string();
+            }
+        }
+        if self.strip_generated_suffix {
+            if let Some((base, suffix)) = normalized.rsplit_once('$') {
+                if !base.is_empty() && !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit()) {
+                    normalized.truncate(base.len());
+                }
+            }
+        }
+
+        if normalized.is_empty() { name.to_string() } else { normalized }
+    }
+}
+
+/// 是否像压缩器生成的函数名：最后一段为1–2个字符的标识符（`t`、`Zr`、`e.n`），
+/// 单独的 `$`、`_` 通常是库名，不计入
+pub fn is_minified_name(name: &str) -> bool {
+    let last = name.rsplit('.').next().unwrap_or(name);
+    (1..=2).contains(&last.len())
+        && !matches!(last, "$" | "_")
+        && last.chars().all(|c| c.is_ascii_alphanumeric() || c == '$' || c == '_')
+        && !last.starts_with(|c: char| c.is_ascii_digit())
+}
+
+thread_local! {
+    static NORMALIZATION: RefCell<NameNormalization> = RefCell::new(NameNormalization::default());
+}
+
+/// 替换全局函数名规范化配置
+pub fn set_normalization(normalization: NameNormalization) {
+    NORMALIZATION.with(|current| *current.borrow_mut() = normalization);
+}
+
+/// 按全局配置规范化函数名
+pub fn normalize(name: &str) -> String {
+    NORMALIZATION.with(|normalization| normalization.borrow().normalize(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let normalization = NameNormalization::default();
+        assert_eq!(normalization.normalize("__webpack_exports__.render"), "render");
+        assert_eq!(normalization.normalize("__WEBPACK_IMPORTED_MODULE_0_react__.createElement"), "createElement");
+        assert_eq!(normalization.normalize("Array.map"), "map");
+        assert_eq!(normalization.normalize("Cart.prototype.checkout"), "Cart.checkout");
+        assert_eq!(normalization.normalize("bound bound onClick"), "onClick");
+        assert_eq!(normalization.normalize("handler$12"), "handler");
+        assert_eq!(normalization.normalize("$1"), "$1");
+        assert_eq!(normalization.normalize("<anonymous>"), "<anonymous>");
+
+        assert!(is_minified_name("t") && is_minified_name("Zr") && is_minified_name("e.n") && is_minified_name("$a"));
+        assert!(!is_minified_name("render") && !is_minified_name("$") && !is_minified_name("<anonymous>") && !is_minified_name("42") && !is_minified_name(""));
+
+        let keep_bound = NameNormalization { strip_bound: false, ..NameNormalization::default() };
+        assert_eq!(keep_bound.normalize("bound onClick$1"), "bound onClick");
+    }
+}
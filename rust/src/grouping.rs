@@ -0,0 +1,306 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use crate::hash::{self, HashAlgorithm};
+use crate::parser::{ErrorParser, StackFrame};
+use crate::severity::{self, Severity};
+use crate::simd::LineIndex;
+use crate::storm::{StormDetector, StormEvent, StormPolicy};
+use crate::utils::now_ms;
+
+/// 参与分组的要素
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupingRule {
+    /// 栈顶的业务代码帧（函数名与文件，不含行列号）
+    TopInAppFrame,
+    /// 错误类型，如 `TypeError`
+    ErrorType,
+    /// 去掉数字、ID、引号内容后的消息模板
+    MessageTemplate,
+    /// 严重级别，同一错误的致命与已处理事件分为不同的组
+    Severity,
+}
+
+/// 分组配置，JSON为 `{"rules": ["error_type", "top_in_app_frame"], "in_app_frames": 2}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct GroupingOptions {
+    pub rules: Vec<GroupingRule>,
+    /// `top_in_app_frame` 使用的业务代码帧数量
+    pub in_app_frames: usize,
+}
+
+impl Default for GroupingOptions {
+    fn default() -> Self {
+        GroupingOptions {
+            rules: vec![GroupingRule::ErrorType, GroupingRule::TopInAppFrame, GroupingRule::MessageTemplate],
+            in_app_frames: 1,
+        }
+    }
+}
+
+/// `Grouper::record_at` 的结果
+#[derive(Serialize, Debug, PartialEq)]
+pub struct GroupedError {
+    pub group_key: String,
+    /// 是否单独上报，错误风暴中超出上报数量的事件为false
+    pub report: bool,
+}
+
+/// 按规则为错误分配分组键，并按分组键检测错误风暴
+pub struct Grouper {
+    parser: ErrorParser,
+    options: GroupingOptions,
+    storms: StormDetector,
+}
+
+impl Grouper {
+    pub fn new(options: GroupingOptions) -> Self {
+        Grouper { parser: ErrorParser::new(), options, storms: StormDetector::default() }
+    }
+
+    pub fn set_options(&mut self, options: GroupingOptions) {
+        self.options = options;
+    }
+
+    /// 分组并记录一次错误：同一分组的事件相对滚动基线突增时只上报少量事件，风暴结束后产生汇总事件
+    pub fn record_at(&mut self, stack: &str, severity: Option<Severity>, now: f64) -> GroupedError {
+        let group_key = self.group_key_with_severity(stack, severity);
+        let report = self.storms.record_at(&group_key, now);
+        GroupedError { group_key, report }
+    }
+
+    /// 按已算好的分组键或指纹记录一次错误，返回是否应单独上报
+    pub fn record_key_at(&mut self, key: &str, now: f64) -> bool {
+        self.storms.record_at(key, now)
+    }
+
+    /// 取出已结束的错误风暴汇总事件，`now` 用于关闭已过期的窗口
+    pub fn take_storm_events_at(&mut self, now: f64) -> Vec<StormEvent> {
+        self.storms.take_events_at(now)
+    }
+
+    /// 分组是否处于风暴中
+    pub fn in_storm(&self, key: &str) -> bool {
+        self.storms.in_storm(key)
+    }
+
+    pub fn set_storm_policy(&mut self, policy: StormPolicy) {
+        self.storms.set_policy(policy);
+    }
+
+    pub fn clear_storms(&mut self) {
+        self.storms.clear();
+    }
+
+    /// 参与分组的各要素文本，便于排查分组结果；严重级别按未捕获的错误推断
+    pub fn components(&self, stack: &str) -> Vec<String> {
+        self.components_with_severity(stack, None)
+    }
+
+    /// 同 `components`，`severity` 为已知的严重级别（如 `parse_with_severity` 的结果）
+    pub fn components_with_severity(&self, stack: &str, severity: Option<Severity>) -> Vec<String> {
+        let frames = self.parser.parse_frames(stack);
+        let (error_type, message) = ErrorParser::parse_error_header(LineIndex::new(stack).line(0).unwrap_or(""));
+
+        let mut components = Vec::new();
+        for rule in &self.options.rules {
+            match rule {
+                GroupingRule::ErrorType => components.extend(error_type.as_ref().map(|t| format!("type:{}", t))),
+                GroupingRule::TopInAppFrame => components.extend(self.top_frames(&frames)),
+                GroupingRule::MessageTemplate => components.push(format!("message:{}", message_template(&message))),
+                GroupingRule::Severity => {
+                    let severity = severity.unwrap_or_else(|| severity::infer_from_stack(stack, &frames));
+                    components.push(format!("severity:{}", severity.as_str()));
+                }
+            }
+        }
+        components
+    }
+
+    /// 栈顶的若干业务代码帧，没有业务代码帧时退回栈顶帧
+    fn top_frames(&self, frames: &[StackFrame]) -> Vec<String> {
+        let mut selected: Vec<&StackFrame> = frames.iter().filter(|frame| frame.is_in_app()).take(self.options.in_app_frames).collect();
+        if selected.is_empty() {
+            selected.extend(frames.first());
+        }
+        selected.iter()
+            .map(|frame| {
+                let file = frame.relative_file_name();
+                format!("frame:{}|{}", frame.function_name_str(), file.split(['?', '#']).next().unwrap_or(file))
+            })
+            .collect()
+    }
+
+    /// 分组键：各要素的64位哈希（十六进制），使用全局指纹算法
+    pub fn group_key(&self, stack: &str) -> String {
+        self.group_key_with_severity(stack, None)
+    }
+
+    /// 按已知的严重级别计算分组键
+    pub fn group_key_with_severity(&self, stack: &str, severity: Option<Severity>) -> String {
+        format!("{:016x}", self.group_hash(stack, severity, hash::fingerprint_algorithm()))
+    }
+
+    /// 使用指定算法计算的数值分组键
+    pub fn group_hash(&self, stack: &str, severity: Option<Severity>, algorithm: HashAlgorithm) -> u64 {
+        algorithm.hash_u64(self.components_with_severity(stack, severity).join("\n").as_bytes())
+    }
+}
+
+impl Default for Grouper {
+    fn default() -> Self {
+        Grouper::new(GroupingOptions::default())
+    }
+}
+
+thread_local! {
+    static GROUPER: RefCell<Grouper> = RefCell::new(Grouper::default());
+}
+
+/// 替换全局分组器的分组规则，风暴检测状态保留
+pub fn set_options(options: GroupingOptions) {
+    GROUPER.with(|grouper| grouper.borrow_mut().set_options(options));
+}
+
+/// 以当前时间分组并记录一次错误
+pub fn record(stack: &str, severity: Option<Severity>) -> GroupedError {
+    GROUPER.with(|grouper| grouper.borrow_mut().record_at(stack, severity, now_ms()))
+}
+
+/// 以当前时间按分组键记录一次错误
+pub fn record_key(key: &str) -> bool {
+    GROUPER.with(|grouper| grouper.borrow_mut().record_key_at(key, now_ms()))
+}
+
+pub fn take_storm_events() -> Vec<StormEvent> {
+    GROUPER.with(|grouper| grouper.borrow_mut().take_storm_events_at(now_ms()))
+}
+
+pub fn set_storm_policy(policy: StormPolicy) {
+    GROUPER.with(|grouper| grouper.borrow_mut().set_storm_policy(policy));
+}
+
+pub fn clear_storms() {
+    GROUPER.with(|grouper| grouper.borrow_mut().clear_storms());
+}
+
+/// 风暴检测状态的副本，用于持久化
+pub fn storm_snapshot() -> StormDetector {
+    GROUPER.with(|grouper| grouper.borrow().storms.clone())
+}
+
+/// 用持久化的状态替换风暴检测状态
+pub fn restore_storms(storms: StormDetector) {
+    GROUPER.with(|grouper| grouper.borrow_mut().storms = storms);
+}
+
+/// 单词是否为ID：UUID或不少于8位的十六进制串
+fn is_identifier(word: &str) -> bool {
+    let hex: String = word.chars().filter(|c| *c != '-').collect();
+    hex.len() >= 8 && hex.chars().all(|c| c.is_ascii_hexdigit()) && hex.chars().any(|c| c.is_ascii_digit())
+}
+
+/// 消息模板：引号内容替换为 `<str>`，URL为 `<url>`，ID为 `<id>`，数值为 `<num>`。
+/// 引号只在单词边界处起止，`can't`、`user's` 中的撇号不算引号
+pub fn message_template(message: &str) -> String {
+    // 先去掉引号内的内容
+    let mut unquoted = String::with_capacity(message.len());
+    let mut quote: Option<(char, usize)> = None;
+    let mut previous: Option<char> = None;
+    let mut chars = message.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        match quote {
+            Some((q, _)) if c == q && !is_word(next) => {
+                unquoted.push_str("<str>");
+                quote = None;
+            }
+            Some(_) => {}
+            None if matches!(c, '\'' | '"' | '`') && !is_word(previous) => quote = Some((c, i)),
+            None => unquoted.push(c),
+        }
+        previous = Some(c);
+    }
+    // 未闭合的引号按普通字符处理
+    if let Some((_, start)) = quote {
+        unquoted.push_str(&message[start..]);
+    }
+
+    unquoted.split_whitespace()
+        .map(|word| {
+            let core = word.trim_matches(|c: char| matches!(c, ',' | '.' | ';' | ':' | '(' | ')' | '[' | ']' | '{' | '}'));
+            let placeholder = if core.contains("://") {
+                "<url>"
+            } else if is_identifier(core) {
+                "<id>"
+            } else if !core.is_empty() && core.chars().any(|c| c.is_ascii_digit())
+                && core.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | '_' | 'x' | 'e'))
+            {
+                "<num>"
+            } else {
+                return word.to_string();
+            };
+            word.replacen(core, placeholder, 1)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_template() {
+        assert_eq!(message_template("Cannot read properties of undefined (reading 'id')"), "Cannot read properties of undefined (reading <str>)");
+        assert_eq!(message_template("Request 42 failed after 1.5s: https://api.test/x?id=9"), "Request <num> failed after 1.5s: <url>");
+        assert_eq!(message_template("Order 3f2a9c1e-77b0-4c1a-9d2e-0a1b2c3d4e5f not found (code 404)."), "Order <id> not found (code <num>).");
+        assert_eq!(message_template("it's broken"), "it's broken");
+        // 撇号不是引号，之后真正的引号仍然替换
+        assert_eq!(message_template("can't find module 'lodash'"), "can't find module <str>");
+        assert_eq!(message_template("user's \"draft\" isn't saved"), "user's <str> isn't saved");
+        assert_eq!(message_template("unclosed 'quote here"), "unclosed 'quote here");
+    }
+
+    #[test]
+    fn test_group_key_rules() {
+        let grouper = Grouper::default();
+        let a = "TypeError: user 42 is undefined\n at r (https://cdn.test/node_modules/react/index.js:1:1)\n at render (https://cdn.test/app.js?v=1:10:15)";
+        let b = "TypeError: user 7 is undefined\n at r (https://cdn.test/node_modules/react/index.js:9:9)\n at render (https://cdn.test/app.js?v=2:99:1)";
+        assert_eq!(grouper.components(a), vec!["type:TypeError", "frame:render|https://cdn.test/app.js", "message:user <num> is undefined"]);
+        assert_eq!(grouper.group_key(a), grouper.group_key(b));
+        assert_ne!(grouper.group_key(a), grouper.group_key(&a.replace("render", "paint")));
+
+        // 只按错误类型分组
+        let by_type = Grouper::new(serde_json::from_str(r#"{"rules": ["error_type"]}"#).unwrap());
+        assert_eq!(by_type.group_key(a), by_type.group_key("TypeError: other\n at f (/src/f.js:1:1)"));
+
+        // 没有业务代码帧时退回栈顶帧
+        let frames_only = Grouper::new(GroupingOptions { rules: vec![GroupingRule::TopInAppFrame], in_app_frames: 2 });
+        assert_eq!(frames_only.components("Error\n at x (node:internal/a:1:1)"), vec!["frame:x|node:internal/a"]);
+
+        // 按严重级别分组：未给出时按未捕获推断
+        let by_severity = Grouper::new(serde_json::from_str(r#"{"rules": ["error_type", "severity"]}"#).unwrap());
+        assert_eq!(by_severity.components("TypeError: x\n at f (/src/f.js:1:1)"), vec!["type:TypeError", "severity:fatal"]);
+        assert_ne!(by_severity.group_key_with_severity(a, Some(Severity::Warning)), by_severity.group_key_with_severity(a, Some(Severity::Fatal)));
+    }
+
+    #[test]
+    fn test_storms_by_group() {
+        let mut grouper = Grouper::default();
+        grouper.set_storm_policy(StormPolicy { window_ms: 1000.0, spike_factor: 5.0, min_events: 10, report_limit: 2 });
+        // 消息中的ID不同，仍属于同一分组的风暴
+        let reported = (0..50)
+            .filter(|i| grouper.record_at(&format!("TypeError: user {} is undefined\n at render (/src/app.js:1:1)", i), None, *i as f64).report)
+            .count();
+        assert_eq!(reported, 12);
+        let key = grouper.group_key("TypeError: user 1 is undefined\n at render (/src/app.js:1:1)");
+        assert!(grouper.in_storm(&key));
+        assert!(grouper.record_at("Error: other\n at f (/src/f.js:1:1)", None, 60.0).report);
+
+        let events = grouper.take_storm_events_at(10_000.0);
+        assert_eq!((events.len(), events[0].fingerprint.as_str(), events[0].total_events), (1, key.as_str(), 50));
+    }
+}
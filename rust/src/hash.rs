@@ -0,0 +1,371 @@
+use wasm_bindgen::prelude::*;
+use std::cell::Cell;
+
+/// 指纹使用的哈希算法
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// 速度优先（默认）
+    XxHash64 = 0,
+    /// 抗碰撞，适合安全敏感的去重场景
+    Blake3 = 1,
+}
+
+impl HashAlgorithm {
+    /// 按名称解析算法，不区分大小写
+    pub fn from_name(name: &str) -> Option<HashAlgorithm> {
+        match name.to_ascii_lowercase().as_str() {
+            "xxhash64" | "xxh64" | "xxhash" => Some(HashAlgorithm::XxHash64),
+            "blake3" => Some(HashAlgorithm::Blake3),
+            _ => None,
+        }
+    }
+
+    /// 算法名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::XxHash64 => "xxhash64",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// 计算摘要并输出小写十六进制
+    pub fn hash_hex(&self, input: &[u8]) -> String {
+        match self {
+            HashAlgorithm::XxHash64 => format!("{:016x}", xxhash64(input, 0)),
+            HashAlgorithm::Blake3 => to_hex(&blake3(input)),
+        }
+    }
+
+    /// 计算64位摘要（BLAKE3取前8字节）
+    pub fn hash_u64(&self, input: &[u8]) -> u64 {
+        match self {
+            HashAlgorithm::XxHash64 => xxhash64(input, 0),
+            HashAlgorithm::Blake3 => {
+                let digest = blake3(input);
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(&digest[..8]);
+                u64::from_be_bytes(bytes)
+            }
+        }
+    }
+}
+
+thread_local! {
+    static FINGERPRINT_ALGORITHM: Cell<HashAlgorithm> = const { Cell::new(HashAlgorithm::XxHash64) };
+}
+
+/// 设置指纹计算使用的算法
+pub fn set_fingerprint_algorithm(algorithm: HashAlgorithm) {
+    FINGERPRINT_ALGORITHM.with(|current| current.set(algorithm));
+}
+
+/// 当前指纹算法
+pub fn fingerprint_algorithm() -> HashAlgorithm {
+    FINGERPRINT_ALGORITHM.with(|current| current.get())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// xxHash64
+// ---------------------------------------------------------------------------
+
+const PRIME64_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME64_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME64_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME64_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME64_5: u64 = 0x27D4_EB2F_1656_67C5;
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(buf)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(buf)
+}
+
+fn xxh64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+fn xxh64_merge(acc: u64, value: u64) -> u64 {
+    (acc ^ xxh64_round(0, value))
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(PRIME64_4)
+}
+
+/// 计算xxHash64
+pub fn xxhash64(input: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    let mut rest = input;
+
+    let mut hash = if len >= 32 {
+        let mut v1 = seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2);
+        let mut v2 = seed.wrapping_add(PRIME64_2);
+        let mut v3 = seed;
+        let mut v4 = seed.wrapping_sub(PRIME64_1);
+
+        while rest.len() >= 32 {
+            v1 = xxh64_round(v1, read_u64(&rest[0..]));
+            v2 = xxh64_round(v2, read_u64(&rest[8..]));
+            v3 = xxh64_round(v3, read_u64(&rest[16..]));
+            v4 = xxh64_round(v4, read_u64(&rest[24..]));
+            rest = &rest[32..];
+        }
+
+        let mut acc = v1.rotate_left(1)
+            .wrapping_add(v2.rotate_left(7))
+            .wrapping_add(v3.rotate_left(12))
+            .wrapping_add(v4.rotate_left(18));
+        acc = xxh64_merge(acc, v1);
+        acc = xxh64_merge(acc, v2);
+        acc = xxh64_merge(acc, v3);
+        xxh64_merge(acc, v4)
+    } else {
+        seed.wrapping_add(PRIME64_5)
+    };
+
+    hash = hash.wrapping_add(len as u64);
+
+    while rest.len() >= 8 {
+        hash ^= xxh64_round(0, read_u64(rest));
+        hash = hash.rotate_left(27).wrapping_mul(PRIME64_1).wrapping_add(PRIME64_4);
+        rest = &rest[8..];
+    }
+
+    if rest.len() >= 4 {
+        hash ^= (read_u32(rest) as u64).wrapping_mul(PRIME64_1);
+        hash = hash.rotate_left(23).wrapping_mul(PRIME64_2).wrapping_add(PRIME64_3);
+        rest = &rest[4..];
+    }
+
+    for &byte in rest {
+        hash ^= (byte as u64).wrapping_mul(PRIME64_5);
+        hash = hash.rotate_left(11).wrapping_mul(PRIME64_1);
+    }
+
+    // 雪崩混合
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(PRIME64_3);
+    hash ^= hash >> 32;
+    hash
+}
+
+// ---------------------------------------------------------------------------
+// BLAKE3（单线程、无密钥的哈希模式）
+// ---------------------------------------------------------------------------
+
+const BLAKE3_IV: [u32; 8] = [
+    0x6A09_E667, 0xBB67_AE85, 0x3C6E_F372, 0xA54F_F53A,
+    0x510E_527F, 0x9B05_688C, 0x1F83_D9AB, 0x5BE0_CD19,
+];
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+const CHUNK_LEN: usize = 1024;
+const BLOCK_LEN: usize = 64;
+const CHUNK_START: u32 = 1;
+const CHUNK_END: u32 = 2;
+const PARENT: u32 = 4;
+const ROOT: u32 = 8;
+
+#[allow(clippy::many_single_char_names)]
+fn g(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, mx: u32, my: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(mx);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(my);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+fn blake3_round(state: &mut [u32; 16], m: &[u32; 16]) {
+    // 列混合
+    g(state, 0, 4, 8, 12, m[0], m[1]);
+    g(state, 1, 5, 9, 13, m[2], m[3]);
+    g(state, 2, 6, 10, 14, m[4], m[5]);
+    g(state, 3, 7, 11, 15, m[6], m[7]);
+    // 对角线混合
+    g(state, 0, 5, 10, 15, m[8], m[9]);
+    g(state, 1, 6, 11, 12, m[10], m[11]);
+    g(state, 2, 7, 8, 13, m[12], m[13]);
+    g(state, 3, 4, 9, 14, m[14], m[15]);
+}
+
+fn compress(cv: &[u32; 8], block: &[u32; 16], counter: u64, block_len: u32, flags: u32) -> [u32; 16] {
+    let mut state = [
+        cv[0], cv[1], cv[2], cv[3], cv[4], cv[5], cv[6], cv[7],
+        BLAKE3_IV[0], BLAKE3_IV[1], BLAKE3_IV[2], BLAKE3_IV[3],
+        counter as u32, (counter >> 32) as u32, block_len, flags,
+    ];
+    let mut block = *block;
+
+    for round in 0..7 {
+        blake3_round(&mut state, &block);
+        if round < 6 {
+            let mut permuted = [0u32; 16];
+            for (i, &source) in MSG_PERMUTATION.iter().enumerate() {
+                permuted[i] = block[source];
+            }
+            block = permuted;
+        }
+    }
+
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= cv[i];
+    }
+    state
+}
+
+fn block_words(bytes: &[u8]) -> [u32; 16] {
+    let mut padded = [0u8; BLOCK_LEN];
+    padded[..bytes.len()].copy_from_slice(bytes);
+    let mut words = [0u32; 16];
+    for (i, word) in words.iter_mut().enumerate() {
+        *word = read_u32(&padded[i * 4..]);
+    }
+    words
+}
+
+fn first_8(words: &[u32; 16]) -> [u32; 8] {
+    let mut cv = [0u32; 8];
+    cv.copy_from_slice(&words[..8]);
+    cv
+}
+
+// 尚未决定是否为根节点的压缩输入
+struct Output {
+    cv: [u32; 8],
+    block: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        first_8(&compress(&self.cv, &self.block, self.counter, self.block_len, self.flags))
+    }
+
+    fn root_hash(&self) -> [u8; 32] {
+        let words = compress(&self.cv, &self.block, 0, self.block_len, self.flags | ROOT);
+        let mut out = [0u8; 32];
+        for (i, word) in words[..8].iter().enumerate() {
+            out[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+}
+
+fn chunk_output(chunk: &[u8], counter: u64) -> Output {
+    let mut cv = BLAKE3_IV;
+    let block_count = chunk.len().div_ceil(BLOCK_LEN).max(1);
+
+    for i in 0..block_count {
+        let start = i * BLOCK_LEN;
+        let end = (start + BLOCK_LEN).min(chunk.len());
+        let block = block_words(&chunk[start..end]);
+        let mut flags = if i == 0 { CHUNK_START } else { 0 };
+
+        if i + 1 == block_count {
+            flags |= CHUNK_END;
+            return Output { cv, block, counter, block_len: (end - start) as u32, flags };
+        }
+        cv = first_8(&compress(&cv, &block, counter, BLOCK_LEN as u32, flags));
+    }
+    unreachable!()
+}
+
+fn parent_output(left: &[u32; 8], right: &[u32; 8]) -> Output {
+    let mut block = [0u32; 16];
+    block[..8].copy_from_slice(left);
+    block[8..].copy_from_slice(right);
+    Output { cv: BLAKE3_IV, block, counter: 0, block_len: BLOCK_LEN as u32, flags: PARENT }
+}
+
+/// 计算BLAKE3摘要（32字节）
+pub fn blake3(input: &[u8]) -> [u8; 32] {
+    let chunk_count = input.len().div_ceil(CHUNK_LEN).max(1);
+    let mut cv_stack: Vec<[u32; 8]> = Vec::new();
+
+    // 除最后一个块外，按完成的块数合并子树
+    for index in 0..chunk_count - 1 {
+        let chunk = &input[index * CHUNK_LEN..(index + 1) * CHUNK_LEN];
+        let mut cv = chunk_output(chunk, index as u64).chaining_value();
+        let mut total_chunks = index as u64 + 1;
+        while total_chunks & 1 == 0 {
+            let left = cv_stack.pop().unwrap();
+            cv = parent_output(&left, &cv).chaining_value();
+            total_chunks >>= 1;
+        }
+        cv_stack.push(cv);
+    }
+
+    let last_start = (chunk_count - 1) * CHUNK_LEN;
+    let mut output = chunk_output(&input[last_start..], (chunk_count - 1) as u64);
+    while let Some(left) = cv_stack.pop() {
+        output = parent_output(&left, &output.chaining_value());
+    }
+    output.root_hash()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xxhash64_vectors() {
+        assert_eq!(xxhash64(b"", 0), 0xEF46_DB37_51D8_E999);
+        assert_eq!(xxhash64(b"a", 0), 0xD24E_C4F1_A98C_6E5B);
+        assert_eq!(xxhash64(b"abc", 0), 0x44BC_2CF5_AD77_0999);
+        // 覆盖32字节分组路径
+        let long = b"Nobody inspects the spammish repetition";
+        assert_eq!(xxhash64(long, 0), 0xFBCE_A83C_8A37_8BF1);
+        assert_ne!(xxhash64(long, 0), xxhash64(long, 1));
+    }
+
+    #[test]
+    fn test_blake3_vectors() {
+        assert_eq!(
+            to_hex(&blake3(b"")),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            to_hex(&blake3(b"abc")),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        // 官方测试向量：输入为 `i % 251` 字节序列，覆盖单块、块链与父节点合并
+        let input: Vec<u8> = (0..3072).map(|i| (i % 251) as u8).collect();
+        for (len, expected) in [
+            (1024, "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7"),
+            (1025, "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444"),
+            (3072, "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2"),
+        ] {
+            assert_eq!(to_hex(&blake3(&input[..len])), expected, "{}", len);
+        }
+    }
+
+    #[test]
+    fn test_algorithm_selection() {
+        assert_eq!(HashAlgorithm::from_name("BLAKE3"), Some(HashAlgorithm::Blake3));
+        assert_eq!(HashAlgorithm::from_name("md5"), None);
+        assert_eq!(HashAlgorithm::XxHash64.hash_hex(b"").len(), 16);
+        assert_eq!(HashAlgorithm::Blake3.hash_hex(b"").len(), 64);
+
+        assert_eq!(fingerprint_algorithm(), HashAlgorithm::XxHash64);
+        set_fingerprint_algorithm(HashAlgorithm::Blake3);
+        assert_eq!(fingerprint_algorithm(), HashAlgorithm::Blake3);
+    }
+}
@@ -0,0 +1,241 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use crate::utils::now_ms;
+
+// 单个直方图最多的桶边界数量
+const MAX_BOUNDARIES: usize = 512;
+
+/// 直方图的桶边界布局，JSON为 `{"exponential": {"start": 1, "factor": 2, "count": 16}}` 形式
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BucketLayout {
+    /// `start, start + width, ...` 共 `count` 个边界
+    Linear { start: f64, width: f64, count: usize },
+    /// `start, start * factor, ...` 共 `count` 个边界，适合跨数量级的耗时
+    Exponential { start: f64, factor: f64, count: usize },
+    /// 自定义边界，需严格递增
+    Custom { boundaries: Vec<f64> },
+}
+
+impl BucketLayout {
+    /// 计算桶边界，参数无效时返回错误
+    pub fn boundaries(&self) -> Result<Vec<f64>, String> {
+        // 先检查数量再生成，避免JS传入的超大count分配过多内存
+        let count = match self {
+            BucketLayout::Linear { count, .. } | BucketLayout::Exponential { count, .. } => *count,
+            BucketLayout::Custom { boundaries } => boundaries.len(),
+        };
+        if count > MAX_BOUNDARIES {
+            return Err(format!("桶边界过多: {} > {}", count, MAX_BOUNDARIES));
+        }
+        let boundaries = match self {
+            BucketLayout::Linear { start, width, count } => {
+                if *width <= 0.0 {
+                    return Err(format!("线性桶宽度必须为正数: {}", width));
+                }
+                (0..*count).map(|i| start + width * i as f64).collect()
+            }
+            BucketLayout::Exponential { start, factor, count } => {
+                if *start <= 0.0 || *factor <= 1.0 {
+                    return Err(format!("指数桶需要 start > 0 且 factor > 1: {}, {}", start, factor));
+                }
+                (0..*count).map(|i| start * factor.powi(i as i32)).collect()
+            }
+            BucketLayout::Custom { boundaries } => boundaries.clone(),
+        };
+
+        if boundaries.iter().any(|b| !b.is_finite()) || boundaries.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("桶边界必须是严格递增的有限数值".to_string());
+        }
+        Ok(boundaries)
+    }
+
+    /// 默认的耗时布局：1ms起按2倍增长，覆盖到约32秒
+    pub fn default_timing() -> BucketLayout {
+        BucketLayout::Exponential { start: 1.0, factor: 2.0, count: 16 }
+    }
+}
+
+/// OpenTelemetry显式边界直方图数据点（OTLP/JSON字段命名）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HistogramDataPoint {
+    #[serde(rename = "startTimeUnixNano")]
+    pub start_time_unix_nano: String,
+    #[serde(rename = "timeUnixNano")]
+    pub time_unix_nano: String,
+    pub count: u64,
+    pub sum: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// 比边界多一个桶，最后一个桶为 `(最后边界, +∞)`
+    #[serde(rename = "bucketCounts")]
+    pub bucket_counts: Vec<u64>,
+    #[serde(rename = "explicitBounds")]
+    pub explicit_bounds: Vec<f64>,
+}
+
+/// 毫秒时间戳转为OTLP的纳秒字符串
+fn unix_nano(ms: f64) -> String {
+    format!("{}", (ms.max(0.0) * 1_000_000.0) as u64)
+}
+
+/// 固定边界的直方图，桶区间为左开右闭 `(lower, upper]`，与OpenTelemetry一致
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    start: f64,
+}
+
+impl Histogram {
+    pub fn new(layout: &BucketLayout, start: f64) -> Result<Histogram, String> {
+        let bounds = layout.boundaries()?;
+        Ok(Histogram {
+            counts: vec![0; bounds.len() + 1],
+            bounds,
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            start,
+        })
+    }
+
+    /// 记录一个值，非有限值被忽略
+    pub fn record(&mut self, value: f64) -> bool {
+        if !value.is_finite() {
+            return false;
+        }
+        let index = self.bounds.partition_point(|bound| *bound < value);
+        self.counts[index] += 1;
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        true
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 导出累计数据点
+    pub fn export_at(&self, now: f64) -> HistogramDataPoint {
+        let has_values = self.count > 0;
+        HistogramDataPoint {
+            start_time_unix_nano: unix_nano(self.start),
+            time_unix_nano: unix_nano(now),
+            count: self.count,
+            sum: self.sum,
+            min: has_values.then_some(self.min),
+            max: has_values.then_some(self.max),
+            bucket_counts: self.counts.clone(),
+            explicit_bounds: self.bounds.clone(),
+        }
+    }
+
+    /// 清空计数并开始新的统计区间（增量上报）
+    pub fn reset(&mut self, now: f64) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+        self.count = 0;
+        self.sum = 0.0;
+        self.min = f64::INFINITY;
+        self.max = f64::NEG_INFINITY;
+        self.start = now;
+    }
+}
+
+thread_local! {
+    static HISTOGRAMS: RefCell<BTreeMap<String, Histogram>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// 创建或替换命名直方图
+pub fn create(name: &str, layout: &BucketLayout) -> Result<(), String> {
+    let histogram = Histogram::new(layout, now_ms())?;
+    HISTOGRAMS.with(|histograms| histograms.borrow_mut().insert(name.to_string(), histogram));
+    Ok(())
+}
+
+/// 记录一个值，直方图不存在时按默认耗时布局创建
+pub fn record(name: &str, value: f64) -> bool {
+    HISTOGRAMS.with(|histograms| {
+        let mut histograms = histograms.borrow_mut();
+        if !histograms.contains_key(name) {
+            match Histogram::new(&BucketLayout::default_timing(), now_ms()) {
+                Ok(histogram) => histograms.insert(name.to_string(), histogram),
+                Err(_) => return false,
+            };
+        }
+        histograms.get_mut(name).is_some_and(|histogram| histogram.record(value))
+    })
+}
+
+/// 导出所有直方图，`reset` 为true时导出后清空（增量上报）
+pub fn export_all(reset: bool) -> BTreeMap<String, HistogramDataPoint> {
+    let now = now_ms();
+    HISTOGRAMS.with(|histograms| {
+        histograms.borrow_mut().iter_mut()
+            .map(|(name, histogram)| {
+                let point = histogram.export_at(now);
+                if reset {
+                    histogram.reset(now);
+                }
+                (name.clone(), point)
+            })
+            .collect()
+    })
+}
+
+pub fn remove(name: &str) -> bool {
+    HISTOGRAMS.with(|histograms| histograms.borrow_mut().remove(name).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layouts() {
+        let linear = BucketLayout::Linear { start: 0.0, width: 10.0, count: 4 };
+        assert_eq!(linear.boundaries(), Ok(vec![0.0, 10.0, 20.0, 30.0]));
+        let exponential = BucketLayout::Exponential { start: 1.0, factor: 2.0, count: 5 };
+        assert_eq!(exponential.boundaries(), Ok(vec![1.0, 2.0, 4.0, 8.0, 16.0]));
+        assert!(BucketLayout::Custom { boundaries: vec![5.0, 1.0] }.boundaries().is_err());
+        assert!(BucketLayout::Exponential { start: 0.0, factor: 2.0, count: 3 }.boundaries().is_err());
+        // 超大数量在分配前拒绝
+        assert!(BucketLayout::Linear { start: 0.0, width: 1.0, count: usize::MAX }.boundaries().is_err());
+
+        let parsed: BucketLayout = serde_json::from_str(r#"{"exponential": {"start": 1, "factor": 2, "count": 5}}"#).unwrap();
+        assert_eq!(parsed, exponential);
+    }
+
+    #[test]
+    fn test_record_and_export() {
+        let layout = BucketLayout::Custom { boundaries: vec![10.0, 100.0] };
+        let mut histogram = Histogram::new(&layout, 1.0).unwrap();
+        for value in [5.0, 10.0, 11.0, 250.0, f64::NAN] {
+            histogram.record(value);
+        }
+
+        let point = histogram.export_at(2.0);
+        // 边界值落在左侧桶
+        assert_eq!(point.bucket_counts, vec![2, 1, 1]);
+        assert_eq!((point.count, point.sum, point.min, point.max), (4, 276.0, Some(5.0), Some(250.0)));
+        assert_eq!((point.start_time_unix_nano.as_str(), point.time_unix_nano.as_str()), ("1000000", "2000000"));
+
+        let json = serde_json::to_string(&point).unwrap();
+        assert!(json.contains(r#""bucketCounts":[2,1,1],"explicitBounds":[10.0,100.0]"#), "{}", json);
+
+        histogram.reset(3.0);
+        let point = histogram.export_at(4.0);
+        assert_eq!((point.count, point.min), (0, None));
+        assert_eq!(point.bucket_counts, vec![0, 0, 0]);
+    }
+}
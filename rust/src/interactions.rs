@@ -0,0 +1,235 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+// 目标选择器的最大长度，超出部分截断
+const MAX_TARGET_CHARS: usize = 128;
+
+/// 交互类型
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InteractionKind {
+    Click,
+    Input,
+    Navigation,
+}
+
+/// 宿主上报的一次交互，`value` 只用于计算长度，不会被保存
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Interaction {
+    pub kind: InteractionKind,
+    /// 元素选择器或导航的目标路径
+    pub target: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    pub timestamp: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    kind: InteractionKind,
+    target: String,
+    timestamp: f64,
+    repeat: u32,
+    length: Option<u32>,
+}
+
+/// 压缩后的单条交互，`dt` 为距上一条的毫秒数
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CompactInteraction {
+    pub kind: InteractionKind,
+    pub target: String,
+    pub dt: u32,
+    /// 连续重复的次数（如连续点击、逐字输入）
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub repeat: u32,
+    /// 输入值的长度，值本身不保留
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<u32>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn is_one(value: &u32) -> bool {
+    *value == 1
+}
+
+/// 附加到错误上的交互摘要，`start` 为第一条交互的时间戳
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InteractionBlob {
+    pub start: f64,
+    pub events: Vec<CompactInteraction>,
+    /// 因超出条数上限被丢弃的交互数
+    #[serde(default)]
+    pub dropped: u32,
+}
+
+/// 记录时即压缩的交互日志：连续相同的交互合并计数，只保留最近的若干条
+pub struct InteractionLog {
+    entries: VecDeque<Entry>,
+    capacity: usize,
+    dropped: u32,
+}
+
+impl InteractionLog {
+    pub fn new(capacity: usize) -> Self {
+        InteractionLog {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// 记录一次交互，与上一条类型和目标相同时只增加计数
+    pub fn record(&mut self, interaction: Interaction) {
+        if self.capacity == 0 || !interaction.timestamp.is_finite() {
+            return;
+        }
+        let target: String = interaction.target.chars().take(MAX_TARGET_CHARS).collect();
+        let length = interaction.value.map(|value| value.chars().count() as u32);
+
+        if let Some(last) = self.entries.back_mut() {
+            if last.kind == interaction.kind && last.target == target {
+                last.repeat += 1;
+                last.length = length.or(last.length);
+                return;
+            }
+        }
+
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+        self.entries.push_back(Entry {
+            kind: interaction.kind,
+            target,
+            timestamp: interaction.timestamp,
+            repeat: 1,
+            length,
+        });
+    }
+
+    /// 调整保留条数
+    pub fn configure(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+    }
+
+    /// 生成时间戳差分编码的摘要，没有交互时返回None
+    pub fn blob(&self) -> Option<InteractionBlob> {
+        let start = self.entries.front()?.timestamp;
+        let mut previous = start;
+        let events = self.entries.iter()
+            .map(|entry| {
+                let dt = (entry.timestamp - previous).max(0.0).round() as u32;
+                previous = entry.timestamp;
+                CompactInteraction {
+                    kind: entry.kind,
+                    target: entry.target.clone(),
+                    dt,
+                    repeat: entry.repeat,
+                    length: entry.length,
+                }
+            })
+            .collect();
+        Some(InteractionBlob { start, events, dropped: self.dropped })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.dropped = 0;
+    }
+}
+
+impl Default for InteractionLog {
+    fn default() -> Self {
+        InteractionLog::new(30)
+    }
+}
+
+thread_local! {
+    static LOG: RefCell<InteractionLog> = RefCell::new(InteractionLog::default());
+}
+
+pub fn record(interaction: Interaction) {
+    LOG.with(|log| log.borrow_mut().record(interaction));
+}
+
+pub fn configure(capacity: usize) {
+    LOG.with(|log| log.borrow_mut().configure(capacity));
+}
+
+/// 组装错误事件时调用，取当前交互摘要
+pub fn blob() -> Option<InteractionBlob> {
+    LOG.with(|log| log.borrow().blob())
+}
+
+pub fn clear() {
+    LOG.with(|log| log.borrow_mut().clear());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interaction(kind: InteractionKind, target: &str, value: Option<&str>, timestamp: f64) -> Interaction {
+        Interaction {
+            kind,
+            target: target.to_string(),
+            value: value.map(str::to_string),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_compaction() {
+        let mut log = InteractionLog::new(3);
+        log.record(interaction(InteractionKind::Navigation, "/cart", None, 1000.0));
+        for (i, typed) in ["a", "ab", "abc"].iter().enumerate() {
+            log.record(interaction(InteractionKind::Input, "input#coupon", Some(typed), 1500.0 + i as f64));
+        }
+        log.record(interaction(InteractionKind::Click, "button#apply", None, 2200.0));
+        log.record(interaction(InteractionKind::Click, "button#apply", None, 2300.0));
+        assert_eq!(log.len(), 3);
+
+        let blob = log.blob().unwrap();
+        assert_eq!((blob.start, blob.dropped), (1000.0, 0));
+        assert_eq!(blob.events[1].dt, 500);
+        assert_eq!((blob.events[1].repeat, blob.events[1].length), (3, Some(3)));
+        assert_eq!((blob.events[2].dt, blob.events[2].repeat), (700, 2));
+
+        let json = serde_json::to_string(&blob).unwrap();
+        assert!(!json.contains("abc"), "{}", json);
+        assert!(json.contains(r#"{"kind":"navigation","target":"/cart","dt":0}"#), "{}", json);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut log = InteractionLog::new(2);
+        assert!(log.blob().is_none());
+        for (i, target) in ["a", "b", "c"].iter().enumerate() {
+            log.record(interaction(InteractionKind::Click, target, None, i as f64 * 10.0));
+        }
+        let blob = log.blob().unwrap();
+        assert_eq!((blob.start, blob.dropped, blob.events.len()), (10.0, 1, 2));
+
+        log.configure(0);
+        log.record(interaction(InteractionKind::Click, "d", None, 30.0));
+        assert!(log.is_empty());
+        log.clear();
+        assert!(log.blob().is_none());
+    }
+}
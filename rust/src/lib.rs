@@ -2,149 +2,1750 @@
 // Rust实现的高性能错误堆栈解析器
 
 use wasm_bindgen::prelude::*;
+use regex::Regex;
 use serde::{Serialize, Deserialize};
-use serde_json;
+use std::collections::{BTreeMap, HashMap};
 
+mod aggregates;
+mod app_root;
+mod artifact;
+mod baseline;
+mod breadcrumbs;
+mod budget;
+mod build_manifest;
+mod bundler;
+mod cold_start;
+mod context;
+mod envelope;
+mod errors;
+mod event_buffer;
+mod exported_frame;
+mod fingerprint;
+mod fixture;
+mod function_names;
+mod grouping;
+mod hash;
+mod histogram;
+mod interactions;
+mod long_tasks;
+mod mapping_index;
+mod metrics;
+mod naming;
+mod ownership;
+mod packages;
 mod parser;
+mod parser_options;
+#[cfg(feature = "polyglot")]
+mod polyglot;
+mod redact;
+mod rejection;
+mod remap_cache;
+mod retry;
+mod routing;
+mod sampling;
+mod session;
+mod severity;
+mod similarity;
 mod simd;
+mod source_context;
+mod sourcemap;
+mod sourcemap_registry;
+mod state;
+mod storm;
+mod summary;
+mod symbol_server;
+mod symbolication_queue;
+mod taxonomy;
+mod time_budget;
+mod tokenized;
+mod trace_context;
 mod utils;
+mod uuid;
+mod wasm_symbols;
+mod worker;
 
-pub use parser::{ErrorParser, StackFrame};
-pub use simd::SimdParser;
+pub use aggregates::{AggregateBucket, AggregateEviction, AggregatePayload, AggregateStats, Aggregates, ExpiredGroup, RetentionPolicy};
+pub use artifact::{ArtifactIssue, ArtifactIssueKind, ArtifactReport};
+pub use baseline::{Baseline, BaselineStatus};
+pub use build_manifest::BuildManifest;
+pub use breadcrumbs::{Breadcrumb, BreadcrumbBuffer};
+pub use context::{ErrorContext, ReleaseInfo};
+pub use budget::{FeatureReport, FeatureStatus, MemoryStatus, RuntimeStats};
+pub use envelope::{ErrorEnvelope, SdkInfo};
+pub use errors::ParseError;
+pub use event_buffer::{EventBuffer, EventBufferStats, EventKind};
+pub use exported_frame::ExportedStackFrame;
+pub use function_names::NameNormalization;
+pub use grouping::{GroupedError, Grouper, GroupingOptions, GroupingRule};
+pub use hash::HashAlgorithm;
+pub use histogram::{BucketLayout, Histogram, HistogramDataPoint};
+pub use interactions::{CompactInteraction, Interaction, InteractionBlob, InteractionKind, InteractionLog};
+pub use long_tasks::{LongTask, ProfilerTrace, SlowCodeReport, TaskAttribution};
+pub use naming::FieldNaming;
+pub use ownership::{OwnershipRule, OwnershipRules};
+pub use packages::{PackageInfo, PackageMap};
+pub use parser::{AsyncSegment, Dialect, ErrorParser, FrameOrigin, GroupMapping, GroupRef, LineFormat, ParseOptions, ParsedError, ParsedFrames, StackFrame};
+pub use parser_options::{ParserOptions, WeakStrategy};
+#[cfg(feature = "polyglot")]
+pub use polyglot::{Language, PolyglotFrames};
+pub use redact::RedactedStack;
+pub use rejection::{Rejection, RejectionKind};
+pub use remap_cache::{OriginalLocation, RemapCache, RemapCacheStats};
+pub use retry::{EndpointState, RetryPolicy, RetryTracker, SendDecision};
+pub use routing::{Project, ProjectBatch, RoutedBatches, RoutingTable};
+pub use sampling::SeverityRates;
+pub use session::ParserSession;
+pub use severity::{Mechanism, Severity, SeverityRule, SeverityRules};
+pub use similarity::{DiffFrame, ShiftedFrame, StackDiff};
+pub use simd::{LineIndex, SimdParser};
+pub use source_context::{SourceContext, SourceContextOptions, SourceToken};
+pub use sourcemap::{Mapping, SourceMapConsumer, Symbolication, SymbolicationReason};
+pub use sourcemap_registry::SourceMapRegistry;
+pub use storm::{StormDetector, StormEvent, StormPolicy};
+pub use summary::{SourceLocation, StackSummary};
+pub use symbol_server::{SymbolLookup, SymbolRequest, SymbolResponse, SymbolServerClient};
+pub use symbolication_queue::{PendingBundle, SymbolicationQueue};
+pub use taxonomy::ErrorCategory;
+pub use time_budget::{BudgetedFrames, Deadline};
+pub use tokenized::Tokenized;
+pub use trace_context::{TraceParent, TraceState};
+pub use wasm_symbols::{WasmLineEntry, WasmSymbolTable};
+pub use worker::WorkerOp;
 use utils::console_log;
 
-// 用于从WASM导出的栈帧结构体
-#[derive(Serialize, Deserialize)]
-pub struct ExportedStackFrame {
-    pub function_name: String,
-    pub file_name: String,
-    pub line_number: u32,
-    pub column_number: u32,
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+// 将栈帧转换为可导出格式并序列化为JSON
+fn frames_to_json(frames: Vec<StackFrame>) -> String {
+    frames_to_json_with(frames, naming::field_naming())
+}
+
+fn frames_to_json_with(frames: Vec<StackFrame>, field_naming: FieldNaming) -> String {
+    let exported_frames: Vec<ExportedStackFrame> = frames.into_iter()
+        .map(ExportedStackFrame::from)
+        .collect();
+
+    match naming::to_json(&exported_frames, field_naming) {
+        Ok(json) => json,
+        Err(e) => {
+            console_log(&format!("JSON序列化错误: {}", e));
+            String::from("[]")
+        }
+    }
+}
+
+/// 按全局字段命名序列化，失败时记录日志并返回 `fallback`
+pub(crate) fn to_json_or<T: Serialize>(value: &T, fallback: &str) -> String {
+    match naming::to_json(value, naming::field_naming()) {
+        Ok(json) => json,
+        Err(e) => {
+            console_log(&format!("JSON序列化错误: {}", e));
+            fallback.to_string()
+        }
+    }
+}
+
+// 直接转换为JS对象，省去JSON字符串的二次序列化；map输出为普通对象，结构与JSON版本一致
+fn try_to_js_value<T: Serialize>(value: &T) -> Result<JsValue, ParseError> {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    let result = match naming::field_naming() {
+        FieldNaming::SnakeCase => value.serialize(&serializer).map_err(|e| e.to_string()),
+        field_naming => serde_json::to_value(value)
+            .map_err(|e| e.to_string())
+            .and_then(|json| naming::rename_keys(json, field_naming).serialize(&serializer).map_err(|e| e.to_string())),
+    };
+    result.map_err(ParseError::Serialization)
+}
+
+pub(crate) fn to_js_value<T: Serialize>(value: &T) -> JsValue {
+    match try_to_js_value(value) {
+        Ok(value) => value,
+        Err(e) => {
+            console_log(&format!("JS对象转换错误: {}", e));
+            JsValue::NULL
+        }
+    }
+}
+
+// 带严重级别的解析结果
+#[derive(Serialize)]
+struct SeverityReport {
+    severity: Severity,
+    frames: Vec<ExportedStackFrame>,
+}
+
+// 附带负责团队的解析结果
+#[derive(Serialize)]
+struct OwnershipReport {
+    owners: Vec<String>,
+    frames: Vec<ExportedStackFrame>,
+}
+
+// 附带面包屑的解析结果
+#[derive(Serialize)]
+struct BreadcrumbReport {
+    frames: Vec<ExportedStackFrame>,
+    breadcrumbs: Vec<Breadcrumb>,
 }
 
 // 全局初始化 - 设置panic hook并返回标准解析器
 #[wasm_bindgen]
-pub fn init_parser() {
-    utils::set_panic_hook();
-    
-    // 在初始化时输出一些基本信息
-    console_log("PerfLite WASM Parser 初始化完成");
+pub fn init_parser() {
+    utils::set_panic_hook();
+    
+    // 在初始化时输出一些基本信息
+    console_log("PerfLite WASM Parser 初始化完成");
+}
+
+// 带内存预算的初始化，超出预算时主动回收缓存并降级
+#[wasm_bindgen]
+pub fn init_with_memory_budget(max_bytes: u32) {
+    init_parser();
+    set_memory_budget(max_bytes);
+}
+
+// 设置内存预算（字节），0表示不限制
+#[wasm_bindgen]
+pub fn set_memory_budget(max_bytes: u32) {
+    budget::set_budget(if max_bytes == 0 { None } else { Some(max_bytes as usize) });
+}
+
+// 获取内存占用与降级状态，返回JSON字符串
+#[wasm_bindgen]
+pub fn get_memory_status() -> String {
+    to_json_or(&budget::status(), "{}")
+}
+
+// 获取各子系统（内存预算、聚合数据、位置映射缓存、事件缓冲区）的占用与淘汰统计，返回JSON字符串
+#[wasm_bindgen]
+pub fn get_stats() -> String {
+    to_json_or(&budget::runtime_stats(), "{}")
+}
+
+// 重建内部表并释放多余容量，长时间运行的页面（如展示屏）可在空闲时调用
+#[wasm_bindgen]
+pub fn compact() {
+    budget::compact();
+}
+
+// 冷启动初始化：推迟panic hook与正则编译等非必要工作，首个错误由轻量解析器处理
+#[wasm_bindgen]
+pub fn init_cold_start() {
+    cold_start::init();
+}
+
+// 首个错误的快速解析路径，之后的调用自动升级到完整解析器，返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_first_error(stack: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+
+    frames_to_json(cold_start::parse_first_error(stack))
+}
+
+// 提前升级到完整解析器，适合在空闲回调中调用
+#[wasm_bindgen]
+pub fn upgrade_parser() {
+    cold_start::upgrade();
+}
+
+// 提供一个SIMD优化的解析器初始化函数
+#[wasm_bindgen]
+pub fn init_simd_parser() {
+    utils::set_panic_hook();
+    
+    // 确认是否支持SIMD
+    #[cfg(target_feature = "simd128")]
+    console_log("SIMD支持已启用");
+    
+    #[cfg(not(target_feature = "simd128"))]
+    console_log("SIMD支持未启用");
+}
+
+// 标准版本解析栈信息，返回JSON字符串
+#[wasm_bindgen]
+pub fn parse(stack: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+    
+    let parser = ErrorParser::new();
+    let frames = parser.parse_frames(stack);
+    
+    frames_to_json(frames)
+}
+
+// 快速判断字符串是否像错误栈（至少一行像栈帧），不分配内存，用于在完整解析前过滤任意字符串
+#[wasm_bindgen]
+pub fn looks_like_stack(s: &str) -> bool {
+    simd::looks_like_stack(s)
+}
+
+// 统计像栈帧的行数，不分配内存
+#[wasm_bindgen]
+pub fn count_frames(s: &str) -> u32 {
+    simd::count_frames(s)
+}
+
+// 只识别Chrome/V8格式的快速解析，跳过格式探测与正则匹配，返回JSON字符串；已知运行在Chromium内核时使用
+#[wasm_bindgen]
+pub fn parse_chrome_fast(stack: &str) -> String {
+    frames_to_json(ErrorParser::new().parse_chrome_fast(stack))
+}
+
+// 解析栈信息，直接返回栈帧对象数组；转换失败时返回null
+#[wasm_bindgen]
+pub fn parse_js(stack: &str) -> JsValue {
+    let frames: Vec<ExportedStackFrame> = ErrorParser::new().parse_frames(stack).into_iter()
+        .map(ExportedStackFrame::from)
+        .collect();
+    to_js_value(&frames)
+}
+
+// 严格版本的解析，返回栈帧对象数组；空输入、过大输入、无法识别的格式或序列化失败时抛出 {code, message}
+#[wasm_bindgen]
+pub fn try_parse(stack: &str) -> Result<JsValue, JsValue> {
+    let frames: Vec<ExportedStackFrame> = ErrorParser::new().try_parse_frames(stack)?.into_iter()
+        .map(ExportedStackFrame::from)
+        .collect();
+    Ok(try_to_js_value(&frames)?)
+}
+
+// 严格版本的事件封装，失败时抛出 {code, message}
+#[wasm_bindgen]
+pub fn try_parse_to_envelope(stack: &str) -> Result<JsValue, JsValue> {
+    let frames = ErrorParser::new().try_parse_frames(stack)?;
+    Ok(try_to_js_value(&envelope::build(stack, frames, envelope::received_now(), None))?)
+}
+
+// 解析JS侧已切分好的栈：每行起始的UTF-16下标（JS字符串下标）与格式字节
+// （0未知、1消息行、2异步分隔行、3 V8、4 Firefox/Safari、5 wasm），返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_pretokenized(text: &str, offsets: &[u32], formats: &[u8]) -> String {
+    let parser = ErrorParser::new();
+    frames_to_json(parser.parse_pretokenized(text, offsets, formats))
+}
+
+// 使用标识符重命名表（原始名 -> 混淆名的JSON对象）解析栈信息，返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_with_rename_map(stack: &str, rename_map_json: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+
+    let mut parser = ErrorParser::new();
+    parser.set_rename_map(rename_map_json);
+    let frames = parser.parse_frames(stack);
+
+    frames_to_json(frames)
+}
+
+// 使用自定义栈帧格式（JSON数组 `[{"name", "regex", "groups": {"function", "file", "line", "column"}}]`）解析栈信息，
+// 自定义格式在内置格式之后尝试，返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_with_patterns(stack: &str, patterns_json: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+
+    let mut parser = ErrorParser::new();
+    match serde_json::from_str::<Vec<PatternSpec>>(patterns_json) {
+        Ok(patterns) => {
+            for pattern in patterns {
+                match Regex::new(&pattern.regex) {
+                    Ok(regex) => parser.add_pattern(&pattern.name, regex, pattern.groups),
+                    Err(e) => console_log(&format!("自定义格式 {} 的正则表达式错误: {}", pattern.name, e)),
+                }
+            }
+        }
+        Err(e) => console_log(&format!("自定义格式解析错误: {}", e)),
+    }
+    let frames = parser.parse_frames(stack);
+
+    frames_to_json(frames)
+}
+
+// `parse_with_patterns` 的一条自定义格式
+#[derive(Deserialize)]
+struct PatternSpec {
+    name: String,
+    regex: String,
+    groups: GroupMapping,
+}
+
+// 容错解析任意字节（不可信输入）：非法UTF-8替换为U+FFFD，超长输入截断，保证不panic且返回合法UTF-8的JSON。
+// 与只放宽行列号格式的 `parse_with_lenient_numbers` 不同
+#[wasm_bindgen]
+pub fn parse_lenient(bytes: &[u8]) -> String {
+    let parser = ErrorParser::new();
+    frames_to_json(parser.parse_lossy(bytes))
+}
+
+// 宽松数字模式解析栈信息（容忍全角数字与千分位分隔符），返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_with_lenient_numbers(stack: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+
+    let mut parser = ErrorParser::new();
+    parser.set_lenient_numbers(true);
+    let frames = parser.parse_frames(stack);
+
+    frames_to_json(frames)
+}
+
+// 按选项解析栈信息，选项JSON如 `{"dialect": "jsc", "collapse_microtasks": true}`，只对本次调用生效；
+// dialect可由宿主按UA嗅探给出，跳过逐行格式探测；raw_lines在内存预算降级后不生效。无效的项记录日志后忽略，
+// 行列号起点等全局设置由 `set_parser_options` 修改。返回 {dialect, frames} JSON，dialect为实际匹配的方言
+#[wasm_bindgen]
+pub fn parse_with_options(stack: &str, options_json: &str) -> String {
+    let (mut options, errors) = ParseOptions::from_json(options_json);
+    for error in &errors {
+        console_log(error);
+    }
+    options.raw_lines &= budget::raw_lines_enabled();
+    let mut parser = ErrorParser::new();
+    parser.apply_options(&options);
+
+    let (mut frames, dialect) = parser.parse_frames_with_dialect(stack);
+    if options.redact {
+        frames.iter_mut().for_each(redact::redact_frame);
+    }
+    let result = ParsedFrames {
+        dialect,
+        frames: frames.into_iter().map(ExportedStackFrame::from).collect(),
+    };
+    to_json_or(&result, "{}")
+}
+
+// 生成可嵌入会话回放负载的脱敏栈，返回 {stack, fingerprint} JSON；指纹与未脱敏的栈一致
+#[wasm_bindgen]
+pub fn redact_stack(stack: &str) -> String {
+    to_json_or(&redact::redact_stack(stack), "{}")
+}
+
+// 合并连续的运行时微任务帧后解析栈信息，返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_with_collapsed_microtasks(stack: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+
+    let mut parser = ErrorParser::new();
+    parser.set_collapse_microtasks(true);
+    let frames = parser.parse_frames(stack);
+
+    frames_to_json(frames)
+}
+
+// 注册框架映射（路径片段 -> 框架名的JSON对象），之后解析的帧按其标注 `framework`，替换之前注册的映射
+#[wasm_bindgen]
+pub fn set_framework_map(map_json: &str) -> bool {
+    match serde_json::from_str::<HashMap<String, String>>(map_json) {
+        Ok(map) => {
+            parser::set_framework_map(map);
+            true
+        }
+        Err(e) => {
+            console_log(&format!("框架映射解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 使用wasm符号表（格式同 `register_wasm_symbols`，只用于本次解析）解析混合JS/wasm栈信息，返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_with_wasm_names(stack: &str, names_json: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+
+    let frames = ErrorParser::new().parse_frames(stack);
+    let frames = match WasmSymbolTable::parse(names_json) {
+        Ok(table) => frames.iter().map(|frame| if frame.is_wasm() { table.symbolize(frame) } else { frame.clone() }).collect(),
+        Err(e) => {
+            console_log(&e);
+            frames
+        }
+    };
+
+    frames_to_json(frames)
+}
+
+// 单行压缩产物模式解析栈信息，压缩帧带 `minified: true` 标记，并一律尝试用已注册的source map还原，
+// 还原结果与原因记录在 `symbolicated`、`symbolication_reason` 注解中，返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_minified(stack: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+
+    let mut parser = ErrorParser::new();
+    parser.set_minified_mode(true);
+    let frames = parser.parse_frames(stack).iter()
+        .map(|frame| if frame.minified() { sourcemap_registry::symbolicate(frame) } else { frame.clone() })
+        .collect();
+
+    frames_to_json(frames)
+}
+
+// 按异步边界（`--- Promise.then ---`、`setTimeout (async)`、`at async`）拆分栈，返回 [{cause, frames}] JSON数组
+#[wasm_bindgen]
+pub fn parse_async_segments_json(stack: &str) -> String {
+    let parser = ErrorParser::new();
+    to_json_or(&parser.parse_async_segments(stack), "[]")
+}
+
+// 解析链式错误（Node的 `[cause]:`、`Caused by:`），返回外层在前的 [{error_type, message, frames}] JSON数组
+#[wasm_bindgen]
+pub fn parse_error_chain_json(stack: &str) -> String {
+    let parser = ErrorParser::new();
+    to_json_or(&parser.parse_error_chain(stack), "[]")
+}
+
+// 创建或替换命名直方图，布局JSON如 {"exponential": {"start": 1, "factor": 2, "count": 16}}、
+// {"linear": {"start": 0, "width": 50, "count": 20}} 或 {"custom": {"boundaries": [100, 250, 1000]}}
+#[wasm_bindgen]
+pub fn create_histogram(name: &str, layout_json: &str) -> bool {
+    let result = serde_json::from_str::<BucketLayout>(layout_json)
+        .map_err(|e| format!("直方图布局解析错误: {}", e))
+        .and_then(|layout| histogram::create(name, &layout));
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            console_log(&e);
+            false
+        }
+    }
+}
+
+// 向直方图记录一个值，未创建的直方图使用默认耗时布局
+#[wasm_bindgen]
+pub fn record_histogram(name: &str, value: f64) -> bool {
+    histogram::record(name, value)
+}
+
+// 导出所有直方图为 {名称: OTel数据点} 的JSON，`reset` 为true时导出后清空
+#[wasm_bindgen]
+pub fn export_histograms(reset: bool) -> String {
+    // 直方图名称是用户数据，只转换数据点的字段名
+    let points: serde_json::Result<BTreeMap<String, serde_json::Value>> = histogram::export_all(reset).into_iter()
+        .map(|(name, point)| Ok((name, serde_json::from_str(&naming::to_json(&point, naming::field_naming())?)?)))
+        .collect();
+    match points.and_then(|points| serde_json::to_string(&points)) {
+        Ok(json) => json,
+        Err(e) => {
+            console_log(&format!("JSON序列化错误: {}", e));
+            String::from("{}")
+        }
+    }
+}
+
+// 删除命名直方图
+#[wasm_bindgen]
+pub fn remove_histogram(name: &str) -> bool {
+    histogram::remove(name)
+}
+
+// 从拼接的控制台/日志文本中拆分出多个独立错误并逐个解析，返回 [{error_type, message, frames}] JSON数组
+#[wasm_bindgen]
+pub fn split_and_parse(log_text: &str) -> String {
+    let parser = ErrorParser::new();
+    to_json_or(&parser.split_and_parse(log_text), "[]")
+}
+
+// 汇总数值指标（Float64Array），`ops` 为逗号分隔的统计项，如 "min,max,mean,p50,p95,p99,stddev"，
+// 为空时输出全部，返回 {count, ...} 的JSON字符串
+#[wasm_bindgen]
+pub fn aggregate_f64(values: &[f64], ops: &str) -> String {
+    match serde_json::to_string(&metrics::aggregate(values, ops)) {
+        Ok(json) => json,
+        Err(e) => {
+            console_log(&format!("JSON序列化错误: {}", e));
+            String::from("{}")
+        }
+    }
+}
+
+// 汇总整数指标（Int32Array），统计项同 `aggregate_f64`
+#[wasm_bindgen]
+pub fn aggregate_i32(values: &[i32], ops: &str) -> String {
+    let values: Vec<f64> = values.iter().map(|&v| v as f64).collect();
+    aggregate_f64(&values, ops)
+}
+
+// 解析完整错误：返回 {error_type, message, frames} 的JSON字符串
+#[wasm_bindgen]
+pub fn parse_error_json(stack: &str) -> String {
+    let parser = ErrorParser::new();
+    to_json_or(&parser.parse_error(stack), "{}")
+}
+
+// 解析栈信息并封装为完整事件（event_id、接收时间、SDK版本、错误），返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_to_envelope(stack: &str) -> String {
+    envelope_to_json(stack, envelope::received_now(), None)
+}
+
+// 使用调用方提供的接收时间（毫秒）封装事件
+#[wasm_bindgen]
+pub fn parse_to_envelope_at(stack: &str, received_ms: f64) -> String {
+    envelope_to_json(stack, received_ms, None)
+}
+
+// 封装为错误事件并直接返回JS对象；转换失败时返回null
+#[wasm_bindgen]
+pub fn parse_to_envelope_js(stack: &str) -> JsValue {
+    let frames = ErrorParser::new().parse_frames(stack);
+    to_js_value(&envelope::build(stack, frames, envelope::received_now(), None))
+}
+
+// 封装事件并关联traceparent头中的trace_id/span_id，头部无效时按无trace处理
+#[wasm_bindgen]
+pub fn parse_to_envelope_with_trace(stack: &str, traceparent: &str) -> String {
+    let trace = TraceParent::parse(traceparent).ok();
+    envelope_to_json(stack, envelope::received_now(), trace.as_ref())
+}
+
+// 设置错误上下文（值为JSON），之后组装的事件会附带当前上下文的快照；超出大小上限时返回false
+#[wasm_bindgen]
+pub fn set_context(key: &str, value_json: &str) -> bool {
+    match context::set(key, value_json) {
+        Ok(()) => true,
+        Err(e) => {
+            console_log(&e);
+            false
+        }
+    }
+}
+
+// 设置发布信息（`{"release", "environment", "build_id"}` 的JSON），之后组装的事件在顶层带这些字段，替换之前的设置
+#[wasm_bindgen]
+pub fn set_release_context(release_json: &str) -> bool {
+    match serde_json::from_str::<ReleaseInfo>(release_json) {
+        Ok(release) => {
+            context::set_release(release);
+            true
+        }
+        Err(e) => {
+            console_log(&format!("发布信息解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 删除一个上下文键
+#[wasm_bindgen]
+pub fn clear_context(key: &str) -> bool {
+    context::remove(key)
+}
+
+// 清空全部上下文
+#[wasm_bindgen]
+pub fn clear_all_context() {
+    context::clear();
+}
+
+// 注入事件接收时间使用的时钟函数，传undefined恢复系统时间
+#[wasm_bindgen]
+pub fn set_envelope_clock(clock: Option<js_sys::Function>) {
+    envelope::set_clock(clock);
+}
+
+// 解析traceparent头，返回 {version, trace_id, span_id, flags} 的JSON，无效时返回undefined
+#[wasm_bindgen]
+pub fn parse_traceparent(header: &str) -> Option<String> {
+    let trace = TraceParent::parse(header).ok()?;
+    naming::to_json(&trace, naming::field_naming()).ok()
+}
+
+// 生成新trace的traceparent头
+#[wasm_bindgen]
+pub fn generate_traceparent(sampled: bool) -> String {
+    TraceParent::generate(sampled).to_header()
+}
+
+// 在传入的trace下生成子span的traceparent头，传入无效时返回undefined
+#[wasm_bindgen]
+pub fn child_traceparent(header: &str) -> Option<String> {
+    TraceParent::parse(header).ok().map(|trace| trace.child().to_header())
+}
+
+// 在tracestate头中设置条目（移到最前），返回新的头部值；条目无效时原样返回
+#[wasm_bindgen]
+pub fn update_tracestate(header: &str, key: &str, value: &str) -> String {
+    let mut state = TraceState::parse(header);
+    match state.set(key, value) {
+        Ok(()) => state.to_header(),
+        Err(e) => {
+            console_log(&e);
+            header.to_string()
+        }
+    }
+}
+
+// 读取tracestate头中某个键的值
+#[wasm_bindgen]
+pub fn get_tracestate_value(header: &str, key: &str) -> Option<String> {
+    TraceState::parse(header).get(key).map(str::to_string)
+}
+
+fn envelope_to_json(stack: &str, received_ms: f64, trace: Option<&TraceParent>) -> String {
+    let parser = ErrorParser::new();
+    let frames = parser.parse_frames(stack);
+    let envelope = envelope::build(stack, frames, received_ms, trace);
+
+    to_json_or(&envelope, "{}")
+}
+
+// 解析栈信息并还原每一帧：优先使用按文件URL注册的source map，其次是传入的map（可为空字符串），
+// wasm帧使用已注册的符号表还原，返回原始文件/行/列/函数名的JSON字符串；
+// JS帧带 `symbolicated`（full、partial、none）与 `symbolication_reason` 注解
+#[wasm_bindgen]
+pub fn parse_and_map(stack: &str, source_map_json: &str) -> String {
+    let parser = ErrorParser::new();
+    let frames = parser.parse_frames(stack);
+
+    let fallback = if source_map_json.is_empty() {
+        None
+    } else {
+        match SourceMapConsumer::parse(source_map_json) {
+            Ok(consumer) => Some(consumer),
+            Err(e) => {
+                // map不可用时JS帧保持未还原
+                console_log(&e);
+                None
+            }
+        }
+    };
+    let frames = frames.iter()
+        .map(|frame| {
+            if frame.is_wasm() {
+                return wasm_symbols::symbolize(frame);
+            }
+            sourcemap_registry::map_frame(frame)
+                // 旧版产物的帧不用传入的新map还原
+                .or_else(|| fallback.as_ref().filter(|_| !frame.is_stale_bundle()).map(|consumer| consumer.map_frame(frame)))
+                .unwrap_or_else(|| sourcemap_registry::unmapped(frame))
+        })
+        .collect();
+
+    frames_to_json(frames)
+}
+
+// 限时解析栈信息（`symbolicate` 为true时按已注册的source map与符号表还原），`max_micros` 为0表示不限时。
+// 超时时返回已处理的帧、`budget_exceeded: true` 与 `continuation` 句柄，用 `resume_parse` 继续
+#[wasm_bindgen]
+pub fn parse_within(stack: &str, symbolicate: bool, max_micros: u32) -> String {
+    budgeted_to_json(time_budget::parse(stack, symbolicate, max_micros))
+}
+
+// 在 `max_micros` 微秒内尽量多地解析栈帧（不还原），超时时返回 `continuation` 句柄，
+// SDK在下一次空闲回调中用 `resume_parse` 继续，低端设备上单次阻塞主线程不超过预算。
+// 正则只编译一次，继续时复用首次调用建立的解析器与行索引，每次调用只处理本段的行
+#[wasm_bindgen]
+pub fn parse_with_budget(stack: &str, max_micros: u32) -> String {
+    parse_within(stack, false, max_micros)
+}
+
+// 用 `parse_within` 返回的句柄继续处理剩余的帧，句柄只能使用一次
+#[wasm_bindgen]
+pub fn resume_parse(handle: u32, max_micros: u32) -> String {
+    match time_budget::resume(handle, max_micros) {
+        Some(result) => budgeted_to_json(result),
+        None => {
+            console_log(&format!("未知的继续句柄: {}", handle));
+            String::from("{}")
+        }
+    }
+}
+
+// 放弃未完成的限时解析，释放保留的栈文本
+#[wasm_bindgen]
+pub fn cancel_parse(handle: u32) -> bool {
+    time_budget::cancel(handle)
+}
+
+fn budgeted_to_json(result: BudgetedFrames) -> String {
+    to_json_or(&result, "{}")
+}
+
+// 将长任务条目（JSON数组）与Self-Profiling API的trace合并，返回慢代码报告的JSON数组；
+// 热点帧按已注册的source map还原，期间没有采样的长任务不产生报告
+#[wasm_bindgen]
+pub fn long_task_reports(tasks_json: &str, trace_json: &str) -> String {
+    let (tasks, trace) = match (serde_json::from_str::<Vec<LongTask>>(tasks_json), serde_json::from_str::<ProfilerTrace>(trace_json)) {
+        (Ok(tasks), Ok(trace)) => (tasks, trace),
+        (Err(e), _) | (_, Err(e)) => {
+            console_log(&format!("长任务或profiler trace解析错误: {}", e));
+            return String::from("[]");
+        }
+    };
+    let reports: Vec<SlowCodeReport> = tasks.iter().filter_map(|task| trace.culprit(task)).collect();
+
+    to_json_or(&reports, "[]")
+}
+
+// 注册打包产物URL对应的source map，解析一次后缓存在WASM中供 `parse_and_map` 复用
+#[wasm_bindgen]
+pub fn register_source_map(url: &str, source_map_json: &str) -> bool {
+    match sourcemap_registry::register(url, source_map_json) {
+        Ok(()) => {
+            budget::enforce();
+            true
+        }
+        Err(e) => {
+            console_log(&e);
+            false
+        }
+    }
+}
+
+// 移除已注册的source map及其映射缓存
+#[wasm_bindgen]
+pub fn unregister_source_map(url: &str) -> bool {
+    sourcemap_registry::unregister(url)
+}
+
+// 设置最多缓存的source map数量，超出时淘汰最久未使用的
+#[wasm_bindgen]
+pub fn set_source_map_capacity(capacity: u32) {
+    sourcemap_registry::set_capacity(capacity as usize);
+}
+
+// 清空已注册的source map
+#[wasm_bindgen]
+pub fn clear_source_maps() {
+    sourcemap_registry::clear();
+}
+
+// 注册wasm模块的符号表（名称段 `{"42": "name"}`，或带行号表的 `{"functions": {...}, "lines": [...]}`），
+// 模块URL为空时作为未单独注册模块的默认表
+#[wasm_bindgen]
+pub fn register_wasm_symbols(module_url: &str, table_json: &str) -> bool {
+    match WasmSymbolTable::parse(table_json) {
+        Ok(table) => {
+            wasm_symbols::register(module_url, table);
+            true
+        }
+        Err(e) => {
+            console_log(&e);
+            false
+        }
+    }
+}
+
+// 移除wasm模块的符号表
+#[wasm_bindgen]
+pub fn unregister_wasm_symbols(module_url: &str) -> bool {
+    wasm_symbols::unregister(module_url)
+}
+
+// 清空全部wasm符号表
+#[wasm_bindgen]
+pub fn clear_wasm_symbols() {
+    wasm_symbols::clear();
+}
+
+// 错误事件（JSON负载）入缓冲区，缓冲区满时优先淘汰指标，返回是否入队
+#[wasm_bindgen]
+pub fn enqueue_error(payload_json: &str) -> bool {
+    enqueue_event(EventKind::Error, payload_json)
+}
+
+// 指标事件（JSON负载）入缓冲区，返回是否入队
+#[wasm_bindgen]
+pub fn enqueue_metric(payload_json: &str) -> bool {
+    enqueue_event(EventKind::Metric, payload_json)
+}
+
+fn enqueue_event(kind: EventKind, payload_json: &str) -> bool {
+    match event_buffer::enqueue(kind, payload_json) {
+        Ok(queued) => queued,
+        Err(e) => {
+            console_log(&e);
+            false
+        }
+    }
+}
+
+// 取出不超过 `max_bytes` 字节的事件（visibilitychange/pagehide时调用），返回 [{"type", "payload"}] JSON数组
+#[wasm_bindgen]
+pub fn flush(max_bytes: u32) -> String {
+    event_buffer::flush(max_bytes as usize)
+}
+
+// 设置事件缓冲的数量与字节上限
+#[wasm_bindgen]
+pub fn configure_event_buffer(max_events: u32, max_bytes: u32) {
+    event_buffer::configure(max_events as usize, max_bytes as usize);
+}
+
+// 清空事件缓冲
+#[wasm_bindgen]
+pub fn clear_event_buffer() {
+    event_buffer::clear();
+}
+
+// 获取事件缓冲状态，返回JSON字符串
+#[wasm_bindgen]
+pub fn get_event_buffer_stats() -> String {
+    to_json_or(&event_buffer::stats(), "{}")
+}
+
+// 导出待发送事件为不透明的二进制数据（Uint8Array），宿主可持久化到IndexedDB，不清空缓冲区
+#[wasm_bindgen]
+pub fn export_queue() -> Vec<u8> {
+    event_buffer::export_queue()
+}
+
+// 导入 `export_queue` 导出的数据，返回重新入队的事件数，数据无效时返回0
+#[wasm_bindgen]
+pub fn import_queue(bytes: &[u8]) -> u32 {
+    match event_buffer::import_queue(bytes) {
+        Ok(imported) => imported as u32,
+        Err(e) => {
+            console_log(&e);
+            0
+        }
+    }
+}
+
+// 设置多项目路由表（JSON，含 projects 与可选的 default_project），配置无效时返回false
+#[wasm_bindgen]
+pub fn set_routing_table(table_json: &str) -> bool {
+    let table: RoutingTable = match serde_json::from_str(table_json) {
+        Ok(table) => table,
+        Err(e) => {
+            console_log(&format!("路由表解析错误: {}", e));
+            return false;
+        }
+    };
+    match routing::set_table(table) {
+        Ok(()) => true,
+        Err(e) => {
+            console_log(&e);
+            false
+        }
+    }
+}
+
+// 将一组错误栈（JSON字符串数组）按项目分批，返回各项目的上报地址、脱敏配置与事件
+#[wasm_bindgen]
+pub fn route_errors(stacks_json: &str) -> String {
+    let stacks: Vec<String> = match serde_json::from_str(stacks_json) {
+        Ok(stacks) => stacks,
+        Err(e) => {
+            console_log(&format!("错误栈列表解析错误: {}", e));
+            return String::from("{}");
+        }
+    };
+    let routed = routing::route_all(&stacks, envelope::received_now());
+
+    to_json_or(&routed, "{}")
+}
+
+// 记录一次发送结果（HTTP状态码，网络错误为0；可带Retry-After毫秒数），返回 "sent"、"retry" 或 "drop"
+#[wasm_bindgen]
+pub fn on_send_result(endpoint: &str, status: u16, retry_after_ms: Option<f64>) -> String {
+    retry::on_send_result(endpoint, status, retry_after_ms).as_str().to_string()
+}
+
+// 距离允许再次向该地址发送还需等待的毫秒数
+#[wasm_bindgen]
+pub fn next_send_delay(endpoint: &str) -> f64 {
+    retry::next_send_delay(endpoint)
+}
+
+// 按指纹或分组键记录一次错误，返回是否应单独上报；错误风暴期间超出上报数量的事件返回false
+#[wasm_bindgen]
+pub fn record_error_for_storm(fingerprint: &str) -> bool {
+    grouping::record_key(fingerprint)
+}
+
+// 按全局分组规则分组并记录一次错误，`severity` 为空字符串时按未捕获推断；
+// 返回 {group_key, report} JSON，同一分组突增为错误风暴时 `report` 为false
+#[wasm_bindgen]
+pub fn record_error_group(stack: &str, severity: &str) -> String {
+    let grouped = grouping::record(stack, Severity::from_name(severity));
+    to_json_or(&grouped, "{}")
+}
+
+// 替换全局分组器的分组规则（JSON，同 `group_key`），用于 `record_error_group`
+#[wasm_bindgen]
+pub fn set_grouping_options(options_json: &str) -> bool {
+    match serde_json::from_str::<GroupingOptions>(options_json) {
+        Ok(options) => {
+            grouping::set_options(options);
+            true
+        }
+        Err(e) => {
+            console_log(&format!("分组规则解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 取出已结束的错误风暴汇总事件（JSON数组）
+#[wasm_bindgen]
+pub fn take_error_storms() -> String {
+    to_json_or(&grouping::take_storm_events(), "[]")
+}
+
+// 替换错误风暴判定参数（JSON格式：{"window_ms", "spike_factor", "min_events", "report_limit"}）
+#[wasm_bindgen]
+pub fn set_storm_policy(policy_json: &str) -> bool {
+    match serde_json::from_str::<StormPolicy>(policy_json) {
+        Ok(policy) => {
+            grouping::set_storm_policy(policy);
+            true
+        }
+        Err(e) => {
+            console_log(&format!("风暴策略解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 清空错误风暴状态
+#[wasm_bindgen]
+pub fn clear_error_storms() {
+    grouping::clear_storms();
+}
+
+// 替换重试策略（JSON格式：{"base_delay_ms", "max_delay_ms", "max_attempts", "jitter"}）
+#[wasm_bindgen]
+pub fn set_retry_policy(policy_json: &str) -> bool {
+    match serde_json::from_str::<RetryPolicy>(policy_json) {
+        Ok(policy) => {
+            retry::set_policy(policy);
+            true
+        }
+        Err(e) => {
+            console_log(&format!("重试策略解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 获取某个地址的重试状态，返回JSON字符串
+#[wasm_bindgen]
+pub fn get_retry_state(endpoint: &str) -> String {
+    to_json_or(&retry::state(endpoint), "{}")
+}
+
+// 清空全部重试状态
+#[wasm_bindgen]
+pub fn reset_retry_state() {
+    retry::reset();
+}
+
+// 替换严重级别规则表（JSON格式：{"rules": [...], "default": "error"}）
+#[wasm_bindgen]
+pub fn set_severity_rules(rules_json: &str) -> bool {
+    match serde_json::from_str::<SeverityRules>(rules_json) {
+        Ok(rules) => {
+            severity::set_rules(rules);
+            true
+        }
+        Err(e) => {
+            console_log(&format!("严重级别规则解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 解析栈信息并按捕获方式推断严重级别，返回 {"severity", "frames"} JSON；捕获方式为空串时按默认值，格式错误时返回 {}
+#[wasm_bindgen]
+pub fn parse_with_severity(stack: &str, mechanism_json: &str) -> String {
+    let mechanism = if mechanism_json.trim().is_empty() {
+        Mechanism::default()
+    } else {
+        match serde_json::from_str::<Mechanism>(mechanism_json) {
+            Ok(mechanism) => mechanism,
+            Err(e) => {
+                console_log(&format!("捕获方式解析错误: {}", e));
+                return String::from("{}");
+            }
+        }
+    };
+    let parser = ErrorParser::new();
+    let frames = parser.parse_frames(stack);
+
+    // 首行通常是错误消息
+    let message = LineIndex::new(stack).line(0).unwrap_or("").trim();
+    let report = SeverityReport {
+        severity: severity::infer(&mechanism, message, &frames),
+        frames: frames.into_iter().map(ExportedStackFrame::from).collect(),
+    };
+
+    to_json_or(&report, "{}")
+}
+
+// 加载CODEOWNERS风格的归属规则（每行 `路径模式 团队...`），返回规则条数
+#[wasm_bindgen]
+pub fn set_ownership_rules(codeowners: &str) -> u32 {
+    let rules = OwnershipRules::parse(codeowners);
+    let count = rules.rules.len() as u32;
+    ownership::set_rules(rules);
+    count
+}
+
+// 解析栈信息并按第一个业务代码帧确定负责团队，返回 {"owners", "frames"} JSON
+#[wasm_bindgen]
+pub fn parse_with_owner(stack: &str) -> String {
+    let parser = ErrorParser::new();
+    let frames = parser.parse_frames(stack);
+    let report = OwnershipReport {
+        owners: ownership::owners_for_frames(&frames),
+        frames: frames.into_iter().map(ExportedStackFrame::from).collect(),
+    };
+
+    to_json_or(&report, "{}")
+}
+
+// 记录一条面包屑（类别、消息、级别），时间戳取当前时间
+#[wasm_bindgen]
+pub fn add_breadcrumb(category: &str, message: &str, level: &str) {
+    breadcrumbs::add(category, message, level);
+    budget::enforce();
+}
+
+// 配置面包屑缓冲区容量，以及解析错误时附加的最近条数
+#[wasm_bindgen]
+pub fn configure_breadcrumbs(capacity: u32, attach_count: u32) {
+    breadcrumbs::configure(capacity as usize, attach_count as usize);
+}
+
+// 清空面包屑
+#[wasm_bindgen]
+pub fn clear_breadcrumbs() {
+    breadcrumbs::clear();
+}
+
+// 记录用户交互（单个对象或数组，JSON），输入值只保留长度；之后组装的事件会附带压缩后的交互摘要
+#[wasm_bindgen]
+pub fn record_interactions(events_json: &str) -> bool {
+    let events = match serde_json::from_str::<Vec<Interaction>>(events_json) {
+        Ok(events) => events,
+        Err(_) => match serde_json::from_str::<Interaction>(events_json) {
+            Ok(event) => vec![event],
+            Err(e) => {
+                console_log(&format!("交互事件解析错误: {}", e));
+                return false;
+            }
+        },
+    };
+    events.into_iter().for_each(interactions::record);
+    true
+}
+
+// 设置保留的交互条数（合并后）
+#[wasm_bindgen]
+pub fn configure_interactions(capacity: u32) {
+    interactions::configure(capacity as usize);
+}
+
+// 清空交互记录
+#[wasm_bindgen]
+pub fn clear_interactions() {
+    interactions::clear();
+}
+
+// 解析栈信息并附加最近的面包屑，返回 {"frames", "breadcrumbs"} JSON
+#[wasm_bindgen]
+pub fn parse_with_breadcrumbs(stack: &str) -> String {
+    let parser = ErrorParser::new();
+    let report = BreadcrumbReport {
+        frames: parser.parse_frames(stack).into_iter().map(ExportedStackFrame::from).collect(),
+        breadcrumbs: breadcrumbs::recent(),
+    };
+
+    to_json_or(&report, "{}")
+}
+
+// 注册打包产物源码，供上下文提取使用；内存降级后忽略
+#[wasm_bindgen]
+pub fn register_bundle_source(url: &str, source: &str) {
+    if !budget::context_lines_enabled() {
+        return;
+    }
+    source_context::register_bundle(url, source);
+    budget::enforce();
+}
+
+// 移除已注册的打包产物源码
+#[wasm_bindgen]
+pub fn unregister_bundle_source(url: &str) -> bool {
+    source_context::unregister_bundle(url)
+}
+
+// 设置当前构建清单（JSON对象：打包产物URL或路径 -> 内容哈希）。帧URL查询参数中的哈希与清单不符时
+// 标记 `stale_bundle: true` 且不再用已注册的source map还原；JSON无效时返回false且不修改
+#[wasm_bindgen]
+pub fn set_build_manifest(manifest_json: &str) -> bool {
+    match serde_json::from_str::<HashMap<String, String>>(manifest_json) {
+        Ok(map) => {
+            build_manifest::set_build_manifest(BuildManifest::new(map));
+            true
+        }
+        Err(e) => {
+            console_log(&format!("构建清单解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 设置导出栈帧的行列号起点（0或1，默认都为1），例如对接source map规范的后端使用 `(1, 0)`；
+// 起点为0时未知位置导出为u32最大值（`UNKNOWN_POSITION`）
+#[wasm_bindgen]
+pub fn set_parser_options(line_base: u32, column_base: u32) {
+    parser_options::set_options(parser_options::options().with_positions(line_base, column_base));
+}
+
+// 设置指纹的位置容差：是否忽略列号（默认忽略）、行号量化桶宽度（0或1不量化），避免构建间的细小偏移拆分分组
+#[wasm_bindgen]
+pub fn set_fingerprint_tolerance(ignore_columns: bool, line_bucket: u32) {
+    parser_options::set_options(parser_options::options().with_fingerprint_tolerance(ignore_columns, line_bucket));
+}
+
+// 导出可持久化的状态（已注册的source map、框架映射、包清单、风暴检测与聚合数据），
+// 页面刷新或把模块移到worker后用 `import_state` 恢复，不必重新注册
+#[wasm_bindgen]
+pub fn export_state() -> Vec<u8> {
+    state::export_state()
+}
+
+// 恢复 `export_state` 导出的状态，数据无效时返回false且不修改
+#[wasm_bindgen]
+pub fn import_state(bytes: &[u8]) -> bool {
+    match state::import_state(bytes) {
+        Ok(()) => {
+            budget::enforce();
+            true
+        }
+        Err(e) => {
+            console_log(&e);
+            false
+        }
+    }
+}
+
+// 设置弱指纹的计算方式（`in_app_frames`、`top_frames` 或 `message_template`）与使用的栈帧数（0按1处理），
+// 名称无效时返回false且不修改
+#[wasm_bindgen]
+pub fn set_weak_fingerprint_strategy(strategy: &str, frames: u32) -> bool {
+    match WeakStrategy::from_name(strategy) {
+        Some(strategy) => {
+            parser_options::set_options(parser_options::options().with_weak_fingerprint(strategy, frames));
+            true
+        }
+        None => false,
+    }
+}
+
+// 压缩后源码行中列号所在的词法单元 `{start, end, token}`（UTF-16下标，不含end），供界面高亮；
+// 列号按全局约定传入，超出行尾时返回undefined
+#[wasm_bindgen]
+pub fn token_at(line_text: &str, column: u32) -> Option<String> {
+    let token = source_context::token_at(line_text, parser_options::options().import_column(column))?;
+    match naming::to_json(&token, naming::field_naming()) {
+        Ok(json) => Some(json),
+        Err(e) => {
+            console_log(&format!("JSON序列化错误: {}", e));
+            None
+        }
+    }
+}
+
+// 配置上下文行提取：前后行数、单行最大长度、每个错误的总字节上限
+#[wasm_bindgen]
+pub fn set_source_context_options(radius: u32, max_line_length: u32, max_total_bytes: u32) {
+    source_context::set_options(SourceContextOptions::new(radius, max_line_length, max_total_bytes));
+}
+
+// 解析栈信息并附加已注册源码的上下文行，返回JSON字符串
+#[wasm_bindgen]
+pub fn parse_with_context(stack: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+
+    let parser = ErrorParser::new();
+    let mut frames = parser.parse_frames(stack);
+    if budget::context_lines_enabled() {
+        source_context::attach_context(&mut frames, &source_context::options());
+    }
+
+    frames_to_json(frames)
+}
+
+// 设置聚合数据保留策略：时间桶长度与分组过期时间（毫秒），非正数时保留原策略并返回false
+#[wasm_bindgen]
+pub fn set_retention_policy(bucket_ms: f64, group_ttl_ms: f64) -> bool {
+    match RetentionPolicy::new(bucket_ms, group_ttl_ms) {
+        Ok(policy) => {
+            aggregates::set_policy(policy);
+            true
+        }
+        Err(e) => {
+            console_log(&e);
+            false
+        }
+    }
+}
+
+// 设置聚合数据的内存上限（字节），超出时淘汰最久未出现的分组，0表示不限制
+#[wasm_bindgen]
+pub fn set_aggregates_memory_limit(max_bytes: u32) {
+    aggregates::set_max_bytes(if max_bytes == 0 { None } else { Some(max_bytes as usize) });
+}
+
+// 注入聚合数据的淘汰回调，每淘汰一个分组或丢弃一个时间桶调用一次，参数为 {"type", ...} JSON；传undefined取消
+#[wasm_bindgen]
+pub fn set_aggregates_eviction_callback(callback: Option<js_sys::Function>) {
+    aggregates::set_eviction_callback(callback);
+}
+
+// 记录一次分组（如错误指纹）出现，计入当前时间桶
+#[wasm_bindgen]
+pub fn record_group(key: &str) {
+    aggregates::record(key);
+}
+
+// 取出已关闭的时间桶与过期分组，返回 {"buckets", "expired_groups", "dropped_buckets", "evicted_groups"} JSON
+#[wasm_bindgen]
+pub fn flush_expired() -> String {
+    to_json_or(&aggregates::flush_expired(), "{}")
+}
+
+// 校验打包产物与source map是否一致，返回校验报告JSON
+#[wasm_bindgen]
+pub fn verify_artifact(bundle_text: &str, map_json: &str) -> String {
+    let report = artifact::verify(bundle_text, map_json);
+
+    to_json_or(&report, "{}")
+}
+
+// 获取位置映射缓存的条目数与命中率，返回JSON字符串
+#[wasm_bindgen]
+pub fn get_remap_cache_stats() -> String {
+    to_json_or(&remap_cache::stats(), "{}")
+}
+
+// 设置位置映射缓存的最大条目数
+#[wasm_bindgen]
+pub fn set_remap_cache_capacity(capacity: u32) {
+    remap_cache::set_capacity(capacity as usize);
+}
+
+// 设置位置映射缓存的内存上限（字节），超出时淘汰最早的条目，0表示只按条目数限制
+#[wasm_bindgen]
+pub fn set_remap_cache_memory_limit(max_bytes: u32) {
+    remap_cache::set_max_bytes(if max_bytes == 0 { None } else { Some(max_bytes as usize) });
+}
+
+// 释放某个文件的位置映射缓存（其source map被淘汰时调用），返回释放条数
+#[wasm_bindgen]
+pub fn release_remap_cache(file_url: &str) -> u32 {
+    remap_cache::release_file(file_url) as u32
+}
+
+// 清空位置映射缓存
+#[wasm_bindgen]
+pub fn clear_remap_cache() {
+    remap_cache::clear();
+}
+
+// 提取栈信息中的所有数字
+#[wasm_bindgen]
+pub fn parse_numbers(stack: &str) -> Vec<u32> {
+    ErrorParser::new().parse_numbers(stack)
+}
+
+// 解析每一帧的行列号，按 [行, 列, 行, 列, ...] 平铺返回
+#[wasm_bindgen]
+pub fn parse_line_column(stack: &str) -> Vec<u32> {
+    ErrorParser::new().parse_line_column(stack)
+}
+
+// SIMD优化版本解析数字
+#[wasm_bindgen]
+#[cfg(target_feature = "simd128")]
+pub fn parse_numbers_simd(stack: &str) -> Vec<u32> {
+    let parser = SimdParser::new();
+    parser.parse_numbers(stack)
+}
+
+// SIMD优化版本解析数字 (非SIMD回退版本)
+#[wasm_bindgen]
+#[cfg(not(target_feature = "simd128"))]
+pub fn parse_numbers_simd(stack: &str) -> Vec<u32> {
+    console_log("SIMD未启用，使用标准解析");
+    let parser = ErrorParser::new();
+    parser.parse_numbers(stack)
+}
+
+// SIMD优化完整栈解析，返回JSON字符串
+#[wasm_bindgen]
+#[cfg(target_feature = "simd128")]
+pub fn parse_stack_simd(stack: &str) -> String {
+    if stack.is_empty() {
+        return String::from("[]");
+    }
+    
+    let parser = SimdParser::new();
+    let frames = parser.parse_stack_simd(stack);
+    
+    frames_to_json(frames)
+}
+
+// SIMD优化完整栈解析 (非SIMD回退版本)
+#[wasm_bindgen]
+#[cfg(not(target_feature = "simd128"))]
+pub fn parse_stack_simd(stack: &str) -> String {
+    console_log("SIMD未启用，使用标准解析");
+    parse(stack)
+}
+
+// SIMD优化的行列号解析，按 [行, 列, 帧下标, ...] 三元组返回有位置的帧，帧下标与 `parse` 的结果对应
+#[wasm_bindgen]
+#[cfg(target_feature = "simd128")]
+pub fn parse_line_column_simd(stack: &str) -> Vec<u32> {
+    let parser = SimdParser::new();
+    parser.parse_line_column(stack)
+}
+
+// SIMD优化的行列号解析 (非SIMD回退版本)
+#[wasm_bindgen]
+#[cfg(not(target_feature = "simd128"))]
+pub fn parse_line_column_simd(stack: &str) -> Vec<u32> {
+    console_log("SIMD未启用，使用标准解析");
+    let parser = ErrorParser::new();
+    parser.parse_line_column_indexed(stack)
+}
+
+// 编码发给worker的二进制请求，`id` 由调用方分配，用于匹配响应。
+// 把返回的 `Uint8Array.buffer` 以transfer方式 `postMessage` 给加载了本模块的worker，解析全部在worker中完成
+#[wasm_bindgen]
+pub fn encode_request(op: WorkerOp, id: u32, stack: &str) -> Vec<u8> {
+    worker::encode_request(op, id, stack)
+}
+
+// 在worker中执行 `encode_request` 编码的请求，返回二进制响应（格式见 `WorkerOp`），无效请求返回失败响应
+#[wasm_bindgen]
+pub fn handle_request(bytes: &[u8]) -> Vec<u8> {
+    worker::handle_request(bytes)
+}
+
+// 只扫描一遍输入并保存分词结果，返回句柄；需要栈帧、数字与指纹时把句柄传给下面的 `*_tokenized` 接口，
+// 不再各自重新扫描。最多保留32个，用完后调用 `release_tokenized` 释放
+#[wasm_bindgen]
+pub fn tokenize(stack: &str) -> u32 {
+    tokenized::tokenize(stack)
+}
+
+// 按句柄取栈帧JSON，未知句柄返回空数组
+#[wasm_bindgen]
+pub fn parse_tokenized(handle: u32) -> String {
+    tokenized::with(handle, |tokenized| frames_to_json(tokenized.frames().to_vec())).unwrap_or_else(|| String::from("[]"))
+}
+
+// 按句柄取所有数字，与 `parse_numbers_simd` 一致
+#[wasm_bindgen]
+pub fn parse_numbers_tokenized(handle: u32) -> Vec<u32> {
+    tokenized::with(handle, |tokenized| tokenized.numbers().to_vec()).unwrap_or_default()
+}
+
+// 按句柄取 [行, 列, 帧下标, ...] 三元组，与 `parse_line_column_simd` 一致
+#[wasm_bindgen]
+pub fn parse_line_column_tokenized(handle: u32) -> Vec<u32> {
+    tokenized::with(handle, |tokenized| tokenized.line_column()).unwrap_or_default()
+}
+
+// 按句柄计算指纹，未知句柄返回undefined
+#[wasm_bindgen]
+pub fn fingerprint_tokenized(handle: u32) -> Option<String> {
+    tokenized::with(handle, |tokenized| tokenized.fingerprint())
+}
+
+// 释放分词结果
+#[wasm_bindgen]
+pub fn release_tokenized(handle: u32) -> bool {
+    tokenized::release(handle)
+}
+
+// `parse_numbers_simd` 的类型化数组版本，结果一次性复制到 `Uint32Array`
+#[wasm_bindgen]
+pub fn parse_numbers_simd_array(stack: &str) -> js_sys::Uint32Array {
+    js_sys::Uint32Array::from(parse_numbers_simd(stack).as_slice())
+}
+
+// 提取数字及其起始字节偏移，按 [值, 偏移, 值, 偏移, ...] 平铺为 `Uint32Array`
+#[wasm_bindgen]
+pub fn parse_numbers_with_offsets_simd(stack: &str) -> js_sys::Uint32Array {
+    js_sys::Uint32Array::from(SimdParser::new().parse_numbers_positions(stack).as_slice())
+}
+
+// `parse_line_column_simd` 的类型化数组版本
+#[wasm_bindgen]
+pub fn parse_line_column_simd_array(stack: &str) -> js_sys::Uint32Array {
+    js_sys::Uint32Array::from(parse_line_column_simd(stack).as_slice())
+}
+
+// 复用缓冲区模式：结果以 `[count, values...]` 写入WASM内存，返回count；
+// 轮询调用方用 `new Uint32Array(memory.buffer, number_buffer_ptr(), count + 1)` 读取，避免每次分配
+#[wasm_bindgen]
+pub fn parse_numbers_into_buffer(stack: &str) -> u32 {
+    simd::fill_number_buffer(&parse_numbers_simd(stack))
+}
+
+// 行列号的复用缓冲区模式，格式同 `parse_numbers_into_buffer`
+#[wasm_bindgen]
+pub fn parse_line_column_into_buffer(stack: &str) -> u32 {
+    simd::fill_number_buffer(&parse_line_column_simd(stack))
+}
+
+// 数字输出缓冲区的地址，下一次写入后失效
+#[wasm_bindgen]
+pub fn number_buffer_ptr() -> usize {
+    simd::number_buffer_ptr()
+}
+
+// 使用指定算法（xxhash64 / blake3）计算字符串的十六进制摘要，算法未知时返回undefined
+#[wasm_bindgen]
+pub fn hash_string(algo: &str, input: &str) -> Option<String> {
+    HashAlgorithm::from_name(algo).map(|algorithm| algorithm.hash_hex(input.as_bytes()))
+}
+
+// 设置栈帧与事件JSON的字段命名风格："snake_case"（默认）或 "camelCase"
+#[wasm_bindgen]
+pub fn set_json_field_naming(name: &str) -> bool {
+    match FieldNaming::from_name(name) {
+        Some(field_naming) => {
+            naming::set_field_naming(field_naming);
+            true
+        }
+        None => false,
+    }
+}
+
+// 获取当前的字段命名风格
+#[wasm_bindgen]
+pub fn get_json_field_naming() -> String {
+    naming::field_naming().name().to_string()
+}
+
+// 按指定的字段命名风格解析，不影响全局设置；名称无效时使用全局设置
+#[wasm_bindgen]
+pub fn parse_with_naming(stack: &str, naming_name: &str) -> String {
+    let field_naming = FieldNaming::from_name(naming_name).unwrap_or_else(naming::field_naming);
+    let parser = ErrorParser::new();
+    frames_to_json_with(parser.parse_frames(stack), field_naming)
+}
+
+// 计算栈的稳定指纹（16位十六进制），忽略列号、查询参数与第三方包内部细节，用于去重
+#[wasm_bindgen]
+pub fn fingerprint(stack: &str) -> String {
+    fingerprint::fingerprint(stack)
+}
+
+// 黄金样本回放：输入 `[[stack, expected_fingerprint], ...]`，返回当前解析器下指纹发生变化的样本，
+// 升级wasm前用于评估分组变动
+#[wasm_bindgen]
+pub fn replay_fingerprints(cases_json: &str) -> String {
+    let cases: Vec<(String, String)> = match serde_json::from_str(cases_json) {
+        Ok(cases) => cases,
+        Err(e) => {
+            console_log(&format!("回放样本解析错误: {}", e));
+            return String::from("{}");
+        }
+    };
+    to_json_or(&fingerprint::replay(&cases), "{}")
+}
+
+// 注册单体仓库的包清单（路径前缀 -> `{"name", "version"}` 的JSON对象），之后解析的帧带 `package` 与 `package_version` 注解，替换之前的清单
+#[wasm_bindgen]
+pub fn set_package_map(map_json: &str) -> bool {
+    match serde_json::from_str::<HashMap<String, PackageInfo>>(map_json) {
+        Ok(map) => {
+            packages::set_package_map(PackageMap::new(map));
+            true
+        }
+        Err(e) => {
+            console_log(&format!("包清单解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 配置应用根路径前缀（字符串数组的JSON），导出帧、指纹与分组中的文件名去掉这些前缀，替换之前的配置
+#[wasm_bindgen]
+pub fn set_app_roots(roots_json: &str) -> bool {
+    match serde_json::from_str::<Vec<String>>(roots_json) {
+        Ok(roots) => {
+            app_root::set_roots(roots);
+            true
+        }
+        Err(e) => {
+            console_log(&format!("应用根路径解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 配置函数名规范化（JSON，缺省的规则均启用），影响指纹与导出帧的 `normalized_function_name`
+#[wasm_bindgen]
+pub fn set_name_normalization(options_json: &str) -> bool {
+    match serde_json::from_str::<NameNormalization>(options_json) {
+        Ok(normalization) => {
+            function_names::set_normalization(normalization);
+            true
+        }
+        Err(e) => {
+            console_log(&format!("函数名规范化配置解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 按当前配置规范化单个函数名
+#[wasm_bindgen]
+pub fn normalize_function_name(name: &str) -> String {
+    function_names::normalize(name)
+}
+
+// 按分组规则（JSON，可为空字符串使用默认规则）计算错误的分组键，可直接用于 `record_group`；
+// 规则JSON无效时抛出错误，不会静默按默认规则分组
+#[wasm_bindgen]
+pub fn group_key(stack: &str, options_json: &str) -> Result<String, JsValue> {
+    let options = if options_json.trim().is_empty() {
+        GroupingOptions::default()
+    } else {
+        serde_json::from_str::<GroupingOptions>(options_json)
+            .map_err(|e| JsValue::from_str(&format!("分组规则解析错误: {}", e)))?
+    };
+    Ok(Grouper::new(options).group_key(stack))
+}
+
+// 计算两个错误栈的相似度（0到1），忽略列号、查询参数与小幅行号漂移，用于聚类发布后几乎相同的错误
+#[wasm_bindgen]
+pub fn similarity(stack_a: &str, stack_b: &str) -> f32 {
+    similarity::similarity(stack_a, stack_b, similarity::DEFAULT_LINE_DRIFT)
+}
+
+// 统计错误栈：各来源的帧数、最深业务代码帧下标与涉及的文件数，返回JSON；用于采样丢弃的错误
+#[wasm_bindgen]
+pub fn summarize(stack: &str) -> String {
+    to_json_or(&summary::summarize(stack), "{}")
+}
+
+// 解析后端的Python traceback或JVM异常栈，返回 {language, header, frames, fingerprint} JSON，
+// 栈帧与指纹和JS栈共用同一模型；无法识别时返回 `{}`
+#[cfg(feature = "polyglot")]
+#[wasm_bindgen]
+pub fn parse_polyglot(text: &str) -> String {
+    let Some(stack) = polyglot::parse(text) else {
+        return String::from("{}");
+    };
+    to_json_or(&PolyglotFrames::from(stack), "{}")
 }
 
-// 提供一个SIMD优化的解析器初始化函数
+// 生成匿名化但结构相同的栈（方言、帧数、URL形态与行列号不变，标识符与主机确定性地随机替换），
+// 可附在解析问题的报告中或加入测试语料，不泄露私有路径；不传种子时使用固定种子，同一输入总是得到同一夹具
 #[wasm_bindgen]
-pub fn init_simd_parser() {
-    utils::set_panic_hook();
-    
-    // 确认是否支持SIMD
-    #[cfg(target_feature = "simd128")]
-    console_log("SIMD支持已启用");
-    
-    #[cfg(not(target_feature = "simd128"))]
-    console_log("SIMD支持未启用");
+pub fn make_fixture(stack: &str, seed: Option<u32>) -> String {
+    fixture::make_fixture(stack, seed.map_or(fixture::DEFAULT_SEED, u64::from))
 }
 
-// 标准版本解析栈信息，返回JSON字符串
+// 按URL形态、分块文件命名与运行时帧名推断打包工具：webpack、vite、rollup、parcel、esbuild 或 unknown，
+// 供符号化服务选择路径改写方式
 #[wasm_bindgen]
-pub fn parse(stack: &str) -> String {
-    if stack.is_empty() {
-        return String::from("[]");
-    }
-    
-    let parser = ErrorParser::new();
-    let frames = parser.parse_simd(stack);
-    
-    // 将栈帧转换为可导出格式
-    let exported_frames: Vec<ExportedStackFrame> = frames.into_iter()
-        .map(|frame| ExportedStackFrame {
-            function_name: frame.function_name().to_string(),
-            file_name: frame.file_name().to_string(),
-            line_number: frame.line_number(),
-            column_number: frame.column_number(),
-        })
-        .collect();
-    
-    // 序列化为JSON
-    match serde_json::to_string(&exported_frames) {
-        Ok(json) => json,
-        Err(e) => {
-            console_log(&format!("JSON序列化错误: {}", e));
-            String::from("[]")
-        }
-    }
+pub fn detect_bundler(stack: &str) -> String {
+    bundler::detect_bundler(stack).as_str().to_string()
 }
 
-// SIMD优化版本解析数字
+// 一批错误栈（JSON字符串数组，或单个错误栈文本）中去重后的 [{file, line, column}]，供符号服务器预取source map
 #[wasm_bindgen]
-#[cfg(target_feature = "simd128")]
-pub fn parse_numbers_simd(stack: &str) -> Vec<u32> {
-    let parser = SimdParser::new();
-    parser.parse_numbers(stack)
+pub fn unique_locations(stack: &str) -> String {
+    let stacks: Vec<String> = serde_json::from_str(stack).unwrap_or_else(|_| vec![stack.to_string()]);
+    to_json_or(&summary::unique_locations(&stacks), "[]")
 }
 
-// SIMD优化版本解析数字 (非SIMD回退版本)
+// 解析不是Error对象的Promise拒绝负载（普通对象、字符串、GraphQL响应、Response转储），返回消息、合成指纹与 `has_stack` 的JSON
 #[wasm_bindgen]
-#[cfg(not(target_feature = "simd128"))]
-pub fn parse_numbers_simd(stack: &str) -> Vec<u32> {
-    console_log("SIMD未启用，使用标准解析");
-    let parser = ErrorParser::new();
-    parser.parse_numbers(stack)
+pub fn parse_rejection(json_or_text: &str) -> String {
+    to_json_or(&rejection::parse_rejection(json_or_text), "{}")
 }
 
-// SIMD优化完整栈解析，返回JSON字符串
+// 比较两个错误栈，返回新增、删除与行号位移帧的JSON（行号漂移在容差内视为未变），用于判断发布后崩溃位置是否变化
 #[wasm_bindgen]
-#[cfg(target_feature = "simd128")]
-pub fn parse_stack_simd(stack: &str) -> String {
-    if stack.is_empty() {
-        return String::from("[]");
-    }
-    
-    let parser = SimdParser::new();
-    let frames = parser.parse_stack_simd(stack);
-    
-    // 将栈帧转换为可导出格式
-    let exported_frames: Vec<ExportedStackFrame> = frames.into_iter()
-        .map(|frame| ExportedStackFrame {
-            function_name: frame.function_name().to_string(),
-            file_name: frame.file_name().to_string(),
-            line_number: frame.line_number(),
-            column_number: frame.column_number(),
-        })
-        .collect();
-    
-    // 序列化为JSON
-    match serde_json::to_string(&exported_frames) {
-        Ok(json) => json,
-        Err(e) => {
-            console_log(&format!("JSON序列化错误: {}", e));
-            String::from("[]")
+pub fn diff_stacks(stack_a: &str, stack_b: &str) -> String {
+    let diff = similarity::diff(stack_a, stack_b, similarity::DEFAULT_LINE_DRIFT);
+    to_json_or(&diff, "{}")
+}
+
+// 选择指纹计算使用的哈希算法，默认xxhash64
+#[wasm_bindgen]
+pub fn set_fingerprint_hash(algo: &str) -> bool {
+    match HashAlgorithm::from_name(algo) {
+        Some(algorithm) => {
+            hash::set_fingerprint_algorithm(algorithm);
+            true
         }
+        None => false,
     }
 }
 
-// SIMD优化完整栈解析 (非SIMD回退版本)
+// 当前指纹计算使用的哈希算法名称
 #[wasm_bindgen]
-#[cfg(not(target_feature = "simd128"))]
-pub fn parse_stack_simd(stack: &str) -> String {
-    console_log("SIMD未启用，使用标准解析");
-    parse(stack)
+pub fn get_fingerprint_hash() -> String {
+    hash::fingerprint_algorithm().name().to_string()
 }
 
-// SIMD优化的行列号解析
+// 按trace id或会话ID确定性采样，同一ID在各端得到相同的采样结果
 #[wasm_bindgen]
-#[cfg(target_feature = "simd128")]
-pub fn parse_line_column_simd(stack: &str) -> Vec<u32> {
-    let parser = SimdParser::new();
-    parser.parse_line_column(stack)
+pub fn should_sample(id: &str, rate: f64) -> bool {
+    sampling::should_sample(id, rate)
 }
 
-// SIMD优化的行列号解析 (非SIMD回退版本)
+// 按严重级别（fatal、error、warning、info，来自 `parse_with_severity`）调整采样率后确定性采样，
+// 未知的级别按error处理
 #[wasm_bindgen]
-#[cfg(not(target_feature = "simd128"))]
-pub fn parse_line_column_simd(stack: &str) -> Vec<u32> {
-    console_log("SIMD未启用，使用标准解析");
-    let parser = ErrorParser::new();
-    parser.parse_line_column(stack)
+pub fn should_sample_with_severity(id: &str, rate: f64, severity: &str) -> bool {
+    sampling::should_sample_severity(id, rate, Severity::from_name(severity).unwrap_or(Severity::Error))
+}
+
+// 设置各严重级别的采样系数（JSON格式：{"fatal", "error", "warning", "info"}，未给出的为1），
+// 与采样率相乘，路由表的项目采样同样生效
+#[wasm_bindgen]
+pub fn set_severity_sample_rates(rates_json: &str) -> bool {
+    match serde_json::from_str::<SeverityRates>(rates_json) {
+        Ok(rates) => {
+            sampling::set_severity_rates(rates);
+            true
+        }
+        Err(e) => {
+            console_log(&format!("级别采样系数解析错误: {}", e));
+            false
+        }
+    }
+}
+
+// 生成随机UUID v4
+#[wasm_bindgen]
+pub fn uuid_v4() -> String {
+    uuid::v4()
+}
+
+// 生成按时间排序的UUID v7
+#[wasm_bindgen]
+pub fn uuid_v7() -> String {
+    uuid::v7()
 }
 
 // 提供版本信息
@@ -154,6 +1755,12 @@ pub fn get_version() -> String {
     version.to_string()
 }
 
+// 可选子系统（SIMD、source map、上下文行、符号还原、线程、压缩）的编译与运行状态及能力等级，返回JSON
+#[wasm_bindgen]
+pub fn feature_report() -> String {
+    to_json_or(&budget::feature_report(), "{}")
+}
+
 // 判断是否启用了SIMD
 #[wasm_bindgen]
 pub fn is_simd_enabled() -> bool {
@@ -243,4 +1850,554 @@ mod tests {
         assert!(parsed.is_ok());
         assert_eq!(parsed.unwrap().len(), 1);
     }
+
+    #[test]
+    fn test_annotations_round_trip() {
+        let mut frame = StackFrame::new("render".to_string(), "/src/App.js".to_string(), 10, 15);
+        frame.set_annotation("team".to_string(), "checkout".to_string());
+
+        let json = serde_json::to_string(&vec![ExportedStackFrame::from(frame)]).unwrap();
+        assert!(json.contains("\"annotations\":{\"team\":\"checkout\"}"));
+
+        let parsed: Vec<ExportedStackFrame> = serde_json::from_str(&json).unwrap();
+        let restored = StackFrame::from(parsed.into_iter().next().unwrap());
+        assert_eq!(restored.get_annotation("team"), Some("checkout".to_string()));
+
+        // 没有注解时不输出该字段
+        assert!(!parse("Error\n at a (/a.js:1:2)").contains("annotations"));
+    }
+
+    #[test]
+    fn test_async_boundary_output() {
+        let json = parse("Error\n at a (/a.js:1:2)\n - - - - -\n at b (/b.js:3:4)");
+        let frames: Vec<ExportedStackFrame> = serde_json::from_str(&json).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert!(frames[1].async_boundary);
+        assert_eq!(json.matches("async_boundary").count(), 1);
+    }
+
+    #[test]
+    fn test_firefox_frame_flags_output() {
+        let json = parse("async*load@https://app.test/main.js:1:2\nnotify@resource://gre/modules/Timer.sys.mjs:3:4");
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["is_async"], true);
+        assert_eq!(value[1]["browser_internal"], true);
+    }
+
+    #[test]
+    fn test_parse_numbers_exports() {
+        let stack = "Error: x\n at a (/a.js:1:2)\n at b (/b.js:30:40)";
+        assert_eq!(parse_numbers(stack), vec![1, 2, 30, 40]);
+        assert_eq!(parse_line_column(stack), vec![1, 2, 30, 40]);
+        assert_eq!(parse_line_column_simd(stack), vec![1, 2, 0, 30, 40, 1]);
+    }
+
+    #[test]
+    fn test_parse_is_idempotent() {
+        let json = parse("Error: x\n at a (/a.js:1:2)\n - - - - -\n at b (/b.js:3:4)");
+        assert_eq!(parse(&json), json);
+    }
+
+    #[test]
+    fn test_parse_to_envelope() {
+        let json = parse_to_envelope_at("Error: boom\n at a (/a.js:1:2)", 1234.0);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["received"], 1234.0);
+        assert_eq!(value["event_id"].as_str().map(str::len), Some(36));
+        assert_eq!(value["sdk"]["version"], get_version().as_str());
+        assert_eq!(value["error"]["error_type"], "Error");
+        assert_eq!(value["error"]["message"], "Error: boom");
+        assert_eq!(value["error"]["frames"][0]["file_name"], "/a.js");
+
+        let value: serde_json::Value = serde_json::from_str(&parse_to_envelope("Error")).unwrap();
+        assert!(value["received"].as_f64().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_context_exports() {
+        assert!(set_context("user", r#"{"id_hash": "9f86d0"}"#));
+        assert!(!set_context("user", "not json"));
+        let envelope: serde_json::Value = serde_json::from_str(&parse_to_envelope("Error: boom")).unwrap();
+        assert_eq!(envelope["contexts"]["user"]["id_hash"], "9f86d0");
+
+        assert!(clear_context("user"));
+        assert!(!clear_context("user"));
+        clear_all_context();
+        let envelope: serde_json::Value = serde_json::from_str(&parse_to_envelope("Error: boom")).unwrap();
+        assert!(envelope.get("contexts").is_none());
+    }
+
+    #[test]
+    fn test_trace_context_exports() {
+        let header = generate_traceparent(true);
+        let child = child_traceparent(&header).unwrap();
+        assert_eq!(child[..36], header[..36]);
+        assert!(child_traceparent("garbage").is_none());
+
+        let envelope: serde_json::Value = serde_json::from_str(&parse_to_envelope_with_trace("Error", &header)).unwrap();
+        assert_eq!(envelope["trace_id"].as_str(), Some(&header[3..35]));
+        let envelope: serde_json::Value = serde_json::from_str(&parse_to_envelope_with_trace("Error", "")).unwrap();
+        assert!(envelope.get("trace_id").is_none());
+
+        assert_eq!(update_tracestate("a=1", "perflite", "on"), "perflite=on,a=1");
+        assert_eq!(get_tracestate_value("a=1,b=2", "b").as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn test_parse_and_map() {
+        let map = r#"{"version":3,"sources":["src/cart.js"],"names":["addItem"],"mappings":"AAAA,SAASA"}"#;
+        let stack = "TypeError: x\n at a (https://cdn.test/cart.min.js:1:10)\n at b (https://cdn.test/cart.min.js:3:1)";
+        let frames: Vec<ExportedStackFrame> = serde_json::from_str(&parse_and_map(stack, map)).unwrap();
+        assert_eq!(frames[0].file_name, "src/cart.js");
+        assert_eq!(frames[0].function_name, "addItem");
+        assert_eq!((frames[0].line_number, frames[0].column_number), (1, 10));
+        // 无映射的帧保持原样
+        assert_eq!(frames[1].file_name, "https://cdn.test/cart.min.js");
+        assert_eq!(frames[0].annotations.get("symbolicated").map(String::as_str), Some("full"));
+        assert_eq!(frames[1].annotations.get("symbolication_reason").map(String::as_str), Some("mapping_miss"));
+
+        let frames: Vec<ExportedStackFrame> = serde_json::from_str(&parse_and_map(stack, "{}")).unwrap();
+        assert_eq!(frames[0].function_name, "a");
+        assert_eq!(frames[0].annotations.get("symbolication_reason").map(String::as_str), Some("no_map"));
+
+        // 已注册的map按帧文件URL查找，无需每次传入
+        assert!(register_source_map("https://cdn.test/cart.min.js", map));
+        assert!(!register_source_map("https://cdn.test/bad.js", "{}"));
+        let frames: Vec<ExportedStackFrame> = serde_json::from_str(&parse_and_map(stack, "")).unwrap();
+        assert_eq!(frames[0].function_name, "addItem");
+        assert!(unregister_source_map("https://cdn.test/cart.min.js"));
+        clear_source_maps();
+    }
+
+    #[test]
+    fn test_parse_minified_remaps() {
+        let stack = "TypeError: x\n at t (https://min.test/app.min.js:1:10)\n at n (https://min.test/vendor.js:3:1)";
+        let frames: Vec<ExportedStackFrame> = serde_json::from_str(&parse_minified(stack)).unwrap();
+        assert!(frames[0].minified && !frames[1].minified);
+        assert_eq!(frames[0].annotations.get("symbolication_reason").map(String::as_str), Some("no_map"));
+        assert!(frames[1].annotations.is_empty());
+
+        assert!(register_source_map("https://min.test/app.min.js", r#"{"version":3,"sources":["src/cart.js"],"names":["addItem"],"mappings":"AAAA,SAASA"}"#));
+        let frames: Vec<ExportedStackFrame> = serde_json::from_str(&parse_minified(stack)).unwrap();
+        assert_eq!((frames[0].function_name.as_str(), frames[0].file_name.as_str()), ("addItem", "src/cart.js"));
+        assert!(frames[0].minified);
+        assert!(unregister_source_map("https://min.test/app.min.js"));
+    }
+
+    #[test]
+    fn test_parse_and_map_wasm_frames() {
+        assert!(register_wasm_symbols("", r#"{"7": "app::main"}"#));
+        assert!(!register_wasm_symbols("", "not json"));
+
+        let map = r#"{"version":3,"sources":["src/cart.js"],"names":["addItem"],"mappings":"AAAA,SAASA"}"#;
+        let stack = "RuntimeError: unreachable\n at https://cdn.test/app.wasm:wasm-function[7]:0x40\n at a (https://cdn.test/cart.min.js:1:10)";
+        let frames: Vec<ExportedStackFrame> = serde_json::from_str(&parse_and_map(stack, map)).unwrap();
+        assert_eq!(frames[0].function_name, "app::main");
+        assert_eq!(frames[0].wasm_offset, Some(0x40));
+        assert_eq!(frames[1].function_name, "addItem");
+
+        clear_wasm_symbols();
+        assert!(!unregister_wasm_symbols(""));
+    }
+
+    #[test]
+    fn test_parse_with_wasm_names() {
+        let stack = "RuntimeError: unreachable\n at wasm://wasm/6d7a9f2e:wasm-function[42]:0x1a2b\n at $render (https://cdn.test/app.wasm:wasm-function[7]:0x88)";
+        let frames: Vec<ExportedStackFrame> = serde_json::from_str(&parse_with_wasm_names(stack, r#"{"42": "core::panicking::panic", "7": "ignored"}"#)).unwrap();
+        // 名称段只还原没有真实名称的帧
+        assert_eq!(frames[0].function_name, "core::panicking::panic");
+        assert_eq!(frames[0].address, Some(0x1a2b));
+        assert_eq!(frames[1].function_name, "$render");
+    }
+
+    #[test]
+    fn test_parse_with_owner() {
+        assert_eq!(set_ownership_rules("# 归属规则\n*.js @web\n/src/checkout/ @payments\n"), 2);
+        let stack = "Error: x\n at pay (https://shop.test/src/checkout/pay.js:1:2)\n at main (https://shop.test/src/index.js:1:1)";
+        let report: serde_json::Value = serde_json::from_str(&parse_with_owner(stack)).unwrap();
+        assert_eq!(report["owners"][0].as_str(), Some("@payments"));
+        assert_eq!(report["frames"].as_array().map(Vec::len), Some(2));
+
+        let envelope: serde_json::Value = serde_json::from_str(&parse_to_envelope(stack)).unwrap();
+        assert_eq!(envelope["error"]["owners"][0].as_str(), Some("@payments"));
+        set_ownership_rules("");
+    }
+
+    #[test]
+    fn test_event_buffer_exports() {
+        configure_event_buffer(10, 4096);
+        assert!(enqueue_error(&parse_to_envelope("Error: x\n at a (/src/a.js:1:2)")));
+        assert!(enqueue_metric(r#"{"name":"lcp","value":1200}"#));
+        assert!(!enqueue_metric("not json"));
+
+        let payloads: Vec<serde_json::Value> = serde_json::from_str(&flush(4096)).unwrap();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[0]["type"].as_str(), Some("error"));
+        assert_eq!(payloads[0]["payload"]["error"]["message"].as_str(), Some("Error: x"));
+
+        let stats: EventBufferStats = serde_json::from_str(&get_event_buffer_stats()).unwrap();
+        assert_eq!(stats.queued, 0);
+        clear_event_buffer();
+    }
+
+    #[test]
+    fn test_parse_error_chain_json() {
+        let value: serde_json::Value = serde_json::from_str(&parse_error_chain_json("Error: outer\n at a (/a.js:1:2)\nCaused by: RangeError: inner")).unwrap();
+        assert_eq!(value[1]["error_type"], "RangeError");
+        assert_eq!(value[0]["frames"][0]["file_name"], "/a.js");
+    }
+
+    #[test]
+    fn test_histogram_exports() {
+        assert!(create_histogram("lcp", r#"{"linear": {"start": 1000, "width": 1000, "count": 3}}"#));
+        assert!(!create_histogram("bad", r#"{"linear": {"start": 0, "width": 0, "count": 3}}"#));
+        assert!(record_histogram("lcp", 2400.0));
+        assert!(record_histogram("ttfb", 120.0));
+
+        let value: serde_json::Value = serde_json::from_str(&export_histograms(true)).unwrap();
+        assert_eq!(value["lcp"]["bucketCounts"][2].as_u64(), Some(1));
+        assert_eq!(value["ttfb"]["explicitBounds"].as_array().map(Vec::len), Some(16));
+        let value: serde_json::Value = serde_json::from_str(&export_histograms(false)).unwrap();
+        assert_eq!(value["lcp"]["count"].as_u64(), Some(0));
+        assert!(remove_histogram("lcp") && remove_histogram("ttfb"));
+
+        // camelCase只转换数据点字段，名称保持原样
+        assert!(record_histogram("page_load", 10.0) && set_json_field_naming("camelCase"));
+        let value: serde_json::Value = serde_json::from_str(&export_histograms(false)).unwrap();
+        assert!(value["page_load"]["startTimeUnixNano"].is_string());
+        assert!(set_json_field_naming("snake_case") && remove_histogram("page_load"));
+    }
+
+    #[test]
+    fn test_split_and_parse() {
+        let value: serde_json::Value = serde_json::from_str(&split_and_parse("TypeError: a\n at f (/a.js:1:2)\nlog line\nError: b")).unwrap();
+        assert_eq!(value.as_array().map(Vec::len), Some(2));
+        assert_eq!(value[1]["message"], "b");
+    }
+
+    #[test]
+    fn test_aggregate_exports() {
+        let value: serde_json::Value = serde_json::from_str(&aggregate_i32(&[4, 1, 3, 2], "min,max,p50")).unwrap();
+        assert_eq!(value["count"].as_u64(), Some(4));
+        assert_eq!((value["min"].as_f64(), value["max"].as_f64(), value["p50"].as_f64()), (Some(1.0), Some(4.0), Some(2.5)));
+    }
+
+    #[test]
+    fn test_parse_error_json() {
+        let value: serde_json::Value = serde_json::from_str(&parse_error_json("TypeError: boom\n at a (/a.js:1:2)")).unwrap();
+        assert_eq!(value["error_type"], "TypeError");
+        assert_eq!(value["message"], "boom");
+        assert_eq!(value["frames"][0]["file_name"], "/a.js");
+    }
+
+    #[test]
+    fn test_field_naming() {
+        let stack = "Error: test\n at Component (/src/App.js:10:20)";
+        let json = parse_with_naming(stack, "camelCase");
+        assert!(json.contains(r#""functionName":"Component""#) && json.contains(r#""lineNumber":10"#));
+        assert!(parse(stack).contains(r#""function_name":"Component""#));
+
+        assert!(set_json_field_naming("camelCase"));
+        assert_eq!(get_json_field_naming(), "camelCase");
+        let envelope = parse_to_envelope("Error: boom\n at a (/a.js:1:2)");
+        assert!(envelope.contains(r#""eventId""#) && envelope.contains(r#""fileName":"/a.js""#));
+        assert!(parse_with_owner("Error: boom\n at a (/a.js:1:2)").contains(r#""lineNumber":1"#));
+        assert!(get_stats().contains(r#""remapCache""#) && get_stats().contains(r#""eventBuffer""#));
+
+        // camelCase输出可以重新输入
+        let camel = parse_with_naming(stack, "camel");
+        assert!(parse_with_naming(&camel, "snake_case").contains(r#""function_name":"Component""#));
+
+        assert!(!set_json_field_naming("kebab-case"));
+        assert!(set_json_field_naming("snake_case"));
+    }
+
+    #[test]
+    fn test_storm_exports() {
+        clear_error_storms();
+        assert!(set_storm_policy(r#"{"min_events": 3, "report_limit": 1}"#));
+        assert!(!set_storm_policy("[]"));
+        let reported = (0..10).filter(|_| record_error_for_storm("fp")).count();
+        assert_eq!(reported, 4);
+        assert_eq!(take_error_storms(), "[]");
+        clear_error_storms();
+        set_storm_policy("{}");
+    }
+
+    #[test]
+    fn test_retry_exports() {
+        assert!(set_retry_policy(r#"{"base_delay_ms": 500, "jitter": 0}"#));
+        assert!(!set_retry_policy("[]"));
+        assert!(!set_retry_policy(r#"{"base_delay_ms": 500, "max_delay_ms": 100}"#));
+        assert_eq!(on_send_result("https://ingest.test", 503, None), "retry");
+        let delay = next_send_delay("https://ingest.test");
+        assert!(delay > 0.0 && delay <= 500.0);
+        assert_eq!(on_send_result("https://ingest.test", 400, None), "drop");
+
+        let state: EndpointState = serde_json::from_str(&get_retry_state("https://ingest.test")).unwrap();
+        assert_eq!((state.consecutive_failures, state.dropped_events), (1, 1));
+        reset_retry_state();
+        assert_eq!(next_send_delay("https://ingest.test"), 0.0);
+    }
+
+    #[test]
+    fn test_uuid_exports() {
+        assert_eq!(uuid_v4().as_bytes()[14], b'4');
+        assert_eq!(uuid_v7().as_bytes()[14], b'7');
+        assert_ne!(uuid_v7(), uuid_v7());
+    }
+
+    #[test]
+    fn test_parse_with_severity() {
+        let stack = "TypeError: x is undefined\n at render (/src/App.js:10:15)";
+        let json = parse_with_severity(stack, r#"{"handled": false, "source": "onerror"}"#);
+        assert!(json.starts_with("{\"severity\":\"fatal\",\"frames\":["));
+
+        let json = parse_with_severity(stack, r#"{"handled": true, "source": "console"}"#);
+        assert!(json.contains("\"severity\":\"warning\""));
+
+        assert!(set_severity_rules(r#"{"rules": [{"message_contains": "TypeError", "severity": "info"}]}"#));
+        assert!(parse_with_severity(stack, "").contains("\"severity\":\"info\""));
+        assert!(!set_severity_rules("[]"));
+
+        // 格式错误的捕获方式不会被当作未处理错误
+        assert_eq!(parse_with_severity(stack, r#"{"handled": "true"}"#), "{}");
+    }
+
+    #[test]
+    fn test_interactions_in_envelope() {
+        assert!(record_interactions(r#"[{"kind": "click", "target": "button#buy", "timestamp": 10}, {"kind": "click", "target": "button#buy", "timestamp": 20}]"#));
+        assert!(record_interactions(r#"{"kind": "input", "target": "input#email", "value": "a@b.c", "timestamp": 50}"#));
+        assert!(!record_interactions(r#"{"kind": "scroll"}"#));
+
+        let value: serde_json::Value = serde_json::from_str(&parse_to_envelope("Error: x")).unwrap();
+        let events = value["contexts"]["interactions"]["events"].as_array().unwrap();
+        assert_eq!((events[0]["repeat"].as_u64(), events[1]["dt"].as_u64(), events[1]["length"].as_u64()), (Some(2), Some(40), Some(5)));
+
+        // 用户设置的同名上下文不被覆盖
+        assert!(set_context("interactions", r#"{"source": "app"}"#));
+        let value: serde_json::Value = serde_json::from_str(&parse_to_envelope("Error: x")).unwrap();
+        assert_eq!(value["contexts"]["interactions"]["source"], "app");
+        assert!(clear_context("interactions"));
+
+        clear_interactions();
+        assert!(!parse_to_envelope("Error: x").contains("interactions"));
+    }
+
+    #[test]
+    fn test_parse_with_breadcrumbs() {
+        configure_breadcrumbs(10, 2);
+        add_breadcrumb("navigation", "/home", "info");
+        add_breadcrumb("ui.click", "button#buy", "info");
+        add_breadcrumb("xhr", "POST /api/cart 500", "error");
+
+        let json = parse_with_breadcrumbs("Error: x\n at buy (/src/cart.js:3:9)");
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["frames"].as_array().unwrap().len(), 1);
+
+        let crumbs = value["breadcrumbs"].as_array().unwrap();
+        assert_eq!(crumbs.len(), 2);
+        assert_eq!(crumbs[0]["category"], "ui.click");
+        assert_eq!(crumbs[1]["level"], "error");
+
+        clear_breadcrumbs();
+        assert!(parse_with_breadcrumbs("Error: x").contains("\"breadcrumbs\":[]"));
+    }
+
+    #[test]
+    fn test_hash_string() {
+        assert_eq!(hash_string("xxhash64", "").as_deref(), Some("ef46db3751d8e999"));
+        assert_eq!(hash_string("blake3", "abc").map(|h| h.len()), Some(64));
+        assert_eq!(hash_string("sha1", "abc"), None);
+        assert!(set_fingerprint_hash("blake3"));
+        assert!(!set_fingerprint_hash("crc32"));
+        assert_eq!(get_fingerprint_hash(), "blake3");
+    }
+
+    #[test]
+    fn test_parse_with_context() {
+        register_bundle_source("/bundle/main.js", "const a = 1;\nthrow new Error('x');\nconst b = 2;");
+        let json = parse_with_context("Error: x\n at main (/bundle/main.js:2:7)");
+        assert!(json.contains("\"context_line\":\"throw new Error('x');\""));
+        assert!(json.contains("\"pre_context\":[\"const a = 1;\"]"));
+        assert!(unregister_bundle_source("/bundle/main.js"));
+
+        // 未注册源码时不附加上下文
+        assert!(!parse_with_context("Error: x\n at main (/bundle/main.js:2:7)").contains("context"));
+    }
+
+    #[test]
+    fn test_memory_budget_degradation() {
+        set_memory_budget(64);
+        register_bundle_source("/budget/main.js", &"x".repeat(256));
+        let status: serde_json::Value = serde_json::from_str(&get_memory_status()).unwrap();
+        assert_eq!(status["budget_bytes"], 64);
+        assert_eq!(status["context_lines"], false);
+        assert_eq!(status["degraded"], true);
+
+        // 降级后不再接受新的源码
+        register_bundle_source("/budget/small.js", "x");
+        assert!(!unregister_bundle_source("/budget/small.js"));
+
+        set_memory_budget(0);
+        assert!(get_memory_status().contains("\"budget_bytes\":null"));
+    }
+
+    #[test]
+    fn test_remap_cache_exports() {
+        set_remap_cache_capacity(16);
+        assert_eq!(release_remap_cache("/never/cached.js"), 0);
+        let stats: RemapCacheStats = serde_json::from_str(&get_remap_cache_stats()).unwrap();
+        assert_eq!(stats.entries, 0);
+        clear_remap_cache();
+    }
+
+    #[test]
+    fn test_flush_expired() {
+        set_retention_policy(0.0, 0.0);
+        record_group("TypeError@render");
+        let payload: AggregatePayload = serde_json::from_str(&flush_expired()).unwrap();
+        assert_eq!(payload.expired_groups[0].key, "TypeError@render");
+        assert_eq!(payload.buckets[0].counts.get("TypeError@render"), Some(&1));
+        set_retention_policy(3_600_000.0, 1_800_000.0);
+    }
+
+    #[test]
+    fn test_verify_artifact() {
+        let bundle = "a();\n//# sourceMappingURL=app.js.map";
+        let json = verify_artifact(bundle, r#"{"version":3,"file":"app.js","sources":["a.js"],"names":[],"mappings":"AAAA"}"#);
+        assert!(json.starts_with("{\"ok\":true"));
+        assert!(verify_artifact(bundle, "{}").contains("\"unsupported_version\""));
+    }
+
+    #[test]
+    fn test_parse_lenient_random_input() {
+        // 基于种子栈片段与随机字节的变异输入，覆盖非法UTF-8、超长数字与截断的位置
+        let seeds: [&[u8]; 4] = [
+            b"TypeError: x\n at render (https://cdn.test/app.js:10:15)",
+            b"f@http://a.test/b.js:1:2\n@debugger eval code:1:1",
+            b"    at wasm-function[12]:0x1f\n    at async Promise.all (index 0)",
+            b"\xff\xfe at (:::::99999999999999999999:)",
+        ];
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..2000 {
+            let mut input = seeds[(next() % seeds.len() as u64) as usize].to_vec();
+            for _ in 0..(next() % 8) {
+                let at = (next() as usize) % (input.len() + 1);
+                match next() % 3 {
+                    0 => input.insert(at, next() as u8),
+                    1 if at < input.len() => {
+                        input.remove(at);
+                    }
+                    _ => {
+                        input.splice(at..at, b":0x(@".iter().copied());
+                    }
+                }
+            }
+            let json = parse_lenient(&input);
+            assert!(serde_json::from_str::<serde_json::Value>(&json).unwrap().is_array());
+        }
+
+        let frames: serde_json::Value = serde_json::from_str(&parse_lenient(b"Error\n at f (/src/\xc3(x.js:1:2)")).unwrap();
+        assert_eq!(frames[0]["file_name"].as_str(), Some("/src/\u{fffd}(x.js"));
+        // 修饰名中超长的长度前缀
+        assert!(parse_lenient(b"Error\n 1: 0x1 _Z18446744073709551615x").starts_with('['));
+    }
+
+    #[test]
+    fn test_set_app_roots() {
+        let a = "TypeError: x\n at render (https://cdn-a.test/assets/js/app.js:10:15)";
+        let b = "TypeError: x\n at render (/var/www/app/js/app.js:10:15)";
+        assert_ne!(fingerprint(a), fingerprint(b));
+
+        assert!(set_app_roots(r#"["https://cdn-a.test/assets/", "/var/www/app/"]"#));
+        let value: serde_json::Value = serde_json::from_str(&parse_error_json(a)).unwrap();
+        assert_eq!(value["frames"][0]["file_name"], "js/app.js");
+        assert_eq!(fingerprint(a), fingerprint(b));
+
+        assert!(!set_app_roots("{}"));
+        assert!(set_app_roots("[]"));
+        assert_ne!(fingerprint(a), fingerprint(b));
+    }
+
+    #[test]
+    fn test_set_package_map() {
+        assert!(set_package_map(r#"{"packages/checkout/": {"name": "@shop/checkout", "version": "1.2.0"}}"#));
+        let value: serde_json::Value = serde_json::from_str(&parse_error_json("Error: x\n at pay (/repo/packages/checkout/pay.js:1:2)\n at main (/repo/src/main.js:3:4)")).unwrap();
+        assert_eq!(value["frames"][0]["annotations"]["package"], "@shop/checkout");
+        assert_eq!(value["frames"][0]["annotations"]["package_version"], "1.2.0");
+        assert!(value["frames"][1].get("annotations").is_none());
+
+        assert!(!set_package_map(r#"{"packages/": "x"}"#));
+    }
+
+    #[test]
+    fn test_severity_sampling() {
+        let id = "4bf92f3577b34da6a3ffffffffffffff";
+        assert!(set_severity_sample_rates(r#"{"info": 0}"#));
+        assert!(!should_sample_with_severity(id, 1.0, "info"));
+        assert!(should_sample_with_severity(id, 1.0, "fatal") && should_sample_with_severity(id, 1.0, "bogus"));
+        assert!(!set_severity_sample_rates("[]"));
+        assert!(set_severity_sample_rates("{}"));
+    }
+
+    #[test]
+    fn test_parse_with_budget_resumes() {
+        let stack: String = std::iter::once("Error: deep".to_string())
+            .chain((0..400).map(|i| format!("    at f{} (/src/deep.js:{}:1)", i, i + 1)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // 1微秒的预算不足以处理400帧，需要多次用句柄继续
+        let mut value: serde_json::Value = serde_json::from_str(&parse_with_budget(&stack, 1)).unwrap();
+        let mut names: Vec<String> = Vec::new();
+        let mut continuations = 0;
+        loop {
+            names.extend(value["frames"].as_array().unwrap().iter().map(|frame| frame["function_name"].as_str().unwrap().to_string()));
+            let Some(handle) = value["continuation"].as_u64() else {
+                break;
+            };
+            assert_eq!(value["budget_exceeded"], true);
+            continuations += 1;
+            value = serde_json::from_str(&resume_parse(handle as u32, 1)).unwrap();
+        }
+        assert!(continuations >= 2);
+        assert_eq!(names.len(), 400);
+        assert_eq!((names[0].as_str(), names[399].as_str()), ("f0", "f399"));
+        assert_eq!(value["budget_exceeded"], false);
+    }
+
+    #[test]
+    fn test_parse_with_options() {
+        let stack = "render@https://cdn.test/app.js:10:15\nmap@[native code]";
+        let value: serde_json::Value = serde_json::from_str(&parse_with_options(stack, "{}")).unwrap();
+        assert_eq!(value["dialect"], "jsc");
+        assert_eq!(value["frames"].as_array().map(Vec::len), Some(2));
+
+        let value: serde_json::Value = serde_json::from_str(&parse_with_options(stack, r#"{"dialect": "v8"}"#)).unwrap();
+        assert_eq!(value["dialect"], "v8");
+        assert_eq!(value["frames"].as_array().map(Vec::len), Some(0));
+
+        // 无效的项忽略，其余选项仍然生效；全局的行列号设置不能按次修改
+        let value: serde_json::Value = serde_json::from_str(&parse_with_options(stack, r#"{"dialect": "chakra", "redact": true, "column_base": 0}"#)).unwrap();
+        assert_eq!(value["dialect"], "jsc");
+        assert_eq!(value["frames"][0]["column_number"], 15);
+        assert!(!value["frames"][0]["file_name"].as_str().unwrap().contains("cdn.test"));
+        assert_eq!(parser_options::options(), ParserOptions::default());
+
+        // 全局设置在解析期间保持生效
+        set_parser_options(1, 0);
+        let value: serde_json::Value = serde_json::from_str(&parse_with_options(stack, "{}")).unwrap();
+        assert_eq!(value["frames"][0]["column_number"], 14);
+        parser_options::set_options(ParserOptions::default());
+
+        let value: serde_json::Value = serde_json::from_str(&parse_with_options(stack, r#"{"redact": true}"#)).unwrap();
+        assert_eq!(value["frames"][0]["file_name"].as_str().map(|file| file.ends_with("/app.js") && !file.contains("cdn.test")), Some(true));
+    }
 }
\ No newline at end of file
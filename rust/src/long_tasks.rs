@@ -0,0 +1,204 @@
+use serde::{Serialize, Deserialize};
+use crate::exported_frame::ExportedStackFrame;
+use crate::parser::StackFrame;
+use crate::sourcemap_registry;
+
+/// Long Tasks API的归因信息（`TaskAttributionTiming`）
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TaskAttribution {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, rename = "containerType")]
+    pub container_type: String,
+    #[serde(default, rename = "containerSrc")]
+    pub container_src: String,
+    #[serde(default, rename = "containerId")]
+    pub container_id: String,
+    #[serde(default, rename = "containerName")]
+    pub container_name: String,
+}
+
+/// 一条 `longtask` 性能条目
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LongTask {
+    #[serde(rename = "startTime")]
+    pub start_time: f64,
+    pub duration: f64,
+    #[serde(default)]
+    pub attribution: Vec<TaskAttribution>,
+}
+
+/// JS Self-Profiling API的栈帧，行列号从1开始
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProfilerFrame {
+    pub name: String,
+    #[serde(default, rename = "resourceId")]
+    pub resource_id: Option<usize>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    #[serde(default)]
+    pub column: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProfilerStack {
+    #[serde(rename = "frameId")]
+    pub frame_id: usize,
+    #[serde(default, rename = "parentId")]
+    pub parent_id: Option<usize>,
+}
+
+/// 一次采样，`stackId` 缺失表示空闲
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProfilerSample {
+    pub timestamp: f64,
+    #[serde(default, rename = "stackId")]
+    pub stack_id: Option<usize>,
+}
+
+/// `Profiler.stop()` 返回的trace
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ProfilerTrace {
+    #[serde(default)]
+    pub resources: Vec<String>,
+    #[serde(default)]
+    pub frames: Vec<ProfilerFrame>,
+    #[serde(default)]
+    pub stacks: Vec<ProfilerStack>,
+    #[serde(default)]
+    pub samples: Vec<ProfilerSample>,
+}
+
+/// 慢代码报告：长任务期间采样最多的栈顶帧及其调用栈，均已按source map还原
+#[derive(Serialize)]
+pub struct SlowCodeReport {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub start_time: f64,
+    pub duration: f64,
+    pub attribution: Vec<TaskAttribution>,
+    /// 长任务期间的非空闲采样数
+    pub sample_count: u32,
+    /// 栈顶为热点帧的采样占比
+    pub hot_ratio: f64,
+    pub hot_frame: ExportedStackFrame,
+    /// 热点帧所在的调用栈，栈顶在前
+    pub stack: Vec<ExportedStackFrame>,
+}
+
+impl ProfilerTrace {
+    fn frame(&self, frame_id: usize) -> Option<StackFrame> {
+        let frame = self.frames.get(frame_id)?;
+        let file = frame.resource_id.and_then(|id| self.resources.get(id)).cloned().unwrap_or_default();
+        let name = if frame.name.is_empty() { "<anonymous>".to_string() } else { frame.name.clone() };
+        Some(StackFrame::new(name, file, frame.line.unwrap_or(0), frame.column.unwrap_or(0)))
+    }
+
+    /// 从栈顶沿父节点展开调用栈，遇到环或无效ID时停止
+    fn unwind(&self, stack_id: usize) -> Vec<StackFrame> {
+        let mut frames = Vec::new();
+        let mut current = Some(stack_id);
+        // 按访问过的节点数限制步数，无效frameId的节点不产生帧，不能按帧数判断环
+        let mut steps = 0;
+        while let Some(stack) = current.and_then(|id| self.stacks.get(id)) {
+            if steps >= self.stacks.len() {
+                break;
+            }
+            steps += 1;
+            frames.extend(self.frame(stack.frame_id));
+            current = stack.parent_id;
+        }
+        frames
+    }
+
+    /// 找出长任务期间的热点栈顶帧，任务时间段内没有采样时返回None
+    pub fn culprit(&self, task: &LongTask) -> Option<SlowCodeReport> {
+        let end = task.start_time + task.duration;
+        let stack_ids: Vec<usize> = self.samples.iter()
+            .filter(|sample| sample.timestamp >= task.start_time && sample.timestamp <= end)
+            .filter_map(|sample| sample.stack_id)
+            .filter(|id| *id < self.stacks.len())
+            .collect();
+
+        // 按栈顶帧计数，次数相同时取最早出现的
+        let mut counts: Vec<(usize, usize, u32)> = Vec::new();
+        for &stack_id in &stack_ids {
+            let frame_id = self.stacks[stack_id].frame_id;
+            match counts.iter_mut().find(|(id, _, _)| *id == frame_id) {
+                Some(entry) => entry.2 += 1,
+                None => counts.push((frame_id, stack_id, 1)),
+            }
+        }
+        let (_, stack_id, hits) = counts.iter().copied().reduce(|best, entry| if entry.2 > best.2 { entry } else { best })?;
+
+        let stack: Vec<StackFrame> = self.unwind(stack_id).iter()
+            .map(sourcemap_registry::symbolicate)
+            .collect();
+        let hot_frame = stack.first()?.clone();
+        Some(SlowCodeReport {
+            kind: "slow_code".to_string(),
+            start_time: task.start_time,
+            duration: task.duration,
+            attribution: task.attribution.clone(),
+            sample_count: stack_ids.len() as u32,
+            hot_ratio: hits as f64 / stack_ids.len() as f64,
+            hot_frame: ExportedStackFrame::from(hot_frame),
+            stack: stack.into_iter().map(ExportedStackFrame::from).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace() -> ProfilerTrace {
+        serde_json::from_str(r#"{
+            "resources": ["https://cdn.test/app.js"],
+            "frames": [
+                {"name": "main", "resourceId": 0, "line": 1, "column": 10},
+                {"name": "sortRows", "resourceId": 0, "line": 40, "column": 3},
+                {"name": "layout"}
+            ],
+            "stacks": [
+                {"frameId": 0},
+                {"frameId": 1, "parentId": 0},
+                {"frameId": 2, "parentId": 0}
+            ],
+            "samples": [
+                {"timestamp": 90, "stackId": 2},
+                {"timestamp": 100, "stackId": 1},
+                {"timestamp": 110, "stackId": 1},
+                {"timestamp": 120, "stackId": 2},
+                {"timestamp": 130},
+                {"timestamp": 200, "stackId": 2}
+            ]
+        }"#).unwrap()
+    }
+
+    #[test]
+    fn test_culprit() {
+        let task: LongTask = serde_json::from_str(r#"{"startTime": 95, "duration": 60, "attribution": [{"name": "self", "containerType": "window"}]}"#).unwrap();
+        let report = trace().culprit(&task).unwrap();
+        assert_eq!((report.sample_count, report.hot_ratio), (3, 2.0 / 3.0));
+        assert_eq!(report.hot_frame.function_name, "sortRows");
+        assert_eq!((report.hot_frame.line_number, report.stack.len()), (40, 2));
+        assert_eq!(report.stack[1].function_name, "main");
+        assert_eq!(report.attribution[0].container_type, "window");
+
+        let idle = LongTask { start_time: 125.0, duration: 50.0, attribution: Vec::new() };
+        assert!(trace().culprit(&idle).is_none());
+    }
+
+    #[test]
+    fn test_unwind_stops_on_cycle() {
+        let mut trace = trace();
+        trace.stacks[0].parent_id = Some(1);
+        assert_eq!(trace.unwind(1).len(), 3);
+
+        // 环上的节点引用不存在的frameId时同样终止
+        trace.stacks[0].frame_id = 9;
+        trace.stacks[1].frame_id = 9;
+        assert!(trace.unwind(1).is_empty());
+    }
+}
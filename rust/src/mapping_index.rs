@@ -0,0 +1,232 @@
+use std::cell::{Cell, OnceCell};
+use std::mem::size_of;
+use crate::sourcemap::{decode_segment_fields, Mapping};
+
+// 行内每块的segment数，打包产物常把整个bundle压成一行，按块解码避免一次展开整行
+const BLOCK_SEGMENTS: u32 = 256;
+
+/// 一个生成行：mappings中的字节范围、行首的跨行VLQ状态与首次查询时建立的块索引
+struct Line {
+    start: usize,
+    end: usize,
+    // 行首的 source、original_line、original_column、name 累计值
+    state: [i64; 4],
+    segments: u32,
+    blocks: OnceCell<Vec<Block>>,
+}
+
+/// 行内连续的一段segment：字节范围、块首的列与VLQ状态、延迟解码的映射
+struct Block {
+    start: usize,
+    end: usize,
+    column: i64,
+    state: [i64; 4],
+    // 块内第一条映射的生成列，按列二分定位块
+    first_column: u32,
+    segments: u32,
+    decoded: OnceCell<Vec<Mapping>>,
+}
+
+/// 三级映射索引：注册时只扫描行边界并累计跨行的VLQ状态；某一行第一次被查询时建立块索引，
+/// 只解码目标列所在的块并缓存，数百MB的source map也只展开实际查询到的部分
+#[derive(Default)]
+pub struct MappingIndex {
+    mappings: String,
+    lines: Vec<Line>,
+    count: usize,
+    // 已建立的块索引与已解码映射的字节数
+    expanded_bytes: Cell<usize>,
+}
+
+impl MappingIndex {
+    /// 建立索引并校验全部segment，格式非法时返回错误
+    pub fn new(mappings: String) -> Result<MappingIndex, String> {
+        let mut lines = Vec::new();
+        let mut state = [0i64; 4];
+        let mut count = 0;
+        let mut start = 0;
+
+        for (line, text) in mappings.split(';').enumerate() {
+            let line_state = state;
+            let mut segments = 0;
+            for segment in text.split(',').filter(|s| !s.is_empty()) {
+                let (fields, len) = decode_segment_fields(segment)
+                    .ok_or_else(|| format!("第 {} 行存在非法segment: {}", line + 1, segment))?;
+                for i in 1..len {
+                    state[i - 1] += fields[i];
+                }
+                segments += 1;
+            }
+            count += segments as usize;
+            lines.push(Line { start, end: start + text.len(), state: line_state, segments, blocks: OnceCell::new() });
+            start += text.len() + 1;
+        }
+
+        // 末尾没有映射的行不参与查询
+        while lines.last().is_some_and(|line| line.segments == 0) {
+            lines.pop();
+        }
+        Ok(MappingIndex { mappings, lines, count, expanded_bytes: Cell::new(0) })
+    }
+
+    /// 映射总条数
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// 生成位置（从0开始）对应的映射：同一行中列号不大于目标的最后一条
+    pub fn lookup(&self, line: usize, column: u32) -> Option<&Mapping> {
+        let entry = self.lines.get(line)?;
+        let blocks = entry.blocks.get_or_init(|| self.build_blocks(entry));
+        let block = &blocks[blocks.partition_point(|block| block.first_column <= column).checked_sub(1)?];
+        let mappings = block.decoded.get_or_init(|| self.decode_block(line, block));
+        let index = mappings.partition_point(|mapping| mapping.generated_column <= column);
+        mappings[..index].last()
+    }
+
+    /// 扫描一行，每 `BLOCK_SEGMENTS` 个segment记录一次字节位置、列与VLQ状态
+    fn build_blocks(&self, entry: &Line) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::with_capacity(entry.segments.div_ceil(BLOCK_SEGMENTS) as usize);
+        let mut state = entry.state;
+        let mut column = 0i64;
+        let mut offset = entry.start;
+
+        // 建立索引时已校验，这里的segment都合法
+        for segment in self.mappings[entry.start..entry.end].split(',') {
+            let segment_start = offset;
+            offset += segment.len() + 1;
+            let Some((fields, len)) = decode_segment_fields(segment) else {
+                continue;
+            };
+            if blocks.last().map_or(true, |block| block.segments == BLOCK_SEGMENTS) {
+                if let Some(previous) = blocks.last_mut() {
+                    previous.end = segment_start;
+                }
+                blocks.push(Block {
+                    start: segment_start,
+                    end: entry.end,
+                    column,
+                    state,
+                    first_column: (column + fields[0]).max(0) as u32,
+                    segments: 0,
+                    decoded: OnceCell::new(),
+                });
+            }
+            column += fields[0];
+            for i in 1..len {
+                state[i - 1] += fields[i];
+            }
+            if let Some(block) = blocks.last_mut() {
+                block.segments += 1;
+            }
+        }
+        self.expanded_bytes.set(self.expanded_bytes.get() + blocks.len() * size_of::<Block>());
+        blocks
+    }
+
+    fn decode_block(&self, line: usize, block: &Block) -> Vec<Mapping> {
+        let [mut source, mut original_line, mut original_column, mut name] = block.state;
+        let mut column = block.column;
+        let mut decoded = Vec::with_capacity(block.segments as usize);
+
+        for segment in self.mappings[block.start..block.end].split(',').filter(|s| !s.is_empty()) {
+            let Some((fields, len)) = decode_segment_fields(segment) else {
+                continue;
+            };
+            column += fields[0];
+            let mut mapping = Mapping {
+                generated_line: line as u32,
+                generated_column: column.max(0) as u32,
+                source: None,
+                original_line: 0,
+                original_column: 0,
+                name: None,
+            };
+            if len >= 4 {
+                source += fields[1];
+                original_line += fields[2];
+                original_column += fields[3];
+                mapping.source = Some(source.max(0) as u32);
+                mapping.original_line = original_line.max(0) as u32;
+                mapping.original_column = original_column.max(0) as u32;
+            }
+            if len == 5 {
+                name += fields[4];
+                mapping.name = Some(name.max(0) as u32);
+            }
+            decoded.push(mapping);
+        }
+
+        // 同一行内的segment通常已按列排序，防御性地再排一次
+        decoded.sort_by_key(|mapping| mapping.generated_column);
+        self.expanded_bytes.set(self.expanded_bytes.get() + decoded.len() * size_of::<Mapping>());
+        decoded
+    }
+
+    /// 原始的 `mappings` 字符串
+    pub fn as_str(&self) -> &str {
+        &self.mappings
+    }
+
+    /// 估算占用的字节数：原始字符串、行索引与已展开的块，随查询增长
+    pub fn approx_bytes(&self) -> usize {
+        self.mappings.len() + self.lines.len() * size_of::<Line>() + self.expanded_bytes.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decoded_blocks(index: &MappingIndex) -> usize {
+        index.lines.iter()
+            .filter_map(|line| line.blocks.get())
+            .flatten()
+            .filter(|block| block.decoded.get().is_some())
+            .count()
+    }
+
+    #[test]
+    fn test_lazy_line_decoding() {
+        let index = MappingIndex::new("AAAA,SAASA;;AACA,EAAC;GACE;".to_string()).unwrap();
+        assert_eq!(index.len(), 5);
+        assert_eq!(decoded_blocks(&index), 0);
+        let bytes = index.approx_bytes();
+
+        // 跳过前面的行直接查询，跨行状态仍然正确
+        let mapping = index.lookup(3, 3).unwrap();
+        assert_eq!((mapping.generated_column, mapping.original_line, mapping.original_column), (3, 2, 12));
+        assert_eq!(decoded_blocks(&index), 1);
+        assert!(index.approx_bytes() > bytes);
+
+        let mapping = index.lookup(2, 5).unwrap();
+        assert_eq!((mapping.generated_column, mapping.original_line, mapping.original_column), (2, 1, 10));
+        assert_eq!(index.lookup(0, 9).unwrap().name, Some(0));
+        assert!(index.lookup(1, 0).is_none());
+        assert!(index.lookup(3, 2).is_none());
+        assert!(index.lookup(4, 0).is_none());
+    }
+
+    #[test]
+    fn test_block_decoding() {
+        // 一行1000个segment，每个列号加1：只解码目标列所在的块
+        let mappings = vec!["CAAC"; 1000].join(",");
+        let index = MappingIndex::new(mappings).unwrap();
+        let mapping = index.lookup(0, 700).unwrap();
+        assert_eq!((mapping.generated_column, mapping.original_column), (700, 700));
+        assert_eq!(decoded_blocks(&index), 1);
+        assert_eq!(index.lines[0].blocks.get().map(Vec::len), Some(4));
+
+        // 块边界两侧
+        assert_eq!(index.lookup(0, 256).unwrap().generated_column, 256);
+        assert_eq!(index.lookup(0, 257).unwrap().generated_column, 257);
+        assert_eq!(index.lookup(0, 5000).unwrap().generated_column, 1000);
+        assert!(index.lookup(0, 0).is_none());
+    }
+
+    #[test]
+    fn test_invalid_segment() {
+        assert!(MappingIndex::new("AAAA;AB".to_string()).is_err());
+        assert_eq!(MappingIndex::new(String::new()).unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,158 @@
+use serde_json::{Map, Value};
+
+/// 一次遍历得到的基础统计量
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Moments {
+    pub count: usize,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// 使用SIMD同时累加和与最值，NaN与无穷值需由调用方预先过滤
+pub fn moments(values: &[f64]) -> Moments {
+    let mut result = Moments {
+        count: values.len(),
+        sum: 0.0,
+        min: f64::INFINITY,
+        max: f64::NEG_INFINITY,
+    };
+    #[cfg(target_feature = "simd128")]
+    let rest = unsafe {
+        use std::arch::wasm32::*;
+        let mut sum = f64x2_splat(0.0);
+        let mut min = f64x2_splat(f64::INFINITY);
+        let mut max = f64x2_splat(f64::NEG_INFINITY);
+
+        let chunks = values.len() / 2;
+        for i in 0..chunks {
+            let v = v128_load(values.as_ptr().add(i * 2) as *const v128);
+            sum = f64x2_add(sum, v);
+            min = f64x2_pmin(min, v);
+            max = f64x2_pmax(max, v);
+        }
+
+        result.sum = f64x2_extract_lane::<0>(sum) + f64x2_extract_lane::<1>(sum);
+        result.min = f64x2_extract_lane::<0>(min).min(f64x2_extract_lane::<1>(min));
+        result.max = f64x2_extract_lane::<0>(max).max(f64x2_extract_lane::<1>(max));
+        &values[chunks * 2..]
+    };
+    #[cfg(not(target_feature = "simd128"))]
+    let rest = values;
+
+    // 降级处理与剩余元素
+    for &value in rest {
+        result.sum += value;
+        result.min = result.min.min(value);
+        result.max = result.max.max(value);
+    }
+    result
+}
+
+/// Welford算法的总体方差，避免 `平方和/n - 均值²` 在数值较大、波动较小时的相消误差
+pub fn variance(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let (mut mean, mut m2) = (0.0, 0.0);
+    for (i, &value) in values.iter().enumerate() {
+        let delta = value - mean;
+        mean += delta / (i + 1) as f64;
+        m2 += delta * (value - mean);
+    }
+    Some(m2 / values.len() as f64)
+}
+
+/// 线性插值的分位数，`sorted` 需已升序排列
+pub fn percentile(sorted: &[f64], q: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+/// 解析 `p95`、`p99.9` 形式的分位数名称
+fn percentile_op(op: &str) -> Option<f64> {
+    let q = op.strip_prefix('p')?.parse::<f64>().ok()?;
+    if (0.0..=100.0).contains(&q) { Some(q / 100.0) } else { None }
+}
+
+/// 支持的统计项，`ops` 为空时输出全部
+const DEFAULT_OPS: [&str; 7] = ["min", "max", "mean", "p50", "p95", "p99", "stddev"];
+
+/// 按 `ops`（逗号分隔）汇总数值，非有限值不参与计算；没有数据时各项为null，未知的统计项被忽略
+pub fn aggregate(values: &[f64], ops: &str) -> Map<String, Value> {
+    let finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let stats = moments(&finite);
+    let requested: Vec<&str> = ops.split(',').map(str::trim).filter(|op| !op.is_empty()).collect();
+    let requested = if requested.is_empty() { DEFAULT_OPS.to_vec() } else { requested };
+
+    // 只有需要分位数时才排序
+    let mut sorted = Vec::new();
+    if requested.iter().any(|op| percentile_op(op).is_some()) {
+        sorted = finite.clone();
+        sorted.sort_unstable_by(f64::total_cmp);
+    }
+
+    let count = stats.count as f64;
+    let mean = (stats.count > 0).then(|| stats.sum / count);
+    let mut result = Map::new();
+    result.insert("count".to_string(), Value::from(stats.count as u64));
+    for op in requested {
+        let value = match op {
+            "min" => (stats.count > 0).then_some(stats.min),
+            "max" => (stats.count > 0).then_some(stats.max),
+            "sum" => Some(stats.sum),
+            "mean" => mean,
+            // 总体标准差
+            "stddev" => variance(&finite).map(f64::sqrt),
+            op => match percentile_op(op) {
+                Some(q) => percentile(&sorted, q),
+                None => continue,
+            },
+        };
+        result.insert(op.to_string(), value.map_or(Value::Null, Value::from));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moments_match_scalar() {
+        let values: Vec<f64> = (1..=101).map(|v| v as f64).collect();
+        let stats = moments(&values);
+        assert_eq!((stats.count, stats.sum, stats.min, stats.max), (101, 5151.0, 1.0, 101.0));
+    }
+
+    #[test]
+    fn test_stddev_of_large_values() {
+        // 时间戳量级的数值，朴素公式会因相消误差得到0或NaN
+        let values = [1.7e12 + 1.0, 1.7e12 + 2.0, 1.7e12 + 3.0];
+        let stddev = aggregate(&values, "stddev")["stddev"].as_f64().unwrap();
+        assert!((stddev - (2.0f64 / 3.0).sqrt()).abs() < 1e-6, "{}", stddev);
+    }
+
+    #[test]
+    fn test_aggregate_ops() {
+        let values: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+        let result = aggregate(&values, "");
+        assert_eq!(result["count"].as_u64(), Some(100));
+        assert_eq!(result["min"].as_f64(), Some(1.0));
+        assert_eq!(result["mean"].as_f64(), Some(50.5));
+        assert_eq!(result["p50"].as_f64(), Some(50.5));
+        assert!((result["p95"].as_f64().unwrap() - 95.05).abs() < 1e-9);
+        assert!((result["stddev"].as_f64().unwrap() - 28.866070).abs() < 1e-6);
+
+        let result = aggregate(&[3.0, f64::NAN, 1.0, f64::INFINITY], "max, p99.9, sum, bogus");
+        assert_eq!(result.len(), 4);
+        assert_eq!((result["count"].as_u64(), result["max"].as_f64(), result["sum"].as_f64()), (Some(2), Some(3.0), Some(4.0)));
+
+        let empty = aggregate(&[], "min,p50");
+        assert_eq!((empty["count"].as_u64(), empty["min"].is_null(), empty["p50"].is_null()), (Some(0), true, true));
+    }
+}
@@ -0,0 +1,145 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::cell::Cell;
+use wasm_bindgen::prelude::*;
+
+// 内容为用户数据的字段，其中的键不做转换
+const OPAQUE_FIELDS: [&str; 3] = ["annotations", "contexts", "counts"];
+
+/// JSON输出的字段命名风格
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldNaming {
+    /// `function_name`（默认）
+    SnakeCase = 0,
+    /// `functionName`
+    CamelCase = 1,
+}
+
+impl FieldNaming {
+    /// 按名称解析，不区分大小写
+    pub fn from_name(name: &str) -> Option<FieldNaming> {
+        match name.to_ascii_lowercase().as_str() {
+            "snake_case" | "snake" => Some(FieldNaming::SnakeCase),
+            "camelcase" | "camel" => Some(FieldNaming::CamelCase),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldNaming::SnakeCase => "snake_case",
+            FieldNaming::CamelCase => "camelCase",
+        }
+    }
+
+    /// 转换单个字段名
+    pub fn convert(&self, key: &str) -> String {
+        match self {
+            FieldNaming::SnakeCase => to_snake_case(key),
+            FieldNaming::CamelCase => to_camel_case(key),
+        }
+    }
+}
+
+/// `function_name` -> `functionName`，开头的下划线保留
+pub fn to_camel_case(key: &str) -> String {
+    let prefix = key.len() - key.trim_start_matches('_').len();
+    let mut result = key[..prefix].to_string();
+    let mut upper = false;
+    for c in key[prefix..].chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            result.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// `functionName` -> `function_name`
+pub fn to_snake_case(key: &str) -> String {
+    let mut result = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            if !result.is_empty() {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// 递归转换对象的字段名，注解等用户数据原样保留
+pub fn rename_keys(value: Value, naming: FieldNaming) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(map.into_iter()
+            .map(|(key, value)| {
+                let value = if OPAQUE_FIELDS.contains(&key.as_str()) { value } else { rename_keys(value, naming) };
+                (naming.convert(&key), value)
+            })
+            .collect()),
+        Value::Array(items) => Value::Array(items.into_iter().map(|item| rename_keys(item, naming)).collect()),
+        value => value,
+    }
+}
+
+/// 按指定命名风格序列化，snake_case直接输出结构体本身的字段名
+pub fn to_json<T: Serialize>(value: &T, naming: FieldNaming) -> serde_json::Result<String> {
+    match naming {
+        FieldNaming::SnakeCase => serde_json::to_string(value),
+        FieldNaming::CamelCase => serde_json::to_string(&rename_keys(serde_json::to_value(value)?, naming)),
+    }
+}
+
+/// 序列化为JSON并写入 `writer`，蛇形命名时不经过中间值
+pub fn write_json<W: std::io::Write, T: Serialize>(writer: W, value: &T, naming: FieldNaming) -> serde_json::Result<()> {
+    match naming {
+        FieldNaming::SnakeCase => serde_json::to_writer(writer, value),
+        FieldNaming::CamelCase => serde_json::to_writer(writer, &rename_keys(serde_json::to_value(value)?, naming)),
+    }
+}
+
+thread_local! {
+    static FIELD_NAMING: Cell<FieldNaming> = const { Cell::new(FieldNaming::SnakeCase) };
+}
+
+/// 设置全局字段命名风格
+pub fn set_field_naming(naming: FieldNaming) {
+    FIELD_NAMING.with(|current| current.set(naming));
+}
+
+/// 当前的全局字段命名风格
+pub fn field_naming() -> FieldNaming {
+    FIELD_NAMING.with(|current| current.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_conversion() {
+        assert_eq!(to_camel_case("function_name"), "functionName");
+        assert_eq!(to_camel_case("_private_key"), "_privateKey");
+        assert_eq!(to_camel_case("line"), "line");
+        assert_eq!(to_snake_case("functionName"), "function_name");
+        assert_eq!(to_snake_case("wasmOffset"), "wasm_offset");
+        assert_eq!(to_snake_case(&to_camel_case("is_in_app")), "is_in_app");
+    }
+
+    #[test]
+    fn test_rename_keys_keeps_user_data() {
+        let value = serde_json::json!([{"file_name": "a.js", "annotations": {"release_channel": "beta"}, "context": {"pre_lines": []}}]);
+        let json = serde_json::to_string(&rename_keys(value, FieldNaming::CamelCase)).unwrap();
+        assert_eq!(json, r#"[{"annotations":{"release_channel":"beta"},"context":{"preLines":[]},"fileName":"a.js"}]"#);
+        assert_eq!(FieldNaming::from_name("camelCase"), Some(FieldNaming::CamelCase));
+        assert_eq!(FieldNaming::from_name("kebab"), None);
+    }
+}
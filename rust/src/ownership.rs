@@ -0,0 +1,170 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use crate::parser::StackFrame;
+
+/// 一条归属规则：路径模式 -> 负责团队
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OwnershipRule {
+    pub pattern: String,
+    pub owners: Vec<String>,
+}
+
+/// CODEOWNERS风格的归属规则表，后出现的规则优先
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct OwnershipRules {
+    pub rules: Vec<OwnershipRule>,
+}
+
+impl OwnershipRules {
+    /// 解析CODEOWNERS文本：每行 `模式 团队...`，忽略空行、`#` 注释与 `[Section]` 标题
+    pub fn parse(text: &str) -> OwnershipRules {
+        let rules = text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+            .filter_map(|line| {
+                let mut tokens = line.split_whitespace();
+                let pattern = tokens.next()?.to_string();
+                let owners = tokens.take_while(|token| !token.starts_with('#')).map(str::to_string).collect();
+                Some(OwnershipRule { pattern, owners })
+            })
+            .collect();
+        OwnershipRules { rules }
+    }
+
+    /// 路径的负责团队：取最后一条匹配的规则，规则没有团队时表示不归属任何人
+    pub fn owners_for(&self, path: &str) -> Vec<String> {
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        self.rules.iter().rev()
+            .find(|rule| pattern_matches(&rule.pattern, &segments))
+            .map(|rule| rule.owners.clone())
+            .unwrap_or_default()
+    }
+
+    /// 按第一个业务代码帧确定错误的负责团队
+    pub fn owners_for_frames(&self, frames: &[StackFrame]) -> Vec<String> {
+        frames.iter()
+            .find(|frame| frame.is_in_app())
+            .map(|frame| self.owners_for(frame_path(frame.file_name_str())))
+            .unwrap_or_default()
+    }
+}
+
+/// 栈帧文件名中的路径部分：去掉协议、主机与查询参数
+fn frame_path(file: &str) -> &str {
+    let path = match file.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+        None => file,
+    };
+    path.split(['?', '#']).next().unwrap_or(path)
+}
+
+/// 按CODEOWNERS语义匹配：含 `/` 的模式从根目录开始匹配，否则匹配任意层级；
+/// 模式匹配目录时也匹配其下所有文件，以 `/` 结尾的模式只匹配目录
+fn pattern_matches(pattern: &str, path: &[&str]) -> bool {
+    let directory_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+
+    let mut segments: Vec<&str> = trimmed.split('/').filter(|s| !s.is_empty()).collect();
+    if !anchored {
+        segments.insert(0, "**");
+    }
+    match_segments(&segments, path, directory_only)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str], directory_only: bool) -> bool {
+    match pattern.split_first() {
+        // 模式已用完：完整匹配文件，或匹配到了某个目录
+        None => !directory_only || !path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..], directory_only)),
+        Some((segment, rest)) => path.split_first()
+            .is_some_and(|(first, tail)| glob_matches(segment, first) && match_segments(rest, tail, directory_only)),
+    }
+}
+
+/// 单段通配：`*` 匹配任意字符，`?` 匹配单个字符
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // 让 `*` 多吞一个字符后重试
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+thread_local! {
+    static RULES: RefCell<OwnershipRules> = RefCell::new(OwnershipRules::default());
+}
+
+/// 替换全局归属规则
+pub fn set_rules(rules: OwnershipRules) {
+    RULES.with(|current| *current.borrow_mut() = rules);
+}
+
+/// 按全局规则确定错误的负责团队
+pub fn owners_for_frames(frames: &[StackFrame]) -> Vec<String> {
+    RULES.with(|rules| rules.borrow().owners_for_frames(frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODEOWNERS: &str = "\
+        # 默认负责人\n\
+        *                @shop/platform\n\
+        *.css            @shop/design\n\
+        /src/checkout/   @shop/payments @alice\n\
+        src/**/cart*.js  @shop/cart\n\
+        /src/legacy/\n";
+
+    #[test]
+    fn test_last_matching_rule_wins() {
+        let rules = OwnershipRules::parse(CODEOWNERS);
+        assert_eq!(rules.rules.len(), 5);
+        assert_eq!(rules.owners_for("src/App.js"), vec!["@shop/platform"]);
+        assert_eq!(rules.owners_for("static/css/main.css"), vec!["@shop/design"]);
+        assert_eq!(rules.owners_for("src/checkout/pay/form.js"), vec!["@shop/payments", "@alice"]);
+        assert_eq!(rules.owners_for("src/views/cartView.js"), vec!["@shop/cart"]);
+        // 没有团队的规则取消归属
+        assert!(rules.owners_for("src/legacy/old.js").is_empty());
+        // 以 `/` 结尾的模式不匹配同名文件
+        assert_eq!(rules.owners_for("src/checkout"), vec!["@shop/platform"]);
+    }
+
+    #[test]
+    fn test_owners_for_top_in_app_frame() {
+        let rules = OwnershipRules::parse(CODEOWNERS);
+        let frames = vec![
+            StackFrame::new("r".to_string(), "https://cdn.test/node_modules/react-dom/index.js".to_string(), 1, 1),
+            StackFrame::new("pay".to_string(), "webpack://shop/src/checkout/pay.js?v=2".to_string(), 3, 4),
+            StackFrame::new("main".to_string(), "/src/index.js".to_string(), 1, 1),
+        ];
+        assert_eq!(rules.owners_for_frames(&frames), vec!["@shop/payments", "@alice"]);
+        assert!(OwnershipRules::default().owners_for_frames(&frames).is_empty());
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*.js", "app.js"));
+        assert!(glob_matches("cart?.js", "cart2.js"));
+        assert!(glob_matches("a*b*c", "axxbyyc"));
+        assert!(!glob_matches("*.js", "app.ts"));
+        assert!(!glob_matches("cart?.js", "cart.js"));
+    }
+}
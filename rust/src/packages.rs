@@ -0,0 +1,102 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::app_root;
+use crate::parser::StackFrame;
+
+/// 路径前缀对应的包
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PackageInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// 单体仓库的包清单：路径前缀 -> 包，JSON为 `{"packages/checkout/": {"name": "@shop/checkout", "version": "1.2.0"}}`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PackageMap {
+    // 按前缀长度降序，最长的匹配优先
+    entries: Vec<(String, PackageInfo)>,
+}
+
+impl PackageMap {
+    pub fn new(map: HashMap<String, PackageInfo>) -> Self {
+        let mut entries: Vec<(String, PackageInfo)> = map.into_iter()
+            .map(|(prefix, package)| (prefix.trim_start_matches('/').to_string(), package))
+            .filter(|(prefix, _)| !prefix.is_empty())
+            .collect();
+        entries.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        PackageMap { entries }
+    }
+
+    /// 清单的JSON表示，`new` 的逆操作
+    pub fn to_map(&self) -> HashMap<String, PackageInfo> {
+        self.entries.iter().cloned().collect()
+    }
+
+    /// 文件所属的包：相对路径以前缀开头，或绝对路径/URL中含以 `/` 开始的前缀
+    pub fn package_for(&self, file: &str) -> Option<&PackageInfo> {
+        let relative = app_root::relativize(file);
+        self.entries.iter()
+            .find(|(prefix, _)| {
+                relative.starts_with(prefix.as_str())
+                    || file.match_indices(prefix.as_str()).any(|(at, _)| file[..at].ends_with('/'))
+            })
+            .map(|(_, package)| package)
+    }
+
+    /// 为帧添加 `package` 与 `package_version` 注解，不属于任何包时不修改
+    pub fn annotate(&self, frame: &mut StackFrame) {
+        let Some(package) = self.package_for(frame.file_name_str()) else {
+            return;
+        };
+        frame.set_annotation("package".to_string(), package.name.clone());
+        if let Some(version) = &package.version {
+            frame.set_annotation("package_version".to_string(), version.clone());
+        }
+    }
+}
+
+thread_local! {
+    static PACKAGES: RefCell<PackageMap> = RefCell::new(PackageMap::default());
+}
+
+/// 替换全局包清单
+pub fn set_package_map(map: PackageMap) {
+    PACKAGES.with(|current| *current.borrow_mut() = map);
+}
+
+/// 当前的全局包清单
+pub fn package_map() -> PackageMap {
+    PACKAGES.with(|packages| packages.borrow().clone())
+}
+
+/// 按全局包清单为帧添加包注解
+pub fn annotate(frame: &mut StackFrame) {
+    PACKAGES.with(|packages| packages.borrow().annotate(frame));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_for() {
+        let map: HashMap<String, PackageInfo> = serde_json::from_str(r#"{
+            "packages/checkout/": {"name": "@shop/checkout", "version": "1.2.0"},
+            "packages/checkout/vendor/": {"name": "@shop/vendor"},
+            "/packages/ui/": {"name": "@shop/ui"}
+        }"#).unwrap();
+        let map = PackageMap::new(map);
+
+        let package = map.package_for("webpack://shop/packages/checkout/src/cart.ts").unwrap();
+        assert_eq!((package.name.as_str(), package.version.as_deref()), ("@shop/checkout", Some("1.2.0")));
+        assert_eq!(map.package_for("/repo/packages/checkout/vendor/x.js").unwrap().name, "@shop/vendor");
+        assert_eq!(map.package_for("packages/ui/button.tsx").unwrap().name, "@shop/ui");
+        assert!(map.package_for("/repo/mypackages/ui/button.tsx").is_none());
+
+        let mut frame = StackFrame::new("f".to_string(), "/repo/packages/checkout/a.js".to_string(), 1, 1);
+        map.annotate(&mut frame);
+        assert_eq!((frame.get_annotation("package").as_deref(), frame.get_annotation("package_version").as_deref()), (Some("@shop/checkout"), Some("1.2.0")));
+    }
+}
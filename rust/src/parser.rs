@@ -1,7 +1,442 @@
 use wasm_bindgen::prelude::*;
 use regex::Regex;
-use std::collections::HashMap;
-use crate::utils::{console_log, format_stack_frame};
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use crate::exported_frame::{self, ExportedStackFrame};
+use crate::app_root;
+use crate::build_manifest;
+use crate::errors::{ParseError, MAX_INPUT_BYTES};
+use crate::fingerprint;
+use crate::time_budget::Deadline;
+use crate::function_names;
+use crate::naming::{self, FieldNaming};
+use crate::packages;
+use crate::parser_options::{self, ParserOptions, UNKNOWN_POSITION};
+use crate::simd::{find_location_suffix, LineIndex};
+use crate::source_context::SourceContext;
+use crate::taxonomy::{self, ErrorCategory};
+use crate::utils::{console_log, demangle, format_stack_frame, parse_number_lenient, split_location_with};
+use crate::wasm_symbols::is_generic_name;
+
+// 判定为单行压缩产物的最小列号
+const MINIFIED_COLUMN_THRESHOLD: u32 = 1000;
+// 压缩帧指纹计算时的列号分桶宽度
+const MINIFIED_COLUMN_BUCKET: u32 = 64;
+// Safari中wasm帧的位置
+const WASM_CODE_LOCATION: &str = "[wasm code]";
+// Safari中内置函数帧的位置
+const NATIVE_CODE_LOCATION: &str = "[native code]";
+// 原生崩溃帧没有二进制名时使用的文件名
+const NATIVE_BINARY_LOCATION: &str = "<native>";
+// Vue组件栈中没有源文件的组件帧使用的文件名
+const VUE_COMPONENT_LOCATION: &str = "<vue component>";
+
+// longjohn等库的纯连字符分隔行的最少长度，更短的连字符行可能是消息内容
+const MIN_SEPARATOR_DASHES: usize = 10;
+
+/// 某个Safari版本起出现的帧写法
+struct SafariDialect {
+    /// 起始的Safari主版本号
+    since: u32,
+    /// 没有 `@` 与位置的整行特殊名称帧：行文本 -> 函数名
+    bare_frames: &'static [(&'static str, &'static str)],
+    /// async函数恢复与Promise调度的引擎内部帧（位于 `[native code]`），按微任务帧合并
+    scheduler_functions: &'static [&'static str],
+}
+
+// Safari/JavaScriptCore的帧写法表，按版本递增
+const SAFARI_DIALECTS: [SafariDialect; 2] = [
+    SafariDialect {
+        since: 0,
+        bare_frames: &[],
+        scheduler_functions: &["promiseReactionJob", "asyncFunctionResume"],
+    },
+    SafariDialect {
+        since: 17,
+        bare_frames: &[("module code", "module_code"), ("global code", "global_code"), ("eval code", "eval_code")],
+        scheduler_functions: &["promiseReactionJobWithoutPromise", "asyncGeneratorResume"],
+    },
+];
+
+// 内置框架映射：文件路径片段 -> 框架名。包按完整的路径段匹配（`node_modules/react/` 不匹配 `react-router`），
+// 不按 `/_next/`、`/_nuxt/` 这类部署目录判断，应用自己的分块同样放在这些目录下
+const BUILTIN_FRAMEWORKS: [(&str, &str); 13] = [
+    ("node_modules/react/", "React"),
+    ("node_modules/react-dom/", "React"),
+    ("node_modules/vue/", "Vue"),
+    ("node_modules/@vue/", "Vue"),
+    (".vue", "Vue"),
+    (VUE_COMPONENT_LOCATION, "Vue"),
+    ("node_modules/angular/", "Angular"),
+    ("node_modules/@angular/", "Angular"),
+    ("node_modules/svelte/", "Svelte"),
+    ("node_modules/@sveltejs/", "Svelte"),
+    ("node_modules/next/", "Next.js"),
+    ("node_modules/nuxt/", "Nuxt"),
+    ("node_modules/jquery/", "jQuery"),
+];
+
+thread_local! {
+    // 全局注册的框架映射，新建的解析器都会带上
+    static CUSTOM_FRAMEWORKS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+
+    // 内置的V8、Gecko与wasm帧正则，限时解析等频繁新建解析器的路径不再重复编译
+    static BUILTIN_REGEXES: (Regex, Regex, Regex) = (
+        Regex::new(r"at\s+([^\s\(]+)?\s*(\(([^)]+)\))?").unwrap(),
+        Regex::new(r"([^@]*)@(.+):(\d+):(\d+)").unwrap(),
+        Regex::new(r"wasm-function\[(\d+)\](?::(0x[0-9a-fA-F]+))?").unwrap(),
+    );
+}
+
+/// 多个片段匹配时取最长的片段，等长时按片段排序保证结果稳定
+fn longest_framework_match<'a>(patterns: impl Iterator<Item = (&'a str, &'a str)>, file: &str) -> Option<String> {
+    patterns
+        .filter(|(pattern, _)| file.contains(pattern))
+        .max_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| b.0.cmp(a.0)))
+        .map(|(_, framework)| framework.to_string())
+}
+
+/// 按内置与全局注册的框架映射识别框架，用于没有解析器实例的场合（如source map还原后）
+fn default_framework(file: &str) -> Option<String> {
+    CUSTOM_FRAMEWORKS.with(|custom| {
+        let custom = custom.borrow();
+        let builtin = BUILTIN_FRAMEWORKS.iter().copied().filter(|(pattern, _)| !custom.contains_key(*pattern));
+        longest_framework_match(builtin.chain(custom.iter().map(|(k, v)| (k.as_str(), v.as_str()))), file)
+    })
+}
+
+/// 把UTF-16下标换算为UTF-8字节偏移：超出文本长度或落在代理对中间的下标为None。
+/// 下标通常递增，按升序处理只需扫描一遍文本
+fn utf16_to_byte_offsets(text: &str, offsets: &[u32]) -> Vec<Option<usize>> {
+    let mut order: Vec<usize> = (0..offsets.len()).collect();
+    order.sort_by_key(|&i| offsets[i]);
+    let mut converted = vec![None; offsets.len()];
+    let mut chars = text.char_indices().peekable();
+    let mut utf16 = 0u32;
+    for i in order {
+        let target = offsets[i];
+        while utf16 < target {
+            match chars.next() {
+                Some((_, c)) => utf16 += c.len_utf16() as u32,
+                None => break,
+            }
+        }
+        if utf16 == target {
+            converted[i] = Some(chars.peek().map_or(text.len(), |&(byte, _)| byte));
+        }
+    }
+    converted
+}
+
+/// 替换全局注册的框架映射（路径片段 -> 框架名），内置映射保留
+pub fn set_framework_map(map: HashMap<String, String>) {
+    CUSTOM_FRAMEWORKS.with(|custom| *custom.borrow_mut() = map.into_iter().filter(|(k, _)| !k.is_empty()).collect());
+}
+
+/// 当前全局注册的框架映射
+pub fn framework_map() -> HashMap<String, String> {
+    CUSTOM_FRAMEWORKS.with(|custom| custom.borrow().clone())
+}
+
+/// 栈中出现的框架，按首次出现的顺序去重
+pub fn detected_frameworks(frames: &[StackFrame]) -> Vec<String> {
+    let mut frameworks: Vec<String> = Vec::new();
+    for framework in frames.iter().filter_map(|frame| frame.framework.as_ref()) {
+        if !frameworks.contains(framework) {
+            frameworks.push(framework.clone());
+        }
+    }
+    frameworks
+}
+
+/// 有位置的帧按 `[行, 列, 帧下标, ...]` 三元组平铺，行列号按全局约定导出；
+/// 没有位置（行号为0）的帧跳过，帧下标仍对应其在 `frames` 中的位置
+pub fn line_column_triples(frames: &[StackFrame]) -> Vec<u32> {
+    let positions = parser_options::options();
+    frames.iter()
+        .enumerate()
+        .filter(|(_, frame)| frame.line_number > 0)
+        .flat_map(|(index, frame)| [positions.export_line(frame.line_number), positions.export_column(frame.column_number), index as u32])
+        .collect()
+}
+
+/// URL中主机之后的路径部分，不是URL时返回空字符串
+fn url_path(file: &str) -> &str {
+    file.split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|i| &rest[i..]))
+        .unwrap_or("")
+}
+
+/// 预分词输入中每行的格式字节
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineFormat {
+    /// 未知，按完整流程识别
+    Unknown = 0,
+    /// 错误消息等非栈帧行，直接跳过
+    Message = 1,
+    /// 异步边界分隔行
+    AsyncSeparator = 2,
+    /// V8 `at func (file:line:col)`
+    V8 = 3,
+    /// Firefox/Safari `func@file:line:col`
+    Gecko = 4,
+    /// wasm帧
+    Wasm = 5,
+}
+
+impl LineFormat {
+    /// 未定义的字节按未知格式处理
+    pub fn from_byte(byte: u8) -> LineFormat {
+        match byte {
+            1 => LineFormat::Message,
+            2 => LineFormat::AsyncSeparator,
+            3 => LineFormat::V8,
+            4 => LineFormat::Gecko,
+            5 => LineFormat::Wasm,
+            _ => LineFormat::Unknown,
+        }
+    }
+}
+
+/// 自定义格式中捕获组的引用：组序号或命名组
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum GroupRef {
+    Index(usize),
+    Name(String),
+}
+
+/// 自定义格式的捕获组映射，JSON为 `{"function": 1, "file": "file", "line": 3, "column": 4}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GroupMapping {
+    #[serde(default)]
+    pub function: Option<GroupRef>,
+    pub file: GroupRef,
+    #[serde(default)]
+    pub line: Option<GroupRef>,
+    #[serde(default)]
+    pub column: Option<GroupRef>,
+}
+
+// 运行时注册的栈帧格式
+struct CustomPattern {
+    name: String,
+    regex: Regex,
+    groups: GroupMapping,
+}
+
+/// 栈格式方言，调用方按UA嗅探给出时只尝试该引擎的写法，跳过逐行探测
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Dialect {
+    /// 未知，逐行探测
+    #[default]
+    Auto,
+    /// Chrome、Edge、Node
+    V8,
+    /// Firefox
+    #[serde(rename = "spidermonkey")]
+    SpiderMonkey,
+    /// Safari、Bun
+    Jsc,
+    /// React Native
+    Hermes,
+}
+
+impl Dialect {
+    /// 按名称解析，不区分大小写
+    pub fn from_name(name: &str) -> Option<Dialect> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(Dialect::Auto),
+            "v8" => Some(Dialect::V8),
+            "spidermonkey" => Some(Dialect::SpiderMonkey),
+            "jsc" => Some(Dialect::Jsc),
+            "hermes" => Some(Dialect::Hermes),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dialect::Auto => "auto",
+            Dialect::V8 => "v8",
+            Dialect::SpiderMonkey => "spidermonkey",
+            Dialect::Jsc => "jsc",
+            Dialect::Hermes => "hermes",
+        }
+    }
+}
+
+/// 解析后的错误
+#[derive(Serialize, Deserialize)]
+pub struct ParsedError {
+    /// 首行中的错误类型，如 `TypeError`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_type: Option<String>,
+    /// `parse_error` 中为去掉错误类型后的消息；错误事件中为完整首行
+    pub message: String,
+    /// 跨域脚本错误、CSP拦截、分块加载失败等特殊错误形态，此时指纹按类别计算
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<ErrorCategory>,
+    /// 按归属规则确定的负责团队
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    /// 栈中识别到的前端框架，按首次出现的顺序
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub detected_frameworks: Vec<String>,
+    /// 按完整栈帧计算的指纹，与 `fingerprint` 相同
+    #[serde(default)]
+    pub strong_fingerprint: String,
+    /// 只取少量栈顶帧、不含行号的指纹，用于分层合并分组，计算方式见 `ParserOptions`
+    #[serde(default)]
+    pub weak_fingerprint: String,
+    pub frames: Vec<ExportedStackFrame>,
+}
+
+/// `parse_with_options` 的结果：栈帧与实际匹配的方言
+#[derive(Serialize, Deserialize)]
+pub struct ParsedFrames {
+    pub dialect: Dialect,
+    pub frames: Vec<ExportedStackFrame>,
+}
+
+/// 按异步边界拆分的一段调用栈
+#[derive(Serialize, Deserialize)]
+pub struct AsyncSegment {
+    /// 进入该段的异步起因，如 `Promise.then`、`setTimeout`；首段与未知时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    pub frames: Vec<ExportedStackFrame>,
+}
+
+/// `parse_with_options` 的单次解析选项，JSON为 `{"dialect": "jsc", "collapse_microtasks": true}`；
+/// 只作用于传入的解析器，不修改全局的 `ParserOptions`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ParseOptions {
+    pub dialect: Dialect,
+    pub lenient_numbers: bool,
+    pub collapse_microtasks: bool,
+    pub fold_zone_frames: bool,
+    /// 输出脱敏的栈帧：路径只保留文件名，来源替换为哈希
+    pub redact: bool,
+    /// 无法识别格式的行保留为原始帧，内存预算降级时关闭
+    pub raw_lines: bool,
+}
+
+impl ParseOptions {
+    /// 从JSON对象读取选项，键可用蛇形或驼峰命名。逐项应用：无效或未知的项保持默认值并记入返回的错误，不影响其他项
+    pub fn from_json(json: &str) -> (ParseOptions, Vec<String>) {
+        let mut options = ParseOptions::default();
+        let object = match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(json) {
+            Ok(object) => object,
+            Err(e) => return (options, vec![format!("解析选项不是JSON对象: {}", e)]),
+        };
+        let mut errors = Vec::new();
+        for (key, value) in &object {
+            let applied = match naming::to_snake_case(key).as_str() {
+                "dialect" => value.as_str().and_then(Dialect::from_name).map(|dialect| options.dialect = dialect),
+                "lenient_numbers" => value.as_bool().map(|enabled| options.lenient_numbers = enabled),
+                "collapse_microtasks" => value.as_bool().map(|enabled| options.collapse_microtasks = enabled),
+                "fold_zone_frames" => value.as_bool().map(|enabled| options.fold_zone_frames = enabled),
+                "redact" => value.as_bool().map(|enabled| options.redact = enabled),
+                "raw_lines" => value.as_bool().map(|enabled| options.raw_lines = enabled),
+                _ => {
+                    errors.push(format!("未知的解析选项: {}", key));
+                    continue;
+                }
+            };
+            if applied.is_none() {
+                errors.push(format!("解析选项 {} 的值无效: {}", key, value));
+            }
+        }
+        (options, errors)
+    }
+}
+
+/// 逐行累积的方言证据：解析的同一遍中记录，不必为判断方言再次解析
+#[derive(Default)]
+struct DialectEvidence {
+    v8: bool,
+    gecko: bool,
+    // 遇到某个引擎特有的写法后即确定
+    decided: Option<Dialect>,
+}
+
+impl DialectEvidence {
+    /// 记录一行及其解析出的栈帧
+    fn observe(&mut self, line: &str, frame: &StackFrame) {
+        if self.decided.is_some() {
+            return;
+        }
+        let trimmed = line.trim();
+        if frame.hermes_bytecode {
+            self.decided = Some(Dialect::Hermes);
+            return;
+        }
+        if trimmed.starts_with("at ") {
+            self.v8 = true;
+            return;
+        }
+        let Some((name, location)) = trimmed.split_once('@') else {
+            return;
+        };
+        if name.contains('*') || location.contains(" > ") {
+            self.decided = Some(Dialect::SpiderMonkey);
+        } else if location == WASM_CODE_LOCATION {
+            self.decided = Some(Dialect::Jsc);
+        } else if frame.is_wasm() {
+            // wasm帧的写法各引擎相近，不作为证据
+        } else if location == NATIVE_CODE_LOCATION || name.contains(' ') || frame.column_number == 0 {
+            self.decided = Some(Dialect::Jsc);
+        } else {
+            self.gecko = true;
+        }
+    }
+
+    fn dialect(&self) -> Dialect {
+        match (self.decided, self.v8, self.gecko) {
+            (Some(dialect), _, _) => dialect,
+            (None, true, _) => Dialect::V8,
+            (None, false, true) => Dialect::SpiderMonkey,
+            (None, false, false) => Dialect::Auto,
+        }
+    }
+}
+
+/// 栈帧来源分类
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameOrigin {
+    /// 业务代码
+    #[default]
+    App,
+    /// 第三方代码：`node_modules` 或配置了应用路径前缀时不在前缀下的文件
+    NodeModules,
+    /// 浏览器或运行时内部代码（扩展、`resource://`、Node内部模块等）
+    BrowserInternal,
+    /// 引擎内置函数，没有源码位置
+    Native,
+    /// eval/`new Function` 执行的代码
+    Eval,
+    /// Node/Electron原生崩溃栈中的C++帧，带机器地址
+    NativeBinary,
+}
+
+impl FrameOrigin {
+    /// 与JSON输出一致的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrameOrigin::App => "app",
+            FrameOrigin::NodeModules => "node_modules",
+            FrameOrigin::BrowserInternal => "browser_internal",
+            FrameOrigin::Native => "native",
+            FrameOrigin::Eval => "eval",
+            FrameOrigin::NativeBinary => "native_binary",
+        }
+    }
+}
 
 /// 错误栈帧结构
 #[wasm_bindgen]
@@ -11,6 +446,36 @@ pub struct StackFrame {
     file_name: String,
     line_number: u32,
     column_number: u32,
+    // 插件/富化阶段附加的键值元数据（包名、负责团队、模块ID等）
+    annotations: BTreeMap<String, String>,
+    // 附加的源码上下文
+    context: Option<SourceContext>,
+    // 该帧之前存在异步边界分隔行
+    async_boundary: bool,
+    // 异步调用方帧（Firefox的 `async*` 前缀）
+    is_async: bool,
+    // Node的 `Promise.all (index N)` 组合器帧中的元素下标
+    promise_index: Option<u32>,
+    // 来自单行压缩产物
+    minified: bool,
+    // 引擎内置函数，没有源码位置
+    is_native: bool,
+    // Hermes字节码帧，列号是字节码偏移，需要Hermes source map还原
+    hermes_bytecode: bool,
+    // eval执行的代码，位置相对于eval的代码；eval_origin为调用eval处的位置
+    is_eval: bool,
+    eval_origin: Option<String>,
+    // wasm帧的函数索引与模块内字节偏移
+    wasm_function: Option<u32>,
+    wasm_offset: Option<u32>,
+    // 原生崩溃帧的机器地址
+    address: Option<u64>,
+    // 无法识别格式的原始行，函数名为整行文本
+    raw: bool,
+    // 解析时确定的来源分类
+    origin: FrameOrigin,
+    // 按文件路径识别的前端框架
+    framework: Option<String>,
 }
 
 #[wasm_bindgen]
@@ -22,6 +487,22 @@ impl StackFrame {
             file_name,
             line_number,
             column_number,
+            annotations: BTreeMap::new(),
+            context: None,
+            async_boundary: false,
+            is_async: false,
+            promise_index: None,
+            minified: false,
+            is_native: false,
+            hermes_bytecode: false,
+            is_eval: false,
+            eval_origin: None,
+            wasm_function: None,
+            wasm_offset: None,
+            address: None,
+            raw: false,
+            origin: FrameOrigin::App,
+            framework: None,
         }
     }
     
@@ -30,20 +511,469 @@ impl StackFrame {
         self.function_name.clone()
     }
 
+    /// 按全局规范化配置处理后的函数名，指纹使用该名称
+    #[wasm_bindgen(getter)]
+    pub fn normalized_function_name(&self) -> String {
+        function_names::normalize(&self.function_name)
+    }
+
     #[wasm_bindgen(getter)]
     pub fn file_name(&self) -> String {
         self.file_name.clone()
     }
 
+    /// 行号，按 `set_parser_options` 设置的起点导出
+    #[wasm_bindgen(getter = line_number)]
+    pub fn exported_line_number(&self) -> u32 {
+        parser_options::options().export_line(self.line_number)
+    }
+
+    /// 列号，按 `set_parser_options` 设置的起点导出
+    #[wasm_bindgen(getter = column_number)]
+    pub fn exported_column_number(&self) -> u32 {
+        parser_options::options().export_column(self.column_number)
+    }
+
+    /// 是否为跨越异步边界后的第一帧
+    #[wasm_bindgen(getter)]
+    pub fn async_boundary(&self) -> bool {
+        self.async_boundary
+    }
+
+    pub fn set_async_boundary(&mut self, async_boundary: bool) {
+        self.async_boundary = async_boundary;
+    }
+
+    /// 是否为异步调用方帧
+    #[wasm_bindgen(getter)]
+    pub fn is_async(&self) -> bool {
+        self.is_async
+    }
+
+    pub fn set_async(&mut self, is_async: bool) {
+        self.is_async = is_async;
+    }
+
+    /// `Promise.all`、`Promise.allSettled` 等组合器帧中被拒绝的元素下标
+    #[wasm_bindgen(getter)]
+    pub fn promise_index(&self) -> Option<u32> {
+        self.promise_index
+    }
+
+    pub fn set_promise_index(&mut self, promise_index: Option<u32>) {
+        self.promise_index = promise_index;
+    }
+
+    /// 是否来自单行压缩产物，此类帧需要source map还原后才有意义
+    #[wasm_bindgen(getter)]
+    pub fn minified(&self) -> bool {
+        self.minified
+    }
+
+    pub fn set_minified(&mut self, minified: bool) {
+        self.minified = minified;
+    }
+
+    /// 函数名是否像压缩器生成的短名（`t`、`Zr`），source map还原出真实函数名后不再成立。
+    /// 与 `minified`（帧位于单行压缩产物中，由解析模式与文件形态判定）无关：未压缩文件中的短名为true，
+    /// 压缩产物中保留原名的函数为false
+    #[wasm_bindgen(getter)]
+    pub fn has_minified_name(&self) -> bool {
+        function_names::is_minified_name(&self.function_name)
+    }
+
+    /// 压缩短名的展示名：由文件名与位置合成（`app.min.js#fn@10:200`），函数名不是压缩短名时为undefined
+    pub fn display_name(&self) -> Option<String> {
+        if !self.has_minified_name() {
+            return None;
+        }
+        let file = self.file_name.split(['?', '#']).next().unwrap_or("");
+        let file = file.trim_end_matches('/').rsplit('/').next().unwrap_or(file);
+        let positions = parser_options::options();
+        Some(format!("{}#fn@{}:{}", file, positions.export_line(self.line_number), positions.export_column(self.column_number)))
+    }
+
+    /// 是否为引擎内置函数帧：V8的 `(<anonymous>)`、`(native)` 与Safari的 `[native code]`
+    #[wasm_bindgen(getter)]
+    pub fn is_native(&self) -> bool {
+        self.is_native
+    }
+
+    pub fn set_native(&mut self, is_native: bool) {
+        self.is_native = is_native;
+    }
+
+    /// 是否为Hermes字节码帧（`address at index.android.bundle:1:234567`），需要Hermes符号化
+    #[wasm_bindgen(getter)]
+    pub fn hermes_bytecode(&self) -> bool {
+        self.hermes_bytecode
+    }
+
+    pub fn set_hermes_bytecode(&mut self, hermes_bytecode: bool) {
+        self.hermes_bytecode = hermes_bytecode;
+    }
+
+    /// 是否为保留的原始行（解析选项 `raw_lines`），不参与指纹与分组
     #[wasm_bindgen(getter)]
+    pub fn raw(&self) -> bool {
+        self.raw
+    }
+
+    pub fn set_raw(&mut self, raw: bool) {
+        self.raw = raw;
+    }
+
+    /// 是否为eval/`new Function` 执行的代码
+    #[wasm_bindgen(getter)]
+    pub fn is_eval(&self) -> bool {
+        self.is_eval
+    }
+
+    /// 调用eval处的位置，例如 `http://x/app.js:1:1`
+    #[wasm_bindgen(getter)]
+    pub fn eval_origin(&self) -> Option<String> {
+        self.eval_origin.clone()
+    }
+
+    /// wasm帧的函数索引（`wasm-function[N]` 中的N）
+    #[wasm_bindgen(getter)]
+    pub fn wasm_function(&self) -> Option<u32> {
+        self.wasm_function
+    }
+
+    /// wasm帧在模块内的字节偏移
+    #[wasm_bindgen(getter)]
+    pub fn wasm_offset(&self) -> Option<u32> {
+        self.wasm_offset
+    }
+
+    /// 原生崩溃帧的机器地址（`1: 0x55d0b2 node::Abort()` 中的 `0x55d0b2`）
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> Option<u64> {
+        self.address
+    }
+
+    pub fn set_address(&mut self, address: Option<u64>) {
+        self.address = address;
+    }
+
+    /// 来源分类：业务代码、第三方、浏览器/运行时内部、内置函数、eval或原生二进制
+    #[wasm_bindgen(getter)]
+    pub fn origin(&self) -> FrameOrigin {
+        self.origin
+    }
+
+    pub fn set_origin(&mut self, origin: FrameOrigin) {
+        self.origin = origin;
+    }
+
+    /// 帧所属的前端框架，如 `React`、`Next.js`
+    #[wasm_bindgen(getter)]
+    pub fn framework(&self) -> Option<String> {
+        self.framework.clone()
+    }
+
+    pub fn set_framework(&mut self, framework: Option<String>) {
+        self.framework = framework;
+    }
+
+    /// 是否为WebAssembly帧
+    pub fn is_wasm(&self) -> bool {
+        self.wasm_function.is_some() || self.wasm_offset.is_some() || self.file_name == WASM_CODE_LOCATION
+    }
+
+    /// 指纹计算使用的列号：压缩帧按桶取整，避免细小的列偏移产生新分组；wasm帧没有列号，使用字节偏移
+    pub fn fingerprint_column(&self) -> u32 {
+        if let Some(offset) = self.wasm_offset {
+            offset
+        } else if self.minified {
+            self.column_number / MINIFIED_COLUMN_BUCKET * MINIFIED_COLUMN_BUCKET
+        } else {
+            self.column_number
+        }
+    }
+
+    /// 是否为浏览器内部代码（Gecko的 `resource://`、`chrome://` 模块）
+    pub fn is_browser_internal(&self) -> bool {
+        self.file_name.starts_with("resource://") || self.file_name.starts_with("chrome://")
+    }
+
+    /// 是否来自与当前构建清单不符的旧版产物（浏览器缓存），此类帧不应使用新版source map还原
+    pub fn is_stale_bundle(&self) -> bool {
+        build_manifest::is_stale(&self.file_name)
+    }
+
+    /// 是否为Node运行时内部代码（`node:` 协议或旧版的 `internal/` 路径）
+    pub fn is_node_internal(&self) -> bool {
+        self.file_name.starts_with("node:") || self.file_name.starts_with("internal/")
+    }
+
+    /// 直接构建JS对象，字段与JSON输出一致（按全局字段命名风格），不经过serde的中间值
+    pub fn js_object(&self) -> JsValue {
+        let field_naming = naming::field_naming();
+        let positions = parser_options::options();
+        let object = js_sys::Object::new();
+        let set = |target: &JsValue, key: &str, value: JsValue| {
+            let _ = js_sys::Reflect::set(target, &JsValue::from_str(key), &value);
+        };
+        let field = |key: &str, value: JsValue| set(&object, &field_naming.convert(key), value);
+        let flag = |key: &str, value: bool| if value { field(key, JsValue::from_bool(true)) };
+
+        field("function_name", JsValue::from_str(&self.function_name));
+        let normalized = function_names::normalize(&self.function_name);
+        if normalized != self.function_name {
+            field("normalized_function_name", JsValue::from_str(&normalized));
+        }
+        flag("has_minified_name", self.has_minified_name());
+        if let Some(display_name) = self.display_name() {
+            field("display_name", JsValue::from_str(&display_name));
+        }
+        field("file_name", JsValue::from_str(self.relative_file_name()));
+        field("line_number", JsValue::from(positions.export_line(self.line_number)));
+        field("column_number", JsValue::from(positions.export_column(self.column_number)));
+        field("origin", JsValue::from_str(self.origin.as_str()));
+        if let Some(framework) = &self.framework {
+            field("framework", JsValue::from_str(framework));
+        }
+        if !self.annotations.is_empty() {
+            // 注解的键是用户数据，不做命名转换
+            let annotations = js_sys::Object::new();
+            for (key, value) in &self.annotations {
+                set(&annotations, key, JsValue::from_str(value));
+            }
+            field("annotations", annotations.into());
+        }
+        if let Some(context) = &self.context {
+            field("context", crate::to_js_value(context));
+        }
+        flag("async_boundary", self.async_boundary);
+        flag("is_async", self.is_async);
+        if let Some(index) = self.promise_index {
+            field("promise_index", JsValue::from(index));
+        }
+        flag("browser_internal", self.is_browser_internal());
+        flag("node_internal", self.is_node_internal());
+        flag("stale_bundle", self.is_stale_bundle());
+        flag("minified", self.minified);
+        flag("is_native", self.is_native);
+        flag("hermes_bytecode", self.hermes_bytecode);
+        flag("raw", self.raw);
+        flag("is_eval", self.is_eval);
+        if let Some(eval_origin) = &self.eval_origin {
+            field("eval_origin", JsValue::from_str(eval_origin));
+        }
+        if let Some(function) = self.wasm_function {
+            field("wasm_function", JsValue::from(function));
+        }
+        if let Some(offset) = self.wasm_offset {
+            field("wasm_offset", JsValue::from(offset));
+        }
+        if let Some(address) = self.address {
+            field("address", JsValue::from(address));
+        }
+        object.into()
+    }
+
+    /// 设置注解，已存在的键会被覆盖
+    pub fn set_annotation(&mut self, key: String, value: String) {
+        self.annotations.insert(key, value);
+    }
+
+    /// 读取注解
+    pub fn get_annotation(&self, key: &str) -> Option<String> {
+        self.annotations.get(key).cloned()
+    }
+
+    /// 是否为业务代码：排除第三方依赖（含source map `ignoreList` 中的文件）、浏览器扩展及运行时内部帧
+    pub fn is_in_app(&self) -> bool {
+        let file = self.file_name.as_str();
+        !(self.address.is_some() || self.has_no_source() || file.contains("node_modules/") || self.is_runtime_internal())
+            && self.origin != FrameOrigin::NodeModules
+    }
+
+    /// 删除注解，返回被删除的值
+    pub fn remove_annotation(&mut self, key: &str) -> Option<String> {
+        self.annotations.remove(key)
+    }
+}
+
+// 浏览器扩展与运行时内部代码的文件前缀
+const INTERNAL_PREFIXES: [&str; 10] = [
+    "node:", "internal/", "ext:", "chrome-extension://", "moz-extension://",
+    "safari-extension://", "safari-web-extension://", "resource://", "chrome://", "webpack/runtime",
+];
+
+impl StackFrame {
+    /// 无法识别格式的原始行：函数名为去掉首尾空白的整行，没有文件，行列号未知
+    pub(crate) fn raw_line(line: &str) -> StackFrame {
+        let mut frame = StackFrame::new(line.trim().to_string(), String::new(), 0, 0);
+        frame.raw = true;
+        frame
+    }
+
+    /// 内部行号（从1开始，0为未知），不受导出起点影响
     pub fn line_number(&self) -> u32 {
         self.line_number
     }
 
-    #[wasm_bindgen(getter)]
+    /// 内部列号（从1开始，0为未知），不受导出起点影响
     pub fn column_number(&self) -> u32 {
         self.column_number
     }
+
+    /// 函数名的借用访问，Rust侧读取时避免getter的复制
+    pub fn function_name_str(&self) -> &str {
+        &self.function_name
+    }
+
+    pub fn file_name_str(&self) -> &str {
+        &self.file_name
+    }
+
+    /// 去掉应用根路径前缀后的文件名，导出、指纹与分组使用
+    pub fn relative_file_name(&self) -> &str {
+        app_root::relativize(&self.file_name)
+    }
+
+    pub fn eval_origin_str(&self) -> Option<&str> {
+        self.eval_origin.as_deref()
+    }
+
+    pub fn framework_str(&self) -> Option<&str> {
+        self.framework.as_deref()
+    }
+
+    /// 转换为导出结构，字符串与注解直接移动而不复制
+    pub fn into_exported(self) -> ExportedStackFrame {
+        let normalized_function_name = function_names::normalize(&self.function_name);
+        let browser_internal = self.is_browser_internal();
+        let node_internal = self.is_node_internal();
+        let stale_bundle = self.is_stale_bundle();
+        let positions = parser_options::options();
+        let has_minified_name = self.has_minified_name();
+        let display_name = self.display_name();
+        let root_len = self.file_name.len() - self.relative_file_name().len();
+        let mut file_name = self.file_name;
+        file_name.drain(..root_len);
+        ExportedStackFrame {
+            normalized_function_name: (normalized_function_name != self.function_name).then_some(normalized_function_name),
+            function_name: self.function_name,
+            has_minified_name,
+            display_name,
+            file_name,
+            line_number: positions.export_line(self.line_number),
+            column_number: positions.export_column(self.column_number),
+            origin: self.origin,
+            framework: self.framework,
+            annotations: self.annotations,
+            context: self.context,
+            async_boundary: self.async_boundary,
+            is_async: self.is_async,
+            promise_index: self.promise_index,
+            browser_internal,
+            node_internal,
+            stale_bundle,
+            minified: self.minified,
+            is_native: self.is_native,
+            hermes_bytecode: self.hermes_bytecode,
+            raw: self.raw,
+            is_eval: self.is_eval,
+            eval_origin: self.eval_origin,
+            wasm_function: self.wasm_function,
+            wasm_offset: self.wasm_offset,
+            address: self.address,
+        }
+    }
+
+    /// 没有可用的源码文件（匿名、内置函数位置）
+    pub(crate) fn has_no_source(&self) -> bool {
+        let file = self.file_name.as_str();
+        file.is_empty() || file == "<anonymous>" || file == NATIVE_CODE_LOCATION || file == "InternalBytecode.js" || file == "native"
+    }
+
+    fn is_runtime_internal(&self) -> bool {
+        INTERNAL_PREFIXES.iter().any(|prefix| self.file_name.starts_with(prefix))
+    }
+
+    /// 是否位于任一应用路径前缀下，没有配置前缀时总是返回true
+    fn matches_app_prefix(&self, app_prefixes: &[String]) -> bool {
+        let file = self.file_name.as_str();
+        app_prefixes.is_empty()
+            || app_prefixes.iter().any(|prefix| file.starts_with(prefix.as_str()) || url_path(file).starts_with(prefix.as_str()))
+    }
+
+    /// 按文件位置与标记分类来源，`app_prefixes` 非空时不在任一前缀下的文件视为第三方
+    pub fn classify(&self, app_prefixes: &[String]) -> FrameOrigin {
+        let file = self.file_name.as_str();
+        if self.address.is_some() {
+            FrameOrigin::NativeBinary
+        } else if self.is_eval {
+            FrameOrigin::Eval
+        } else if self.is_native || self.has_no_source() {
+            FrameOrigin::Native
+        } else if self.is_runtime_internal() {
+            FrameOrigin::BrowserInternal
+        } else if file.contains("node_modules/") || !self.matches_app_prefix(app_prefixes) {
+            FrameOrigin::NodeModules
+        } else {
+            FrameOrigin::App
+        }
+    }
+
+    /// 全部注解（按键排序）
+    pub fn annotations(&self) -> &BTreeMap<String, String> {
+        &self.annotations
+    }
+
+    /// 批量合并注解
+    pub fn extend_annotations<I: IntoIterator<Item = (String, String)>>(&mut self, annotations: I) {
+        self.annotations.extend(annotations);
+    }
+
+    /// 附加的源码上下文
+    pub fn context(&self) -> Option<&SourceContext> {
+        self.context.as_ref()
+    }
+
+    /// 设置源码上下文
+    pub fn set_context(&mut self, context: SourceContext) {
+        self.context = Some(context);
+    }
+
+    /// 清除源码上下文
+    pub fn clear_context(&mut self) {
+        self.context = None;
+    }
+
+    /// 标记为eval帧并记录调用eval处的位置
+    pub fn set_eval(&mut self, is_eval: bool, eval_origin: Option<String>) {
+        self.is_eval = is_eval;
+        self.eval_origin = eval_origin;
+    }
+
+    /// 设置wasm函数索引与字节偏移
+    pub fn set_wasm_location(&mut self, function_index: Option<u32>, offset: Option<u32>) {
+        self.wasm_function = function_index;
+        self.wasm_offset = offset;
+    }
+
+    /// 替换函数名与位置（source map还原后调用）
+    pub fn set_location(&mut self, function_name: String, file_name: String, line_number: u32, column_number: u32) {
+        self.function_name = function_name;
+        self.file_name = file_name;
+        self.line_number = line_number;
+        self.column_number = column_number;
+    }
+
+    /// 位置被替换后重新确定来源、框架与所属包：还原出的源文件可能属于 `node_modules` 或其他包，
+    /// 不能沿用生成产物的分类。解析器实例上的应用路径前缀针对生成产物的URL，这里不再使用
+    pub(crate) fn reclassify(&mut self) {
+        self.origin = self.classify(&[]);
+        self.framework = default_framework(&self.file_name);
+        self.annotations.remove("package");
+        self.annotations.remove("package_version");
+        packages::annotate(self);
+    }
 }
 
 /// 错误栈解析器
@@ -52,126 +982,1327 @@ pub struct ErrorParser {
     // 正则表达式缓存
     chrome_regex: Regex,
     firefox_regex: Regex,
-    safari_regex: Regex,
-    // 框架映射缓存
+    wasm_regex: Regex,
+    // 框架映射：文件路径片段 -> 框架名
     framework_map: HashMap<String, String>,
+    // 反混淆映射：混淆名 -> 原始名
+    rename_map: HashMap<String, String>,
+    // 宽松数字解析（全角数字、千分位分隔符）
+    lenient_numbers: bool,
+    // 合并连续的运行时微任务帧
+    collapse_microtasks: bool,
+    // 折叠连续的zone.js包装帧
+    fold_zone_frames: bool,
+    // 第一帧之后无法识别的行保留为原始帧
+    raw_lines: bool,
+    // 单行压缩产物处理模式
+    minified_mode: bool,
+    // 应用代码的路径前缀，为空时只按 `node_modules` 判定第三方
+    app_prefixes: Vec<String>,
+    // 自定义栈帧格式，在内置格式之后按注册顺序尝试
+    custom_patterns: Vec<CustomPattern>,
+    // 已知的Safari主版本号，0为未知（启用所有版本的写法）
+    safari_version: u32,
+    // 调用方给出的方言，auto时逐行探测
+    dialect: Dialect,
 }
 
 #[wasm_bindgen]
 impl ErrorParser {
     /// 创建新的错误解析器
     pub fn new() -> Self {
-        // 初始化正则表达式
-        let chrome_regex = Regex::new(r"at\s+([^\s\(]+)?\s*(\(([^)]+)\))?").unwrap();
-        let firefox_regex = Regex::new(r"([^@]*)@(.+):(\d+):(\d+)").unwrap();
-        let safari_regex = Regex::new(r"([^@]*)@([^:]+):(\d+):(\d+)").unwrap();
+        // 内置格式的正则只编译一次，克隆共享编译结果
+        let (chrome_regex, firefox_regex, wasm_regex) = BUILTIN_REGEXES.with(Clone::clone);
         
         // 初始化框架映射
-        let mut framework_map = HashMap::new();
-        framework_map.insert("node_modules/react".to_string(), "React".to_string());
-        framework_map.insert("node_modules/vue".to_string(), "Vue".to_string());
-        framework_map.insert("node_modules/angular".to_string(), "Angular".to_string());
+        let mut framework_map: HashMap<String, String> = BUILTIN_FRAMEWORKS.iter()
+            .map(|(pattern, framework)| (pattern.to_string(), framework.to_string()))
+            .collect();
+        // 调用方注册的映射覆盖内置映射
+        CUSTOM_FRAMEWORKS.with(|custom| framework_map.extend(custom.borrow().iter().map(|(k, v)| (k.clone(), v.clone()))));
         
         ErrorParser {
             chrome_regex,
             firefox_regex,
-            safari_regex,
+            wasm_regex,
             framework_map,
+            rename_map: HashMap::new(),
+            lenient_numbers: false,
+            collapse_microtasks: false,
+            fold_zone_frames: false,
+            raw_lines: false,
+            minified_mode: false,
+            app_prefixes: Vec::new(),
+            custom_patterns: Vec::new(),
+            safari_version: 0,
+            dialect: Dialect::Auto,
         }
     }
 
-    /// 解析错误栈
-    pub fn parse(&self, stack: &str) -> String {
-        if stack.is_empty() {
-            return String::new();
-        }
+    /// 设置已知的Safari主版本号（如从UA解析），只启用该版本已有的帧写法，避免旧版本的消息行被误识别；
+    /// 0表示未知，启用全部写法
+    pub fn set_safari_version(&mut self, major: u32) {
+        self.safari_version = major;
+    }
 
-        let mut result = String::new();
-        let lines: Vec<&str> = stack.split('\n').collect();
-        
-        for line in lines {
-            // 尝试使用Chrome格式解析
-            if let Some(caps) = self.chrome_regex.captures(line) {
-                let func_name = caps.get(1).map_or("<anonymous>", |m| m.as_str());
-                
-                if let Some(location) = caps.get(3) {
-                    let loc_parts: Vec<&str> = location.as_str().split(':').collect();
-                    if loc_parts.len() >= 3 {
-                        let file = loc_parts[0..loc_parts.len()-2].join(":");
-                        let line_num = loc_parts[loc_parts.len()-2].parse::<u32>().unwrap_or(0);
-                        let col_num = loc_parts[loc_parts.len()-1].parse::<u32>().unwrap_or(0);
-                        
-                        // 格式化输出
-                        let frame = format_stack_frame(func_name, &file, line_num, col_num);
-                        result.push_str(&frame);
-                        result.push('\n');
-                    }
-                }
-                continue;
-            }
-            
-            // 尝试使用Firefox格式解析
-            if let Some(caps) = self.firefox_regex.captures(line) {
-                let func_name = caps.get(1).map_or("<anonymous>", |m| m.as_str());
-                let file = caps.get(2).map_or("", |m| m.as_str());
-                let line_num = caps.get(3).and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(0);
-                let col_num = caps.get(4).and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(0);
-                
-                // 格式化输出
-                let frame = format_stack_frame(func_name, file, line_num, col_num);
-                result.push_str(&frame);
-                result.push('\n');
-                continue;
-            }
-            
-            // 尝试使用Safari格式解析
-            if let Some(caps) = self.safari_regex.captures(line) {
-                let func_name = caps.get(1).map_or("<anonymous>", |m| m.as_str());
-                let file = caps.get(2).map_or("", |m| m.as_str());
-                let line_num = caps.get(3).and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(0);
-                let col_num = caps.get(4).and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(0);
-                
-                // 格式化输出
-                let frame = format_stack_frame(func_name, file, line_num, col_num);
-                result.push_str(&frame);
-                result.push('\n');
+    /// 按名称（`v8`、`spidermonkey`、`jsc`、`hermes`、`auto`）设置方言，未知名称返回false且不修改
+    pub fn set_dialect(&mut self, name: &str) -> bool {
+        match Dialect::from_name(name) {
+            Some(dialect) => {
+                self.dialect = dialect;
+                true
             }
+            None => false,
         }
-        
-        result
     }
 
-    /// 使用SIMD优化解析错误栈
-    pub fn parse_simd(&self, stack: &str) -> Vec<StackFrame> {
-        let mut frames = Vec::new();
-        
+    /// 启用或关闭宽松数字解析，用于本地化工具链产生的行列号
+    pub fn set_lenient_numbers(&mut self, enabled: bool) {
+        self.lenient_numbers = enabled;
+    }
+
+    /// 启用后，连续的 `processTicksAndRejections`、`at async` 等运行时微任务帧合并为一帧，
+    /// 合并数量记录在 `collapsed_frames` 注解中
+    pub fn set_collapse_microtasks(&mut self, enabled: bool) {
+        self.collapse_microtasks = enabled;
+    }
+
+    /// 启用后，Angular应用中连续的zone.js包装帧（`ZoneDelegate.invoke`、`onInvokeTask` 等）
+    /// 折叠为一个 `zone.js (N frames)` 占位帧，折叠数量记录在 `collapsed_frames` 注解中
+    pub fn set_fold_zone_frames(&mut self, enabled: bool) {
+        self.fold_zone_frames = enabled;
+    }
+
+    /// 启用后，第一帧之后无法识别格式的非空行保留为 `raw` 帧，函数名为整行文本、没有文件与位置，
+    /// 不再直接丢弃；首帧之前的行属于错误消息，仍然跳过
+    pub fn set_raw_lines(&mut self, enabled: bool) {
+        self.raw_lines = enabled;
+    }
+
+    /// 启用单行压缩产物处理模式：识别压缩帧并标记 `minified`，
+    /// 被标记的帧在指纹计算中按列号分桶；还原由调用方完成（`parse_minified` 使用已注册的source map）
+    pub fn set_minified_mode(&mut self, enabled: bool) {
+        self.minified_mode = enabled;
+    }
+
+    /// 注册一条框架映射：文件路径片段 -> 框架名，可用于把内部包归为框架
+    pub fn add_framework_mapping(&mut self, path_fragment: &str, framework_name: &str) {
+        if !path_fragment.is_empty() {
+            self.framework_map.insert(path_fragment.to_string(), framework_name.to_string());
+        }
+    }
+
+    /// 按文件路径识别框架，多个片段匹配时取最长的片段
+    pub fn detect_framework(&self, file: &str) -> Option<String> {
+        longest_framework_match(self.framework_map.iter().map(|(k, v)| (k.as_str(), v.as_str())), file)
+    }
+
+    /// 从JSON数组加载应用代码的路径前缀（如 `["/src/", "https://cdn.test/app/"]`），
+    /// 前缀匹配完整文件名或URL的路径部分
+    pub fn set_app_prefixes(&mut self, json: &str) -> bool {
+        match serde_json::from_str::<Vec<String>>(json) {
+            Ok(prefixes) => {
+                self.app_prefixes = prefixes;
+                true
+            }
+            Err(e) => {
+                console_log(&format!("应用路径前缀解析错误: {}", e));
+                false
+            }
+        }
+    }
+
+    /// 从JSON对象加载标识符重命名表（原始名 -> 混淆名），内部按反向映射还原函数名
+    pub fn set_rename_map(&mut self, json: &str) -> bool {
+        match serde_json::from_str::<HashMap<String, String>>(json) {
+            Ok(map) => {
+                self.rename_map = map.into_iter()
+                    .map(|(original, minified)| (minified, original))
+                    .collect();
+                true
+            }
+            Err(e) => {
+                console_log(&format!("重命名表解析错误: {}", e));
+                false
+            }
+        }
+    }
+
+    /// 注册自定义栈帧格式：`regex` 匹配一行，`group_mapping_json` 指定函数名、文件、行列号所在的捕获组。
+    /// 在内置的Chrome/Firefox/Safari格式之后尝试，同名格式会被替换，匹配到的帧带 `pattern` 注解
+    pub fn register_pattern(&mut self, name: &str, regex: &str, group_mapping_json: &str) -> bool {
+        let regex = match Regex::new(regex) {
+            Ok(regex) => regex,
+            Err(e) => {
+                console_log(&format!("自定义格式 {} 的正则表达式错误: {}", name, e));
+                return false;
+            }
+        };
+        match serde_json::from_str::<GroupMapping>(group_mapping_json) {
+            Ok(groups) => {
+                self.add_pattern(name, regex, groups);
+                true
+            }
+            Err(e) => {
+                console_log(&format!("自定义格式 {} 的捕获组映射解析错误: {}", name, e));
+                false
+            }
+        }
+    }
+
+    /// 添加单条重命名记录
+    pub fn add_rename(&mut self, original: String, minified: String) {
+        self.rename_map.insert(minified, original);
+    }
+
+    /// 清空重命名表
+    pub fn clear_rename_map(&mut self) {
+        self.rename_map.clear();
+    }
+
+    /// 解析行列号，解析失败时返回0
+    fn parse_number(&self, s: &str) -> u32 {
+        if self.lenient_numbers {
+            parse_number_lenient(s).unwrap_or(0)
+        } else {
+            s.parse::<u32>().unwrap_or(0)
+        }
+    }
+
+    /// 拆分 `file:line:col`，缺少列号时列号为0
+    fn split_location<'a>(&self, location: &'a str) -> Option<(&'a str, u32, u32)> {
+        // 常见的 `file:line:col` 先走不分配内存的快速路径，其余形式交给通用拆分
+        if !self.lenient_numbers {
+            if let Some((end, line, column)) = find_location_suffix(location.as_bytes()).filter(|(end, _, _)| *end > 0) {
+                return Some((&location[..end], line, column));
+            }
+        }
+        split_location_with(location, |part| {
+            if self.lenient_numbers { parse_number_lenient(part) } else { part.parse::<u32>().ok() }
+        }).filter(|(file, _, _)| !file.is_empty())
+    }
+
+    /// 识别已处理过的输入：PerfLite的JSON栈帧数组，或 `parse` 输出的 `file:line:col|func` 列表。
+    /// 非空的JSON对象数组视为栈帧数组，其中的栈帧缺少文件或位置越界时返回错误
+    pub(crate) fn reparse_processed(stack: &str) -> Option<Result<Vec<StackFrame>, ParseError>> {
+        let trimmed = stack.trim();
+        if trimmed.starts_with('[') {
+            // 兼容camelCase输出
+            let value = naming::rename_keys(serde_json::from_str(trimmed).ok()?, FieldNaming::SnakeCase);
+            let serde_json::Value::Array(frames) = value else { return None };
+            if frames.is_empty() || !frames.iter().all(serde_json::Value::is_object) {
+                return None;
+            }
+            return Some(exported_frame::import_frames(frames));
+        }
+
+        let frames = LineIndex::new(trimmed).lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Self::parse_formatted_line)
+            .collect::<Option<Vec<StackFrame>>>()?;
+        if frames.is_empty() { None } else { Some(Ok(frames)) }
+    }
+
+    /// 解析一行 `file:line:col|func`
+    fn parse_formatted_line(line: &str) -> Option<StackFrame> {
+        let (location, function_name) = line.split_once('|')?;
+        let mut parts = location.rsplitn(3, ':');
+        let positions = parser_options::options();
+        // `format_frames` 把未知位置写作 `?`，换算回内部的0
+        let position = |part: &str, import: fn(&ParserOptions, u32) -> u32| match part {
+            "?" => Some(0),
+            _ => part.parse::<u32>().ok().map(|value| import(&positions, value)),
+        };
+        let column = position(parts.next()?, ParserOptions::import_column)?;
+        let line_number = position(parts.next()?, ParserOptions::import_line)?;
+        let file = parts.next().filter(|file| !file.is_empty())?;
+        Some(StackFrame::new(function_name.to_string(), file.to_string(), line_number, column))
+    }
+
+    /// 异步边界分隔行标记的起因：DevTools的 `--- Promise.then ---` 与复制栈时的 `setTimeout (async)`
+    /// 返回 `Promise.then`、`setTimeout`；longjohn的纯连字符分隔行没有起因，返回空串
+    fn async_separator_cause(line: &str) -> Option<&str> {
+        let line = line.trim();
+        if line.len() >= MIN_SEPARATOR_DASHES && line.bytes().all(|b| b == b'-') {
+            return Some("");
+        }
+        let cause = match line.strip_suffix(" (async)") {
+            Some(cause) => cause,
+            None => line.strip_prefix("--- ")?.strip_suffix(" ---")?,
+        };
+        // 起因是函数名或属性路径，Chrome还会写作 `await in loadData (async)`
+        let name = cause.strip_prefix("await in ").unwrap_or(cause);
+        let valid = !name.is_empty() && name.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '$')));
+        valid.then_some(cause)
+    }
+
+    /// 标记分隔行之后的第一帧，起因记录在 `async_cause` 注解中
+    fn mark_async_boundary(frame: &mut StackFrame, cause: Option<&str>) {
+        frame.set_async_boundary(cause.is_some());
+        if let Some(cause) = cause.filter(|cause| !cause.is_empty()) {
+            frame.set_annotation("async_cause".to_string(), cause.to_string());
+        }
+    }
+
+    /// 按重命名表还原函数名，`a.b` 形式的每一段分别替换
+    fn deobfuscate(&self, name: &str) -> String {
+        if self.rename_map.is_empty() {
+            return name.to_string();
+        }
+
+        name.split('.')
+            .map(|segment| self.rename_map.get(segment).map_or(segment, |s| s.as_str()))
+            .collect::<Vec<&str>>()
+            .join(".")
+    }
+
+    /// 当前Safari版本已有的帧写法
+    fn safari_dialects(&self) -> impl Iterator<Item = &'static SafariDialect> + '_ {
+        SAFARI_DIALECTS.iter().filter(|dialect| self.safari_version == 0 || dialect.since <= self.safari_version)
+    }
+
+    /// 是否为Node/V8运行时或JavaScriptCore的微任务调度帧
+    fn is_microtask_frame(frame: &StackFrame) -> bool {
+        const MICROTASK_FUNCTIONS: [&str; 3] = ["processTicksAndRejections", "runMicrotasks", "runNextTicks"];
+
+        let file = frame.file_name.as_str();
+        let jsc_scheduler = file == NATIVE_CODE_LOCATION
+            && SAFARI_DIALECTS.iter().any(|dialect| dialect.scheduler_functions.contains(&frame.function_name.as_str()));
+        jsc_scheduler
+            || MICROTASK_FUNCTIONS.iter().any(|name| frame.function_name.ends_with(name))
+            || file.contains("internal/process/task_queues")
+            || (frame.is_async && (frame.is_node_internal() || frame.promise_index.is_some()))
+    }
+
+    /// 合并连续的微任务帧，保留每段的第一帧
+    fn collapse_microtask_frames(frames: Vec<StackFrame>) -> Vec<StackFrame> {
+        let mut collapsed: Vec<StackFrame> = Vec::with_capacity(frames.len());
+        let mut run: Option<(usize, usize)> = None;
+
+        for frame in frames {
+            if !Self::is_microtask_frame(&frame) {
+                run = None;
+                collapsed.push(frame);
+                continue;
+            }
+
+            match run.as_mut() {
+                Some((head, count)) => {
+                    *count += 1;
+                    collapsed[*head].set_annotation("collapsed_frames".to_string(), count.to_string());
+                }
+                None => {
+                    run = Some((collapsed.len(), 1));
+                    collapsed.push(frame);
+                }
+            }
+        }
+
+        collapsed
+    }
+
+    /// 按选项合并微任务帧与折叠zone.js帧
+    fn fold_frames(&self, mut frames: Vec<StackFrame>) -> Vec<StackFrame> {
+        if frames.is_empty() {
+            return frames;
+        }
+        if self.collapse_microtasks {
+            frames = Self::collapse_microtask_frames(frames);
+        }
+        if self.fold_zone_frames {
+            frames = Self::fold_zone_frame_runs(frames);
+        }
+        frames
+    }
+
+    /// 是否为zone.js的包装帧：位于zone.js文件中，或是zone.js的调度函数
+    fn is_zone_frame(frame: &StackFrame) -> bool {
+        const ZONE_FUNCTIONS: [&str; 10] = [
+            "ZoneDelegate.invoke", "ZoneDelegate.invokeTask", "Zone.run", "Zone.runTask", "Zone.runGuarded",
+            "ZoneTask.invokeTask", "onInvoke", "onInvokeTask", "drainMicroTaskQueue", "globalZoneAwareCallback",
+        ];
+
+        // 按路径段匹配，`timezone.js` 等业务文件不算
+        let zone_file = fingerprint::strip_query(&frame.file_name).split(['/', '\\']).any(|segment| {
+            matches!(segment, "zone.js" | "zone.min.js") || segment.starts_with("zone-evergreen") || segment.starts_with("zone-node")
+        });
+        // zone.js的内部类带下划线前缀，如 `_ZoneDelegate`
+        let name = frame.function_name.split(" [as ").next().unwrap_or("").trim_start_matches('_');
+        zone_file || ZONE_FUNCTIONS.iter().any(|zone_function| {
+            name.strip_suffix(zone_function).is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('.'))
+        })
+    }
+
+    /// 把连续两帧以上的zone.js帧折叠为一个占位帧，单独出现的保持原样
+    fn fold_zone_frame_runs(frames: Vec<StackFrame>) -> Vec<StackFrame> {
+        let mut folded: Vec<StackFrame> = Vec::with_capacity(frames.len());
+        let mut run: Vec<StackFrame> = Vec::new();
+        let flush = |run: &mut Vec<StackFrame>, folded: &mut Vec<StackFrame>| {
+            if run.len() < 2 {
+                folded.append(run);
+                return;
+            }
+            let mut placeholder = StackFrame::new(format!("zone.js ({} frames)", run.len()), "node_modules/zone.js".to_string(), 0, 0);
+            placeholder.set_origin(FrameOrigin::NodeModules);
+            placeholder.set_framework(Some("Angular".to_string()));
+            placeholder.set_async_boundary(run[0].async_boundary);
+            placeholder.set_annotation("collapsed_frames".to_string(), run.len().to_string());
+            folded.push(placeholder);
+            run.clear();
+        };
+
+        for frame in frames {
+            if Self::is_zone_frame(&frame) {
+                run.push(frame);
+            } else {
+                flush(&mut run, &mut folded);
+                folded.push(frame);
+            }
+        }
+        flush(&mut run, &mut folded);
+        folded
+    }
+
+    /// 正则未能拆出位置时，按末尾括号或裸位置拆分 `at` 之后的内容
+    fn split_chrome_location(rest: &str) -> Option<(&str, &str)> {
+        let rest = rest.trim();
+        if let Some(inner) = rest.strip_suffix(')') {
+            let open = inner.rfind(" (")?;
+            return Some((inner[..open].trim(), &inner[open + 2..]));
+        }
+        if rest.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(("<anonymous>", rest))
+    }
+
+    /// 位置以URL开头且没有括号的帧，如Deno的 `at file:///home/app/main.ts:10:5`、
+    /// `at ext:deno_http/00_serve.js:100:5`；路径里可能带空格或括号，整体作为位置处理
+    fn parse_url_first_line(&self, line: &str) -> Option<StackFrame> {
+        let location = line.trim().strip_prefix("at ")?.trim_start();
+        if !Self::starts_with_url(location) {
+            return None;
+        }
+
+        let (file, line_number, column) = self.split_location(location)?;
+        Some(StackFrame::new("<anonymous>".to_string(), file.to_string(), line_number, column))
+    }
+
+    /// 是否以 `scheme://` 或Deno/Node的 `ext:`、`node:` 内部模块前缀开头（排除 `C:\` 这样的盘符）
+    fn starts_with_url(location: &str) -> bool {
+        if location.starts_with("ext:") || location.starts_with("node:") {
+            return true;
+        }
+        location.split_once("://").is_some_and(|(scheme, _)| {
+            scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        })
+    }
+
+    /// JavaScriptCore（Safari、Bun）的 `name@location` 帧：
+    /// `module code@`、`global code@`、`eval code@` 等带空格的特殊名称，
+    /// `[native code]` 与Bun的 `native:7:39` 内置函数，以及缺少列号或位置的帧。
+    /// Gecko特有的 `async*` 等异步起因前缀与 `line N > eval` 写法留给Firefox格式处理；
+    /// Safari 17起没有 `@` 的 `module code` 等整行帧按版本写法表识别
+    fn parse_jsc_line(&self, line: &str) -> Option<StackFrame> {
+        let line = line.trim();
+        if line.starts_with("at ") || line.contains(" > ") {
+            return None;
+        }
+        let Some((func_name, location)) = line.split_once('@') else {
+            let func_name = self.safari_dialects()
+                .find_map(|dialect| dialect.bare_frames.iter().find(|(text, _)| *text == line))?.1;
+            let mut frame = StackFrame::new(func_name.to_string(), "<anonymous>".to_string(), 0, 0);
+            frame.set_eval(func_name == "eval_code", None);
+            return Some(frame);
+        };
+        if func_name.contains('*') {
+            return None;
+        }
+        let func_name = match func_name {
+            "" => "<anonymous>",
+            "module code" => "module_code",
+            "global code" => "global_code",
+            "eval code" => "eval_code",
+            // 其余带空格的内容更可能是错误消息而不是栈帧
+            name if name.contains(char::is_whitespace) => return None,
+            name => name,
+        };
+
+        let mut frame = if location.is_empty() {
+            StackFrame::new(func_name.to_string(), "<anonymous>".to_string(), 0, 0)
+        } else if location == NATIVE_CODE_LOCATION {
+            StackFrame::new(func_name.to_string(), NATIVE_CODE_LOCATION.to_string(), 0, 0)
+        } else {
+            let mut numbers = Vec::new();
+            let mut file = location;
+            while numbers.len() < 2 {
+                match file.rsplit_once(':') {
+                    Some((rest, number)) if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => {
+                        numbers.insert(0, self.parse_number(number));
+                        file = rest;
+                    }
+                    _ => break,
+                }
+            }
+            if file.is_empty() {
+                return None;
+            }
+            StackFrame::new(self.deobfuscate(func_name), file.to_string(), numbers.first().copied().unwrap_or(0), numbers.get(1).copied().unwrap_or(0))
+        };
+
+        if frame.file_name == NATIVE_CODE_LOCATION || frame.file_name == "native" {
+            frame.set_native(true);
+        }
+        if func_name == "eval_code" {
+            frame.set_eval(true, None);
+        }
+        Some(frame)
+    }
+
+    /// 解析V8的eval帧 `at foo (eval at bar (http://x/app.js:1:1), <anonymous>:2:10)`，
+    /// 嵌套eval时取最内层的真实位置作为来源
+    fn parse_chrome_eval_line(&self, line: &str) -> Option<StackFrame> {
+        let rest = line.trim().strip_prefix("at ")?;
+        let open = rest.find(" (eval at ")?;
+        let inner = rest[open + 2..].strip_suffix(')')?;
+
+        let split = inner.rfind("), ")?;
+        let (origin_part, location) = (&inner[..split], &inner[split + 3..]);
+        let origin = origin_part[origin_part.rfind('(')? + 1..].split(')').next()?;
+
+        let mut parts = location.rsplitn(3, ':');
+        let column = self.parse_number(parts.next()?);
+        let line_number = self.parse_number(parts.next()?);
+        let file = parts.next().unwrap_or("<anonymous>");
+
+        let mut frame = StackFrame::new(self.deobfuscate(rest[..open].trim()), file.to_string(), line_number, column);
+        frame.set_eval(true, Some(origin.to_string()));
+        Some(frame)
+    }
+
+    /// Gecko的eval位置 `http://x/app.js line 1 > eval`，返回调用eval处的 `文件:行`
+    fn split_gecko_eval(file: &str) -> Option<String> {
+        let (origin_file, rest) = file.split_once(" line ")?;
+        let (origin_line, kind) = rest.split_once(" > ")?;
+        if !(kind.starts_with("eval") || kind.starts_with("Function")) {
+            return None;
+        }
+        Some(format!("{}:{}", origin_file, origin_line.trim()))
+    }
+
+    /// 没有行列号的帧：V8内置函数 `(<anonymous>)`、`(native)`，以及Node的 `Promise.all (index 0)`
+    fn parse_locationless_frame(&self, func_name: &str, location: &str) -> Option<StackFrame> {
+        let frame = match location {
+            "<anonymous>" | "native" => {
+                let mut frame = StackFrame::new(self.deobfuscate(func_name), location.to_string(), 0, 0);
+                frame.set_native(true);
+                frame
+            }
+            _ => {
+                let index = location.strip_prefix("index ")?.parse::<u32>().ok()?;
+                let mut frame = StackFrame::new(self.deobfuscate(func_name), "<anonymous>".to_string(), 0, 0);
+                frame.set_promise_index(Some(index));
+                frame
+            }
+        };
+        Some(frame)
+    }
+
+    /// 取出 `name [as alias]` 中的别名
+    fn method_alias(func_name: &str) -> Option<&str> {
+        let start = func_name.rfind("[as ")?;
+        func_name[start + 4..].strip_suffix(']').map(str::trim)
+    }
+
+    /// 单行压缩产物的特征：位于第1行且列号很大，或文件名带 `.min.`
+    fn looks_minified(frame: &StackFrame) -> bool {
+        (frame.line_number <= 1 && frame.column_number >= MINIFIED_COLUMN_THRESHOLD)
+            || frame.file_name.contains(".min.")
+    }
+
+    /// 是否有wasm帧的写法：`wasm-function[N]`、`:wasm-` 或Safari的 `[wasm code]`，其余行不必尝试wasm格式
+    fn looks_like_wasm(line: &str) -> bool {
+        line.contains("wasm-function") || line.contains(":wasm-") || line.trim_end().ends_with(WASM_CODE_LOCATION)
+    }
+
+    /// 解析wasm帧：
+    /// Chrome `at name (wasm://wasm/abc:wasm-function[42]:0x1a2b)`、`at https://x/app.wasm:wasm-function[42]:0x1a2b`，
+    /// Firefox `name@https://x/app.wasm:wasm-function[42]:0x1a2b`，Safari `<?>.wasm-function[42]@[wasm code]`。
+    /// 字节偏移记录在 `address` 与 `wasm_offset` 中，行列号未知；函数名由 `wasm_symbols` 的符号表还原
+    fn parse_wasm_line(&self, line: &str) -> Option<StackFrame> {
+        if !Self::looks_like_wasm(line) {
+            return None;
+        }
+        let trimmed = line.trim();
+        let (name, location) = match trimmed.strip_prefix("at ") {
+            Some(rest) => Self::split_chrome_location(rest).unwrap_or(("", rest)),
+            None => trimmed.split_once('@')?,
+        };
+
+        let caps = self.wasm_regex.captures(trimmed);
+        if caps.is_none() && location != WASM_CODE_LOCATION {
+            return None;
+        }
+        let function_index = caps.as_ref()
+            .and_then(|caps| caps.get(1))
+            .and_then(|m| m.as_str().parse::<u32>().ok());
+        let offset = caps.as_ref()
+            .and_then(|caps| caps.get(2))
+            .and_then(|m| u32::from_str_radix(&m.as_str()[2..], 16).ok());
+
+        // 位置中 `:wasm-function[...]` 之前的部分是模块URL
+        let file = match location.find("wasm-function[") {
+            Some(start) => location[..start].trim_end_matches(':'),
+            None => location,
+        };
+        let file = if file.is_empty() { "<wasm>" } else { file };
+
+        let func_name = if is_generic_name(name) {
+            function_index.map_or_else(|| "<anonymous>".to_string(), |index| format!("wasm-function[{}]", index))
+        } else {
+            self.deobfuscate(name)
+        };
+
+        let mut frame = StackFrame::new(func_name, file.to_string(), 0, 0);
+        frame.set_wasm_location(function_index, offset);
+        frame.set_address(offset.map(u64::from));
+        Some(frame)
+    }
+
+    /// 解析Node/Electron原生崩溃栈的C++帧：`1: 0x55d0b2 node::Abort() [/usr/bin/node]`，
+    /// 修饰名会被还原，原名记录在 `mangled` 注解中
+    fn parse_native_line(&self, line: &str) -> Option<StackFrame> {
+        let (index, rest) = line.trim().split_once(':')?;
+        if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let rest = rest.trim_start();
+        let (address, symbol) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let address = u64::from_str_radix(address.strip_prefix("0x")?, 16).ok()?;
+
+        // 末尾方括号内是二进制文件
+        let mut symbol = symbol.trim();
+        let mut binary = NATIVE_BINARY_LOCATION;
+        if let Some((name, file)) = symbol.strip_suffix(']').and_then(|s| s.rsplit_once('[')) {
+            symbol = name.trim_end();
+            binary = file;
+        }
+
+        let demangled = demangle(symbol);
+        let func_name = match &demangled {
+            Some(name) => name.clone(),
+            None if symbol.is_empty() => "<anonymous>".to_string(),
+            None => symbol.to_string(),
+        };
+        let mut frame = StackFrame::new(func_name, binary.to_string(), 0, 0);
+        frame.set_address(Some(address));
+        if demangled.is_some() {
+            frame.set_annotation("mangled".to_string(), symbol.to_string());
+        }
+        Some(frame)
+    }
+
+    /// Vue组件名：大驼峰（`CartItem`）或短横线（`cart-item`），排除V8的 `<anonymous>` 等小写占位名
+    fn is_vue_component_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) if first.is_ascii_uppercase() => chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$'),
+            Some(first) if first.is_ascii_lowercase() => name.contains('-')
+                && !name.ends_with('-')
+                && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'),
+            _ => false,
+        }
+    }
+
+    /// 解析Vue警告中的组件栈：Vue 3 `at <MyComponent onClick=fn<onClick> msg="hi" >`（可带
+    /// `... (2 recursive calls)`），Vue 2 `---> <MyComponent> at src/components/MyComponent.vue`。
+    /// 不带箭头与文件的 `<Root>` 只在 `found in` 段落中（`found_in` 为true）识别。
+    /// 只保留组件名，props摘要丢弃，递归次数记录在 `recursive_calls` 注解中
+    fn parse_vue_component_line(line: &str, found_in: bool) -> Option<StackFrame> {
+        let trimmed = line.trim();
+        let (inner, file) = if let Some(rest) = trimmed.strip_prefix("at <") {
+            (rest, None)
+        } else {
+            let (arrow, rest) = match trimmed.strip_prefix("--->") {
+                Some(rest) => (true, rest.trim_start()),
+                None => (false, trimmed),
+            };
+            let rest = rest.strip_prefix('<')?;
+            match rest.split_once("> at ") {
+                Some((inner, file)) => (inner, Some(file.trim())),
+                None if arrow || found_in => (rest.strip_suffix('>')?, None),
+                None => return None,
+            }
+        };
+
+        let name_end = inner.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(inner.len());
+        let name = &inner[..name_end];
+        if !Self::is_vue_component_name(name) {
+            return None;
+        }
+
+        let mut frame = StackFrame::new(name.to_string(), file.unwrap_or(VUE_COMPONENT_LOCATION).to_string(), 0, 0);
+        frame.set_annotation("component".to_string(), "vue".to_string());
+        let recursive_calls = inner.strip_suffix(" recursive calls)")
+            .and_then(|rest| rest.rsplit_once("... ("))
+            .and_then(|(_, count)| count.parse::<u32>().ok());
+        if let Some(count) = recursive_calls {
+            frame.set_annotation("recursive_calls".to_string(), count.to_string());
+        }
+        Some(frame)
+    }
+
+    /// 解析单行栈信息，依次尝试wasm、原生、Chrome、Firefox、Safari格式；设置了方言时只尝试该引擎的写法
+    fn parse_line(&self, line: &str) -> Option<StackFrame> {
+        let frame = match self.dialect {
+            Dialect::Auto => return self.parse_line_auto(line),
+            Dialect::V8 | Dialect::Hermes => self.parse_wasm_line(line)
+                .or_else(|| self.parse_native_line(line))
+                .or_else(|| self.parse_v8_line(line)),
+            Dialect::SpiderMonkey => self.parse_wasm_line(line).or_else(|| self.parse_gecko_frame(line)),
+            Dialect::Jsc => self.parse_wasm_line(line)
+                .or_else(|| self.parse_jsc_line(line))
+                .or_else(|| self.parse_gecko_frame(line)),
+        };
+        frame.or_else(|| Self::parse_vue_component_line(line, false))
+            .or_else(|| self.parse_custom_frame(line))
+    }
+
+    /// 逐行探测格式
+    fn parse_line_auto(&self, line: &str) -> Option<StackFrame> {
+        if let Some(frame) = self.parse_wasm_line(line) {
+            return Some(frame);
+        }
+
+        if let Some(frame) = self.parse_native_line(line) {
+            return Some(frame);
+        }
+
+        if let Some(frame) = Self::parse_vue_component_line(line, false) {
+            return Some(frame);
+        }
+
+        // V8的 `at async fn (...)` 异步调用方帧
+        if let Some(rest) = line.trim_start().strip_prefix("at async ") {
+            let mut frame = self.parse_line(&format!("at {}", rest))?;
+            frame.set_async(true);
+            return Some(frame);
+        }
+
+        if let Some(frame) = self.parse_chrome_eval_line(line) {
+            return Some(frame);
+        }
+
+        if let Some(frame) = self.parse_url_first_line(line) {
+            return Some(frame);
+        }
+
+        if let Some(frame) = self.parse_jsc_line(line) {
+            return Some(frame);
+        }
+
+        self.parse_chrome_frame(line)
+            .or_else(|| self.parse_gecko_frame(line))
+            .or_else(|| self.parse_custom_frame(line))
+    }
+
+    /// 依次尝试自定义格式，文件为空时视为不匹配
+    fn parse_custom_frame(&self, line: &str) -> Option<StackFrame> {
+        self.custom_patterns.iter().find_map(|pattern| {
+            let caps = pattern.regex.captures(line)?;
+            let group = |group: &GroupRef| match group {
+                GroupRef::Index(index) => caps.get(*index),
+                GroupRef::Name(name) => caps.name(name),
+            }.map(|m| m.as_str());
+            let number = |field: &Option<GroupRef>| field.as_ref().and_then(group).map_or(0, |s| self.parse_number(s));
+
+            let file = group(&pattern.groups.file).filter(|file| !file.is_empty())?;
+            let func_name = pattern.groups.function.as_ref().and_then(group).filter(|name| !name.is_empty()).unwrap_or("<anonymous>");
+            let mut frame = StackFrame::new(self.deobfuscate(func_name), file.to_string(), number(&pattern.groups.line), number(&pattern.groups.column));
+            frame.set_annotation("pattern".to_string(), pattern.name.clone());
+            Some(frame)
+        })
+    }
+
+    /// 按预分词阶段给出的格式解析一行，跳过其他格式的尝试；与 `parse_line` 一样先识别wasm帧，
+    /// 内置格式都不匹配时再尝试自定义格式
+    fn parse_line_as(&self, line: &str, format: LineFormat) -> Option<StackFrame> {
+        let frame = match format {
+            LineFormat::Unknown => return self.parse_line(line),
+            LineFormat::Message | LineFormat::AsyncSeparator => return None,
+            LineFormat::V8 => self.parse_wasm_line(line)
+                .or_else(|| self.parse_native_line(line))
+                .or_else(|| self.parse_v8_line(line)),
+            LineFormat::Gecko => self.parse_wasm_line(line)
+                .or_else(|| self.parse_jsc_line(line))
+                .or_else(|| self.parse_gecko_frame(line)),
+            LineFormat::Wasm => self.parse_wasm_line(line),
+        };
+        frame.or_else(|| self.parse_custom_frame(line))
+    }
+
+    /// V8的各种写法：`at async` 调用方、eval帧、URL在前的帧与 `at func (file:line:col)`
+    fn parse_v8_line(&self, line: &str) -> Option<StackFrame> {
+        if let Some(rest) = line.trim_start().strip_prefix("at async ") {
+            let line = format!("at {}", rest);
+            let mut frame = self.parse_wasm_line(&line).or_else(|| self.parse_v8_line(&line))?;
+            frame.set_async(true);
+            return Some(frame);
+        }
+        self.parse_chrome_eval_line(line)
+            .or_else(|| self.parse_url_first_line(line))
+            .or_else(|| self.parse_chrome_frame(line))
+    }
+
+    /// V8格式 `at func (file:line:col)`
+    fn parse_chrome_frame(&self, line: &str) -> Option<StackFrame> {
+        let caps = self.chrome_regex.captures(line)?;
+        let (func_name, location) = match caps.get(3) {
+            Some(location) => (caps.get(1).map_or("<anonymous>", |m| m.as_str()), location.as_str()),
+            // 压缩代码常见 `at Object.t [as foo] (...)` 或无函数名的 `at https://cdn/x.min.js:1:2`
+            None => Self::split_chrome_location(&line[caps.get(0)?.start() + 2..])?,
+        };
+
+        // Hermes的字节码虚拟地址 `address at index.android.bundle:1:234567`
+        let (location, hermes_bytecode) = match location.strip_prefix("address at ") {
+            Some(location) => (location, true),
+            None => (location, false),
+        };
+        let func_name = if hermes_bytecode && func_name == "anonymous" { "<anonymous>" } else { func_name };
+
+        let mut frame = match self.split_location(location) {
+            Some((file, line_num, col_num)) => StackFrame::new(self.deobfuscate(func_name), file.to_string(), line_num, col_num),
+            // 行列号无法解析时仍保留帧，行列号记为0
+            None => match location.rsplitn(3, ':').nth(2) {
+                Some(file) if !file.is_empty() => StackFrame::new(self.deobfuscate(func_name), file.to_string(), 0, 0),
+                _ => self.parse_locationless_frame(func_name, location)?,
+            },
+        };
+
+        frame.set_hermes_bytecode(hermes_bytecode);
+
+        // V8的 `Socket.emit [as emit]` 方法别名
+        if let Some(alias) = Self::method_alias(func_name) {
+            frame.set_annotation("alias".to_string(), alias.to_string());
+        }
+        Some(frame)
+    }
+
+    /// Firefox/Safari格式 `func@file:line:col`，JavaScriptCore的特殊写法已由 `parse_jsc_line` 处理
+    fn parse_gecko_frame(&self, line: &str) -> Option<StackFrame> {
+        let caps = self.firefox_regex.captures(line)?;
+        let mut func_name = caps.get(1).map_or("<anonymous>", |m| m.as_str());
+        let file = caps.get(2).map_or("", |m| m.as_str());
+        let line_num = caps.get(3).map_or(0, |m| self.parse_number(m.as_str()));
+        let col_num = caps.get(4).map_or(0, |m| self.parse_number(m.as_str()));
+
+        // Gecko用 `起因*` 前缀标记异步调用方，如 `async*`、`promise callback*`、`setTimeout handler*`
+        let async_cause = match func_name.split_once('*') {
+            Some((cause, stripped)) if !cause.is_empty() && !cause.contains('(') => {
+                func_name = stripped;
+                Some(cause)
+            }
+            _ => None,
+        };
+
+        // Safari的匿名函数、模块顶层与全局代码
+        let func_name = match func_name {
+            "" => "<anonymous>",
+            "module code" => "module_code",
+            "global code" => "global_code",
+            name => name,
+        };
+
+        // Gecko的eval帧：行列号相对于eval的代码
+        let eval_origin = Self::split_gecko_eval(file);
+        let file = if eval_origin.is_some() { "<anonymous>" } else { file };
+
+        let mut frame = StackFrame::new(self.deobfuscate(func_name), file.to_string(), line_num, col_num);
+        frame.set_async(async_cause.is_some());
+        if let Some(cause) = async_cause.filter(|cause| *cause != "async") {
+            frame.set_annotation("async_cause".to_string(), cause.to_string());
+        }
+        if eval_origin.is_some() {
+            frame.set_eval(true, eval_origin);
+        }
+        Some(frame)
+    }
+
+    /// 解析错误栈为栈帧列表，这是主要的解析接口，其余输出格式都基于它
+    pub fn parse_frames(&self, stack: &str) -> Vec<StackFrame> {
         if stack.is_empty() {
+            return Vec::new();
+        }
+
+        // 输入已经是解析结果（重复处理），校验后原样返回，校验失败时没有栈帧
+        if let Some(frames) = Self::reparse_processed(stack) {
+            return frames.unwrap_or_default();
+        }
+        
+        let index = LineIndex::new(stack);
+        self.collect_frames(index.lines().map(|line| (line, LineFormat::Unknown)), &mut DialectEvidence::default())
+    }
+
+    /// 解析栈帧并返回实际匹配的方言（判断规则同 `detect_dialect`），方言在同一遍解析中记录
+    pub fn parse_frames_with_dialect(&self, stack: &str) -> (Vec<StackFrame>, Dialect) {
+        if stack.is_empty() {
+            return (Vec::new(), self.dialect);
+        }
+        if let Some(frames) = Self::reparse_processed(stack) {
+            return (frames.unwrap_or_default(), self.dialect);
+        }
+
+        let mut evidence = DialectEvidence::default();
+        let index = LineIndex::new(stack);
+        let frames = self.collect_frames(index.lines().map(|line| (line, LineFormat::Unknown)), &mut evidence);
+        let dialect = if self.dialect == Dialect::Auto { evidence.dialect() } else { self.dialect };
+        (frames, dialect)
+    }
+
+    /// 只识别Chrome/V8格式的快速路径：不做格式探测与正则匹配，逐行扫描 `at func (file:line:col)` 与
+    /// `at file:line:col`，适用于加载时已知是Chromium内核的SDK构建。异步分隔行标记到下一帧，
+    /// 其余不以 `at ` 开头的行跳过，少见的eval与wasm帧交给完整流程
+    pub fn parse_chrome_fast(&self, stack: &str) -> Vec<StackFrame> {
+        let index = LineIndex::new(stack);
+        let mut frames = Vec::new();
+        let mut pending_boundary = None;
+        for line in index.lines() {
+            let Some(rest) = line.trim_start().strip_prefix("at ") else {
+                if let Some(cause) = Self::async_separator_cause(line) {
+                    pending_boundary = (!frames.is_empty()).then_some(cause);
+                }
+                continue;
+            };
+            let frame = if rest.contains("(eval at ") || Self::looks_like_wasm(rest) {
+                self.parse_line(line)
+            } else {
+                self.parse_chrome_fast_line(rest)
+            };
+            if let Some(mut frame) = frame {
+                Self::mark_async_boundary(&mut frame, pending_boundary.take());
+                self.classify_frame(&mut frame);
+                frames.push(frame);
+            }
+        }
+
+        self.fold_frames(frames)
+    }
+
+    /// 快速路径的单行解析，`rest` 为 `at ` 之后的部分
+    fn parse_chrome_fast_line(&self, rest: &str) -> Option<StackFrame> {
+        let (rest, is_async) = match rest.strip_prefix("async ") {
+            Some(rest) => (rest, true),
+            None => (rest, false),
+        };
+        let (func_name, location) = Self::split_chrome_location(rest)?;
+        let mut frame = match self.split_location(location) {
+            Some((file, line_num, col_num)) => StackFrame::new(self.deobfuscate(func_name), file.to_string(), line_num, col_num),
+            None => self.parse_locationless_frame(func_name, location)?,
+        };
+        frame.set_async(is_async);
+        if let Some(alias) = Self::method_alias(func_name) {
+            frame.set_annotation("alias".to_string(), alias.to_string());
+        }
+        Some(frame)
+    }
+
+    /// 使用JS侧预先切分的行解析：`offsets` 为每行起始的UTF-16下标（JS字符串的 `indexOf` 结果），
+    /// `formats` 为每行的格式字节，缺失时按未知格式处理；越界或落在代理对中间的行被跳过
+    pub fn parse_pretokenized(&self, text: &str, offsets: &[u32], formats: &[u8]) -> Vec<StackFrame> {
+        let offsets: Vec<Option<usize>> = utf16_to_byte_offsets(text, offsets);
+        self.parse_line_starts(text, &offsets, formats)
+    }
+
+    /// 按每行起始的UTF-8字节偏移解析，Rust侧自行切分的调用方（`Tokenized`）使用
+    pub(crate) fn parse_pretokenized_bytes(&self, text: &str, line_starts: &[u32], formats: &[u8]) -> Vec<StackFrame> {
+        let line_starts: Vec<Option<usize>> = line_starts.iter().map(|&start| Some(start as usize)).collect();
+        self.parse_line_starts(text, &line_starts, formats)
+    }
+
+    /// 无法换算的偏移为None，该行被跳过；不在字符边界上的字节偏移同样跳过
+    fn parse_line_starts(&self, text: &str, starts: &[Option<usize>], formats: &[u8]) -> Vec<StackFrame> {
+        let lines = starts.iter().enumerate().filter_map(|(i, &start)| {
+            let end = starts.get(i + 1).map_or(Some(text.len()), |&end| end)?;
+            let line = text.get(start?..end)?.trim_end_matches(['\n', '\r']);
+            Some((line, formats.get(i).map_or(LineFormat::Unknown, |&byte| LineFormat::from_byte(byte))))
+        });
+        self.collect_frames(lines, &mut DialectEvidence::default())
+    }
+
+    fn collect_frames<'s>(&self, lines: impl Iterator<Item = (&'s str, LineFormat)>, evidence: &mut DialectEvidence) -> Vec<StackFrame> {
+        let mut frames = Vec::new();
+        let mut pending_boundary = None;
+        // Vue 2警告的 `found in` 段落之后才识别裸 `<Root>` 组件行
+        let mut found_in = false;
+        for (line, format) in lines {
+            // 分隔行本身不产生栈帧，标记到下一帧上
+            let separator = match format {
+                LineFormat::AsyncSeparator => Some(Self::async_separator_cause(line).unwrap_or("")),
+                LineFormat::Unknown => Self::async_separator_cause(line),
+                _ => None,
+            };
+            if let Some(cause) = separator {
+                pending_boundary = (!frames.is_empty()).then_some(cause);
+                continue;
+            }
+
+            found_in |= line.trim() == "found in";
+            let frame = self.parse_line_as(line, format);
+            if let Some(frame) = &frame {
+                evidence.observe(line, frame);
+            }
+            let frame = frame.or_else(|| found_in.then(|| Self::parse_vue_component_line(line, true)).flatten())
+                .or_else(|| (self.raw_lines && !frames.is_empty() && !line.trim().is_empty()).then(|| StackFrame::raw_line(line)));
+            if let Some(mut frame) = frame {
+                Self::mark_async_boundary(&mut frame, pending_boundary.take());
+                self.classify_frame(&mut frame);
+                frames.push(frame);
+            }
+        }
+
+        self.fold_frames(frames)
+    }
+
+    /// 解析阶段确定的帧属性：来源、框架、所属包、压缩标记
+    fn classify_frame(&self, frame: &mut StackFrame) {
+        frame.set_origin(frame.classify(&self.app_prefixes));
+        frame.set_framework(self.detect_framework(&frame.file_name));
+        packages::annotate(frame);
+        if self.minified_mode {
+            frame.set_minified(Self::looks_minified(frame));
+        }
+    }
+
+    /// 解析错误栈，输出每行一帧的 `file:line:col|func` 文本
+    pub fn parse(&self, stack: &str) -> String {
+        Self::format_frames(&self.parse_frames(stack))
+    }
+
+    /// `parse_frames` 的旧名称，保留以兼容已有调用方
+    pub fn parse_simd(&self, stack: &str) -> Vec<StackFrame> {
+        self.parse_frames(stack)
+    }
+
+    /// 提取输入中所有十进制数字串，超出u32范围的跳过
+    pub fn parse_numbers(&self, input: &str) -> Vec<u32> {
+        input.split(|c: char| !c.is_ascii_digit())
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| s.parse::<u32>().ok())
+            .collect()
+    }
+
+    /// 解析错误栈中每一帧的行列号，按 `[行, 列, 行, 列, ...]` 平铺返回，行列号按全局起点导出
+    pub fn parse_line_column(&self, stack: &str) -> Vec<u32> {
+        let positions = parser_options::options();
+        self.parse_frames(stack).iter()
+            .flat_map(|frame| [positions.export_line(frame.line_number), positions.export_column(frame.column_number)])
+            .collect()
+    }
+
+    /// 解析错误栈并按 `[行, 列, 帧下标, ...]` 返回有位置的帧，帧下标与 `parse_frames` 的结果对应
+    pub fn parse_line_column_indexed(&self, stack: &str) -> Vec<u32> {
+        line_column_triples(&self.parse_frames(stack))
+    }
+}
+
+impl ErrorParser {
+    /// 注册已编译的自定义栈帧格式，同名格式会被替换
+    pub fn add_pattern(&mut self, name: &str, regex: Regex, groups: GroupMapping) {
+        let pattern = CustomPattern { name: name.to_string(), regex, groups };
+        match self.custom_patterns.iter_mut().find(|existing| existing.name == name) {
+            Some(existing) => *existing = pattern,
+            None => self.custom_patterns.push(pattern),
+        }
+    }
+
+    /// 在截止时间前从第 `start_line` 行开始解析已建立索引的栈，每帧解析后交给 `process`（如source map还原）。
+    /// 返回已处理的帧与下一次继续的行号，全部处理完时为None；每次至少处理一行以保证推进。
+    /// 已处理格式的输入（JSON帧数组、`file:line:col|func` 文本）由调用方先用 `reparse_processed` 识别
+    pub fn parse_frames_until(
+        &self,
+        index: &LineIndex,
+        start_line: usize,
+        deadline: &Deadline,
+        mut process: impl FnMut(StackFrame) -> StackFrame,
+    ) -> (Vec<StackFrame>, Option<usize>) {
+        let mut frames = Vec::new();
+        let mut next_line = None;
+        // 上一段停在分隔行之后时，边界标记到本段的第一帧
+        let mut pending_boundary = start_line.checked_sub(1).and_then(|previous| index.line(previous)).and_then(Self::async_separator_cause);
+        for line_index in start_line..index.len() {
+            if line_index > start_line && deadline.expired() {
+                next_line = Some(line_index);
+                break;
+            }
+            let Some(line) = index.line(line_index) else {
+                continue;
+            };
+            if let Some(cause) = Self::async_separator_cause(line) {
+                pending_boundary = (start_line > 0 || !frames.is_empty()).then_some(cause);
+                continue;
+            }
+            if let Some(mut frame) = self.parse_line_as(line, LineFormat::Unknown) {
+                Self::mark_async_boundary(&mut frame, pending_boundary.take());
+                self.classify_frame(&mut frame);
+                frames.push(process(frame));
+            }
+        }
+
+        (self.fold_frames(frames), next_line)
+    }
+
+    /// 将栈帧格式化为每行一帧的 `file:line:col|func` 文本，起点为0时未知位置输出为 `?`
+    pub fn format_frames(frames: &[StackFrame]) -> String {
+        let positions = parser_options::options();
+        let mut result = String::new();
+        for frame in frames {
+            let (line, column) = (positions.export_line(frame.line_number), positions.export_column(frame.column_number));
+            if line == UNKNOWN_POSITION || column == UNKNOWN_POSITION {
+                let text = |position: u32| if position == UNKNOWN_POSITION { "?".to_string() } else { position.to_string() };
+                result.push_str(&format!("{}:{}:{}|{}", frame.relative_file_name(), text(line), text(column), frame.function_name));
+            } else {
+                result.push_str(&format_stack_frame(&frame.function_name, frame.relative_file_name(), line, column));
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// 拆分错误首行 `TypeError: Cannot read property...` 为错误类型与消息。
+    /// 浏览器的 `Uncaught ` 前缀与Node的 `Error [ERR_CODE]` 错误码不计入类型；
+    /// 首段不像类型名（如 `Script error.`）时整行作为消息
+    pub fn parse_error_header(line: &str) -> (Option<String>, String) {
+        let line = line.trim();
+        let line = line.strip_prefix("Uncaught ").unwrap_or(line);
+        let (head, message) = match line.split_once(':') {
+            Some((head, message)) => (head, message.trim()),
+            None => (line, ""),
+        };
+        let name = head.split_once(" [").filter(|(_, code)| code.ends_with(']')).map_or(head, |(name, _)| name);
+
+        let is_type_name = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '.'));
+        // 没有冒号时只接受 `Error`、`RangeError` 这类标准写法
+        if !is_type_name || (message.is_empty() && !line.contains(':') && !name.ends_with("Error")) {
+            return (None, line.to_string());
+        }
+        (Some(name.to_string()), message.to_string())
+    }
+
+    /// 容错版本的 `parse_frames`，用于不可信的任意字节输入：非法UTF-8替换为U+FFFD，
+    /// 超过 `MAX_INPUT_BYTES` 的部分截断
+    pub fn parse_lossy(&self, bytes: &[u8]) -> Vec<StackFrame> {
+        let bytes = &bytes[..bytes.len().min(MAX_INPUT_BYTES)];
+        self.parse_frames(&String::from_utf8_lossy(bytes))
+    }
+
+    /// 严格版本的 `parse_frames`：空输入、过大输入、无法识别的格式与无效的JSON栈帧返回错误；
+    /// 只有错误首行而没有栈帧时返回空列表
+    pub fn try_parse_frames(&self, stack: &str) -> Result<Vec<StackFrame>, ParseError> {
+        if stack.trim().is_empty() {
+            return Err(ParseError::EmptyInput);
+        }
+        if stack.len() > MAX_INPUT_BYTES {
+            return Err(ParseError::InputTooLarge { size: stack.len(), limit: MAX_INPUT_BYTES });
+        }
+        if let Some(frames) = Self::reparse_processed(stack) {
             return frames;
         }
-        
-        for line in stack.split('\n') {
-            if let Some(caps) = self.chrome_regex.captures(line) {
-                let func_name = caps.get(1).map_or("<anonymous>", |m| m.as_str()).to_string();
-                
-                if let Some(location) = caps.get(3) {
-                    let loc_parts: Vec<&str> = location.as_str().split(':').collect();
-                    if loc_parts.len() >= 3 {
-                        let file = loc_parts[0..loc_parts.len()-2].join(":");
-                        let line_num = loc_parts[loc_parts.len()-2].parse::<u32>().unwrap_or(0);
-                        let col_num = loc_parts[loc_parts.len()-1].parse::<u32>().unwrap_or(0);
-                        
-                        frames.push(StackFrame {
-                            function_name: func_name,
-                            file_name: file,
-                            line_number: line_num,
-                            column_number: col_num,
-                        });
+        let frames = self.parse_frames(stack);
+        if frames.is_empty() && Self::parse_error_header(LineIndex::new(stack).line(0).unwrap_or("")).0.is_none() {
+            return Err(ParseError::UnrecognizedFormat);
+        }
+        Ok(frames)
+    }
+
+    /// 解析完整错误：首行拆分为 `error_type` 与 `message`，其余为栈帧。
+    /// 首行本身就是栈帧（Firefox、Safari没有消息行）时消息为空
+    pub fn parse_error(&self, stack: &str) -> ParsedError {
+        let first = LineIndex::new(stack).line(0).unwrap_or("");
+        let (error_type, message) = if self.parse_line(first).is_some() || Self::reparse_processed(stack).is_some() {
+            (None, String::new())
+        } else {
+            Self::parse_error_header(first)
+        };
+
+        let frames = self.parse_frames(stack);
+        let (strong_fingerprint, weak_fingerprint) = fingerprint::dual_fingerprints(stack, first, &frames);
+        ParsedError {
+            error_type,
+            message,
+            category: taxonomy::classify(first).map(|classification| classification.category),
+            owners: Vec::new(),
+            detected_frameworks: detected_frameworks(&frames),
+            strong_fingerprint,
+            weak_fingerprint,
+            frames: frames.into_iter().map(ExportedStackFrame::from).collect(),
+        }
+    }
+
+    /// 链式错误的分段标记所在行：Node的 `[cause]: ` 与手写的 `Caused by: `，返回去掉标记后的首行
+    fn cause_header(line: &str) -> Option<&str> {
+        let line = line.trim_start();
+        if let Some(rest) = line.strip_prefix("[cause]:") {
+            return Some(rest.trim_start());
+        }
+        let prefix = line.get(..10)?;
+        if prefix.eq_ignore_ascii_case("caused by:") { Some(line[10..].trim_start()) } else { None }
+    }
+
+    /// 在日志行中查找错误首行 `TypeError: ...`，返回从错误类型开始的部分（去掉时间戳等前缀）。
+    /// 只接受以 `Error`/`Exception` 结尾的类型名，避免把普通的 `INFO: ...` 日志当成错误
+    fn find_error_header(line: &str) -> Option<&str> {
+        let is_name_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '.');
+        for suffix in ["Error", "Exception"] {
+            for (index, _) in line.match_indices(suffix) {
+                let after = &line[index + suffix.len()..];
+                if !(after.is_empty() || after.starts_with(':') || after.starts_with(" [")) {
+                    continue;
+                }
+                let start = line[..index].char_indices()
+                    .rfind(|&(_, c)| !is_name_char(c))
+                    .map_or(0, |(at, c)| at + c.len_utf8());
+                if line[start..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                    let before = line[..start].trim_end();
+                    // 紧跟在说明文字后面的 `... with Error` 不是新的错误
+                    if before.is_empty() || before.ends_with([']', ':', '>', '|', '-']) || before.ends_with("Uncaught") {
+                        return Some(line[start..].trim_end());
                     }
                 }
             }
         }
-        
-        frames
+        None
+    }
+
+    /// 从拼接的控制台/日志文本中拆分出相互独立的错误栈：遇到新的错误首行时开始新的错误，
+    /// 栈帧之后出现的其他日志行结束当前错误；`Caused by:`/`[cause]:` 仍属于当前错误。
+    /// 首行与第一个栈帧之间的行视为多行消息，后面出现栈帧时一并保留
+    pub fn split_errors(&self, text: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut current: Option<Vec<&str>> = None;
+        // 当前错误尚未出现栈帧时暂存的消息行
+        let mut message_lines: Vec<&str> = Vec::new();
+        let mut has_frames = false;
+        for line in LineIndex::new(text).lines() {
+            let trimmed = line.trim();
+            let is_frame = self.parse_line(line).is_some();
+            let continues = current.is_some() && (Self::cause_header(line).is_some()
+                || trimmed.starts_with("...") || trimmed == "}" || is_frame);
+            if continues {
+                if let Some(lines) = current.as_mut() {
+                    if is_frame && !has_frames {
+                        lines.append(&mut message_lines);
+                    }
+                    lines.push(line);
+                }
+                has_frames |= is_frame;
+                continue;
+            }
+
+            let header = if is_frame { None } else { Self::find_error_header(line) };
+            if current.is_some() && !has_frames && header.is_none() && !trimmed.is_empty() {
+                message_lines.push(line);
+                continue;
+            }
+
+            if let Some(lines) = current.take() {
+                errors.push(lines.join("\n"));
+            }
+            message_lines.clear();
+            has_frames = false;
+            current = header.map(|header| vec![header]);
+        }
+        if let Some(lines) = current {
+            errors.push(lines.join("\n"));
+        }
+        errors
+    }
+
+    /// 拆分并逐个解析日志中的错误
+    pub fn split_and_parse(&self, text: &str) -> Vec<ParsedError> {
+        self.split_errors(text).iter().map(|stack| self.parse_error(stack)).collect()
+    }
+
+    /// 按异步边界把栈帧分段，保留异步因果关系：分隔行（`--- Promise.then ---`、`setTimeout (async)`、
+    /// `----------`）与异步调用方帧（V8的 `at async`、Gecko的 `promise callback*`）开始新的一段，
+    /// 连续的异步调用方帧属于同一段（同一条await链）。段的起因取自 `async_cause` 注解，
+    /// 异步调用方帧没有注解时为 `async`
+    pub fn parse_async_segments(&self, stack: &str) -> Vec<AsyncSegment> {
+        let mut segments: Vec<AsyncSegment> = Vec::new();
+        let mut previous_async = false;
+        for frame in self.parse_frames(stack) {
+            let starts_segment = frame.async_boundary || (frame.is_async && !previous_async);
+            previous_async = frame.is_async;
+            if starts_segment || segments.is_empty() {
+                let cause = if starts_segment {
+                    frame.get_annotation("async_cause").or_else(|| frame.is_async.then(|| "async".to_string()))
+                } else {
+                    None
+                };
+                segments.push(AsyncSegment { cause, frames: Vec::new() });
+            }
+            if let Some(segment) = segments.last_mut() {
+                segment.frames.push(ExportedStackFrame::from(frame));
+            }
+        }
+        segments
+    }
+
+    /// 调用方给出的方言
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// 应用 `parse_with_options` 的方言与解析开关
+    pub fn apply_options(&mut self, options: &ParseOptions) {
+        self.dialect = options.dialect;
+        self.lenient_numbers = options.lenient_numbers;
+        self.collapse_microtasks = options.collapse_microtasks;
+        self.fold_zone_frames = options.fold_zone_frames;
+        self.raw_lines = options.raw_lines;
+    }
+
+    /// 栈实际匹配的方言：设置了方言时直接返回；否则按各引擎特有的写法判断——`at ` 前缀为V8，
+    /// 其中有字节码帧时为Hermes；`[native code]`、`module code@` 等带空格的名称与缺少列号的帧为JSC；
+    /// `async*` 等起因前缀与 `> eval` 为SpiderMonkey，只有通用的 `func@file:line:col` 时也按SpiderMonkey；
+    /// 没有可识别的栈帧时为auto
+    /// 只需要方言、不需要栈帧时使用；同时需要两者时用 `parse_frames_with_dialect`
+    pub fn detect_dialect(&self, stack: &str) -> Dialect {
+        if self.dialect != Dialect::Auto {
+            return self.dialect;
+        }
+
+        let mut evidence = DialectEvidence::default();
+        for line in LineIndex::new(stack).lines() {
+            if let Some(frame) = self.parse_line(line) {
+                evidence.observe(line, &frame);
+            }
+        }
+        evidence.dialect()
+    }
+
+    /// 解析链式错误，外层错误在前，每个原因各自带有类型、消息与栈帧
+    pub fn parse_error_chain(&self, stack: &str) -> Vec<ParsedError> {
+        let mut sections: Vec<Vec<&str>> = vec![Vec::new()];
+        for line in LineIndex::new(stack).lines() {
+            match (Self::cause_header(line), sections.last_mut()) {
+                (Some(header), _) => sections.push(vec![header]),
+                (None, Some(section)) => section.push(line),
+                (None, None) => {}
+            }
+        }
+
+        sections.iter()
+            .filter(|section| section.iter().any(|line| !line.trim().is_empty()))
+            .map(|section| self.parse_error(&section.join("\n")))
+            .collect()
+    }
+}
+
+impl Default for ErrorParser {
+    fn default() -> Self {
+        ErrorParser::new()
     }
 }
 
@@ -188,12 +2319,934 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_simd() {
+    fn test_parse_frames() {
         let parser = ErrorParser::new();
         let stack = "Error: test\n at Component (/src/App.js:10:20)";
-        let frames = parser.parse_simd(stack);
+        let frames = parser.parse_frames(stack);
         assert!(!frames.is_empty());
         assert_eq!(frames[0].line_number, 10);
         assert_eq!(frames[0].column_number, 20);
     }
+
+    #[test]
+    fn test_rename_map() {
+        let mut parser = ErrorParser::new();
+        assert!(parser.set_rename_map(r#"{"renderCart": "a", "CartView": "Zr"}"#));
+        assert!(!parser.set_rename_map("not json"));
+
+        let stack = "Error: test\n at Zr.a (/dist/app.min.js:1:200)\n at b (/dist/app.min.js:1:300)";
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames[0].function_name(), "CartView.renderCart");
+        assert_eq!(frames[1].function_name(), "b");
+        assert!(parser.parse(stack).contains("|CartView.renderCart"));
+
+        parser.clear_rename_map();
+        parser.add_rename("checkout".to_string(), "b".to_string());
+        assert_eq!(parser.parse_frames(stack)[1].function_name(), "checkout");
+
+        // Promise组合器的 `(index N)` 帧同样还原
+        parser.add_rename("loadAll".to_string(), "all".to_string());
+        let frames = parser.parse_frames("Error: test\n at async Promise.all (index 0)");
+        assert_eq!((frames[0].function_name().as_str(), frames[0].promise_index()), ("Promise.loadAll", Some(0)));
+    }
+
+    #[test]
+    fn test_parse_error_header() {
+        assert_eq!(ErrorParser::parse_error_header("TypeError: Cannot read property 'x' of undefined"),
+            (Some("TypeError".to_string()), "Cannot read property 'x' of undefined".to_string()));
+        assert_eq!(ErrorParser::parse_error_header("Uncaught RangeError: Maximum call stack size exceeded").0.as_deref(), Some("RangeError"));
+        assert_eq!(ErrorParser::parse_error_header("Error [ERR_INVALID_ARG_TYPE]: The \"path\" argument must be of type string"),
+            (Some("Error".to_string()), "The \"path\" argument must be of type string".to_string()));
+        assert_eq!(ErrorParser::parse_error_header("Error"), (Some("Error".to_string()), String::new()));
+        assert_eq!(ErrorParser::parse_error_header("Script error."), (None, "Script error.".to_string()));
+        assert_eq!(ErrorParser::parse_error_header("Failed to fetch: network down"), (None, "Failed to fetch: network down".to_string()));
+
+        let parser = ErrorParser::new();
+        let error = parser.parse_error("TypeError: x is undefined\n at render (/src/App.js:1:2)");
+        assert_eq!(error.error_type.as_deref(), Some("TypeError"));
+        assert_eq!(error.message, "x is undefined");
+        assert_eq!(error.frames.len(), 1);
+
+        let error = parser.parse_error("render@http://localhost/src/App.js:1:2");
+        assert_eq!((error.error_type, error.message.as_str(), error.frames.len()), (None, "", 1));
+    }
+
+    #[test]
+    fn test_split_errors_in_logs() {
+        let log = "2024-05-01T10:00:00Z [info] server started\n\
+            2024-05-01T10:00:01Z [error] TypeError: Cannot read properties of undefined (reading 'id')\n\
+            \x20   at getUser (/srv/app/users.js:10:5)\n\
+            \x20   at async handler (/srv/app/routes.js:3:1)\n\
+            2024-05-01T10:00:02Z [info] retrying request\n\
+            Uncaught RangeError: Maximum call stack size exceeded\n\
+            \x20   at recurse (/srv/app/loop.js:1:1)\n\
+            Error: wrapped\n\
+            \x20   at main (/srv/app/index.js:5:5)\n\
+            Caused by: Error: inner\n\
+            \x20   at inner (/srv/app/inner.js:2:2)\n\
+            [warn] request finished with Error: none";
+        let parser = ErrorParser::new();
+        let errors = parser.split_and_parse(log);
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].error_type.as_deref(), Some("TypeError"));
+        assert_eq!(errors[0].message, "Cannot read properties of undefined (reading 'id')");
+        assert_eq!(errors[0].frames.len(), 2);
+        assert_eq!(errors[1].error_type.as_deref(), Some("RangeError"));
+        assert_eq!(errors[1].frames.len(), 1);
+        // 链式错误的原因留在同一个错误里
+        assert_eq!(errors[2].message, "wrapped");
+        assert_eq!(errors[2].frames.len(), 2);
+
+        assert!(parser.split_and_parse("[info] nothing to see\nINFO: still fine").is_empty());
+
+        // 多行消息之后的栈帧仍属于该错误；没有栈帧时只保留首行
+        let multiline = "Error: request failed\n  status: 500\n  body: oops\n    at send (/srv/app/http.js:4:2)\n[info] done";
+        assert_eq!(parser.split_errors(multiline), vec!["Error: request failed\n  status: 500\n  body: oops\n    at send (/srv/app/http.js:4:2)"]);
+        assert_eq!(parser.split_errors("Error: bare\nINFO: later"), vec!["Error: bare"]);
+    }
+
+    #[test]
+    fn test_split_errors_after_multibyte_prefix() {
+        let parser = ErrorParser::new();
+        let errors = parser.split_and_parse("错误：TypeError: x is undefined\n    at f (/srv/app/f.js:1:2)");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].error_type.as_deref(), Some("TypeError"));
+        assert_eq!(errors[0].frames.len(), 1);
+        assert_eq!(parser.split_errors("日志 → RangeError: boom"), vec!["RangeError: boom"]);
+    }
+
+    #[test]
+    fn test_parse_error_chain() {
+        let node = "Error: request failed\n\
+            at fetchUser (/src/api.js:10:5)\n\
+            ... 2 lines matching cause stack trace ...\n\
+            at main (/src/index.js:1:1) {\n\
+          [cause]: TypeError: fetch failed\n\
+              at request (/src/http.js:3:7)\n\
+        }";
+        let parser = ErrorParser::new();
+        let chain = parser.parse_error_chain(node);
+        assert_eq!(chain.len(), 2);
+        assert_eq!((chain[0].error_type.as_deref(), chain[0].message.as_str()), (Some("Error"), "request failed"));
+        assert_eq!(chain[0].frames.len(), 2);
+        assert_eq!(chain[0].frames[1].file_name, "/src/index.js");
+        assert_eq!((chain[1].error_type.as_deref(), chain[1].message.as_str()), (Some("TypeError"), "fetch failed"));
+        assert_eq!(chain[1].frames[0].function_name, "request");
+
+        let manual = "DbError: query failed\n at query (/src/db.js:1:2)\nCaused by: Error: timeout\n at socket (/src/net.js:4:5)\ncaused by: Error: ECONNRESET";
+        let chain = parser.parse_error_chain(manual);
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[2].message, "ECONNRESET");
+        assert!(chain[2].frames.is_empty());
+
+        assert_eq!(parser.parse_error_chain("Error: single\n at a (/a.js:1:2)").len(), 1);
+    }
+
+    #[test]
+    fn test_frames_without_column() {
+        let stack = "Error: old safari\n\
+            at render (http://localhost:3000/src/App.js:10)\n\
+            at file:///home/app/main.ts:7\n\
+            handleClick@http://localhost:3000/src/App.js:12\n\
+            @/src/index.js:3";
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].file_name(), "http://localhost:3000/src/App.js");
+        assert_eq!((frames[0].line_number(), frames[0].column_number()), (10, 0));
+        assert_eq!((frames[1].line_number(), frames[1].column_number()), (7, 0));
+        assert_eq!(frames[2].file_name(), "http://localhost:3000/src/App.js");
+        assert_eq!((frames[3].line_number(), frames[3].column_number()), (3, 0));
+        assert_eq!(crate::cold_start::tiny_parse(stack).len(), 4);
+    }
+
+    #[test]
+    fn test_lenient_numbers() {
+        let stack = "Error: test\n at render (/src/App.js:１２:1,024)";
+
+        // 默认严格模式下无法解析
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames[0].line_number(), 0);
+        assert_eq!(frames[0].column_number(), 0);
+
+        let mut parser = ErrorParser::new();
+        parser.set_lenient_numbers(true);
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames[0].line_number(), 12);
+        assert_eq!(frames[0].column_number(), 1024);
+        assert!(parser.parse(stack).contains("App.js:12:1024"));
+    }
+
+    #[test]
+    fn test_async_separators() {
+        let parser = ErrorParser::new();
+        let stack = "Error: boom\n    at fetchUser (/src/api.js:10:5)\n    ---------------------------------------------\n    at loadPage (/src/page.js:20:3)\n    - - - - -\n    at main (/src/index.js:1:1)";
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 3);
+        assert!(!frames[0].async_boundary());
+        assert!(frames[1].async_boundary());
+        assert!(!frames[2].async_boundary());
+
+        // 只接受确切的分隔行写法，消息与日志中相似的行不算
+        assert_eq!(ErrorParser::async_separator_cause("--- Promise.then ---"), Some("Promise.then"));
+        assert_eq!(ErrorParser::async_separator_cause("  setTimeout (async)"), Some("setTimeout"));
+        assert_eq!(ErrorParser::async_separator_cause("await in loadData (async)"), Some("await in loadData"));
+        for line in ["Error: a - b", "--", "---", "- - - - -", "-- retry failed --", "--- see below ---", "setTimeout(async)", "--- a..b ---", "retry in 5s (async)"] {
+            assert!(ErrorParser::async_separator_cause(line).is_none(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_async_segments() {
+        let parser = ErrorParser::new();
+        let stack = "Error: boom\n\
+            at fetchUser (/src/api.js:10:5)\n\
+            at parse (/src/api.js:4:1)\n\
+            --- Promise.then ---\n\
+            at loadPage (/src/page.js:20:3)\n\
+            setTimeout (async)\n\
+            at main (/src/index.js:1:1)\n\
+            at async boot (/src/index.js:9:1)\n\
+            at async start (/src/index.js:12:1)";
+        let segments = parser.parse_async_segments(stack);
+        let causes: Vec<Option<&str>> = segments.iter().map(|segment| segment.cause.as_deref()).collect();
+        assert_eq!(causes, vec![None, Some("Promise.then"), Some("setTimeout"), Some("async")]);
+        // 连续的 `at async` 帧属于同一段
+        assert_eq!(segments.iter().map(|segment| segment.frames.len()).collect::<Vec<_>>(), vec![2, 1, 1, 2]);
+        assert_eq!(segments[1].frames[0].function_name, "loadPage");
+
+        // Gecko的起因前缀
+        let segments = parser.parse_async_segments("a@/src/a.js:1:1\npromise callback*b@/src/b.js:2:2\nc@/src/c.js:3:3");
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[1].cause.as_deref(), segments[1].frames.len()), (Some("promise callback"), 2));
+        assert_eq!(segments[1].frames[0].function_name, "b");
+
+        // 没有异步边界时只有一段
+        assert_eq!(parser.parse_async_segments("Error: x\n at a (/a.js:1:1)").len(), 1);
+        assert!(parser.parse_async_segments("Error: x").is_empty());
+    }
+
+    #[test]
+    fn test_firefox_async_and_resource_frames() {
+        let parser = ErrorParser::new();
+        let stack = "handleClick@http://localhost:3000/src/App.js:12:9\nasync*onSubmit@http://localhost:3000/src/Form.js:30:5\nPromiseReactionJob@resource://gre/modules/Promise.sys.mjs:80:17";
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 3);
+
+        assert!(!frames[0].is_async());
+        assert_eq!(frames[1].function_name(), "onSubmit");
+        assert!(frames[1].is_async());
+        assert_eq!(frames[1].line_number(), 30);
+
+        assert!(frames[2].is_browser_internal());
+        assert!(!frames[2].is_in_app());
+        assert!(!frames[0].is_browser_internal());
+        assert!(parser.parse(stack).contains("|onSubmit"));
+    }
+
+    #[test]
+    fn test_safari_anonymous_and_code_frames() {
+        let parser = ErrorParser::new();
+        let stack = "TypeError: undefined is not an object (evaluating 'cart.items.length')\n\
+            render@http://localhost:8080/static/js/main.js:2:1050\n\
+            @http://localhost:8080/static/js/main.js:2:3000\n\
+            module code@http://localhost:8080/static/js/main.js:5:10\n\
+            global code@http://localhost:8080/index.html:20:7\n\
+            forEach@[native code]";
+        let frames = parser.parse_frames(stack);
+        let names: Vec<String> = frames.iter().map(StackFrame::function_name).collect();
+        assert_eq!(names, vec!["render", "<anonymous>", "module_code", "global_code", "forEach"]);
+        assert!(frames[4].is_native());
+        assert_eq!(frames[4].file_name(), "[native code]");
+        assert!(!frames[0].is_native());
+        assert_eq!(frames[1].file_name(), "http://localhost:8080/static/js/main.js");
+        assert_eq!(frames[1].column_number(), 3000);
+        assert_eq!(frames[3].file_name(), "http://localhost:8080/index.html");
+    }
+
+    #[test]
+    fn test_safari_17_frames() {
+        // Safari 17 async函数中抛出的错误
+        let stack = "TypeError: undefined is not an object (evaluating 'data.user.name')\n\
+            renderProfile@https://app.example.com/assets/index-5f2a1c.js:42:31\n\
+            @https://app.example.com/assets/index-5f2a1c.js:58:20\n\
+            asyncFunctionResume@[native code]\n\
+            promiseReactionJobWithoutPromise@[native code]\n\
+            promiseReactionJob@[native code]\n\
+            module code";
+        let mut parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        let names: Vec<&str> = frames.iter().map(|f| f.function_name_str()).collect();
+        assert_eq!(names, vec!["renderProfile", "<anonymous>", "asyncFunctionResume", "promiseReactionJobWithoutPromise", "promiseReactionJob", "module_code"]);
+        assert!(frames[2..5].iter().all(|f| f.is_native()));
+        assert_eq!((frames[5].file_name_str(), frames[5].line_number()), ("<anonymous>", 0));
+
+        // 调度帧与微任务帧一起合并
+        parser.set_collapse_microtasks(true);
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[2].get_annotation("collapsed_frames").as_deref(), Some("3"));
+
+        // 已知是旧版本时不识别没有 `@` 的整行帧
+        parser.set_safari_version(16);
+        assert_eq!(parser.parse_frames(stack).len(), 3);
+        parser.set_safari_version(17);
+        assert_eq!(parser.parse_frames(stack).len(), 4);
+
+        // Safari 17的wasm与全局代码帧
+        let frames = ErrorParser::new().parse_frames("RuntimeError: Out of bounds memory access\n<?>.wasm-function[12]@[wasm code]\nwasm-stub@[wasm code]\nglobal code@https://app.example.com/app.js:1:20");
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].is_wasm() && frames[1].is_wasm());
+        assert_eq!(frames[2].function_name_str(), "global_code");
+    }
+
+    #[test]
+    fn test_parse_options_from_json() {
+        let (options, errors) = ParseOptions::from_json(r#"{"dialect": "chakra", "collapseMicrotasks": true, "line_base": 0}"#);
+        // 无效的方言与全局项不影响其他项
+        assert_eq!((options.dialect, options.collapse_microtasks), (Dialect::Auto, true));
+        assert_eq!(errors.len(), 2);
+        let (options, errors) = ParseOptions::from_json(r#"{"dialect": "JSC", "redact": true}"#);
+        assert_eq!((options.dialect, options.redact, options.collapse_microtasks), (Dialect::Jsc, true, false));
+        assert!(errors.is_empty());
+        assert_eq!(ParseOptions::from_json("[]").0, ParseOptions::default());
+    }
+
+    #[test]
+    fn test_raw_lines() {
+        let stack = "Error: x\ncontinued message\n at render (https://cdn.test/app.js:10:15)\n    [truncated 3 frames]\n at load (https://cdn.test/app.js:40:2)";
+        let mut parser = ErrorParser::new();
+        assert_eq!(parser.parse_frames(stack).len(), 2);
+
+        // 首帧之前的消息行仍跳过，之后的行保留为原始帧，不影响指纹
+        parser.set_raw_lines(true);
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 3);
+        assert!(frames[1].raw() && !frames[0].raw() && !frames[1].is_in_app());
+        assert_eq!((frames[1].function_name(), frames[1].line_number(), frames[1].column_number()), ("[truncated 3 frames]".to_string(), 0, 0));
+        assert_eq!(fingerprint::fingerprint_frames("Error: x", &frames, crate::hash::HashAlgorithm::XxHash64), fingerprint::fingerprint(stack));
+        assert!(ParseOptions::from_json(r#"{"rawLines": true}"#).0.raw_lines);
+
+        // 未知位置按两种起点导出：从1开始为0，从0开始为未知值，文本中写作 `?`
+        let exported = ExportedStackFrame::from(frames[1].clone());
+        assert_eq!((exported.line_number, exported.column_number), (0, 0));
+        assert_eq!(parser.parse(stack).lines().nth(1), Some(":0:0|[truncated 3 frames]"));
+        parser_options::set_options(parser_options::ParserOptions::new(0, 0));
+        let exported = ExportedStackFrame::from(frames[1].clone());
+        assert_eq!((exported.line_number, exported.column_number), (UNKNOWN_POSITION, UNKNOWN_POSITION));
+        assert_eq!(parser.parse(stack).lines().nth(1), Some(":?:?|[truncated 3 frames]"));
+        parser_options::set_options(parser_options::ParserOptions::default());
+    }
+
+    #[test]
+    fn test_dialect_hint() {
+        let v8 = "TypeError: x\n at render (https://cdn.test/app.js:10:15)\n at async load (https://cdn.test/app.js:40:2)";
+        let gecko = "render@https://cdn.test/app.js:10:15\nasync*load@https://cdn.test/app.js:40:2";
+        let jsc = "render@https://cdn.test/app.js:10:15\nmap@[native code]\nmodule code@https://cdn.test/app.js:1:1";
+
+        let mut parser = ErrorParser::new();
+        assert_eq!(parser.detect_dialect(v8), Dialect::V8);
+        assert_eq!(parser.detect_dialect(gecko), Dialect::SpiderMonkey);
+        assert_eq!(parser.detect_dialect(jsc), Dialect::Jsc);
+        assert_eq!(parser.detect_dialect("render@https://cdn.test/app.js:10:15"), Dialect::SpiderMonkey);
+        assert_eq!(parser.detect_dialect("Error: x"), Dialect::Auto);
+
+        // 给定方言时结果与逐行探测一致，其他引擎的写法不再识别
+        for (dialect, stack) in [("v8", v8), ("spidermonkey", gecko), ("jsc", jsc)] {
+            let expected = parser.parse_frames(stack);
+            assert!(parser.set_dialect(dialect));
+            assert_eq!(parser.detect_dialect(stack).as_str(), dialect);
+            let frames = parser.parse_frames(stack);
+            assert_eq!(frames.len(), expected.len());
+            assert!(frames.iter().zip(&expected).all(|(a, b)| a.function_name_str() == b.function_name_str() && a.is_async() == b.is_async()));
+            assert!(parser.set_dialect("auto"));
+        }
+        assert!(parser.set_dialect("V8"));
+        assert!(parser.parse_frames(gecko).is_empty());
+        assert!(!parser.set_dialect("chakra"));
+        assert_eq!(parser.dialect(), Dialect::V8);
+    }
+
+    #[test]
+    fn test_vue_warn_trace() {
+        let warning = "[Vue warn]: Unhandled error during execution of render function \n\
+            at <CartItem item={id: 1, name: \"x\"} onRemove=fn<onRemove> key=1 >\n\
+            at <CartList items=[{...}] > ... (2 recursive calls)\n\
+            at <App>";
+        let frames = ErrorParser::new().parse_frames(warning);
+        let names: Vec<&str> = frames.iter().map(|f| f.function_name_str()).collect();
+        assert_eq!(names, vec!["CartItem", "CartList", "App"]);
+        assert!(frames.iter().all(|f| f.file_name_str() == VUE_COMPONENT_LOCATION && f.framework_str() == Some("Vue") && f.is_in_app()));
+        assert_eq!(frames[1].get_annotation("recursive_calls").as_deref(), Some("2"));
+        assert_eq!(frames[0].get_annotation("component").as_deref(), Some("vue"));
+
+        // Vue 2的组件栈带源文件
+        let warning = "[Vue warn]: Error in render: \"TypeError: x\"\n\nfound in\n\n\
+            ---> <CartItem> at src/components/CartItem.vue\n\
+            <App> at src/App.vue\n\
+            <Root>";
+        let frames = ErrorParser::new().parse_frames(warning);
+        let names: Vec<(&str, &str)> = frames.iter().map(|f| (f.function_name_str(), f.file_name_str())).collect();
+        assert_eq!(names, vec![("CartItem", "src/components/CartItem.vue"), ("App", "src/App.vue"), ("Root", VUE_COMPONENT_LOCATION)]);
+        assert_eq!(frames[0].framework_str(), Some("Vue"));
+
+        // V8的 `<anonymous>` 位置与段落外的裸 `<...>` 行不是组件
+        let frames = ErrorParser::new().parse_frames("Error: x\n    at <anonymous>\n<Root>\n<kebab-name>");
+        assert!(frames.iter().all(|f| f.framework_str() != Some("Vue") && f.file_name_str() != VUE_COMPONENT_LOCATION), "{:?}", frames);
+        let frames = ErrorParser::new().parse_frames("found in\n\n---> <cart-item>\n<lower>\n<Root>");
+        let names: Vec<&str> = frames.iter().map(|f| f.function_name_str()).collect();
+        assert_eq!(names, vec!["cart-item", "Root"]);
+    }
+
+    #[test]
+    fn test_fold_zone_frames() {
+        let stack = "TypeError: x\n\
+            at SearchComponent.onSubmit (https://app.test/main.js:120:15)\n\
+            at _ZoneDelegate.invokeTask (https://app.test/polyfills.js:3:1)\n\
+            at Object.onInvokeTask (https://app.test/vendor.js:50:2)\n\
+            at _ZoneDelegate.invokeTask (https://app.test/polyfills.js:3:9)\n\
+            at ZoneTask.invokeTask [as invoke] (https://app.test/polyfills.js:4:1)\n\
+            at HTMLButtonElement.handle (https://app.test/main.js:10:1)\n\
+            at drainMicroTaskQueue (https://app.test/node_modules/zone.js/fesm2015/zone.js:70:3)";
+        let mut parser = ErrorParser::new();
+        assert_eq!(parser.parse_frames(stack).len(), 7);
+
+        parser.set_fold_zone_frames(true);
+        let frames = parser.parse_frames(stack);
+        let names: Vec<&str> = frames.iter().map(|f| f.function_name_str()).collect();
+        assert_eq!(names, vec!["SearchComponent.onSubmit", "zone.js (4 frames)", "HTMLButtonElement.handle", "drainMicroTaskQueue"]);
+        assert_eq!(frames[1].get_annotation("collapsed_frames").as_deref(), Some("4"));
+
+        // 文件名只是包含zone.js的业务代码不折叠
+        let app = "Error: x\n at toLocal (https://app.test/src/utils/timezone.js:1:1)\n at format (https://app.test/src/utils/timezone.js:9:1)";
+        assert_eq!(parser.parse_frames(app).len(), 2);
+        assert!(!frames[1].is_in_app());
+        assert_eq!(parser.parse_chrome_fast(stack).len(), 4);
+    }
+
+    #[test]
+    fn test_collapse_microtasks() {
+        let stack = "Error: timeout\n\
+            at fetchUser (/src/api.js:10:5)\n\
+            at process.processTicksAndRejections (node:internal/process/task_queues:95:5)\n\
+            at async Promise.all (index 0)\n\
+            at async runTask (node:internal/async_local_storage:12:3)\n\
+            at process.processTicksAndRejections (node:internal/process/task_queues:95:5)\n\
+            at async loadPage (/src/page.js:20:3)";
+
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 6);
+        assert!(frames[5].is_async());
+        assert_eq!(frames[5].function_name(), "loadPage");
+
+        let mut parser = ErrorParser::new();
+        parser.set_collapse_microtasks(true);
+        let frames = parser.parse_frames(stack);
+        let names: Vec<String> = frames.iter().map(StackFrame::function_name).collect();
+        assert_eq!(names, vec!["fetchUser", "process.processTicksAndRejections", "loadPage"]);
+        assert_eq!(frames[1].get_annotation("collapsed_frames"), Some("4".to_string()));
+        assert_eq!(frames[0].get_annotation("collapsed_frames"), None);
+    }
+
+    #[test]
+    fn test_node_frames() {
+        // 与 benches/parser_benchmark.rs 中的栈一致
+        let stack = "Error: Something went wrong\n\
+            at Object.method (/path/to/file.js:10:15)\n\
+            at processTicksAndRejections (internal/process/task_queues.js:95:5)\n\
+            at async Promise.all (index 0)\n\
+            at async HTMLFormElement.submitForm (app.js:20:30)";
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 4);
+        assert!(frames[1].is_node_internal());
+        assert_eq!(frames[1].file_name(), "internal/process/task_queues.js");
+        assert_eq!(frames[2].function_name(), "Promise.all");
+        assert!(frames[2].is_async());
+        assert_eq!(frames[2].promise_index(), Some(0));
+        assert!(frames[2].annotations().is_empty());
+        assert!(frames[3].is_async());
+        assert_eq!(frames[3].promise_index(), None);
+        assert_eq!((frames[3].function_name().as_str(), frames[3].line_number()), ("HTMLFormElement.submitForm", 20));
+
+        let stack = "TypeError: Cannot read properties of undefined (reading 'id')\n\
+            at Socket.emit [as emit] (node:events:513:28)\n\
+            at new Promise (<anonymous>)\n\
+            at Array.forEach (native)\n\
+            at file:///app/src/index.mjs:3:9\n\
+            at node:internal/main/run_main_module:22:47";
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].file_name(), "node:events");
+        assert_eq!(frames[0].get_annotation("alias"), Some("emit".to_string()));
+        assert!(frames[0].is_node_internal() && !frames[0].is_in_app());
+        assert_eq!((frames[1].function_name().as_str(), frames[1].file_name().as_str()), ("new Promise", "<anonymous>"));
+        assert!(frames[1].is_native() && frames[2].is_native());
+        assert!(!frames[0].is_native());
+        assert_eq!(frames[2].file_name(), "native");
+        assert!(!frames[2].is_in_app());
+        assert_eq!((frames[3].file_name().as_str(), frames[3].line_number()), ("file:///app/src/index.mjs", 3));
+        assert!(frames[3].is_in_app());
+        assert_eq!(frames[4].function_name(), "<anonymous>");
+        assert_eq!(frames[4].file_name(), "node:internal/main/run_main_module");
+    }
+
+    #[test]
+    fn test_eval_frames() {
+        let parser = ErrorParser::new();
+        let stack = "Error: boom\n\
+            at foo (eval at bar (http://x/app.js:1:1), <anonymous>:2:10)\n\
+            at eval (eval at run (eval at load (http://x/app.js:3:4)), <anonymous>:1:5)\n\
+            at bar (http://x/app.js:1:1)";
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].function_name(), "foo");
+        assert_eq!(frames[0].file_name(), "<anonymous>");
+        assert_eq!((frames[0].line_number(), frames[0].column_number()), (2, 10));
+        assert!(frames[0].is_eval());
+        assert_eq!(frames[0].eval_origin().as_deref(), Some("http://x/app.js:1:1"));
+        assert_eq!(frames[1].eval_origin().as_deref(), Some("http://x/app.js:3:4"));
+        assert!(!frames[2].is_eval());
+
+        let frames = parser.parse_frames("foo@http://x/app.js line 20 > eval:2:10\n@http://x/app.js line 3 > Function:1:2");
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].is_eval());
+        assert_eq!(frames[0].eval_origin().as_deref(), Some("http://x/app.js:20"));
+        assert_eq!((frames[0].line_number(), frames[0].column_number()), (2, 10));
+        assert_eq!(frames[1].eval_origin().as_deref(), Some("http://x/app.js:3"));
+    }
+
+    #[test]
+    fn test_jsc_bun_frames() {
+        let stack = "Error: boom\n\
+            foo@/app/src/index.ts:10:20\n\
+            module code@/app/src/index.ts:25:1\n\
+            global code@/app/src/chat app/entry.ts:1:1\n\
+            eval code@\n\
+            @/app/node_modules/@scope/pkg/index.js:3:4\n\
+            processTicksAndRejections@native:7:39\n\
+            handler@/app/src/server.ts:12";
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 7);
+        assert_eq!(frames[0].function_name(), "foo");
+        assert_eq!((frames[0].line_number(), frames[0].column_number()), (10, 20));
+        assert_eq!(frames[1].function_name(), "module_code");
+        // 路径中的 "at " 不会被当成V8格式
+        assert_eq!(frames[2].function_name(), "global_code");
+        assert_eq!(frames[2].file_name(), "/app/src/chat app/entry.ts");
+        assert!(frames[3].is_eval());
+        assert_eq!(frames[3].function_name(), "eval_code");
+        assert_eq!(frames[4].function_name(), "<anonymous>");
+        assert_eq!(frames[4].file_name(), "/app/node_modules/@scope/pkg/index.js");
+        assert!(frames[5].is_native() && !frames[5].is_in_app());
+        assert_eq!((frames[6].line_number(), frames[6].column_number()), (12, 0));
+
+        // 带空格的消息行不是栈帧
+        assert!(parser.parse_frames("Failed to notify user@example.com").is_empty());
+    }
+
+    #[test]
+    fn test_deno_url_first_frames() {
+        let stack = "Error: boom\n\
+            at file:///home/app/main.ts:10:5\n\
+            at async file:///home/app/server.ts:3:1\n\
+            at Server.#respond (https://deno.land/std@0.177.0/http/server.ts:221:18)\n\
+            at file:///home/app/(routes)/my page.ts:1:2\n\
+            at ext:deno_http/00_serve.js:100:5";
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].function_name(), "<anonymous>");
+        assert_eq!(frames[0].file_name(), "file:///home/app/main.ts");
+        assert_eq!((frames[0].line_number(), frames[0].column_number()), (10, 5));
+        assert!(frames[1].is_async());
+        assert_eq!(frames[2].function_name(), "Server.#respond");
+        assert_eq!(frames[3].file_name(), "file:///home/app/(routes)/my page.ts");
+        assert_eq!(frames[4].file_name(), "ext:deno_http/00_serve.js");
+        assert!(frames[0].is_in_app() && !frames[4].is_in_app());
+
+        // Windows盘符不是URL
+        assert!(!ErrorParser::starts_with_url("C:\\app\\main.js:1:2"));
+    }
+
+    #[test]
+    fn test_hermes_frames() {
+        let stack = "TypeError: undefined is not a function\n\
+            at onPress (address at index.android.bundle:1:452701)\n\
+            at anonymous (address at /data/app/index.android.bundle:1:9876)\n\
+            at tryCallTwo (address at InternalBytecode.js:1:1222)\n\
+            at call (native)\n\
+            at render (http://localhost:8081/index.bundle?platform=ios:120:33)";
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].function_name(), "onPress");
+        assert_eq!(frames[0].file_name(), "index.android.bundle");
+        assert_eq!((frames[0].line_number(), frames[0].column_number()), (1, 452701));
+        assert!(frames[0].hermes_bytecode() && frames[0].is_in_app());
+        assert_eq!(frames[1].function_name(), "<anonymous>");
+        assert_eq!(frames[1].file_name(), "/data/app/index.android.bundle");
+        assert!(frames[2].hermes_bytecode() && !frames[2].is_in_app());
+        assert!(frames[3].is_native() && !frames[3].hermes_bytecode());
+        // Metro开发服务器的帧是普通JS帧
+        assert!(!frames[4].hermes_bytecode());
+        assert_eq!(frames[4].line_number(), 120);
+    }
+
+    #[test]
+    fn test_minified_single_line_bundle() {
+        let stack = "TypeError: e is not a function\n\
+            at Object.t [as render] (https://cdn.example.com/app.min.js:1:283746)\n\
+            at https://cdn.example.com/app.min.js:1:104522\n\
+            at n (https://cdn.example.com/vendor.js:1:88)";
+
+        // 即使未开启压缩模式，也能拆出带 `[as x]` 与无函数名的帧
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].function_name(), "Object.t [as render]");
+        assert_eq!(frames[0].column_number(), 283746);
+        assert_eq!(frames[1].function_name(), "<anonymous>");
+        assert!(!frames[0].minified());
+
+        let mut parser = ErrorParser::new();
+        parser.set_minified_mode(true);
+        let frames = parser.parse_frames(stack);
+        assert!(frames[0].minified());
+        assert!(frames[1].minified());
+        assert!(!frames[2].minified());
+        assert_eq!(frames[0].fingerprint_column(), 283712);
+        assert_eq!(frames[2].fingerprint_column(), 88);
+    }
+
+    #[test]
+    fn test_minified_names() {
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames("TypeError: x\n at Zr (https://cdn.example.com/js/app.min.js?v=3:10:200)\n at render (https://cdn.example.com/js/app.js:4:2)");
+        assert!(frames[0].has_minified_name());
+        assert_eq!(frames[0].display_name().as_deref(), Some("app.min.js#fn@10:200"));
+        assert!(!frames[1].has_minified_name());
+        assert_eq!(frames[1].display_name(), None);
+
+        // source map还原出真实函数名后不再标记
+        let mut mapped = frames[0].clone();
+        mapped.set_location("checkout".to_string(), "src/cart.ts".to_string(), 3, 1);
+        let exported = mapped.into_exported();
+        assert!(!exported.has_minified_name && exported.display_name.is_none());
+        let exported = frames[0].clone().into_exported();
+        assert!(exported.has_minified_name);
+    }
+
+    #[test]
+    fn test_wasm_runtime_error_frames() {
+        let stack = "RuntimeError: unreachable\n\
+            at wasm://wasm/6d7a9f2e:wasm-function[42]:0x1a2b\n\
+            at $render (https://cdn.example.com/app.wasm:wasm-function[7]:0x88)\n\
+            at run (https://cdn.example.com/app.js:10:5)";
+
+        let parser = ErrorParser::new();
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].is_wasm());
+        assert_eq!(frames[0].function_name(), "wasm-function[42]");
+        assert_eq!(frames[0].file_name(), "wasm://wasm/6d7a9f2e");
+        assert_eq!((frames[0].wasm_function(), frames[0].wasm_offset()), (Some(42), Some(0x1a2b)));
+        // 偏移不占用列号
+        assert_eq!((frames[0].address(), frames[0].column_number()), (Some(0x1a2b), 0));
+        assert_eq!(frames[1].function_name(), "$render");
+        assert_eq!(frames[1].file_name(), "https://cdn.example.com/app.wasm");
+        assert!(!frames[2].is_wasm());
+        assert_eq!(frames[2].line_number(), 10);
+
+        // Firefox与Safari格式
+        let stack = "RuntimeError: index out of bounds\n\
+            main@https://cdn.example.com/app.wasm:wasm-function[42]:0x10\n\
+            <?>.wasm-function[42]@[wasm code]\n\
+            wasm-stub@[wasm code]";
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].function_name(), "main");
+        assert_eq!(frames[0].wasm_offset(), Some(0x10));
+        assert_eq!(frames[1].function_name(), "wasm-function[42]");
+        assert_eq!(frames[1].file_name(), "[wasm code]");
+        assert!(frames[2].is_wasm());
+        assert_eq!(frames[2].wasm_function(), None);
+    }
+
+    #[test]
+    fn test_parse_numbers_and_line_column() {
+        let parser = ErrorParser::default();
+        assert_eq!(parser.parse_numbers("at a (/v2/app.js:10:20) 99999999999"), vec![2, 10, 20]);
+        assert!(parser.parse_numbers("no digits").is_empty());
+
+        let stack = "Error: test\n at a (/src/v2/a.js:10:20)\n at b (/src/b.js:3:4)";
+        assert_eq!(parser.parse_line_column(stack), vec![10, 20, 3, 4]);
+        assert_eq!(parser.parse_line_column_indexed(&format!("{}\n at Array.map (<anonymous>)", stack)), vec![10, 20, 0, 3, 4, 1]);
+        assert_eq!(parser.parse_simd(stack).len(), 2);
+        assert_eq!(parser.parse(stack), "/src/v2/a.js:10:20|a\n/src/b.js:3:4|b\n");
+    }
+
+    #[test]
+    fn test_reparse_processed_input() {
+        let parser = ErrorParser::new();
+        let stack = "Error: test\n at render (/src/App.js:10:20)\n at https://cdn.test/x.js:1:5";
+
+        // 文本输出再次解析保持不变
+        let formatted = parser.parse(stack);
+        assert_eq!(parser.parse(&formatted), formatted);
+
+        // JSON输出再次解析得到相同的栈帧，注解得以保留
+        let json = r#"[{"function_name":"render","file_name":"/src/App.js","line_number":10,"column_number":20,"annotations":{"team":"web"}}]"#;
+        let frames = parser.parse_frames(json);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].get_annotation("team"), Some("web".to_string()));
+
+        // 格式不完整时按普通栈解析
+        assert!(parser.parse_frames("[1, 2]").is_empty());
+        assert!(parser.parse_frames("/a.js:1:x|f").is_empty());
+
+        // 缺少文件、位置为负数或超出u32的JSON栈帧返回错误，不会填充默认值
+        for json in [
+            r#"[{"function_name":"render","line_number":10,"column_number":20}]"#,
+            r#"[{"function_name":"render","file_name":"/a.js","line_number":-1,"column_number":20}]"#,
+            r#"[{"function_name":"render","file_name":"/a.js","line_number":10,"column_number":4294967296}]"#,
+        ] {
+            assert!(matches!(parser.try_parse_frames(json), Err(ParseError::InvalidFrame { index: 0, .. })), "{}", json);
+            assert!(parser.parse_frames(json).is_empty());
+        }
+
+        // 带原始行帧的JSON输出原样返回
+        let mut raw_parser = ErrorParser::new();
+        raw_parser.set_raw_lines(true);
+        let frames = raw_parser.parse_frames("Error: x\n at render (/src/App.js:10:20)\n    [truncated 3 frames]");
+        let json = serde_json::to_string(&frames.iter().cloned().map(ExportedStackFrame::from).collect::<Vec<_>>()).unwrap();
+        let reparsed = parser.try_parse_frames(&json).unwrap();
+        assert_eq!(reparsed.len(), 2);
+        assert!(reparsed[1].raw() && reparsed[1].file_name().is_empty());
+        assert_eq!(reparsed[1].function_name(), "[truncated 3 frames]");
+    }
+
+    #[test]
+    fn test_annotations() {
+        let mut frame = StackFrame::new("render".to_string(), "/src/App.js".to_string(), 1, 2);
+        assert!(frame.annotations().is_empty());
+
+        frame.set_annotation("team".to_string(), "web".to_string());
+        frame.extend_annotations(vec![("package".to_string(), "app".to_string())]);
+        assert_eq!(frame.get_annotation("team"), Some("web".to_string()));
+        assert_eq!(frame.annotations().keys().collect::<Vec<_>>(), vec!["package", "team"]);
+
+        assert_eq!(frame.remove_annotation("team"), Some("web".to_string()));
+        assert_eq!(frame.get_annotation("team"), None);
+    }
+
+    #[test]
+    fn test_try_parse_frames() {
+        let parser = ErrorParser::new();
+        assert_eq!(parser.try_parse_frames(" \n").unwrap_err(), ParseError::EmptyInput);
+        assert_eq!(parser.try_parse_frames("hello world").unwrap_err().code(), "unrecognized_format");
+        assert!(matches!(parser.try_parse_frames(&"x".repeat(MAX_INPUT_BYTES + 1)), Err(ParseError::InputTooLarge { .. })));
+
+        // 有错误首行但没有栈帧不算失败
+        assert_eq!(parser.try_parse_frames("RangeError: too deep").map(|frames| frames.len()), Ok(0));
+        assert_eq!(parser.try_parse_frames("Error: x\n at f (/a.js:1:2)").map(|frames| frames.len()), Ok(1));
+    }
+
+    #[test]
+    fn test_parse_pretokenized() {
+        let parser = ErrorParser::new();
+        let text = "TypeError: x\n    at f (/src/a.js:1:2)\r\n----------\ng@https://cdn.test/b.js:3:4\nnoise";
+        let mut offsets = vec![0u32];
+        offsets.extend(text.match_indices('\n').map(|(i, _)| i as u32 + 1));
+        let formats = [1, 3, 2, 4];
+
+        let frames = parser.parse_pretokenized(text, &offsets, &formats);
+        let expected = parser.parse_frames(text);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(ErrorParser::format_frames(&frames), ErrorParser::format_frames(&expected));
+        assert!(frames[1].async_boundary());
+
+        // 格式字节错误时该行不产生栈帧，越界的偏移被跳过
+        assert!(parser.parse_pretokenized(text, &offsets[1..2], &[5]).is_empty());
+        assert_eq!(parser.parse_pretokenized(text, &[offsets[1], offsets[2], 9999], &[]).len(), 1);
+
+        // 偏移为JS字符串的UTF-16下标，消息中的中文与emoji不影响之后各行
+        let text = "Error: 加载失败 😀\n    at f (/src/a.js:1:2)\n    at wasm://wasm/00a1b2c3:wasm-function[7]:0x1f\n[frame] g <- /src/b.js#L3C4";
+        let utf16: Vec<u16> = text.encode_utf16().collect();
+        let mut offsets = vec![0u32];
+        offsets.extend(utf16.iter().enumerate().filter(|(_, &unit)| unit == u16::from(b'\n')).map(|(i, _)| i as u32 + 1));
+        let mut parser = ErrorParser::new();
+        assert!(parser.register_pattern("acme", r"^\[frame\] (\S+) <- ([^#]+)#L(\d+)C(\d+)$", r#"{"function": 1, "file": 2, "line": 3, "column": 4}"#));
+        // V8格式的行中同样识别wasm帧与自定义格式
+        let frames = parser.parse_pretokenized(text, &offsets, &[1, 3, 3, 3]);
+        assert_eq!(frames.len(), 3);
+        assert_eq!(ErrorParser::format_frames(&frames), ErrorParser::format_frames(&parser.parse_frames(text)));
+        assert!(frames[1].is_wasm());
+        assert_eq!(frames[2].file_name_str(), "/src/b.js");
+        // 落在代理对中间的下标被跳过
+        assert_eq!(utf16_to_byte_offsets("😀a", &[0, 1, 2, 3, 4]), vec![Some(0), None, Some(4), Some(5), None]);
+    }
+
+    #[test]
+    fn test_parse_chrome_fast() {
+        let stack = "TypeError: x\n    at Socket.emit [as emit] (node:events:513:28)\n    at https://cdn.test/app.min.js:1:2048\n    at async Promise.all (index 1)\n    at async load (https://cdn.test/app.js:20:30)\n    at Array.map (<anonymous>)\n    at eval (eval at run (https://cdn.test/app.js:5:1), <anonymous>:1:2)";
+        let parser = ErrorParser::new();
+        let summary = |frames: Vec<StackFrame>| frames.into_iter()
+            .map(|f| (f.function_name(), f.file_name(), f.line_number(), f.column_number(), f.is_async(), f.origin(), f.get_annotation("alias")))
+            .collect::<Vec<_>>();
+        assert_eq!(summary(parser.parse_chrome_fast(stack)), summary(parser.parse_frames(stack)));
+        assert_eq!(parser.parse_chrome_fast(stack).len(), 6);
+
+        // 非Chrome格式的行被跳过
+        assert!(parser.parse_chrome_fast("foo@https://cdn.test/app.js:1:2").is_empty());
+
+        // 异步分隔行与完整流程一样标记到下一帧；URL中的 `wasm` 不会走完整流程
+        let separated = "Error: x\n    at a (https://cdn.test/wasm-loader.js:1:2)\n    --- setTimeout ---\n    at b (https://cdn.test/app.js:3:4)";
+        let fast = parser.parse_chrome_fast(separated);
+        assert_eq!(fast.len(), 2);
+        assert!(!fast[0].async_boundary() && fast[1].async_boundary());
+        assert_eq!(fast[1].get_annotation("async_cause"), parser.parse_frames(separated)[1].get_annotation("async_cause"));
+        assert_eq!(fast[0].file_name(), "https://cdn.test/wasm-loader.js");
+    }
+
+    #[test]
+    fn test_native_crash_frames() {
+        let parser = ErrorParser::new();
+        let stack = "Error: boom\n    at crash (/src/app.js:3:9)\n 1: 0x55d0b2 node::Abort() [/usr/bin/node]\n 2: 0xa1b2c3 _ZN4node11EnvironmentD2Ev [node]\n 3: 0x7f00 \n    at main (/src/main.js:1:1)";
+        let frames = parser.parse_frames(stack);
+        let names: Vec<&str> = frames.iter().map(|f| f.function_name_str()).collect();
+        assert_eq!(names, vec!["crash", "node::Abort()", "node::Environment::~Environment", "<anonymous>", "main"]);
+
+        assert_eq!((frames[1].address(), frames[1].file_name_str(), frames[1].origin()), (Some(0x55d0b2), "/usr/bin/node", FrameOrigin::NativeBinary));
+        assert_eq!(frames[1].get_annotation("mangled"), None);
+        assert_eq!(frames[2].get_annotation("mangled").as_deref(), Some("_ZN4node11EnvironmentD2Ev"));
+        assert_eq!((frames[3].file_name_str(), frames[3].address()), (NATIVE_BINARY_LOCATION, Some(0x7f00)));
+        assert!(!frames[1].is_in_app());
+        assert_eq!((frames[0].address(), frames[0].origin()), (None, FrameOrigin::App));
+
+        // 不是地址的行不按原生帧解析
+        assert!(parser.parse_native_line("1: abc node::Abort()").is_none());
+        assert!(parser.parse_native_line("at f (/src/a.js:1:1)").is_none());
+    }
+
+    #[test]
+    fn test_frame_origin() {
+        let stack = "Error: x\n at render (https://cdn.test/app/main.js:1:1)\n at r (https://cdn.test/node_modules/react/index.js:2:2)\n at eval (eval at run (https://cdn.test/app/main.js:3:3), <anonymous>:1:1)\n at Array.map (<anonymous>)\n at ext (chrome-extension://abc/content.js:4:4)\n at lib (https://cdn.test/vendor/lib.js:5:5)";
+        let mut parser = ErrorParser::new();
+        let origins = |parser: &ErrorParser| parser.parse_frames(stack).iter().map(|frame| frame.origin()).collect::<Vec<_>>();
+        assert_eq!(origins(&parser), vec![
+            FrameOrigin::App, FrameOrigin::NodeModules, FrameOrigin::Eval,
+            FrameOrigin::Native, FrameOrigin::BrowserInternal, FrameOrigin::App,
+        ]);
+
+        // 配置应用路径前缀后，前缀外的文件视为第三方
+        assert!(parser.set_app_prefixes(r#"["/app/"]"#));
+        assert_eq!(origins(&parser)[5], FrameOrigin::NodeModules);
+        assert_eq!(origins(&parser)[0], FrameOrigin::App);
+        assert!(!parser.set_app_prefixes("{}"));
+    }
+
+    #[test]
+    fn test_detect_framework() {
+        let parser = ErrorParser::new();
+        assert_eq!(parser.detect_framework("https://cdn.test/node_modules/react-dom/cjs/a.js").as_deref(), Some("React"));
+        assert_eq!(parser.detect_framework("/app/node_modules/@sveltejs/kit/src/runtime.js").as_deref(), Some("Svelte"));
+        assert_eq!(parser.detect_framework("/src/App.js"), None);
+        // 只匹配完整的包名路径段，同前缀的其他包与部署目录不算
+        assert_eq!(parser.detect_framework("/app/node_modules/react-router/dist/index.js"), None);
+        assert_eq!(parser.detect_framework("/app/node_modules/vue-i18n/dist/vue-i18n.js"), None);
+        assert_eq!(parser.detect_framework("/app/node_modules/jquery-ui/ui/widget.js"), None);
+        assert_eq!(parser.detect_framework("https://shop.test/_nuxt/entry.4f2a.js"), None);
+
+        let error = parser.parse_error("TypeError: x\n at render (https://shop.test/_next/static/chunks/app.js:1:1)\n at $ (/node_modules/jquery/dist/jquery.js:2:2)\n at r (/node_modules/next/dist/client/index.js:3:3)\n at main (/src/main.js:4:4)");
+        assert_eq!(error.detected_frameworks, vec!["jQuery", "Next.js"]);
+        assert_eq!(error.frames[0].framework, None);
+        assert_eq!(error.frames[1].framework.as_deref(), Some("jQuery"));
+        assert_eq!(error.frames[3].framework, None);
+    }
+
+    #[test]
+    fn test_custom_framework_map() {
+        let mut parser = ErrorParser::new();
+        parser.add_framework_mapping("packages/design-system/", "DesignSystem");
+        assert_eq!(parser.detect_framework("/repo/packages/design-system/button.js").as_deref(), Some("DesignSystem"));
+
+        // 全局映射对之后新建的解析器生效，可覆盖内置映射
+        set_framework_map(HashMap::from([
+            ("node_modules/react/".to_string(), "ReactLegacy".to_string()),
+            ("@acme/ui/".to_string(), "AcmeUI".to_string()),
+        ]));
+        let parser = ErrorParser::new();
+        assert_eq!(parser.detect_framework("/node_modules/@acme/ui/index.js").as_deref(), Some("AcmeUI"));
+        assert_eq!(parser.detect_framework("/node_modules/react/index.js").as_deref(), Some("ReactLegacy"));
+        set_framework_map(HashMap::new());
+        assert_eq!(ErrorParser::new().detect_framework("/node_modules/react/index.js").as_deref(), Some("React"));
+    }
+
+    #[test]
+    fn test_register_pattern() {
+        let mut parser = ErrorParser::new();
+        let stack = "AppError: boom\n[frame] checkout <- /src/cart.js#L12C5\n at render (/src/App.js:1:2)";
+        assert_eq!(parser.parse_frames(stack).len(), 1);
+
+        assert!(parser.register_pattern(
+            "acme",
+            r"^\[frame\] (\S+) <- (?P<file>[^#]+)#L(\d+)C(\d+)$",
+            r#"{"function": 1, "file": "file", "line": 3, "column": 4}"#,
+        ));
+        let frames = parser.parse_frames(stack);
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[0].function_name().as_str(), frames[0].file_name().as_str()), ("checkout", "/src/cart.js"));
+        assert_eq!((frames[0].line_number(), frames[0].column_number()), (12, 5));
+        assert_eq!(frames[0].get_annotation("pattern").as_deref(), Some("acme"));
+        assert_eq!(frames[1].get_annotation("pattern"), None);
+
+        assert!(!parser.register_pattern("bad", "(", r#"{"file": 1}"#));
+        assert!(!parser.register_pattern("bad", ".*", r#"{"line": 1}"#));
+    }
+
+    #[test]
+    fn test_into_exported() {
+        let parser = ErrorParser::new();
+        let frame = parser.parse_frames("Error\n at bound handler$2 (https://cdn.test/node_modules/react/index.js:3:4)").remove(0);
+        assert_eq!((frame.function_name_str(), frame.framework_str()), ("bound handler$2", Some("React")));
+
+        let exported = frame.into_exported();
+        assert_eq!(exported.normalized_function_name.as_deref(), Some("handler"));
+        assert_eq!((exported.file_name.as_str(), exported.line_number, exported.column_number), ("https://cdn.test/node_modules/react/index.js", 3, 4));
+        assert_eq!((exported.origin, exported.framework.as_deref()), (FrameOrigin::NodeModules, Some("React")));
+    }
+
+    #[test]
+    fn test_zero_based_output() {
+        let parser = ErrorParser::new();
+        let stack = "Error: x\n at render (/src/App.js:10:5)";
+        parser_options::set_options(parser_options::ParserOptions::new(0, 0));
+        let exported = ExportedStackFrame::from(parser.parse_frames(stack).remove(0));
+        assert_eq!((exported.line_number, exported.column_number), (9, 4));
+
+        // 导出的结果重新解析后内部位置不变
+        let formatted = parser.parse(stack);
+        assert_eq!(formatted.trim(), "/src/App.js:9:4|render");
+        let reparsed = parser.parse_frames(&formatted);
+        assert_eq!((reparsed[0].line_number(), reparsed[0].column_number()), (10, 5));
+        assert_eq!(StackFrame::from(exported).line_number(), 10);
+
+        // 没有位置的原生帧往返后仍为未知，不会变成第1行
+        let json = serde_json::to_string(&vec![ExportedStackFrame::from(parser.parse_frames("Error: x\n at Array.map (native)").remove(0))]).unwrap();
+        let reparsed = parser.parse_frames(&json);
+        assert_eq!((reparsed[0].line_number(), reparsed[0].column_number()), (0, 0));
+
+        // JS getter、行列号数组与文本输出同样按起点换算，文本中的未知位置写作 `?`
+        let frame = parser.parse_frames(stack).remove(0);
+        assert_eq!((frame.exported_line_number(), frame.exported_column_number()), (9, 4));
+        assert_eq!(parser.parse_line_column(stack), vec![9, 4]);
+        let native = parser.parse("Error: x\n at Array.map (native)");
+        assert!(native.contains(":?:?|") && !native.contains(&u32::MAX.to_string()), "{}", native);
+        assert_eq!(parser.parse_frames(&native)[0].line_number(), 0);
+        parser_options::set_options(parser_options::ParserOptions::default());
+    }
 }
\ No newline at end of file
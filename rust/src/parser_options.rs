@@ -0,0 +1,182 @@
+use wasm_bindgen::prelude::*;
+use std::cell::Cell;
+
+/// 起点为0时未知行列号的导出值
+pub const UNKNOWN_POSITION: u32 = u32::MAX;
+
+/// 弱指纹的计算方式，后端按弱指纹把强指纹不同的分组合并为上一级
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeakStrategy {
+    /// 错误类型加栈顶若干业务代码帧（函数名与文件，不含行列号），没有业务代码帧时使用栈顶帧
+    InAppFrames = 0,
+    /// 错误类型加栈顶若干帧，不区分来源
+    TopFrames = 1,
+    /// 错误类型加消息模板，不使用栈帧
+    MessageTemplate = 2,
+}
+
+impl WeakStrategy {
+    /// 按名称解析，不区分大小写
+    pub fn from_name(name: &str) -> Option<WeakStrategy> {
+        match name.to_ascii_lowercase().as_str() {
+            "in_app_frames" => Some(WeakStrategy::InAppFrames),
+            "top_frames" => Some(WeakStrategy::TopFrames),
+            "message_template" => Some(WeakStrategy::MessageTemplate),
+            _ => None,
+        }
+    }
+}
+
+/// 输出的行列号约定与指纹的位置容差
+///
+/// 内部统一使用JS引擎的约定（行列号从1开始，source map查询结果也换算为从1开始），
+/// 只在导出栈帧（JSON、JS对象、`file:line:col|func` 文本）时换算；
+/// 重新解析已导出的结果时按同一约定换算回来。内部为0的未知位置在起点为1时导出为0，
+/// 起点为0时导出为 `UNKNOWN_POSITION`（0已表示第一行/列），两个方向都换算回0。
+/// 指纹默认忽略列号、不量化行号
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// 行号起点，0或1（默认1）
+    pub line_base: u32,
+    /// 列号起点，0或1（默认1）；source map规范使用0
+    pub column_base: u32,
+    /// 指纹是否忽略列号（默认true）；不忽略时压缩帧的列号按桶取整
+    pub ignore_columns: bool,
+    /// 指纹中行号的量化桶宽度，如5时行号10与14视为相同；0或1不量化
+    pub line_bucket: u32,
+    /// 弱指纹的计算方式（默认 `in_app_frames`）
+    pub weak_strategy: WeakStrategy,
+    /// 弱指纹使用的栈帧数（默认3）
+    pub weak_frames: u32,
+}
+
+#[wasm_bindgen]
+impl ParserOptions {
+    /// 非0的起点都按1处理
+    pub fn new(line_base: u32, column_base: u32) -> Self {
+        ParserOptions {
+            line_base: line_base.min(1),
+            column_base: column_base.min(1),
+            ignore_columns: true,
+            line_bucket: 1,
+            weak_strategy: WeakStrategy::InAppFrames,
+            weak_frames: 3,
+        }
+    }
+
+    /// 设置行列号起点，非0的起点都按1处理，其余选项不变
+    pub fn with_positions(self, line_base: u32, column_base: u32) -> Self {
+        ParserOptions { line_base: line_base.min(1), column_base: column_base.min(1), ..self }
+    }
+
+    /// 设置指纹的位置容差，行列号约定不变
+    pub fn with_fingerprint_tolerance(self, ignore_columns: bool, line_bucket: u32) -> Self {
+        ParserOptions { ignore_columns, line_bucket, ..self }
+    }
+
+    /// 设置弱指纹的计算方式与使用的栈帧数
+    pub fn with_weak_fingerprint(self, weak_strategy: WeakStrategy, weak_frames: u32) -> Self {
+        ParserOptions { weak_strategy, weak_frames, ..self }
+    }
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions::new(1, 1)
+    }
+}
+
+impl ParserOptions {
+    /// 内部行号 -> 输出行号
+    pub fn export_line(&self, line: u32) -> u32 {
+        export_position(line, self.line_base)
+    }
+
+    pub fn export_column(&self, column: u32) -> u32 {
+        export_position(column, self.column_base)
+    }
+
+    /// 指纹使用的行号：按 `line_bucket` 向下取整
+    pub fn quantize_line(&self, line: u32) -> u32 {
+        let bucket = self.line_bucket.max(1);
+        line / bucket * bucket
+    }
+
+    /// 输出行号 -> 内部行号
+    pub fn import_line(&self, line: u32) -> u32 {
+        import_position(line, self.line_base)
+    }
+
+    pub fn import_column(&self, column: u32) -> u32 {
+        import_position(column, self.column_base)
+    }
+}
+
+fn export_position(position: u32, base: u32) -> u32 {
+    match (base, position) {
+        (0, 0) => UNKNOWN_POSITION,
+        (0, position) => position - 1,
+        _ => position,
+    }
+}
+
+fn import_position(position: u32, base: u32) -> u32 {
+    match (base, position) {
+        (0, UNKNOWN_POSITION) => 0,
+        (0, position) => position + 1,
+        _ => position,
+    }
+}
+
+thread_local! {
+    static OPTIONS: Cell<ParserOptions> = Cell::new(ParserOptions::default());
+}
+
+/// 设置全局行列号约定
+pub fn set_options(options: ParserOptions) {
+    OPTIONS.with(|current| current.set(options));
+}
+
+/// 当前全局行列号约定
+pub fn options() -> ParserOptions {
+    OPTIONS.with(|current| current.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_conversion() {
+        let zero_based = ParserOptions::new(0, 0);
+        assert_eq!((zero_based.export_line(10), zero_based.export_column(1)), (9, 0));
+        assert_eq!((zero_based.import_line(9), zero_based.import_column(0)), (10, 1));
+        // 未知位置往返后仍为未知
+        assert_eq!(zero_based.export_line(0), UNKNOWN_POSITION);
+        assert_eq!((zero_based.import_line(zero_based.export_line(0)), zero_based.import_column(zero_based.export_column(0))), (0, 0));
+
+        let default = ParserOptions::default();
+        assert_eq!((default.export_line(10), default.import_column(7)), (10, 7));
+        assert_eq!(ParserOptions::new(5, 2), default);
+    }
+
+    #[test]
+    fn test_with_positions_keeps_other_options() {
+        let options = ParserOptions::default()
+            .with_fingerprint_tolerance(false, 5)
+            .with_weak_fingerprint(WeakStrategy::TopFrames, 2)
+            .with_positions(0, 3);
+        assert_eq!((options.line_base, options.column_base), (0, 1));
+        assert_eq!((options.ignore_columns, options.line_bucket, options.weak_strategy, options.weak_frames), (false, 5, WeakStrategy::TopFrames, 2));
+    }
+
+    #[test]
+    fn test_quantize_line() {
+        let options = ParserOptions::default().with_fingerprint_tolerance(true, 5);
+        assert_eq!((options.quantize_line(10), options.quantize_line(14), options.quantize_line(15)), (10, 10, 15));
+        assert_eq!(options.with_fingerprint_tolerance(true, 0).quantize_line(14), 14);
+        assert_eq!(options.line_base, 1);
+    }
+}
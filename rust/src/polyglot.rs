@@ -0,0 +1,200 @@
+use serde::Serialize;
+use crate::exported_frame::ExportedStackFrame;
+use crate::fingerprint;
+use crate::hash;
+use crate::parser::{FrameOrigin, StackFrame};
+
+// JVM运行时与标准库的包前缀，视为第三方
+const JVM_RUNTIME_PACKAGES: [&str; 10] = ["java.", "javax.", "jdk.", "sun.", "com.sun.", "kotlin.", "kotlinx.", "scala.", "android.", "dalvik."];
+
+/// 后端栈的语言
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    /// Python traceback
+    Python,
+    /// Java、Kotlin、Scala等JVM语言的异常栈
+    Jvm,
+}
+
+impl Language {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Python => "python",
+            Language::Jvm => "jvm",
+        }
+    }
+}
+
+/// 解析后的后端栈：首行为 `类型: 消息` 形式的异常描述，栈帧与JS一样栈顶（抛出位置）在前
+#[derive(Debug)]
+pub struct PolyglotStack {
+    pub language: Language,
+    pub header: String,
+    pub frames: Vec<StackFrame>,
+}
+
+/// `parse_polyglot` 的导出结构，指纹与JS栈使用同一算法
+#[derive(Serialize)]
+pub struct PolyglotFrames {
+    pub language: Language,
+    pub header: String,
+    pub frames: Vec<ExportedStackFrame>,
+    pub fingerprint: String,
+}
+
+impl From<PolyglotStack> for PolyglotFrames {
+    fn from(stack: PolyglotStack) -> Self {
+        PolyglotFrames {
+            fingerprint: fingerprint::fingerprint_frames(&stack.header, &stack.frames, hash::fingerprint_algorithm()),
+            language: stack.language,
+            header: stack.header,
+            frames: stack.frames.into_iter().map(ExportedStackFrame::from).collect(),
+        }
+    }
+}
+
+/// `File "/app/svc.py", line 5, in handler` -> (文件, 行号, 函数名)
+fn parse_python_frame(line: &str) -> Option<(&str, u32, &str)> {
+    let rest = line.trim_start().strip_prefix("File \"")?;
+    let (file, rest) = rest.split_once("\", line ")?;
+    let (line_number, function) = rest.split_once(", in ")?;
+    Some((file, line_number.trim().parse().ok()?, function.trim()))
+}
+
+/// 解析Python traceback：链式异常只取最后一段，即最终抛出的异常
+fn parse_python(text: &str) -> Option<PolyglotStack> {
+    let (_, last) = text.rsplit_once("Traceback (most recent call last):")?;
+    let mut frames = Vec::new();
+    let mut header = String::new();
+    for line in last.lines() {
+        if let Some((file, line_number, function)) = parse_python_frame(line) {
+            let mut frame = StackFrame::new(function.to_string(), file.to_string(), line_number, 0);
+            // 第三方包与解释器内置的冻结模块都不是业务代码，与JVM的运行时包一样按第三方处理
+            let origin = if file.contains("/site-packages/") || file.contains("/dist-packages/") || file.starts_with("<frozen ") {
+                FrameOrigin::NodeModules
+            } else {
+                frame.classify(&[])
+            };
+            frame.set_origin(origin);
+            frames.push(frame);
+        } else if !line.is_empty() && !line.starts_with([' ', '\t']) {
+            header = line.trim().to_string();
+        }
+    }
+    // Python中最近的调用在最后
+    frames.reverse();
+    Some(PolyglotStack { language: Language::Python, header, frames })
+}
+
+/// `at com.acme.Service.handle(Service.java:42)` -> 栈帧，文件名带包路径 `com/acme/Service.java`
+fn parse_jvm_frame(line: &str) -> Option<StackFrame> {
+    let rest = line.trim_start().strip_prefix("at ")?.strip_suffix(')')?;
+    let (method, location) = rest.split_once('(')?;
+    if method.is_empty() || method.contains(' ') {
+        return None;
+    }
+    // Java 9+ 的模块前缀 `java.base/`、`app//`
+    let method = method.rsplit_once('/').map_or(method, |(_, method)| method);
+    let (class, _) = method.rsplit_once('.')?;
+    let (file, line_number) = match location.rsplit_once(':') {
+        Some((file, line_number)) => (file, line_number.parse().ok()?),
+        None => (location, 0),
+    };
+
+    let native = location == "Native Method";
+    let file = match class.rsplit_once('.') {
+        _ if native => "native".to_string(),
+        Some((package, _)) if location != "Unknown Source" => format!("{}/{}", package.replace('.', "/"), file),
+        _ => file.to_string(),
+    };
+    let mut frame = StackFrame::new(method.to_string(), file, line_number, 0);
+    frame.set_native(native);
+    let origin = if native {
+        FrameOrigin::Native
+    } else if JVM_RUNTIME_PACKAGES.iter().any(|package| method.starts_with(package)) {
+        FrameOrigin::NodeModules
+    } else {
+        FrameOrigin::App
+    };
+    frame.set_origin(origin);
+    Some(frame)
+}
+
+/// 解析JVM异常栈：只取最外层异常的栈帧，`Caused by:` 之后的起因与 `... N more` 省略行跳过
+fn parse_jvm(text: &str) -> Option<PolyglotStack> {
+    let mut lines = text.lines().skip_while(|line| line.trim().is_empty());
+    let header = lines.next()?.trim();
+    // 未捕获异常的前缀 `Exception in thread "main" `
+    let header = header.strip_prefix("Exception in thread \"")
+        .and_then(|rest| rest.split_once("\" "))
+        .map_or(header, |(_, header)| header);
+
+    let frames: Vec<StackFrame> = lines
+        .take_while(|line| !line.trim_start().starts_with("Caused by:"))
+        .filter_map(parse_jvm_frame)
+        .collect();
+    if frames.is_empty() {
+        return None;
+    }
+    Some(PolyglotStack { language: Language::Jvm, header: header.to_string(), frames })
+}
+
+/// 识别并解析Python或JVM的栈，都不是时返回None（按JS栈处理）
+pub fn parse(text: &str) -> Option<PolyglotStack> {
+    if text.contains("Traceback (most recent call last):") {
+        return parse_python(text);
+    }
+    parse_jvm(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PYTHON: &str = "Traceback (most recent call last):\n  File \"/app/main.py\", line 10, in <module>\n    main()\n  File \"/usr/lib/python3.11/site-packages/flask/app.py\", line 880, in dispatch\n    return view()\n  File \"/app/svc.py\", line 5, in handler\n    raise ValueError(\"bad id 42\")\n    ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^\nValueError: bad id 42";
+
+    const JVM: &str = "Exception in thread \"main\" java.lang.IllegalStateException: boom\n\tat com.acme.Service$Inner.handle(Service.java:42)\n\tat java.base/jdk.internal.reflect.NativeMethodAccessorImpl.invoke0(Native Method)\n\tat com.acme.Main.main(Main.java:10)\nCaused by: java.io.IOException: disk\n\tat com.acme.Disk.read(Disk.java:7)\n\t... 3 more";
+
+    #[test]
+    fn test_parse_python() {
+        let stack = parse(PYTHON).unwrap();
+        assert_eq!((stack.language, stack.header.as_str()), (Language::Python, "ValueError: bad id 42"));
+        let frames: Vec<(&str, &str, u32)> = stack.frames.iter().map(|frame| (frame.function_name_str(), frame.file_name_str(), frame.line_number())).collect();
+        assert_eq!(frames, vec![("handler", "/app/svc.py", 5), ("dispatch", "/usr/lib/python3.11/site-packages/flask/app.py", 880), ("<module>", "/app/main.py", 10)]);
+        assert!(stack.frames[0].is_in_app() && !stack.frames[1].is_in_app());
+
+        // 链式异常取最后一段
+        let chained = format!("{}\n\nDuring handling of the above exception, another exception occurred:\n\nTraceback (most recent call last):\n  File \"/app/retry.py\", line 3, in retry\nRuntimeError: gave up", PYTHON);
+        let stack = parse(&chained).unwrap();
+        assert_eq!((stack.header.as_str(), stack.frames.len()), ("RuntimeError: gave up", 1));
+    }
+
+    #[test]
+    fn test_parse_jvm() {
+        let stack = parse(JVM).unwrap();
+        assert_eq!((stack.language, stack.header.as_str()), (Language::Jvm, "java.lang.IllegalStateException: boom"));
+        assert_eq!(stack.frames.len(), 3);
+        assert_eq!((stack.frames[0].function_name_str(), stack.frames[0].file_name_str(), stack.frames[0].line_number()), ("com.acme.Service$Inner.handle", "com/acme/Service.java", 42));
+        assert!(stack.frames[1].is_native() && !stack.frames[1].is_in_app());
+        assert!(stack.frames[2].is_in_app());
+        assert!(parse("TypeError: x\n    at render (https://cdn.test/app.js:10:15)").is_none());
+    }
+
+    #[test]
+    fn test_polyglot_fingerprint() {
+        // 指纹与JS使用同一函数：消息中的数字与行号变化规则一致
+        assert_eq!(fingerprint::fingerprint(PYTHON), fingerprint::fingerprint(&PYTHON.replace("bad id 42", "bad id 7")));
+        assert_ne!(fingerprint::fingerprint(PYTHON), fingerprint::fingerprint(&PYTHON.replace("in handler", "in other")));
+        assert_ne!(fingerprint::fingerprint(JVM), fingerprint::fingerprint(&JVM.replace("IllegalStateException", "IllegalArgumentException")));
+        assert_eq!(PolyglotFrames::from(parse(JVM).unwrap()).fingerprint, fingerprint::fingerprint(JVM));
+
+        // 分词结果、事件封装与解析结果的指纹与 `fingerprint` 一致
+        for text in [PYTHON, JVM] {
+            let expected = fingerprint::fingerprint(text);
+            assert_eq!(crate::tokenized::Tokenized::new(text.to_string()).fingerprint(), expected);
+            assert_eq!(crate::envelope::build(text, crate::parser::ErrorParser::new().parse_frames(text), 0.0, None).error.strong_fingerprint, expected);
+            assert_eq!(crate::parser::ErrorParser::new().parse_error(text).strong_fingerprint, expected);
+        }
+    }
+}
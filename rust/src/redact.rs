@@ -0,0 +1,180 @@
+use serde::Serialize;
+use crate::fingerprint::{self, strip_query};
+use crate::hash;
+use crate::parser::{ErrorParser, StackFrame};
+use crate::simd::LineIndex;
+use crate::uuid;
+
+thread_local! {
+    // 来源哈希的会话盐，首次脱敏时随机生成
+    static ORIGIN_SALT: [u8; 32] = {
+        let mut salt = [0u8; 32];
+        uuid::random_bytes(&mut salt);
+        salt
+    };
+}
+
+/// 可嵌入会话回放负载的脱敏栈：路径只保留文件名，来源替换为哈希
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RedactedStack {
+    /// V8格式的脱敏栈文本，首行消息中的URL同样脱敏
+    pub stack: String,
+    /// 按未脱敏的栈计算，与 `fingerprint` 的结果相同
+    pub fingerprint: String,
+}
+
+/// 来源（`scheme://host`）加会话盐的短哈希：同一会话内一致，盐不随负载上报，无法靠枚举候选域名还原
+fn origin_hash(origin: &str) -> String {
+    let digest = ORIGIN_SALT.with(|salt| {
+        let mut input = salt.to_vec();
+        input.extend_from_slice(origin.as_bytes());
+        hash::blake3(&input)
+    });
+    digest[..4].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 脱敏文件路径：去掉查询参数与目录，只保留文件名；URL保留协议并把来源替换为哈希。
+/// 没有路径的位置（`<anonymous>`、`native` 等）与Node内部模块原样保留
+pub fn redact_file(file: &str) -> String {
+    let file = strip_query(file);
+    if file.starts_with("node:") {
+        return file.to_string();
+    }
+    let (origin, path) = match file.split_once("://") {
+        Some((scheme, rest)) => {
+            let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+            (Some((scheme, host)), path)
+        }
+        None => (None, file),
+    };
+    let basename = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    match origin {
+        Some((scheme, host)) if !host.is_empty() => format!("{}://{}/{}", scheme, origin_hash(&format!("{}://{}", scheme, host)), basename),
+        Some((scheme, _)) => format!("{}:///{}", scheme, basename),
+        None => basename.to_string(),
+    }
+}
+
+/// 是否为文件系统路径：绝对路径、`~/`、相对路径与Windows盘符、UNC路径，且至少含一级目录
+fn is_path(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    let drive = bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/');
+    let rooted = drive || ["/", "~/", "./", "../", "\\\\"].iter().any(|prefix| token.starts_with(prefix));
+    rooted && token.trim_start_matches(['/', '\\']).contains(['/', '\\'])
+}
+
+/// 脱敏单个词：URL从协议名开始脱敏，路径只保留文件名
+fn redact_token(token: &str) -> String {
+    if let Some(start) = token.find("://") {
+        let scheme_start = token[..start].char_indices()
+            .rfind(|&(_, c)| !c.is_ascii_alphanumeric() && !matches!(c, '+' | '-' | '.'))
+            .map_or(0, |(at, c)| at + c.len_utf8());
+        return format!("{}{}", &token[..scheme_start], redact_file(&token[scheme_start..]));
+    }
+    if is_path(token) {
+        return redact_file(token);
+    }
+    token.to_string()
+}
+
+/// 脱敏文本中的URL与文件路径（错误消息、eval来源），其余内容不变；词以空白、括号、引号与逗号分隔
+pub fn redact_text(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '"' | '\'' | ',')).unwrap_or(rest.len());
+        redacted.push_str(&redact_token(&rest[..end]));
+        let delimiter = rest[end..].chars().next().map_or(0, char::len_utf8);
+        redacted.push_str(&rest[end..end + delimiter]);
+        rest = &rest[end + delimiter..];
+    }
+    redacted
+}
+
+/// 就地脱敏栈帧：文件名与eval来源脱敏，丢弃可能含源码的上下文
+pub fn redact_frame(frame: &mut StackFrame) {
+    let file = redact_file(frame.file_name_str());
+    frame.set_location(frame.function_name(), file, frame.line_number(), frame.column_number());
+    let eval_origin = frame.eval_origin_str().map(redact_text);
+    frame.set_eval(frame.is_eval(), eval_origin);
+    frame.clear_context();
+}
+
+/// 脱敏后的V8格式帧行
+fn format_frame(frame: &StackFrame) -> String {
+    let location = match (frame.line_number(), frame.column_number()) {
+        (0, _) => frame.file_name_str().to_string(),
+        (line, 0) => format!("{}:{}", frame.file_name_str(), line),
+        (line, column) => format!("{}:{}:{}", frame.file_name_str(), line, column),
+    };
+    match frame.function_name_str() {
+        "" => format!("    at {}", location),
+        function => format!("    at {} ({})", function, location),
+    }
+}
+
+/// 生成脱敏栈，指纹按原始栈帧计算，脱敏前后上报的同一错误仍归入同一分组
+pub fn redact_stack(stack: &str) -> RedactedStack {
+    let header = LineIndex::new(stack).line(0).unwrap_or("");
+    let mut frames = ErrorParser::new().parse_frames(stack);
+    let fingerprint = fingerprint::fingerprint_parsed(stack, header, &frames, hash::fingerprint_algorithm());
+
+    let mut lines = Vec::with_capacity(frames.len() + 1);
+    // 首行只有栈帧时没有消息
+    if ErrorParser::new().parse_frames(header).is_empty() {
+        lines.push(redact_text(header));
+    }
+    for frame in &mut frames {
+        redact_frame(frame);
+        lines.push(format_frame(frame));
+    }
+    RedactedStack { stack: lines.join("\n"), fingerprint }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_file() {
+        let cdn = redact_file("https://cdn.test/static/js/app.js?token=secret");
+        assert!(cdn.starts_with("https://") && cdn.ends_with("/app.js"));
+        assert!(!cdn.contains("cdn.test") && !cdn.contains("static") && !cdn.contains("secret"));
+        // 同一来源的哈希相同
+        assert_eq!(redact_file("https://cdn.test/vendor/lib.js").rsplit_once('/').unwrap().0, cdn.rsplit_once('/').unwrap().0);
+        assert_ne!(redact_file("https://other.test/app.js"), cdn);
+
+        assert_eq!(redact_file("/home/alice/project/src/cart.ts"), "cart.ts");
+        assert_eq!(redact_file("C:\\Users\\alice\\app\\main.js"), "main.js");
+        assert_eq!(redact_file("file:///home/alice/app.js"), "file:///app.js");
+        assert_eq!(redact_file("<anonymous>"), "<anonymous>");
+        assert_eq!(redact_file("node:internal/timers"), "node:internal/timers");
+    }
+
+    #[test]
+    fn test_redact_text() {
+        // 协议名前是多字节字符
+        let text = redact_text("错误：https://cdn.test/a/b.js 加载失败");
+        assert!(text.starts_with("错误：https://") && text.ends_with("/b.js 加载失败") && !text.contains("cdn.test"));
+        assert_eq!(redact_text("ENOENT: no such file or directory, open '/home/alice/secret/keys.txt'"), "ENOENT: no such file or directory, open 'keys.txt'");
+        assert_eq!(redact_text("cannot read C:\\Users\\alice\\app.log"), "cannot read app.log");
+        assert_eq!(redact_text("ratio 1/2 at /tmp"), "ratio 1/2 at /tmp");
+    }
+
+    #[test]
+    fn test_redact_stack() {
+        let stack = "TypeError: failed to load https://cdn.test/users/42/avatar.png\n    at render (https://cdn.test/static/js/app.js?v=1:10:15)\n    at Array.map (<anonymous>)\n    at /home/alice/project/src/index.js:3:1";
+        let redacted = redact_stack(stack);
+        let lines: Vec<&str> = redacted.stack.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("TypeError: failed to load https://") && lines[0].ends_with("/avatar.png"));
+        assert!(!redacted.stack.contains("cdn.test") && !redacted.stack.contains("alice"));
+        assert!(lines[1].starts_with("    at render (https://") && lines[1].ends_with("/app.js:10:15)"));
+        assert_eq!(lines[2], "    at Array.map (<anonymous>)");
+        assert_eq!(lines[3], "    at <anonymous> (index.js:3:1)");
+
+        // 指纹与未脱敏的栈一致，脱敏后的栈仍可解析
+        assert_eq!(redacted.fingerprint, fingerprint::fingerprint(stack));
+        assert_eq!(ErrorParser::new().parse_frames(&redacted.stack).len(), 3);
+    }
+}
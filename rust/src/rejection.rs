@@ -0,0 +1,239 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::fingerprint::{self, strip_query};
+use crate::grouping::message_template;
+use crate::hash;
+use crate::parser::ErrorParser;
+use crate::simd::LineIndex;
+
+// 消息的最大字符数，超出部分截断
+const MAX_MESSAGE_CHARS: usize = 1000;
+// 依次尝试作为消息的对象字段
+const MESSAGE_FIELDS: [&str; 8] = ["message", "error", "reason", "error_description", "detail", "title", "msg", "description"];
+
+/// 拒绝负载的类型
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionKind {
+    /// 带栈的Error或序列化后的Error对象
+    Error,
+    /// 普通对象
+    Object,
+    /// 字符串
+    String,
+    /// fetch的Response或类似的 `{status, statusText, url}`
+    Response,
+    /// GraphQL响应的 `errors` 数组
+    Graphql,
+    /// 数字、布尔值、null
+    Primitive,
+}
+
+impl RejectionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectionKind::Error => "error",
+            RejectionKind::Object => "object",
+            RejectionKind::String => "string",
+            RejectionKind::Response => "response",
+            RejectionKind::Graphql => "graphql",
+            RejectionKind::Primitive => "primitive",
+        }
+    }
+}
+
+/// 未处理的Promise拒绝：尽力提取的消息与合成指纹，非Error负载没有栈也能上报与分组
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Rejection {
+    pub kind: RejectionKind,
+    pub message: String,
+    /// `name`、`code` 字段或GraphQL的 `extensions.code`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub fingerprint: String,
+    pub has_stack: bool,
+}
+
+/// 截断为不超过 `MAX_MESSAGE_CHARS` 个字符
+fn truncate(message: &str) -> String {
+    match message.char_indices().nth(MAX_MESSAGE_CHARS) {
+        Some((end, _)) => message[..end].to_string(),
+        None => message.to_string(),
+    }
+}
+
+fn string_field(object: &Map<String, Value>, key: &str) -> Option<String> {
+    object.get(key).and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// 对象中的消息字段，`error` 为嵌套对象时取其中的消息
+fn object_message(object: &Map<String, Value>) -> Option<String> {
+    MESSAGE_FIELDS.iter().find_map(|key| match object.get(*key)? {
+        Value::String(message) if !message.trim().is_empty() => Some(message.trim().to_string()),
+        Value::Object(nested) => object_message(nested),
+        _ => None,
+    })
+}
+
+impl Rejection {
+    fn new(kind: RejectionKind, message: &str) -> Self {
+        Rejection {
+            kind,
+            message: truncate(message),
+            error_type: None,
+            status: None,
+            url: None,
+            fingerprint: String::new(),
+            has_stack: false,
+        }
+    }
+
+    /// 没有栈时按类型、错误类型与消息模板合成指纹；Response按状态码与URL
+    fn with_fingerprint(mut self) -> Self {
+        let mut input = format!("rejection:{}\n{}", self.kind.as_str(), self.error_type.as_deref().unwrap_or(""));
+        match self.kind {
+            RejectionKind::Response => {
+                input.push_str(&format!("\n{}\n{}", self.status.unwrap_or(0), self.url.as_deref().unwrap_or("")));
+            }
+            _ => {
+                input.push('\n');
+                input.push_str(&message_template(&self.message));
+            }
+        }
+        self.fingerprint = format!("{:016x}", hash::fingerprint_algorithm().hash_u64(input.as_bytes()));
+        self
+    }
+
+    /// 带栈的错误使用常规指纹
+    fn from_stack(stack: &str, error_type: Option<String>, message: Option<String>) -> Self {
+        let (header_type, header_message) = ErrorParser::parse_error_header(LineIndex::new(stack).line(0).unwrap_or(""));
+        let mut rejection = Rejection::new(RejectionKind::Error, &message.unwrap_or(header_message));
+        rejection.error_type = error_type.or(header_type);
+        rejection.fingerprint = fingerprint::fingerprint(stack);
+        rejection.has_stack = true;
+        rejection
+    }
+
+    fn from_text(text: &str) -> Self {
+        if !ErrorParser::new().parse_frames(text).is_empty() {
+            return Self::from_stack(text, None, None);
+        }
+        Rejection::new(RejectionKind::String, text).with_fingerprint()
+    }
+
+    fn from_object(object: &Map<String, Value>) -> Self {
+        let error_type = string_field(object, "name").or_else(|| string_field(object, "code"));
+
+        // 序列化后的Error对象
+        if let Some(stack) = string_field(object, "stack") {
+            if !ErrorParser::new().parse_frames(&stack).is_empty() {
+                return Self::from_stack(&stack, error_type, object_message(object));
+            }
+        }
+
+        // GraphQL响应：取第一个错误
+        if let Some(first) = object.get("errors").and_then(Value::as_array).and_then(|errors| errors.first()) {
+            let (message, code) = match first {
+                Value::Object(error) => (
+                    object_message(error),
+                    error.get("extensions").and_then(Value::as_object).and_then(|extensions| string_field(extensions, "code")),
+                ),
+                Value::String(message) => (Some(message.clone()), None),
+                _ => (None, None),
+            };
+            if let Some(message) = message {
+                let mut rejection = Rejection::new(RejectionKind::Graphql, &message);
+                rejection.error_type = code;
+                return rejection.with_fingerprint();
+            }
+        }
+
+        let status = object.get("status").and_then(Value::as_u64).and_then(|status| u16::try_from(status).ok());
+        if let Some(status) = status.filter(|_| ["statusText", "ok", "url", "headers"].iter().any(|key| object.contains_key(*key))) {
+            let status_text = string_field(object, "statusText").unwrap_or_default();
+            // 查询参数可能含令牌与个人信息，消息与URL都只保留路径
+            let url = string_field(object, "url").map(|url| strip_query(&url).to_string());
+            let mut message = format!("HTTP {} {}", status, status_text).trim_end().to_string();
+            if let Some(url) = &url {
+                message.push_str(&format!(" ({})", url));
+            }
+            let mut rejection = Rejection::new(RejectionKind::Response, &message);
+            rejection.status = Some(status);
+            rejection.url = url;
+            return rejection.with_fingerprint();
+        }
+
+        let message = object_message(object).unwrap_or_else(|| {
+            let mut keys: Vec<&str> = object.keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            format!("Object with keys: {}", keys.join(", "))
+        });
+        let mut rejection = Rejection::new(RejectionKind::Object, &message);
+        rejection.error_type = error_type;
+        rejection.with_fingerprint()
+    }
+}
+
+/// 解析未处理拒绝的负载：JSON（对象、GraphQL响应、Response转储、字符串、原始值）或纯文本，
+/// 文本中含可识别的栈时按Error处理
+pub fn parse_rejection(input: &str) -> Rejection {
+    let input = input.trim();
+    let input = input.strip_prefix("Uncaught (in promise)").map_or(input, str::trim_start);
+    match serde_json::from_str::<Value>(input) {
+        Ok(Value::Object(object)) => Rejection::from_object(&object),
+        Ok(Value::Array(items)) => match items.first() {
+            Some(Value::Object(object)) => Rejection::from_object(object),
+            _ => Rejection::new(RejectionKind::Object, input).with_fingerprint(),
+        },
+        Ok(Value::String(text)) => Rejection::from_text(text.trim()),
+        Ok(value) => Rejection::new(RejectionKind::Primitive, &value.to_string()).with_fingerprint(),
+        Err(_) => Rejection::from_text(input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rejection() {
+        let graphql = parse_rejection(r#"{"data": null, "errors": [{"message": "User 42 not found", "extensions": {"code": "NOT_FOUND"}}]}"#);
+        assert_eq!((graphql.kind, graphql.message.as_str(), graphql.error_type.as_deref()), (RejectionKind::Graphql, "User 42 not found", Some("NOT_FOUND")));
+        assert!(!graphql.has_stack);
+        // 消息中的ID不影响指纹
+        assert_eq!(graphql.fingerprint, parse_rejection(r#"{"errors": [{"message": "User 7 not found", "extensions": {"code": "NOT_FOUND"}}]}"#).fingerprint);
+
+        let response = parse_rejection(r#"{"type": "cors", "url": "https://api.test/cart?id=1", "status": 503, "statusText": "Service Unavailable", "ok": false}"#);
+        assert_eq!((response.kind, response.status), (RejectionKind::Response, Some(503)));
+        assert_eq!(response.message, "HTTP 503 Service Unavailable (https://api.test/cart)");
+        assert_eq!(response.url.as_deref(), Some("https://api.test/cart"));
+        assert_eq!(response.fingerprint, parse_rejection(r#"{"url": "https://api.test/cart?id=2", "status": 503}"#).fingerprint);
+
+        let object = parse_rejection(r#"{"code": "E_TIMEOUT", "error": {"message": "timed out"}}"#);
+        assert_eq!((object.kind, object.message.as_str(), object.error_type.as_deref()), (RejectionKind::Object, "timed out", Some("E_TIMEOUT")));
+        assert_eq!(parse_rejection(r#"{"b": 1, "a": 2}"#).message, "Object with keys: a, b");
+
+        let text = parse_rejection("Uncaught (in promise) cancelled");
+        assert_eq!((text.kind, text.message.as_str()), (RejectionKind::String, "cancelled"));
+        assert_eq!(text.fingerprint, parse_rejection(r#""cancelled""#).fingerprint);
+        // 撇号不是引号，不会吞掉其后的消息
+        assert_eq!(parse_rejection("can't load 'a.js'").fingerprint, parse_rejection("can't load 'b.js'").fingerprint);
+        assert_ne!(parse_rejection("can't load 'a.js'").fingerprint, parse_rejection("can't save 'a.js'").fingerprint);
+        assert_eq!(parse_rejection("null").kind, RejectionKind::Primitive);
+        assert_ne!(parse_rejection("404").fingerprint, parse_rejection(r#""404""#).fingerprint);
+    }
+
+    #[test]
+    fn test_parse_rejection_with_stack() {
+        let stack = "TypeError: x is undefined\n at render (https://cdn.test/app.js:10:15)";
+        let serialized = serde_json::json!({"name": "TypeError", "message": "x is undefined", "stack": stack}).to_string();
+        let rejection = parse_rejection(&serialized);
+        assert_eq!((rejection.kind, rejection.error_type.as_deref(), rejection.has_stack), (RejectionKind::Error, Some("TypeError"), true));
+        assert_eq!(rejection.fingerprint, fingerprint::fingerprint(stack));
+        assert_eq!(parse_rejection(stack).message, "x is undefined");
+    }
+}
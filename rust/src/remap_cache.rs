@@ -0,0 +1,281 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::mem::size_of;
+
+/// 映射后的原始源码位置
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OriginalLocation {
+    pub source: String,
+    pub line: u32,
+    pub column: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// 源文件在source map的 `ignoreList` 中（第三方代码）
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub ignored: bool,
+}
+
+impl OriginalLocation {
+    fn approx_bytes(&self) -> usize {
+        size_of::<OriginalLocation>() + self.source.len() + self.name.as_ref().map_or(0, String::len)
+    }
+}
+
+/// 缓存命中统计
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct RemapCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    /// 估算占用的字节数
+    #[serde(default)]
+    pub approx_bytes: usize,
+    /// 因条目数或内存上限淘汰的条目数
+    #[serde(default)]
+    pub evictions: u64,
+}
+
+type FrameKey = (String, u32, u32);
+
+/// (文件, 行, 列) -> 原始位置 的查询缓存，同一批热点栈帧会反复出现
+pub struct RemapCache {
+    entries: HashMap<FrameKey, Option<OriginalLocation>>,
+    // 插入顺序，满时淘汰最早的条目
+    order: VecDeque<FrameKey>,
+    capacity: usize,
+    // 内存上限，None表示只按条目数限制
+    max_bytes: Option<usize>,
+    hits: u64,
+    misses: u64,
+    bytes: usize,
+    evictions: u64,
+}
+
+impl RemapCache {
+    pub fn new(capacity: usize) -> Self {
+        RemapCache {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+            max_bytes: None,
+            hits: 0,
+            misses: 0,
+            bytes: 0,
+            evictions: 0,
+        }
+    }
+
+    fn entry_bytes(key: &FrameKey, value: &Option<OriginalLocation>) -> usize {
+        // 键在order中也保存了一份
+        2 * (size_of::<FrameKey>() + key.0.len()) + value.as_ref().map_or(0, OriginalLocation::approx_bytes)
+    }
+
+    /// 查询缓存，未命中时调用 `lookup` 并缓存结果（包括映射失败的None）
+    pub fn get_or_insert_with<F>(&mut self, file: &str, line: u32, column: u32, lookup: F) -> Option<OriginalLocation>
+    where
+        F: FnOnce() -> Option<OriginalLocation>,
+    {
+        let key = (file.to_string(), line, column);
+        if let Some(cached) = self.entries.get(&key) {
+            self.hits += 1;
+            return cached.clone();
+        }
+
+        self.misses += 1;
+        let value = lookup();
+        if self.capacity == 0 {
+            return value;
+        }
+        while self.entries.len() >= self.capacity {
+            if !self.evict_front() {
+                break;
+            }
+        }
+        self.bytes += Self::entry_bytes(&key, &value);
+        self.order.push_back(key.clone());
+        self.entries.insert(key, value.clone());
+        self.enforce_max_bytes();
+        value
+    }
+
+    fn enforce_max_bytes(&mut self) {
+        while self.max_bytes.is_some_and(|max_bytes| self.bytes > max_bytes) && self.evict_front() {}
+    }
+
+    fn evict_front(&mut self) -> bool {
+        match self.order.pop_front() {
+            Some(key) => {
+                if let Some(value) = self.entries.remove(&key) {
+                    self.bytes -= Self::entry_bytes(&key, &value);
+                }
+                self.evictions += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 释放某个文件的全部条目（其source map被淘汰时调用），返回释放条数
+    pub fn release_file(&mut self, file: &str) -> usize {
+        let before = self.entries.len();
+        let bytes = &mut self.bytes;
+        self.entries.retain(|key, value| {
+            if key.0 == file {
+                *bytes -= Self::entry_bytes(key, value);
+                false
+            } else {
+                true
+            }
+        });
+        self.order.retain(|key| key.0 != file);
+        before - self.entries.len()
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity && self.evict_front() {}
+    }
+
+    /// 设置内存上限（None表示不限制），超出时淘汰最早的条目
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.max_bytes = max_bytes;
+        self.enforce_max_bytes();
+    }
+
+    /// 释放表的多余容量
+    pub fn compact(&mut self) {
+        self.entries.shrink_to_fit();
+        self.order.shrink_to_fit();
+    }
+
+    /// 清空条目，保留统计
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.bytes = 0;
+    }
+
+    pub fn approx_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn stats(&self) -> RemapCacheStats {
+        let total = self.hits + self.misses;
+        RemapCacheStats {
+            entries: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+            hit_rate: if total == 0 { 0.0 } else { self.hits as f64 / total as f64 },
+            approx_bytes: self.bytes,
+            evictions: self.evictions,
+        }
+    }
+}
+
+impl Default for RemapCache {
+    fn default() -> Self {
+        RemapCache::new(4096)
+    }
+}
+
+thread_local! {
+    static CACHE: RefCell<RemapCache> = RefCell::new(RemapCache::default());
+}
+
+/// 通过全局缓存查询映射结果
+pub fn get_or_insert_with<F>(file: &str, line: u32, column: u32, lookup: F) -> Option<OriginalLocation>
+where
+    F: FnOnce() -> Option<OriginalLocation>,
+{
+    CACHE.with(|cache| cache.borrow_mut().get_or_insert_with(file, line, column, lookup))
+}
+
+/// 释放某个文件的缓存条目
+pub fn release_file(file: &str) -> usize {
+    CACHE.with(|cache| cache.borrow_mut().release_file(file))
+}
+
+pub fn set_capacity(capacity: usize) {
+    CACHE.with(|cache| cache.borrow_mut().set_capacity(capacity));
+}
+
+pub fn set_max_bytes(max_bytes: Option<usize>) {
+    CACHE.with(|cache| cache.borrow_mut().set_max_bytes(max_bytes));
+}
+
+pub fn compact() {
+    CACHE.with(|cache| cache.borrow_mut().compact());
+}
+
+pub fn clear() {
+    CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+pub fn approx_bytes() -> usize {
+    CACHE.with(|cache| cache.borrow().approx_bytes())
+}
+
+pub fn stats() -> RemapCacheStats {
+    CACHE.with(|cache| cache.borrow().stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(line: u32) -> Option<OriginalLocation> {
+        Some(OriginalLocation { source: "src/App.tsx".to_string(), line, column: 4, name: None, ignored: false })
+    }
+
+    #[test]
+    fn test_hits_and_misses() {
+        let mut cache = RemapCache::new(8);
+        let mut lookups = 0;
+        for _ in 0..3 {
+            let result = cache.get_or_insert_with("/main.js", 1, 100, || {
+                lookups += 1;
+                location(10)
+            });
+            assert_eq!(result, location(10));
+        }
+        assert_eq!(lookups, 1);
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 2, 1));
+        assert!((stats.hit_rate - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_capacity_and_release() {
+        let mut cache = RemapCache::new(2);
+        cache.get_or_insert_with("/a.js", 1, 1, || location(1));
+        cache.get_or_insert_with("/b.js", 1, 1, || location(2));
+        cache.get_or_insert_with("/b.js", 2, 1, || None);
+        // 容量为2，最早的 /a.js 条目被淘汰
+        assert_eq!(cache.stats().entries, 2);
+        assert_eq!(cache.release_file("/a.js"), 0);
+
+        assert_eq!(cache.release_file("/b.js"), 2);
+        assert_eq!(cache.approx_bytes(), 0);
+    }
+
+    #[test]
+    fn test_max_bytes() {
+        let mut cache = RemapCache::new(100);
+        for line in 0..10 {
+            cache.get_or_insert_with("/main.js", line, 1, || location(line));
+        }
+        let per_entry = cache.approx_bytes() / 10;
+        cache.set_max_bytes(Some(per_entry * 4));
+        assert_eq!(cache.stats().entries, 4);
+        assert_eq!(cache.stats().evictions, 6);
+
+        cache.get_or_insert_with("/main.js", 99, 1, || location(99));
+        assert!(cache.approx_bytes() <= per_entry * 4);
+        assert_eq!(cache.stats().evictions, 7);
+        cache.compact();
+        assert_eq!(cache.get_or_insert_with("/main.js", 99, 1, || None), location(99));
+    }
+}
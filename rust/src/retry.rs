@@ -0,0 +1,256 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::utils::now_ms;
+use crate::uuid::random_unit;
+
+/// 重试策略，各SDK移植版共用同一套参数；反序列化时缺省字段取默认值并校验
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "RetryFields")]
+pub struct RetryPolicy {
+    /// 第一次失败后的等待时间
+    base_delay_ms: f64,
+    max_delay_ms: f64,
+    /// 连续失败达到该次数后丢弃当前事件
+    max_attempts: u32,
+    /// 抖动比例，0.2表示在 ±20% 范围内随机
+    jitter: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct RetryFields {
+    base_delay_ms: f64,
+    max_delay_ms: f64,
+    max_attempts: u32,
+    jitter: f64,
+}
+
+impl Default for RetryFields {
+    fn default() -> Self {
+        let policy = RetryPolicy::default();
+        RetryFields {
+            base_delay_ms: policy.base_delay_ms,
+            max_delay_ms: policy.max_delay_ms,
+            max_attempts: policy.max_attempts,
+            jitter: policy.jitter,
+        }
+    }
+}
+
+impl TryFrom<RetryFields> for RetryPolicy {
+    type Error = String;
+
+    fn try_from(fields: RetryFields) -> Result<Self, String> {
+        RetryPolicy::new(fields.base_delay_ms, fields.max_delay_ms, fields.max_attempts, fields.jitter)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            base_delay_ms: 1000.0,
+            max_delay_ms: 5.0 * 60.0 * 1000.0,
+            max_attempts: 8,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 退避时间必须是正的有限值且上限不小于初始值，抖动比例在 [0, 1] 内
+    pub fn new(base_delay_ms: f64, max_delay_ms: f64, max_attempts: u32, jitter: f64) -> Result<Self, String> {
+        if !(base_delay_ms.is_finite() && base_delay_ms > 0.0) {
+            return Err(format!("初始退避时间必须为正数: {}", base_delay_ms));
+        }
+        if !(max_delay_ms.is_finite() && max_delay_ms >= base_delay_ms) {
+            return Err(format!("最大退避时间不能小于初始退避时间: {} < {}", max_delay_ms, base_delay_ms));
+        }
+        if !(0.0..=1.0).contains(&jitter) {
+            return Err(format!("抖动比例必须在0到1之间: {}", jitter));
+        }
+        Ok(RetryPolicy { base_delay_ms, max_delay_ms, max_attempts, jitter })
+    }
+
+    /// 指数退避：每次失败翻倍，不超过 `max_delay_ms`
+    fn backoff_ms(&self, failures: u32) -> f64 {
+        let exponent = failures.saturating_sub(1).min(30) as i32;
+        (self.base_delay_ms * 2f64.powi(exponent)).min(self.max_delay_ms)
+    }
+}
+
+/// JS层发送结果对应的处理决定
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SendDecision {
+    /// 发送成功
+    Sent,
+    /// 稍后重试，等待时间见 `next_send_delay`
+    Retry,
+    /// 不可重试或重试次数用尽，丢弃事件
+    Drop,
+}
+
+impl SendDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SendDecision::Sent => "sent",
+            SendDecision::Retry => "retry",
+            SendDecision::Drop => "drop",
+        }
+    }
+}
+
+/// 单个上报地址的重试状态
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct EndpointState {
+    pub consecutive_failures: u32,
+    /// 允许再次发送的时间（Unix毫秒）
+    pub next_attempt_at: f64,
+    pub dropped_events: u32,
+}
+
+/// 状态码是否值得重试：网络错误（0）、超时、限流与服务端错误
+fn is_retryable(status: u16) -> bool {
+    matches!(status, 0 | 408 | 429) || status >= 500
+}
+
+/// 按上报地址记录失败次数并计算退避时间
+#[derive(Default)]
+pub struct RetryTracker {
+    policy: RetryPolicy,
+    endpoints: HashMap<String, EndpointState>,
+}
+
+impl RetryTracker {
+    pub fn new(policy: RetryPolicy) -> Self {
+        RetryTracker { policy, endpoints: HashMap::new() }
+    }
+
+    /// 第 `failures` 次连续失败后的基础退避时间（未加抖动）
+    pub fn backoff_ms(&self, failures: u32) -> f64 {
+        self.policy.backoff_ms(failures)
+    }
+
+    /// 记录一次发送结果：`retry_after_ms` 为服务端 Retry-After 给出的等待时间，`random` 为 [0, 1) 的抖动随机数
+    pub fn on_send_result_at(&mut self, endpoint: &str, status: u16, retry_after_ms: Option<f64>, now: f64, random: f64) -> SendDecision {
+        let policy = self.policy;
+        let state = self.endpoints.entry(endpoint.to_string()).or_default();
+
+        if (200..300).contains(&status) {
+            state.consecutive_failures = 0;
+            state.next_attempt_at = 0.0;
+            return SendDecision::Sent;
+        }
+        if !is_retryable(status) {
+            state.dropped_events += 1;
+            return SendDecision::Drop;
+        }
+
+        state.consecutive_failures += 1;
+        let backoff = policy.backoff_ms(state.consecutive_failures) * (1.0 + policy.jitter * (random * 2.0 - 1.0));
+        // 服务端要求的等待时间优先
+        state.next_attempt_at = now + backoff.max(retry_after_ms.unwrap_or(0.0));
+        if state.consecutive_failures >= policy.max_attempts {
+            // 丢弃当前事件，但保留退避以免继续冲击服务端
+            state.dropped_events += 1;
+            state.consecutive_failures = 0;
+            return SendDecision::Drop;
+        }
+        SendDecision::Retry
+    }
+
+    /// 距离允许再次发送还需等待的毫秒数
+    pub fn next_send_delay_at(&self, endpoint: &str, now: f64) -> f64 {
+        self.endpoints.get(endpoint).map_or(0.0, |state| (state.next_attempt_at - now).max(0.0))
+    }
+
+    pub fn state(&self, endpoint: &str) -> EndpointState {
+        self.endpoints.get(endpoint).copied().unwrap_or_default()
+    }
+
+    pub fn set_policy(&mut self, policy: RetryPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn reset(&mut self) {
+        self.endpoints.clear();
+    }
+}
+
+thread_local! {
+    static TRACKER: RefCell<RetryTracker> = RefCell::new(RetryTracker::default());
+}
+
+/// 以当前时间记录发送结果
+pub fn on_send_result(endpoint: &str, status: u16, retry_after_ms: Option<f64>) -> SendDecision {
+    TRACKER.with(|tracker| tracker.borrow_mut().on_send_result_at(endpoint, status, retry_after_ms, now_ms(), random_unit()))
+}
+
+pub fn next_send_delay(endpoint: &str) -> f64 {
+    TRACKER.with(|tracker| tracker.borrow().next_send_delay_at(endpoint, now_ms()))
+}
+
+pub fn state(endpoint: &str) -> EndpointState {
+    TRACKER.with(|tracker| tracker.borrow().state(endpoint))
+}
+
+pub fn set_policy(policy: RetryPolicy) {
+    TRACKER.with(|tracker| tracker.borrow_mut().set_policy(policy));
+}
+
+pub fn reset() {
+    TRACKER.with(|tracker| tracker.borrow_mut().reset());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENDPOINT: &str = "https://ingest.test/api/1/envelope";
+
+    #[test]
+    fn test_exponential_backoff_and_reset() {
+        let mut tracker = RetryTracker::default();
+        // random = 0.5 时抖动为0
+        assert_eq!(tracker.on_send_result_at(ENDPOINT, 503, None, 0.0, 0.5), SendDecision::Retry);
+        assert_eq!(tracker.next_send_delay_at(ENDPOINT, 0.0), 1000.0);
+        tracker.on_send_result_at(ENDPOINT, 0, None, 0.0, 0.5);
+        assert_eq!(tracker.next_send_delay_at(ENDPOINT, 500.0), 1500.0);
+        assert_eq!(tracker.backoff_ms(20), 300_000.0);
+
+        // 其他地址不受影响
+        assert_eq!(tracker.next_send_delay_at("https://other.test", 0.0), 0.0);
+
+        assert_eq!(tracker.on_send_result_at(ENDPOINT, 200, None, 0.0, 0.5), SendDecision::Sent);
+        assert_eq!(tracker.state(ENDPOINT).consecutive_failures, 0);
+        assert_eq!(tracker.next_send_delay_at(ENDPOINT, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_retry_after_jitter_and_drop() {
+        let mut tracker = RetryTracker::new(RetryPolicy::new(1000.0, 300_000.0, 3, 0.2).unwrap());
+        tracker.on_send_result_at(ENDPOINT, 429, Some(60_000.0), 0.0, 0.5);
+        assert_eq!(tracker.next_send_delay_at(ENDPOINT, 0.0), 60_000.0);
+
+        // 抖动在 ±20% 内
+        tracker.on_send_result_at(ENDPOINT, 502, None, 0.0, 0.0);
+        assert_eq!(tracker.next_send_delay_at(ENDPOINT, 0.0), 1600.0);
+
+        assert_eq!(tracker.on_send_result_at(ENDPOINT, 500, None, 0.0, 0.5), SendDecision::Drop);
+        assert_eq!(tracker.on_send_result_at(ENDPOINT, 413, None, 0.0, 0.5), SendDecision::Drop);
+        let state = tracker.state(ENDPOINT);
+        assert_eq!((state.dropped_events, state.consecutive_failures), (2, 0));
+    }
+
+    #[test]
+    fn test_rejects_invalid_policy() {
+        assert!(RetryPolicy::new(0.0, 1000.0, 3, 0.2).is_err());
+        assert!(RetryPolicy::new(-1.0, 1000.0, 3, 0.2).is_err());
+        assert!(RetryPolicy::new(1000.0, 500.0, 3, 0.2).is_err());
+        assert!(RetryPolicy::new(1000.0, 1000.0, 3, 1.5).is_err());
+        assert!(RetryPolicy::new(1000.0, 1000.0, 3, 0.0).is_ok());
+        assert!(serde_json::from_str::<RetryPolicy>(r#"{"base_delay_ms": 0}"#).is_err());
+        assert!(serde_json::from_str::<RetryPolicy>(r#"{"base_delay_ms": 500000}"#).is_err());
+    }
+}
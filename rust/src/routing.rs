@@ -0,0 +1,198 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use crate::envelope::{self, ErrorEnvelope};
+use crate::ownership;
+use crate::parser::{ErrorParser, StackFrame};
+use crate::sampling::should_sample_severity;
+use crate::severity;
+
+fn default_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_scrub_profile() -> String {
+    "default".to_string()
+}
+
+/// 一个逻辑项目：同一页面上托管的多个应用各自上报到自己的项目
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Project {
+    pub key: String,
+    pub endpoint: String,
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f64,
+    /// 交给宿主执行的脱敏配置名称
+    #[serde(default = "default_scrub_profile")]
+    pub scrub_profile: String,
+    /// 错误的负责团队包含其一时命中（见归属规则）
+    #[serde(default)]
+    pub owners: Vec<String>,
+    /// 第一个业务代码帧的文件URL以其一开头时命中，如 `https://cdn.test/app-a/`
+    #[serde(default)]
+    pub origins: Vec<String>,
+}
+
+impl Project {
+    fn matches(&self, owners: &[String], frames: &[StackFrame]) -> bool {
+        if owners.iter().any(|owner| self.owners.contains(owner)) {
+            return true;
+        }
+        frames.iter()
+            .find(|frame| frame.is_in_app())
+            .is_some_and(|frame| self.origins.iter().any(|origin| frame.file_name_str().starts_with(origin.as_str())))
+    }
+}
+
+/// 路由表：按顺序匹配项目，都不匹配时使用默认项目
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RoutingTable {
+    pub projects: Vec<Project>,
+    #[serde(default)]
+    pub default_project: Option<String>,
+}
+
+/// 发往单个项目的一批事件
+#[derive(Serialize)]
+pub struct ProjectBatch {
+    pub endpoint: String,
+    pub scrub_profile: String,
+    pub events: Vec<ErrorEnvelope>,
+    /// 被项目采样率丢弃的事件数
+    pub sampled_out: u32,
+}
+
+/// `route_all` 的结果，`unrouted` 为没有任何项目接收的错误数
+#[derive(Serialize, Default)]
+pub struct RoutedBatches {
+    pub batches: BTreeMap<String, ProjectBatch>,
+    pub unrouted: u32,
+}
+
+impl RoutingTable {
+    /// 校验项目配置：键唯一、采样率在 [0, 1]、默认项目存在
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, project) in self.projects.iter().enumerate() {
+            if self.projects[..i].iter().any(|other| other.key == project.key) {
+                return Err(format!("项目键重复: {}", project.key));
+            }
+            if !(0.0..=1.0).contains(&project.sample_rate) {
+                return Err(format!("项目 {} 的采样率无效: {}", project.key, project.sample_rate));
+            }
+        }
+        match &self.default_project {
+            Some(key) if self.project(key).is_none() => Err(format!("默认项目不存在: {}", key)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn project(&self, key: &str) -> Option<&Project> {
+        self.projects.iter().find(|project| project.key == key)
+    }
+
+    /// 为错误选择项目：按顺序取第一个负责团队或来源命中的项目
+    pub fn route(&self, owners: &[String], frames: &[StackFrame]) -> Option<&Project> {
+        self.projects.iter()
+            .find(|project| project.matches(owners, frames))
+            .or_else(|| self.default_project.as_deref().and_then(|key| self.project(key)))
+    }
+
+    /// 解析每个错误栈并按项目分批，按事件ID确定性采样，采样率按推断的严重级别调整
+    pub fn route_all(&self, stacks: &[String], received: f64) -> RoutedBatches {
+        let parser = ErrorParser::new();
+        let mut routed = RoutedBatches::default();
+        for stack in stacks {
+            let frames = parser.parse_frames(stack);
+            let Some(project) = self.route(&ownership::owners_for_frames(&frames), &frames) else {
+                routed.unrouted += 1;
+                continue;
+            };
+            let level = severity::infer_from_stack(stack, &frames);
+            let envelope = envelope::build(stack, frames, received, None);
+            let batch = routed.batches.entry(project.key.clone()).or_insert_with(|| ProjectBatch {
+                endpoint: project.endpoint.clone(),
+                scrub_profile: project.scrub_profile.clone(),
+                events: Vec::new(),
+                sampled_out: 0,
+            });
+            if should_sample_severity(&envelope.event_id, project.sample_rate, level) {
+                batch.events.push(envelope);
+            } else {
+                batch.sampled_out += 1;
+            }
+        }
+        routed
+    }
+}
+
+thread_local! {
+    static TABLE: RefCell<RoutingTable> = RefCell::new(RoutingTable::default());
+}
+
+/// 校验并替换全局路由表
+pub fn set_table(table: RoutingTable) -> Result<(), String> {
+    table.validate()?;
+    TABLE.with(|current| *current.borrow_mut() = table);
+    Ok(())
+}
+
+pub fn route_all(stacks: &[String], received: f64) -> RoutedBatches {
+    TABLE.with(|table| table.borrow().route_all(stacks, received))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> RoutingTable {
+        serde_json::from_str(r#"{
+            "projects": [
+                {"key": "checkout", "endpoint": "https://ingest.test/1", "owners": ["@payments"]},
+                {"key": "search", "endpoint": "https://ingest.test/2", "origins": ["https://cdn.test/search/"], "scrub_profile": "strict"},
+                {"key": "shell", "endpoint": "https://ingest.test/3", "sample_rate": 0}
+            ],
+            "default_project": "shell"
+        }"#).unwrap()
+    }
+
+    fn route_key(table: &RoutingTable, owners: &[&str], stack: &str) -> Option<String> {
+        let owners: Vec<String> = owners.iter().map(|owner| owner.to_string()).collect();
+        table.route(&owners, &ErrorParser::new().parse_frames(stack)).map(|project| project.key.clone())
+    }
+
+    #[test]
+    fn test_route_by_owner_origin_and_default() {
+        let table = table();
+        assert!(table.validate().is_ok());
+
+        let search = "Error: b\n at r (https://cdn.test/search/node_modules/x.js:1:1)\n at find (https://cdn.test/search/app.js:2:2)";
+        let shell = "Error: c\n at main (https://cdn.test/shell/app.js:1:1)";
+        assert_eq!(route_key(&table, &["@payments"], shell).as_deref(), Some("checkout"));
+        assert_eq!(route_key(&table, &[], search).as_deref(), Some("search"));
+        assert_eq!(route_key(&table, &[], shell).as_deref(), Some("shell"));
+
+        let routed = table.route_all(&[search.to_string(), search.to_string(), shell.to_string()], 0.0);
+        assert_eq!(routed.batches["search"].events.len(), 2);
+        assert_eq!(routed.batches["search"].scrub_profile, "strict");
+        // 采样率为0的项目只计数
+        assert_eq!((routed.batches["shell"].events.len(), routed.batches["shell"].sampled_out), (0, 1));
+        assert_eq!(routed.unrouted, 0);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut table = table();
+        table.default_project = Some("missing".to_string());
+        assert!(table.validate().is_err());
+        table.default_project = None;
+        table.projects[2].sample_rate = 1.5;
+        assert!(table.validate().is_err());
+
+        let mut table = RoutingTable::default();
+        assert_eq!(table.route_all(&["Error: x".to_string()], 0.0).unrouted, 1);
+        let project: Project = serde_json::from_str(r#"{"key": "a", "endpoint": ""}"#).unwrap();
+        assert_eq!((project.sample_rate, project.scrub_profile.as_str()), (1.0, "default"));
+        table.projects = vec![project.clone(), project];
+        assert!(table.validate().is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use serde::{Serialize, Deserialize};
+use std::cell::Cell;
+use crate::hash::xxhash64;
+use crate::severity::Severity;
+
+// 参与判定的随机位数，与W3C trace-context level 2的随机trace id一致
+const RANDOMNESS_BITS: u32 = 56;
+const RANDOMNESS_MASK: u64 = (1 << RANDOMNESS_BITS) - 1;
+
+/// 取出ID的56位随机值：十六进制的trace id（含带连字符的UUID）取最低56位，其他ID取xxhash64
+pub fn randomness(id: &str) -> u64 {
+    let hex: String = id.chars().filter(|c| *c != '-').collect();
+    if hex.len() >= 14 && hex.len() <= 32 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        if let Ok(value) = u64::from_str_radix(&hex[hex.len() - 14..], 16) {
+            return value;
+        }
+    }
+    xxhash64(id.as_bytes(), 0) & RANDOMNESS_MASK
+}
+
+/// 各严重级别的采样系数，与项目或调用方给出的采样率相乘；JSON为 `{"warning": 0.5, "info": 0.1}`，未给出的级别为1
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct SeverityRates {
+    pub fatal: f64,
+    pub error: f64,
+    pub warning: f64,
+    pub info: f64,
+}
+
+impl Default for SeverityRates {
+    fn default() -> Self {
+        SeverityRates { fatal: 1.0, error: 1.0, warning: 1.0, info: 1.0 }
+    }
+}
+
+impl SeverityRates {
+    pub fn rate(&self, severity: Severity) -> f64 {
+        match severity {
+            Severity::Fatal => self.fatal,
+            Severity::Error => self.error,
+            Severity::Warning => self.warning,
+            Severity::Info => self.info,
+        }
+    }
+}
+
+thread_local! {
+    static SEVERITY_RATES: Cell<SeverityRates> = Cell::new(SeverityRates::default());
+}
+
+/// 替换全局的级别采样系数
+pub fn set_severity_rates(rates: SeverityRates) {
+    SEVERITY_RATES.with(|current| current.set(rates));
+}
+
+/// 按严重级别选择的采样率：`rate` 乘以该级别的系数
+pub fn rate_for(rate: f64, severity: Severity) -> f64 {
+    rate * SEVERITY_RATES.with(|rates| rates.get().rate(severity))
+}
+
+/// 按严重级别调整采样率后确定性采样
+pub fn should_sample_severity(id: &str, rate: f64, severity: Severity) -> bool {
+    should_sample(id, rate_for(rate, severity))
+}
+
+/// 按ID确定性采样：同一ID在JS SDK、Worker与后端重新计算时得到相同结果。
+/// 采用OpenTelemetry的阈值算法，随机值不小于 `(1 - rate) * 2^56` 时采样
+pub fn should_sample(id: &str, rate: f64) -> bool {
+    if rate.is_nan() || rate <= 0.0 {
+        return false;
+    }
+    if rate >= 1.0 {
+        return true;
+    }
+
+    let threshold = ((1.0 - rate) * (1u64 << RANDOMNESS_BITS) as f64) as u64;
+    randomness(id) >= threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_id_randomness() {
+        // 最低56位为 0xffffffffffffff 的trace id总会被采样
+        assert!(should_sample("4bf92f3577b34da6a3ffffffffffffff", 0.0001));
+        assert!(!should_sample("4bf92f3577b34da6a300000000000000", 0.9999));
+        assert_eq!(randomness("4bf92f35-77b3-4da6-a3ce-929d0e0e4736"), 0xce929d0e0e4736);
+
+        assert!(should_sample("anything", 1.0));
+        assert!(!should_sample("anything", 0.0));
+        assert!(!should_sample("anything", f64::NAN));
+    }
+
+    #[test]
+    fn test_sampling_is_deterministic_and_proportional() {
+        assert_eq!(should_sample("session-42", 0.3), should_sample("session-42", 0.3));
+
+        let sampled = (0..10_000).filter(|i| should_sample(&format!("session-{}", i), 0.25)).count();
+        assert!((2_200..2_800).contains(&sampled), "{}", sampled);
+
+        // 提高采样率只会增加被采样的ID
+        let low: Vec<bool> = (0..500).map(|i| should_sample(&format!("s{}", i), 0.1)).collect();
+        let high: Vec<bool> = (0..500).map(|i| should_sample(&format!("s{}", i), 0.5)).collect();
+        assert!(low.iter().zip(&high).all(|(low, high)| !low || *high));
+    }
+
+    #[test]
+    fn test_severity_rates() {
+        set_severity_rates(serde_json::from_str(r#"{"info": 0, "warning": 0.5}"#).unwrap());
+        assert_eq!((rate_for(0.5, Severity::Warning), rate_for(0.5, Severity::Fatal)), (0.25, 0.5));
+        assert!(!should_sample_severity("4bf92f3577b34da6a3ffffffffffffff", 1.0, Severity::Info));
+        assert!(should_sample_severity("4bf92f3577b34da6a3ffffffffffffff", 1.0, Severity::Error));
+        set_severity_rates(SeverityRates::default());
+    }
+}
@@ -0,0 +1,89 @@
+use wasm_bindgen::prelude::*;
+use crate::exported_frame::ExportedStackFrame;
+use crate::naming;
+use crate::parser::ErrorParser;
+use crate::utils::console_log;
+
+/// 复用输出缓冲区的解析会话
+///
+/// 结果JSON直接写入会话持有的字节缓冲区，调用方用 `output_ptr()`/`output_len()`
+/// 从WASM内存读取（如 `new Uint8Array(memory.buffer, ptr, len)`），高频调用时避免每次分配字符串并复制。
+#[wasm_bindgen]
+pub struct ParserSession {
+    parser: ErrorParser,
+    output: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl ParserSession {
+    pub fn new() -> Self {
+        ParserSession {
+            parser: ErrorParser::new(),
+            output: Vec::new(),
+        }
+    }
+
+    /// 解析栈信息并把栈帧JSON写入输出缓冲区，返回写入的字节数；缓冲区内容在下一次调用前有效
+    pub fn parse_into(&mut self, stack: &str) -> u32 {
+        self.output.clear();
+        let frames: Vec<ExportedStackFrame> = self.parser.parse_frames(stack).into_iter()
+            .map(ExportedStackFrame::from)
+            .collect();
+        if let Err(e) = naming::write_json(&mut self.output, &frames, naming::field_naming()) {
+            console_log(&format!("JSON序列化错误: {}", e));
+            self.output.clear();
+            self.output.extend_from_slice(b"[]");
+        }
+        self.output.len() as u32
+    }
+
+    /// 输出缓冲区的地址，缓冲区扩容后会变化，每次 `parse_into` 之后重新读取
+    pub fn output_ptr(&self) -> usize {
+        self.output.as_ptr() as usize
+    }
+
+    pub fn output_len(&self) -> u32 {
+        self.output.len() as u32
+    }
+
+    /// 释放输出缓冲区占用的内存
+    pub fn shrink(&mut self) {
+        self.output = Vec::new();
+    }
+}
+
+impl ParserSession {
+    /// 上一次 `parse_into` 写入的内容
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+}
+
+impl Default for ParserSession {
+    fn default() -> Self {
+        ParserSession::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_into_reuses_buffer() {
+        let mut session = ParserSession::new();
+        let written = session.parse_into("Error: x\n at render (/src/App.js:1:2)\n at main (/src/main.js:3:4)");
+        assert_eq!(written, session.output_len());
+        let frames: serde_json::Value = serde_json::from_slice(session.output()).unwrap();
+        assert_eq!(frames[1]["function_name"].as_str(), Some("main"));
+
+        // 更短的结果写入同一块内存
+        let ptr = session.output_ptr();
+        session.parse_into("Error: y\n at f (/a.js:1:1)");
+        assert_eq!(session.output_ptr(), ptr);
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(session.output()).unwrap().as_array().map(Vec::len), Some(1));
+
+        session.parse_into("");
+        assert_eq!(session.output(), b"[]");
+    }
+}
@@ -0,0 +1,177 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use crate::parser::StackFrame;
+
+/// 错误严重级别
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl Severity {
+    /// 按名称解析，不区分大小写
+    pub fn from_name(name: &str) -> Option<Severity> {
+        match name.to_ascii_lowercase().as_str() {
+            "info" => Some(Severity::Info),
+            "warning" => Some(Severity::Warning),
+            "error" => Some(Severity::Error),
+            "fatal" => Some(Severity::Fatal),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Fatal => "fatal",
+        }
+    }
+}
+
+/// 错误的捕获方式，由JS层上报
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Mechanism {
+    /// 是否被业务代码捕获处理
+    pub handled: bool,
+    /// 捕获来源：onerror、unhandledrejection、console、network 等
+    pub source: String,
+    /// 是否为重试中的请求失败
+    pub retry: bool,
+}
+
+/// 单条严重级别规则，所有已设置的条件都满足时命中
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SeverityRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_frame_in_app: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_contains: Option<String>,
+    pub severity: Severity,
+}
+
+impl SeverityRule {
+    fn matches(&self, mechanism: &Mechanism, message: &str, top_frame_in_app: bool) -> bool {
+        self.handled.map_or(true, |handled| handled == mechanism.handled)
+            && self.source.as_ref().map_or(true, |source| source == &mechanism.source)
+            && self.retry.map_or(true, |retry| retry == mechanism.retry)
+            && self.top_frame_in_app.map_or(true, |in_app| in_app == top_frame_in_app)
+            && self.message_contains.as_ref().map_or(true, |needle| message.contains(needle.as_str()))
+    }
+}
+
+/// 有序规则表，第一条命中的规则决定级别
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SeverityRules {
+    pub rules: Vec<SeverityRule>,
+    #[serde(default = "default_severity")]
+    pub default: Severity,
+}
+
+fn default_severity() -> Severity {
+    Severity::Error
+}
+
+impl Default for SeverityRules {
+    fn default() -> Self {
+        let rule = |handled, source: Option<&str>, retry, top_frame_in_app, severity| SeverityRule {
+            handled,
+            source: source.map(str::to_string),
+            retry,
+            top_frame_in_app,
+            message_contains: None,
+            severity,
+        };
+
+        SeverityRules {
+            rules: vec![
+                // 网络重试只作为提示信息
+                rule(None, Some("network"), Some(true), None, Severity::Info),
+                // 未捕获且栈顶在业务代码中
+                rule(Some(false), None, None, Some(true), Severity::Fatal),
+                rule(None, Some("console"), None, None, Severity::Warning),
+                rule(Some(true), None, None, None, Severity::Warning),
+            ],
+            default: Severity::Error,
+        }
+    }
+}
+
+impl SeverityRules {
+    /// 根据捕获方式、错误消息和栈帧推断级别
+    pub fn evaluate(&self, mechanism: &Mechanism, message: &str, frames: &[StackFrame]) -> Severity {
+        let top_frame_in_app = frames.first().is_some_and(StackFrame::is_in_app);
+        self.rules.iter()
+            .find(|rule| rule.matches(mechanism, message, top_frame_in_app))
+            .map_or(self.default, |rule| rule.severity)
+    }
+}
+
+thread_local! {
+    static RULES: RefCell<SeverityRules> = RefCell::new(SeverityRules::default());
+}
+
+/// 替换全局规则表
+pub fn set_rules(rules: SeverityRules) {
+    RULES.with(|current| *current.borrow_mut() = rules);
+}
+
+/// 没有捕获方式信息时按未捕获处理，从栈文本推断级别
+pub fn infer_from_stack(stack: &str, frames: &[StackFrame]) -> Severity {
+    let message = stack.lines().next().unwrap_or("").trim();
+    infer(&Mechanism::default(), message, frames)
+}
+
+/// 使用全局规则表推断级别
+pub fn infer(mechanism: &Mechanism, message: &str, frames: &[StackFrame]) -> Severity {
+    RULES.with(|rules| rules.borrow().evaluate(mechanism, message, frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(file: &str) -> StackFrame {
+        StackFrame::new("fn".to_string(), file.to_string(), 1, 1)
+    }
+
+    fn mechanism(handled: bool, source: &str, retry: bool) -> Mechanism {
+        Mechanism { handled, source: source.to_string(), retry }
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let rules = SeverityRules::default();
+        let app = vec![frame("/src/App.js")];
+        let vendor = vec![frame("/node_modules/react-dom/index.js")];
+
+        assert_eq!(rules.evaluate(&mechanism(false, "onerror", false), "", &app), Severity::Fatal);
+        assert_eq!(rules.evaluate(&mechanism(false, "onerror", false), "", &vendor), Severity::Error);
+        assert_eq!(rules.evaluate(&mechanism(true, "console", false), "", &app), Severity::Warning);
+        assert_eq!(rules.evaluate(&mechanism(true, "network", true), "", &[]), Severity::Info);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let rules: SeverityRules = serde_json::from_str(
+            r#"{"rules": [{"message_contains": "ResizeObserver", "severity": "info"}], "default": "warning"}"#,
+        ).unwrap();
+
+        let frames = vec![frame("/src/App.js")];
+        let unhandled = mechanism(false, "onerror", false);
+        assert_eq!(rules.evaluate(&unhandled, "ResizeObserver loop limit exceeded", &frames), Severity::Info);
+        assert_eq!(rules.evaluate(&unhandled, "TypeError: x is undefined", &frames), Severity::Warning);
+    }
+}
@@ -1,4 +1,278 @@
 use wasm_bindgen::prelude::*;
+use std::cell::RefCell;
+
+/// 使用SIMD一次性扫描出所有换行符的位置
+pub fn find_newlines(bytes: &[u8]) -> Vec<usize> {
+    let mut positions = Vec::new();
+
+    #[cfg(target_feature = "simd128")]
+    unsafe {
+        use std::arch::wasm32::*;
+        let len = bytes.len();
+        let newline = u8x16_splat(b'\n');
+        let mut i = 0;
+
+        while i + 16 <= len {
+            let chunk = v128_load(bytes.as_ptr().add(i) as *const v128);
+            let mut mask = i8x16_bitmask(i8x16_eq(chunk, newline));
+
+            // 逐位取出命中的换行符
+            while mask != 0 {
+                positions.push(i + mask.trailing_zeros() as usize);
+                mask &= mask - 1;
+            }
+
+            i += 16;
+        }
+
+        // 处理剩余字节
+        for (offset, &b) in bytes[i..].iter().enumerate() {
+            if b == b'\n' {
+                positions.push(i + offset);
+            }
+        }
+    }
+
+    #[cfg(not(target_feature = "simd128"))]
+    {
+        // 降级处理：逐字节扫描
+        for (i, &b) in bytes.iter().enumerate() {
+            if b == b'\n' {
+                positions.push(i);
+            }
+        }
+    }
+
+    positions
+}
+
+/// 使用SIMD从 `end` 向前查找最后一个 `:` 的位置
+pub fn rfind_colon(bytes: &[u8], end: usize) -> Option<usize> {
+    let bytes = &bytes[..end.min(bytes.len())];
+
+    #[cfg(target_feature = "simd128")]
+    unsafe {
+        use std::arch::wasm32::*;
+        let colon = u8x16_splat(b':');
+        let mut i = bytes.len();
+
+        while i >= 16 {
+            let chunk = v128_load(bytes.as_ptr().add(i - 16) as *const v128);
+            let mask = i8x16_bitmask(i8x16_eq(chunk, colon));
+            if mask != 0 {
+                // 最高位对应块内最靠后的命中
+                return Some(i - 16 + (15 - mask.leading_zeros() as usize));
+            }
+            i -= 16;
+        }
+
+        // 处理开头剩余的字节
+        bytes[..i].iter().rposition(|&b| b == b':')
+    }
+
+    #[cfg(not(target_feature = "simd128"))]
+    {
+        // 降级处理：逐字节扫描
+        bytes.iter().rposition(|&b| b == b':')
+    }
+}
+
+/// 非空且全部为ASCII数字的十进制串，超出u32范围时返回None
+fn parse_digits(bytes: &[u8]) -> Option<u32> {
+    if bytes.is_empty() {
+        return None;
+    }
+    bytes.iter().try_fold(0u32, |value, &b| {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        value.checked_mul(10)?.checked_add((b - b'0') as u32)
+    })
+}
+
+/// 使用SIMD从 `start` 向后查找第一个ASCII数字的位置
+pub fn find_digit(bytes: &[u8], start: usize) -> Option<usize> {
+    let i = start.min(bytes.len());
+
+    #[cfg(target_feature = "simd128")]
+    let i = unsafe {
+        use std::arch::wasm32::*;
+        let mut i = i;
+        // 减去 '0' 后按无符号比较，小于10的即为数字
+        let zero = u8x16_splat(b'0');
+        let ten = u8x16_splat(10);
+        while i + 16 <= bytes.len() {
+            let chunk = v128_load(bytes.as_ptr().add(i) as *const v128);
+            let mask = i8x16_bitmask(u8x16_lt(u8x16_sub(chunk, zero), ten));
+            if mask != 0 {
+                return Some(i + mask.trailing_zeros() as usize);
+            }
+            i += 16;
+        }
+        i
+    };
+
+    // 剩余字节（或未启用SIMD时）逐字节扫描
+    bytes[i..].iter().position(u8::is_ascii_digit).map(|offset| i + offset)
+}
+
+/// 使用SIMD从 `start` 向后查找下一个换行符的位置
+pub fn find_newline(bytes: &[u8], start: usize) -> Option<usize> {
+    let i = start.min(bytes.len());
+
+    #[cfg(target_feature = "simd128")]
+    let i = unsafe {
+        use std::arch::wasm32::*;
+        let newline = u8x16_splat(b'\n');
+        let mut i = i;
+        while i + 16 <= bytes.len() {
+            let chunk = v128_load(bytes.as_ptr().add(i) as *const v128);
+            let mask = i8x16_bitmask(i8x16_eq(chunk, newline));
+            if mask != 0 {
+                return Some(i + mask.trailing_zeros() as usize);
+            }
+            i += 16;
+        }
+        i
+    };
+
+    bytes[i..].iter().position(|&b| b == b'\n').map(|offset| i + offset)
+}
+
+/// 一行是否像栈帧：V8的 `at ...` 带位置或括号，Gecko/Safari的 `func@file:line:col`、`func@[native code]`
+fn is_frame_line(line: &[u8]) -> bool {
+    let line = line.trim_ascii();
+    if let Some(rest) = line.strip_prefix(b"at ") {
+        return rest.ends_with(b")") || find_location_suffix(rest).is_some();
+    }
+    line.contains(&b'@')
+        && (find_location_suffix(line).is_some() || line.ends_with(b"[native code]") || line.ends_with(b"[wasm code]"))
+}
+
+/// 不分配内存地逐行扫描，返回像栈帧的行数，达到 `limit` 时提前停止
+fn scan_frame_lines(bytes: &[u8], limit: u32) -> u32 {
+    let mut count = 0;
+    let mut start = 0;
+    while start < bytes.len() && count < limit {
+        let end = find_newline(bytes, start).unwrap_or(bytes.len());
+        if is_frame_line(&bytes[start..end]) {
+            count += 1;
+        }
+        start = end + 1;
+    }
+    count
+}
+
+/// 像栈帧的行数，用于在完整解析前估算规模
+pub fn count_frames(text: &str) -> u32 {
+    scan_frame_lines(text.as_bytes(), u32::MAX)
+}
+
+/// 是否至少有一行像栈帧，找到第一行即返回；用于拒绝 `console.error` 传入的任意字符串
+pub fn looks_like_stack(text: &str) -> bool {
+    scan_frame_lines(text.as_bytes(), 1) > 0
+}
+
+/// 输入中所有十进制数字串及其起始字节偏移，超出u32范围的跳过
+pub fn numbers_with_offsets(bytes: &[u8]) -> Vec<(u32, usize)> {
+    let mut numbers = Vec::new();
+    let mut i = 0;
+    while let Some(start) = find_digit(bytes, i) {
+        let end = bytes[start..].iter().position(|b| !b.is_ascii_digit()).map_or(bytes.len(), |len| start + len);
+        if let Some(value) = parse_digits(&bytes[start..end]) {
+            numbers.push((value, start));
+        }
+        i = end;
+    }
+    numbers
+}
+
+/// 识别位置末尾的 `:行:列` 后缀，返回文件部分的结束位置与行列号。
+/// 只做冒号查找与数字校验，不分配内存，长URL中的其他冒号不影响结果
+pub fn find_location_suffix(bytes: &[u8]) -> Option<(usize, u32, u32)> {
+    let column_colon = rfind_colon(bytes, bytes.len())?;
+    let column = parse_digits(&bytes[column_colon + 1..])?;
+    let line_colon = rfind_colon(bytes, column_colon)?;
+    let line = parse_digits(&bytes[line_colon + 1..column_colon])?;
+    Some((line_colon, line, column))
+}
+
+/// 识别只有行号的 `:行` 后缀（缺少列号的帧），返回文件部分的结束位置与行号
+fn find_line_suffix(bytes: &[u8]) -> Option<(usize, u32)> {
+    let line_colon = rfind_colon(bytes, bytes.len())?;
+    let line = parse_digits(&bytes[line_colon + 1..])?;
+    Some((line_colon, line))
+}
+
+/// 输入文本的行偏移索引
+///
+/// 只扫描一遍输入，之后按行号切片，避免各解析器重复调用 `lines()`/`split('\n')`。
+/// 行尾的 `\r` 会被去掉，末尾的空行不计入，行为与 `str::lines()` 一致。
+pub struct LineIndex<'a> {
+    text: &'a str,
+    newlines: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// 为输入文本建立行索引
+    pub fn new(text: &'a str) -> Self {
+        LineIndex {
+            text,
+            newlines: find_newlines(text.as_bytes()),
+        }
+    }
+
+    /// 用之前建立的换行偏移重建索引（`text` 须与建立时相同），跨调用继续处理同一输入时不再重新扫描
+    pub fn from_newlines(text: &'a str, newlines: Vec<usize>) -> Self {
+        LineIndex { text, newlines }
+    }
+
+    /// 取出换行偏移，供 `from_newlines` 复用
+    pub fn into_newlines(self) -> Vec<usize> {
+        self.newlines
+    }
+
+    /// 行数
+    pub fn len(&self) -> usize {
+        if self.text.is_empty() {
+            return 0;
+        }
+        match self.newlines.last() {
+            Some(&last) if last + 1 == self.text.len() => self.newlines.len(),
+            _ => self.newlines.len() + 1,
+        }
+    }
+
+    /// 是否没有任何行
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 第 `index` 行的起始字节偏移
+    pub fn line_start(&self, index: usize) -> usize {
+        if index == 0 {
+            0
+        } else {
+            self.newlines[index - 1] + 1
+        }
+    }
+
+    /// 获取第 `index` 行的内容（不含换行符）
+    pub fn line(&self, index: usize) -> Option<&'a str> {
+        if index >= self.len() {
+            return None;
+        }
+        let start = self.line_start(index);
+        let end = self.newlines.get(index).copied().unwrap_or(self.text.len());
+        let line = &self.text[start..end];
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+
+    /// 按顺序迭代所有行
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> + '_ {
+        (0..self.len()).filter_map(move |i| self.line(i))
+    }
+}
 
 /// SIMD优化的错误栈解析器
 #[wasm_bindgen]
@@ -16,10 +290,18 @@ impl SimdParser {
         unsafe { self.simd_extract_numbers(bytes) }
     }
     
-    /// 使用SIMD指令加速行列号识别
+    /// 同 `parse_numbers`，按 `[值, 字节偏移, 值, 字节偏移, ...]` 平铺返回，偏移可用于对应回所在的帧
+    pub fn parse_numbers_positions(&self, input: &str) -> Vec<u32> {
+        self.parse_numbers_with_offsets(input).into_iter()
+            .flat_map(|(value, offset)| [value, offset as u32])
+            .collect()
+    }
+
+    /// 识别栈帧的行列号，按 `[行, 列, 帧下标, ...]` 返回，帧下标与 `parse`（`ErrorParser::parse_frames`）的结果对应。
+    /// 帧的识别与完整流程相同，支持V8、Firefox/Safari等全部格式，只有位置后缀的冒号与数字扫描使用SIMD，
+    /// 启用与未启用simd128时结果一致
     pub fn parse_line_column(&self, input: &str) -> Vec<u32> {
-        let bytes = input.as_bytes();
-        unsafe { self.simd_extract_line_column(bytes) }
+        crate::parser::ErrorParser::new().parse_line_column_indexed(input)
     }
 
     /// 使用SIMD指令加速完整错误栈解析
@@ -34,14 +316,15 @@ impl SimdParser {
         }
         
         // 按行分割
-        for line in stack.lines() {
+        let index = LineIndex::new(stack);
+        for line in index.lines() {
             if line.contains(" at ") {
                 let parts: Vec<&str> = line.split(" at ").collect();
                 if parts.len() > 1 {
                     let func_part = parts[1].trim();
                     
                     // 提取函数名
-                    let mut function_name = "";
+                    let function_name;
                     let mut file_name = "";
                     let mut line_num = 0;
                     let mut col_num = 0;
@@ -54,19 +337,16 @@ impl SimdParser {
                             let file_part = &func_part[name_end..];
                             let file_part = file_part.trim_start_matches('(').trim_end_matches(')');
                             
-                            let location_parts: Vec<&str> = file_part.split(':').collect();
-                            file_name = location_parts[0];
-                            
-                            if location_parts.len() > 1 {
-                                if let Ok(l) = location_parts[1].parse::<u32>() {
-                                    line_num = l;
-                                }
-                            }
-                            
-                            if location_parts.len() > 2 {
-                                if let Ok(c) = location_parts[2].parse::<u32>() {
-                                    col_num = c;
-                                }
+                            if let Some((end, line, column)) = find_location_suffix(file_part.as_bytes()) {
+                                file_name = &file_part[..end];
+                                line_num = line;
+                                col_num = column;
+                            } else if let Some((end, line)) = find_line_suffix(file_part.as_bytes()) {
+                                // `file:line` 没有列号，列号记为0
+                                file_name = &file_part[..end];
+                                line_num = line;
+                            } else {
+                                file_name = file_part;
                             }
                         }
                     } else {
@@ -165,46 +445,8 @@ impl SimdParser {
         }
     }
 
-    /// SIMD优化的行列号提取
-    unsafe fn simd_extract_line_column(&self, bytes: &[u8]) -> Vec<u32> {
-        #[cfg(target_feature = "simd128")]
-        {
-            // 查找 "行:列" 格式的数字对
-            let mut result = Vec::new();
-            let mut numbers = self.simd_extract_numbers(bytes);
-            
-            // 如果是连续的两个数字，认为是行列号
-            if numbers.len() >= 2 {
-                for i in 0..numbers.len() - 1 {
-                    result.push(numbers[i]);
-                    result.push(numbers[i + 1]);
-                }
-            }
-            
-            result
-        }
-        #[cfg(not(target_feature = "simd128"))]
-        {
-            let s = std::str::from_utf8_unchecked(bytes);
-            let mut result = Vec::new();
-            // 用正则匹配行列号更可靠，但这里简单处理
-            for part in s.split_whitespace() {
-                if part.contains(':') {
-                    let parts: Vec<&str> = part.split(':').collect();
-                    if parts.len() >= 2 {
-                        for p in parts {
-                            if let Ok(num) = p.parse::<u32>() {
-                                result.push(num);
-                            }
-                        }
-                    }
-                }
-            }
-            result
-        }
-    }
-    
     /// SIMD优化的完整错误栈解析
+    #[allow(dead_code)]
     unsafe fn simd_parse_stack(&self, bytes: &[u8]) -> String {
         let s = std::str::from_utf8_unchecked(bytes);
         let mut result = String::new();
@@ -331,7 +573,7 @@ impl SimdParser {
         #[cfg(not(target_feature = "simd128"))]
         {
             // 降级处理：普通的栈解析
-            for line in s.lines() {
+            for line in LineIndex::new(s).lines() {
                 if line.contains(" at ") {
                     if let Some(file_info) = line.split(" at ").nth(1) {
                         if let Some(file_path) = file_info.trim_start_matches('(').trim_end_matches(')').split(':').next() {
@@ -357,3 +599,141 @@ impl SimdParser {
         result
     }
 }
+
+impl SimdParser {
+    /// 提取数字及其在输入中的起始字节偏移
+    pub fn parse_numbers_with_offsets(&self, input: &str) -> Vec<(u32, usize)> {
+        numbers_with_offsets(input.as_bytes())
+    }
+}
+
+impl Default for SimdParser {
+    fn default() -> Self {
+        SimdParser::new()
+    }
+}
+
+thread_local! {
+    // 长度前缀的数字输出缓冲区：`[count, values...]`，跨调用复用
+    static NUMBER_BUFFER: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 将结果写入复用的输出缓冲区，返回写入的数字个数
+pub fn fill_number_buffer(values: &[u32]) -> u32 {
+    NUMBER_BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        buffer.clear();
+        buffer.push(values.len() as u32);
+        buffer.extend_from_slice(values);
+    });
+    values.len() as u32
+}
+
+/// 输出缓冲区在WASM内存中的地址，每次写入后可能变化
+pub fn number_buffer_ptr() -> usize {
+    NUMBER_BUFFER.with(|buffer| buffer.borrow().as_ptr() as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_newlines() {
+        let text = "Error: test\n at a (/a.js:1:2)\n at b (/b.js:3:4)\n";
+        assert_eq!(find_newlines(text.as_bytes()), vec![11, 29, 47]);
+        assert!(find_newlines(b"no newline here").is_empty());
+    }
+
+    #[test]
+    fn test_line_index() {
+        let index = LineIndex::new("first\r\nsecond\n\nfourth");
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.line(0), Some("first"));
+        assert_eq!(index.line(2), Some(""));
+        assert_eq!(index.line(3), Some("fourth"));
+        assert_eq!(index.line(4), None);
+        assert_eq!(index.line_start(1), 7);
+
+        let trailing = LineIndex::new("a\nb\n");
+        assert_eq!(trailing.lines().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!(LineIndex::new("").is_empty());
+    }
+
+    #[test]
+    fn test_find_location_suffix() {
+        let url = "https://cdn.test:8443/a:b/app.js?v=1:2:10:15";
+        assert_eq!(find_location_suffix(url.as_bytes()), Some((url.len() - 6, 10, 15)));
+        assert_eq!(&url[..url.len() - 6], "https://cdn.test:8443/a:b/app.js?v=1:2");
+        assert_eq!(find_location_suffix(b"/src/app.js:7:0"), Some((11, 7, 0)));
+
+        assert_eq!(find_location_suffix(b"/src/app.js:7"), None);
+        assert_eq!(find_location_suffix(b"/src/app.js:7:"), None);
+        assert_eq!(find_location_suffix(b"/src/app.js:x:1"), None);
+        assert_eq!(find_location_suffix(b"/a.js:1:99999999999"), None);
+
+        // 跨越多个16字节块查找
+        let long = format!("{}:3:4", "x".repeat(40));
+        assert_eq!(rfind_colon(long.as_bytes(), long.len()), Some(42));
+        assert_eq!(rfind_colon(long.as_bytes(), 42), Some(40));
+        assert_eq!(rfind_colon(long.as_bytes(), 40), None);
+    }
+
+    #[test]
+    fn test_parse_stack_simd_without_column() {
+        let frames = SimdParser::new().parse_stack_simd("Error: x\n    at load (/src/app.js:7)\n    at run (https://cdn.test:8443/app.js:3:9)");
+        let locations: Vec<_> = frames.iter().map(|f| (f.file_name(), f.line_number(), f.column_number())).collect();
+        assert_eq!(locations, vec![
+            ("/src/app.js".to_string(), 7, 0),
+            ("https://cdn.test:8443/app.js".to_string(), 3, 9),
+        ]);
+    }
+
+    #[test]
+    fn test_numbers_with_offsets() {
+        let input = "Error 42\n at a (/src/app.js:1234:56) 99999999999 x7";
+        let parser = SimdParser::new();
+        assert_eq!(parser.parse_numbers_with_offsets(input), vec![(42, 6), (1234, 28), (56, 33), (7, 50)]);
+        assert_eq!(parser.parse_numbers_positions(input)[..4], [42, 6, 1234, 28]);
+        assert_eq!(find_digit(input.as_bytes(), 35), Some(37));
+        assert!(numbers_with_offsets(b"no digits").is_empty());
+    }
+
+    #[test]
+    fn test_parse_line_column_triples() {
+        let stack = "Error: x\n    at a (/src/a.js:10:15)\n    at b (/src/b.js:10:15)\n    at Array.map (native)\n    at c (/src/c.js:3:4)";
+        // 相邻帧的相同行列号不合并，没有位置的帧跳过但保留下标
+        assert_eq!(SimdParser::new().parse_line_column(stack), vec![10, 15, 0, 10, 15, 1, 3, 4, 3]);
+        assert!(SimdParser::new().parse_line_column("Error: 1:2").is_empty());
+
+        // 与完整流程的帧下标一致：Firefox/Safari帧与没有函数名的 `at url:l:c` 帧同样有位置
+        let mixed = "render@https://cdn.test/app.js:7:8\n    at https://cdn.test/vendor.js:5:6";
+        assert_eq!(SimdParser::new().parse_line_column(mixed), vec![7, 8, 0, 5, 6, 1]);
+        assert_eq!(SimdParser::new().parse_line_column(mixed), crate::parser::ErrorParser::new().parse_line_column_indexed(mixed));
+    }
+
+    #[test]
+    fn test_stack_prefilter() {
+        let stack = "TypeError: x\r\n    at a (/src/a.js:1:2)\n    at Array.map (<anonymous>)\n    at https://cdn.test/app.js:3:4\nload@https://cdn.test/app.js:5:6\nforEach@[native code]\n";
+        assert_eq!(count_frames(stack), 5);
+        assert!(looks_like_stack(stack));
+
+        assert!(!looks_like_stack("user clicked at least twice"));
+        assert!(!looks_like_stack("mail me at admin@example.com"));
+        assert!(!looks_like_stack(""));
+        assert_eq!(count_frames(&format!("{}\n    at f (/src/f.js:9:9)", "x".repeat(40))), 1);
+        assert_eq!(find_newline(b"ab\ncd\n", 3), Some(5));
+    }
+
+    #[test]
+    fn test_number_buffer() {
+        assert_eq!(fill_number_buffer(&[10, 15, 7]), 3);
+        NUMBER_BUFFER.with(|buffer| assert_eq!(*buffer.borrow(), vec![3, 10, 15, 7]));
+        let ptr = number_buffer_ptr();
+
+        // 长度不超过已有容量时复用同一块内存
+        assert_eq!(fill_number_buffer(&[1]), 1);
+        NUMBER_BUFFER.with(|buffer| assert_eq!(*buffer.borrow(), vec![1, 1]));
+        assert_eq!(number_buffer_ptr(), ptr);
+    }
+}
@@ -0,0 +1,224 @@
+use serde::Serialize;
+use crate::fingerprint::strip_query;
+use crate::parser::{ErrorParser, StackFrame};
+use crate::parser_options;
+
+/// 视为同一位置的最大行号漂移，发布后小范围改动不影响比较
+pub const DEFAULT_LINE_DRIFT: u32 = 5;
+
+/// 参与比较的栈顶帧数上限：比较的时间与差异表的内存按两侧帧数的乘积增长，更深的帧（通常是递归溢出）不比较
+pub const MAX_COMPARED_FRAMES: usize = 512;
+
+/// 截取参与比较的栈顶帧
+fn compared(frames: &[StackFrame]) -> &[StackFrame] {
+    &frames[..frames.len().min(MAX_COMPARED_FRAMES)]
+}
+
+/// 两帧是否为同一函数：函数名与文件相同（忽略查询参数），不比较行列号
+fn same_function(a: &StackFrame, b: &StackFrame) -> bool {
+    a.function_name_str() == b.function_name_str() && strip_query(a.relative_file_name()) == strip_query(b.relative_file_name())
+}
+
+/// 两帧是否视为相同：函数名与文件相同（忽略查询参数与列号），行号相差不超过 `line_drift`
+fn same_frame(a: &StackFrame, b: &StackFrame, line_drift: u32) -> bool {
+    same_function(a, b) && a.line_number().abs_diff(b.line_number()) <= line_drift
+}
+
+/// 基于帧级编辑距离的相似度，范围 [0, 1]，1为完全相同；两边都没有栈帧时为1。只比较栈顶 `MAX_COMPARED_FRAMES` 帧
+pub fn frame_similarity(a: &[StackFrame], b: &[StackFrame], line_drift: u32) -> f32 {
+    let (a, b) = (compared(a), compared(b));
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    // 只保留上一行的编辑距离
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, frame_a) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, frame_b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(!same_frame(frame_a, frame_b, line_drift));
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f32 / longest as f32
+}
+
+/// 解析两个错误栈并计算相似度
+pub fn similarity(stack_a: &str, stack_b: &str, line_drift: u32) -> f32 {
+    let parser = ErrorParser::new();
+    frame_similarity(&parser.parse_frames(stack_a), &parser.parse_frames(stack_b), line_drift)
+}
+
+/// 只在一侧出现的帧
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DiffFrame {
+    /// 在所属栈中的下标
+    pub index: usize,
+    pub function_name: String,
+    pub file_name: String,
+    pub line_number: u32,
+}
+
+impl DiffFrame {
+    fn new(index: usize, frame: &StackFrame) -> Self {
+        DiffFrame {
+            index,
+            function_name: frame.function_name_str().to_string(),
+            file_name: frame.relative_file_name().to_string(),
+            line_number: parser_options::options().export_line(frame.line_number()),
+        }
+    }
+}
+
+/// 两侧都有但行号漂移超出容差的帧
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ShiftedFrame {
+    pub index_a: usize,
+    pub index_b: usize,
+    pub function_name: String,
+    pub file_name: String,
+    pub line_a: u32,
+    pub line_b: u32,
+}
+
+/// 两个栈的结构化差异
+#[derive(Serialize, Debug, PartialEq)]
+pub struct StackDiff {
+    /// 只在b中出现的帧
+    pub added: Vec<DiffFrame>,
+    /// 只在a中出现的帧
+    pub removed: Vec<DiffFrame>,
+    pub shifted: Vec<ShiftedFrame>,
+    /// 行号漂移在容差内的帧数
+    pub unchanged: usize,
+    /// 栈顶帧（崩溃位置）是否变化
+    pub top_frame_changed: bool,
+    /// 有一侧超过 `MAX_COMPARED_FRAMES` 帧，更深的帧没有比较
+    #[serde(skip_serializing_if = "crate::is_false")]
+    pub truncated: bool,
+}
+
+/// 按函数对齐两个栈（最长公共子序列），对齐的帧行号相差超过 `line_drift` 时视为位移；
+/// 只比较栈顶 `MAX_COMPARED_FRAMES` 帧
+pub fn diff_frames(a: &[StackFrame], b: &[StackFrame], line_drift: u32) -> StackDiff {
+    let truncated = a.len() > MAX_COMPARED_FRAMES || b.len() > MAX_COMPARED_FRAMES;
+    let (a, b) = (compared(a), compared(b));
+    // lengths[i][j]：a[i..] 与 b[j..] 的最长公共子序列长度
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if same_function(&a[i], &b[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = StackDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        shifted: Vec::new(),
+        unchanged: 0,
+        top_frame_changed: match (a.first(), b.first()) {
+            (Some(top_a), Some(top_b)) => !same_frame(top_a, top_b, line_drift),
+            (top_a, top_b) => top_a.is_some() != top_b.is_some(),
+        },
+        truncated,
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if same_function(&a[i], &b[j]) {
+            if a[i].line_number().abs_diff(b[j].line_number()) <= line_drift {
+                diff.unchanged += 1;
+            } else {
+                let positions = parser_options::options();
+                diff.shifted.push(ShiftedFrame {
+                    index_a: i,
+                    index_b: j,
+                    function_name: a[i].function_name_str().to_string(),
+                    file_name: a[i].relative_file_name().to_string(),
+                    line_a: positions.export_line(a[i].line_number()),
+                    line_b: positions.export_line(b[j].line_number()),
+                });
+            }
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            diff.removed.push(DiffFrame::new(i, &a[i]));
+            i += 1;
+        } else {
+            diff.added.push(DiffFrame::new(j, &b[j]));
+            j += 1;
+        }
+    }
+    diff.removed.extend((i..a.len()).map(|i| DiffFrame::new(i, &a[i])));
+    diff.added.extend((j..b.len()).map(|j| DiffFrame::new(j, &b[j])));
+    diff
+}
+
+/// 解析两个错误栈并计算差异
+pub fn diff(stack_a: &str, stack_b: &str, line_drift: u32) -> StackDiff {
+    let parser = ErrorParser::new();
+    diff_frames(&parser.parse_frames(stack_a), &parser.parse_frames(stack_b), line_drift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STACK: &str = "TypeError: x\n at render (https://cdn.test/app.js?v=1:10:15)\n at update (https://cdn.test/app.js?v=1:40:2)\n at flush (https://cdn.test/vendor.js:5:1)\n at tick (https://cdn.test/vendor.js:9:1)";
+
+    #[test]
+    fn test_similarity() {
+        // 列号、查询参数与小幅行号漂移不影响
+        let drifted = STACK.replace("?v=1:10:15", "?v=2:12:99");
+        assert_eq!(similarity(STACK, &drifted, DEFAULT_LINE_DRIFT), 1.0);
+        assert!(similarity(STACK, &drifted, 0) < 1.0);
+
+        // 多出一帧：较长的5帧中编辑1次
+        let extra = STACK.replace(" at flush", " at wrap (https://cdn.test/vendor.js:1:1)\n at flush");
+        assert_eq!(similarity(STACK, &extra, DEFAULT_LINE_DRIFT), 0.8);
+
+        assert_eq!(similarity(STACK, "Error: y\n at other (/src/x.js:1:1)", DEFAULT_LINE_DRIFT), 0.0);
+        assert_eq!(similarity("Error: a", "Error: b", DEFAULT_LINE_DRIFT), 1.0);
+    }
+
+    #[test]
+    fn test_diff_stacks() {
+        let drifted = STACK.replace("app.js?v=1:10:15", "app.js?v=2:12:1").replace("app.js?v=1:40:2", "app.js?v=2:90:2");
+        let extra = drifted.replace(" at flush", " at wrap (https://cdn.test/vendor.js:1:1)\n at flush").replace("\n at tick (https://cdn.test/vendor.js:9:1)", "");
+        let result = diff(STACK, &extra, DEFAULT_LINE_DRIFT);
+
+        assert!(!result.top_frame_changed);
+        assert_eq!(result.unchanged, 2);
+        assert_eq!(result.shifted, vec![ShiftedFrame {
+            index_a: 1,
+            index_b: 1,
+            function_name: "update".to_string(),
+            file_name: "https://cdn.test/app.js?v=1".to_string(),
+            line_a: 40,
+            line_b: 90,
+        }]);
+        assert_eq!(result.added, vec![DiffFrame { index: 2, function_name: "wrap".to_string(), file_name: "https://cdn.test/vendor.js".to_string(), line_number: 1 }]);
+        assert_eq!(result.removed.iter().map(|frame| frame.index).collect::<Vec<_>>(), vec![3]);
+
+        // 崩溃位置移到其他函数
+        assert!(diff(STACK, &STACK.replace("render", "paint"), DEFAULT_LINE_DRIFT).top_frame_changed);
+        assert!(!diff("Error: a", "Error: b", DEFAULT_LINE_DRIFT).top_frame_changed);
+        assert!(!result.truncated);
+    }
+
+    #[test]
+    fn test_deep_stacks_are_capped() {
+        let deep: Vec<StackFrame> = (0..20_000).map(|i| StackFrame::new(format!("f{}", i), "/src/a.js".to_string(), 1, 1)).collect();
+        let result = diff_frames(&deep, &deep[1..], DEFAULT_LINE_DRIFT);
+        assert!(result.truncated);
+        assert_eq!(result.unchanged + result.removed.len(), MAX_COMPARED_FRAMES);
+        assert!(frame_similarity(&deep, &deep, 0) == 1.0);
+    }
+}
@@ -0,0 +1,405 @@
+use wasm_bindgen::prelude::*;
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use crate::parser::StackFrame;
+use crate::simd::LineIndex;
+
+/// 源码上下文配置
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct SourceContextOptions {
+    /// 前后各取多少行
+    pub radius: u32,
+    /// 单行最大字符数，超出时围绕列号截取
+    pub max_line_length: u32,
+    /// 每个错误附加上下文的总字节上限
+    pub max_total_bytes: u32,
+}
+
+#[wasm_bindgen]
+impl SourceContextOptions {
+    pub fn new(radius: u32, max_line_length: u32, max_total_bytes: u32) -> Self {
+        SourceContextOptions {
+            radius,
+            max_line_length,
+            max_total_bytes,
+        }
+    }
+}
+
+impl Default for SourceContextOptions {
+    fn default() -> Self {
+        SourceContextOptions::new(5, 200, 16 * 1024)
+    }
+}
+
+/// 栈帧所在位置的源码片段
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SourceContext {
+    pub pre_context: Vec<String>,
+    pub context_line: String,
+    pub post_context: Vec<String>,
+    /// 列号所在词法单元在 `context_line` 中的位置，列号超出该行时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<SourceToken>,
+}
+
+/// 源码行中的一个词法单元，`start`、`end` 为UTF-16下标（从0开始，不含 `end`），可直接用于JS字符串的 `slice`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SourceToken {
+    pub start: u32,
+    pub end: u32,
+    pub token: String,
+}
+
+impl SourceContext {
+    /// 片段占用的字节数
+    pub fn byte_len(&self) -> usize {
+        self.pre_context.iter().map(String::len).sum::<usize>()
+            + self.context_line.len()
+            + self.post_context.iter().map(String::len).sum::<usize>()
+    }
+}
+
+/// 已注册的打包产物源码，按文件URL索引并记录注册顺序
+#[derive(Default)]
+struct BundleStore {
+    sources: HashMap<String, String>,
+    // 各文件的换行偏移，首次提取上下文时建立，同一文件的多个帧不再重复扫描源码
+    newlines: HashMap<String, Vec<usize>>,
+    // 最早注册的在前，内存不足时优先淘汰
+    order: VecDeque<String>,
+    bytes: usize,
+}
+
+impl BundleStore {
+    fn insert(&mut self, url: &str, source: &str) {
+        self.remove(url);
+        self.bytes += url.len() + source.len();
+        self.order.push_back(url.to_string());
+        self.sources.insert(url.to_string(), source.to_string());
+    }
+
+    fn remove(&mut self, url: &str) -> bool {
+        match self.sources.remove(url) {
+            Some(source) => {
+                self.bytes -= url.len() + source.len();
+                if let Some(newlines) = self.newlines.remove(url) {
+                    self.bytes -= newlines.len() * std::mem::size_of::<usize>();
+                }
+                self.order.retain(|registered| registered != url);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn evict_oldest(&mut self) -> Option<String> {
+        let url = self.order.front()?.clone();
+        self.remove(&url);
+        Some(url)
+    }
+
+    fn compact(&mut self) {
+        self.sources.shrink_to_fit();
+        self.newlines.shrink_to_fit();
+        self.order.shrink_to_fit();
+    }
+}
+
+thread_local! {
+    static BUNDLES: RefCell<BundleStore> = RefCell::new(BundleStore::default());
+    static OPTIONS: RefCell<SourceContextOptions> = RefCell::new(SourceContextOptions::default());
+}
+
+/// 注册打包产物源码，用于提取上下文行
+pub fn register_bundle(url: &str, source: &str) {
+    BUNDLES.with(|bundles| bundles.borrow_mut().insert(url, source));
+}
+
+/// 移除已注册的打包产物
+pub fn unregister_bundle(url: &str) -> bool {
+    BUNDLES.with(|bundles| bundles.borrow_mut().remove(url))
+}
+
+/// 淘汰最早注册的打包产物，返回其URL
+pub fn evict_oldest_bundle() -> Option<String> {
+    BUNDLES.with(|bundles| bundles.borrow_mut().evict_oldest())
+}
+
+/// 释放源码表的多余容量
+pub fn compact_bundles() {
+    BUNDLES.with(|bundles| bundles.borrow_mut().compact());
+}
+
+/// 已注册源码占用的字节数
+pub fn bundle_bytes() -> usize {
+    BUNDLES.with(|bundles| bundles.borrow().bytes)
+}
+
+/// 设置全局上下文配置
+pub fn set_options(options: SourceContextOptions) {
+    OPTIONS.with(|current| *current.borrow_mut() = options);
+}
+
+/// 当前全局上下文配置
+pub fn options() -> SourceContextOptions {
+    OPTIONS.with(|current| *current.borrow())
+}
+
+/// 组成标识符或数字的字符
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+// 可连成一个运算符的字符
+const OPERATOR_CHARS: &str = "+-*/%=!<>&|^~?:.";
+
+/// 列号（从1开始，UTF-16单位，与JS引擎一致）所在的词法单元：标识符或数字、带引号的字符串、
+/// 连续的运算符或单个括号等符号；列号落在空白上时取其后的第一个单元，超出行尾时返回None
+pub fn token_at(line: &str, column: u32) -> Option<SourceToken> {
+    // 各字符的UTF-16起始下标
+    let mut chars: Vec<(usize, char)> = Vec::with_capacity(line.len());
+    let mut offset = 0;
+    for c in line.chars() {
+        chars.push((offset, c));
+        offset += c.len_utf16();
+    }
+    let target = column.checked_sub(1)? as usize;
+    let mut index = chars.iter().position(|(at, c)| target < at + c.len_utf16())?;
+    while chars[index].1.is_whitespace() {
+        index += 1;
+        if index == chars.len() {
+            return None;
+        }
+    }
+
+    let c = chars[index].1;
+    let extend = |matches: &dyn Fn(char) -> bool| {
+        let start = (0..index).rev().take_while(|&i| matches(chars[i].1)).last().unwrap_or(index);
+        let end = (index..chars.len()).take_while(|&i| matches(chars[i].1)).last().unwrap_or(index) + 1;
+        (start, end)
+    };
+    let (start, end) = if is_word_char(c) {
+        extend(&is_word_char)
+    } else if OPERATOR_CHARS.contains(c) {
+        extend(&|c| OPERATOR_CHARS.contains(c))
+    } else if matches!(c, '"' | '\'' | '`') {
+        // 从引号开始到未转义的同种引号，没有闭合时到行尾
+        let mut end = index + 1;
+        let mut escaped = false;
+        while end < chars.len() {
+            let next = chars[end].1;
+            end += 1;
+            if !escaped && next == c {
+                break;
+            }
+            escaped = !escaped && next == '\\';
+        }
+        (index, end)
+    } else {
+        (index, index + 1)
+    };
+
+    let utf16_at = |i: usize| chars.get(i).map_or(offset, |(at, _)| *at) as u32;
+    Some(SourceToken {
+        start: utf16_at(start),
+        end: utf16_at(end),
+        token: chars[start..end].iter().map(|(_, c)| c).collect(),
+    })
+}
+
+/// 截断过长的行，保留列号附近的内容
+fn truncate_line(line: &str, column: u32, max_len: usize) -> String {
+    truncate_line_window(line, column, max_len).0
+}
+
+/// 截断过长的行，同时返回保留窗口在原行中的起始字符下标与字符数
+fn truncate_line_window(line: &str, column: u32, max_len: usize) -> (String, (usize, usize)) {
+    let char_count = line.chars().count();
+    if max_len == 0 || char_count <= max_len {
+        return (line.to_string(), (0, char_count));
+    }
+
+    // 列号从1开始，以其为中心截取窗口
+    let center = (column.saturating_sub(1) as usize).min(char_count);
+    let start = center.saturating_sub(max_len / 2).min(char_count - max_len);
+    let window: String = line.chars().skip(start).take(max_len).collect();
+
+    let mut truncated = String::with_capacity(window.len() + 6);
+    if start > 0 {
+        truncated.push('…');
+    }
+    truncated.push_str(&window);
+    if start + max_len < char_count {
+        truncated.push('…');
+    }
+    (truncated, (start, max_len))
+}
+
+/// 把原行中的词法单元换算到截断后的行：减去截掉的前缀并计入开头的省略号，超出窗口的部分截掉
+fn shift_token(line: &str, token: SourceToken, window: (usize, usize)) -> Option<SourceToken> {
+    let (window_start, window_len) = window;
+    let utf16_len = |skip: usize, take: usize| line.chars().skip(skip).take(take).map(|c| c.len_utf16() as u32).sum::<u32>();
+    let prefix = utf16_len(0, window_start);
+    let end_of_window = prefix + utf16_len(window_start, window_len);
+    let ellipsis = if window_start > 0 { '…'.len_utf16() as u32 } else { 0 };
+    let shift = |at: u32| at.clamp(prefix, end_of_window) - prefix + ellipsis;
+    let (start, end) = (shift(token.start), shift(token.end));
+    (start < end).then_some(SourceToken { start, end, token: token.token })
+}
+
+/// 按源码的行索引提取指定位置的上下文（行列号从1开始），前后行数不超过文件的行数
+pub fn extract_context(index: &LineIndex, line: u32, column: u32, options: &SourceContextOptions) -> Option<SourceContext> {
+    if line == 0 {
+        return None;
+    }
+
+    let target = (line - 1) as usize;
+    let context_line = index.line(target)?;
+
+    let radius = (options.radius as usize).min(index.len());
+    let max_len = options.max_line_length as usize;
+    // 上下文行没有对应列，从行首截取
+    let surrounding = |i: usize| index.line(i).map(|l| truncate_line(l, 1, max_len));
+
+    let (truncated, window) = truncate_line_window(context_line, column, max_len);
+    let highlight = token_at(context_line, column).and_then(|token| shift_token(context_line, token, window));
+    Some(SourceContext {
+        pre_context: (target.saturating_sub(radius)..target).filter_map(surrounding).collect(),
+        context_line: truncated,
+        post_context: (target + 1..=target.saturating_add(radius)).filter_map(surrounding).collect(),
+        highlight,
+    })
+}
+
+/// 为栈帧附加已注册源码的上下文，总量不超过 `max_total_bytes`；各文件的行索引缓存在源码表中
+pub fn attach_context(frames: &mut [StackFrame], options: &SourceContextOptions) {
+    let mut remaining = options.max_total_bytes as usize;
+
+    BUNDLES.with(|bundles| {
+        let mut bundles = bundles.borrow_mut();
+        let BundleStore { sources, newlines, bytes, .. } = &mut *bundles;
+        for frame in frames.iter_mut() {
+            let url = frame.file_name_str();
+            let Some(source) = sources.get(url) else {
+                continue;
+            };
+            let cached = newlines.entry(url.to_string()).or_insert_with(|| {
+                let offsets = LineIndex::new(source).into_newlines();
+                *bytes += offsets.len() * std::mem::size_of::<usize>();
+                offsets
+            });
+            let index = LineIndex::from_newlines(source, std::mem::take(cached));
+            let context = extract_context(&index, frame.line_number(), frame.column_number(), options);
+            *cached = index.into_newlines();
+
+            if let Some(context) = context {
+                let size = context.byte_len();
+                if size > remaining {
+                    // 预算耗尽后不再附加
+                    break;
+                }
+                remaining -= size;
+                frame.set_context(context);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "line1\nline2\nline3\nline4\nline5\nline6\nline7";
+
+    #[test]
+    fn test_extract_context_radius() {
+        let options = SourceContextOptions::new(2, 200, 1024);
+        let context = extract_context(&LineIndex::new(SOURCE), 4, 1, &options).unwrap();
+        assert_eq!(context.pre_context, vec!["line2", "line3"]);
+        assert_eq!(context.context_line, "line4");
+        assert_eq!(context.post_context, vec!["line5", "line6"]);
+
+        // 文件边界处不越界
+        let context = extract_context(&LineIndex::new(SOURCE), 1, 1, &options).unwrap();
+        assert!(context.pre_context.is_empty());
+        assert!(extract_context(&LineIndex::new(SOURCE), 99, 1, &options).is_none());
+
+        // 半径超过行数时按行数截取
+        let context = extract_context(&LineIndex::new(SOURCE), 4, 1, &SourceContextOptions::new(u32::MAX, 200, 1024)).unwrap();
+        assert_eq!((context.pre_context.len(), context.post_context.len()), (3, 3));
+    }
+
+    #[test]
+    fn test_truncate_long_line_around_column() {
+        let minified = format!("{}TARGET{}", "a".repeat(500), "b".repeat(500));
+        let options = SourceContextOptions::new(0, 20, 1024);
+        let context = extract_context(&LineIndex::new(&minified), 1, 501, &options).unwrap();
+        assert!(context.context_line.contains("TARGET"));
+        assert!(context.context_line.starts_with('…'));
+        assert!(context.context_line.ends_with('…'));
+        assert_eq!(context.context_line.chars().count(), 22);
+    }
+
+    #[test]
+    fn test_token_at() {
+        let line = r#"var a=e.props.onChange(n),b="x\"y";  fn()"#;
+        let token = |column: u32| token_at(line, column).map(|token| (token.start, token.end, token.token));
+        // 列号落在标识符中间
+        assert_eq!(token(12), Some((8, 13, "props".to_string())));
+        assert_eq!(token(8), Some((7, 8, ".".to_string())));
+        assert_eq!(token(29), Some((28, 34, r#""x\"y""#.to_string())));
+        assert_eq!(token(23), Some((22, 23, "(".to_string())));
+        // 空白之后的第一个单元
+        assert_eq!(token(36), Some((37, 39, "fn".to_string())));
+        assert_eq!(token(0), None);
+        assert_eq!(token(99), None);
+        // 非BMP字符按UTF-16计
+        assert_eq!(token_at("'😀'+foo", 5).map(|token| token.token), Some("+".to_string()));
+
+        // 截断后的上下文行中的高亮位置
+        let minified = format!("{}.call(TARGET){}", "a".repeat(500), "b".repeat(500));
+        let context = extract_context(&LineIndex::new(&minified), 1, 507, &SourceContextOptions::new(0, 20, 1024)).unwrap();
+        let highlight = context.highlight.unwrap();
+        let units: Vec<u16> = context.context_line.encode_utf16().collect();
+        assert_eq!(String::from_utf16(&units[highlight.start as usize..highlight.end as usize]).unwrap(), "TARGET");
+    }
+
+    #[test]
+    fn test_attach_context_byte_cap() {
+        register_bundle("/ctx/app.js", SOURCE);
+        let mut frames = vec![
+            StackFrame::new("a".to_string(), "/ctx/app.js".to_string(), 2, 1),
+            StackFrame::new("b".to_string(), "/ctx/app.js".to_string(), 5, 1),
+        ];
+
+        // 每帧上下文为15字节，预算只够一帧
+        attach_context(&mut frames, &SourceContextOptions::new(1, 200, 20));
+        assert!(frames[0].context().is_some());
+        assert!(frames[1].context().is_none());
+
+        // 行索引在第一次提取时缓存，之后的提取结果相同，移除时一并释放
+        let before = bundle_bytes();
+        let mut again = vec![StackFrame::new("a".to_string(), "/ctx/app.js".to_string(), 2, 1)];
+        attach_context(&mut again, &SourceContextOptions::new(1, 200, 20));
+        assert_eq!((again[0].context(), bundle_bytes()), (frames[0].context(), before));
+        assert!(unregister_bundle("/ctx/app.js"));
+        assert_eq!(bundle_bytes(), 0);
+    }
+
+    #[test]
+    fn test_evict_oldest_bundle() {
+        register_bundle("/evict/a.js", "aaaa");
+        register_bundle("/evict/b.js", "bb");
+        assert_eq!(bundle_bytes(), 11 + 4 + 11 + 2);
+
+        // 重新注册会移到队尾
+        register_bundle("/evict/a.js", "aaaa");
+        assert_eq!(evict_oldest_bundle().as_deref(), Some("/evict/b.js"));
+        assert_eq!(evict_oldest_bundle().as_deref(), Some("/evict/a.js"));
+        assert_eq!(evict_oldest_bundle(), None);
+        assert_eq!(bundle_bytes(), 0);
+    }
+}
@@ -0,0 +1,630 @@
+use wasm_bindgen::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::mapping_index::MappingIndex;
+use crate::parser::{FrameOrigin, StackFrame};
+use crate::remap_cache::OriginalLocation;
+use crate::simd::LineIndex;
+
+/// source map v3 的原始JSON结构
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub(crate) struct RawSourceMap {
+    pub version: u32,
+    pub file: Option<String>,
+    #[serde(rename = "sourceRoot")]
+    pub source_root: Option<String>,
+    pub sources: Vec<String>,
+    pub names: Vec<String>,
+    pub mappings: String,
+    #[serde(rename = "debugId")]
+    pub debug_id: Option<String>,
+    #[serde(rename = "debug_id")]
+    pub legacy_debug_id: Option<String>,
+    /// 第三方源文件在 `sources` 中的下标
+    #[serde(rename = "ignoreList")]
+    pub ignore_list: Vec<u32>,
+    /// Chrome早期使用的 `ignoreList`
+    #[serde(rename = "x_google_ignoreList")]
+    pub legacy_ignore_list: Vec<u32>,
+    /// 索引map（indexed source map）的各段
+    pub sections: Vec<RawSection>,
+}
+
+/// `to_json` 输出的source map结构
+#[derive(Serialize)]
+struct ExportedSourceMap<'a> {
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<&'a str>,
+    sources: &'a [String],
+    names: &'a [String],
+    mappings: &'a str,
+    #[serde(rename = "ignoreList", skip_serializing_if = "<[u32]>::is_empty")]
+    ignore_list: &'a [u32],
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sections: Vec<ExportedSection<'a>>,
+}
+
+#[derive(Serialize)]
+struct ExportedSection<'a> {
+    offset: ExportedOffset,
+    map: ExportedSourceMap<'a>,
+}
+
+#[derive(Serialize)]
+struct ExportedOffset {
+    line: u32,
+    column: u32,
+}
+
+/// 索引map中的一段：从生成位置 `offset` 开始使用内嵌的 `map`
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub(crate) struct RawSection {
+    pub offset: RawOffset,
+    pub map: Option<RawSourceMap>,
+    pub url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(default)]
+pub(crate) struct RawOffset {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// 帧的source map还原程度，记录在 `symbolicated` 注解中
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symbolication {
+    /// 位置与函数名都已还原
+    Full,
+    /// 位置已还原，map中没有函数名
+    Partial,
+    /// 未还原
+    None,
+}
+
+impl Symbolication {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Symbolication::Full => "full",
+            Symbolication::Partial => "partial",
+            Symbolication::None => "none",
+        }
+    }
+}
+
+/// 未完全还原的原因，记录在 `symbolication_reason` 注解中
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolicationReason {
+    /// 帧所在的文件没有注册source map
+    NoMap,
+    /// map中没有覆盖该生成位置的映射
+    MappingMiss,
+    /// 映射没有名称
+    NameMissing,
+    /// 帧来自与构建清单不符的旧版产物
+    StaleBundle,
+}
+
+impl SymbolicationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolicationReason::NoMap => "no_map",
+            SymbolicationReason::MappingMiss => "mapping_miss",
+            SymbolicationReason::NameMissing => "name_missing",
+            SymbolicationReason::StaleBundle => "stale_bundle",
+        }
+    }
+}
+
+/// 为帧添加还原程度与原因注解，供看板按版本统计还原覆盖率
+pub(crate) fn annotate_symbolication(frame: &mut StackFrame, status: Symbolication, reason: Option<SymbolicationReason>) {
+    frame.set_annotation("symbolicated".to_string(), status.as_str().to_string());
+    match reason {
+        Some(reason) => frame.set_annotation("symbolication_reason".to_string(), reason.as_str().to_string()),
+        None => {
+            frame.remove_annotation("symbolication_reason");
+        }
+    }
+}
+
+/// 一条解码后的映射，行列号均从0开始
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mapping {
+    pub generated_line: u32,
+    pub generated_column: u32,
+    pub source: Option<u32>,
+    pub original_line: u32,
+    pub original_column: u32,
+    pub name: Option<u32>,
+}
+
+/// 解码单个base64 VLQ字符
+fn base64_value(byte: u8) -> Option<i64> {
+    match byte {
+        b'A'..=b'Z' => Some((byte - b'A') as i64),
+        b'a'..=b'z' => Some((byte - b'a') as i64 + 26),
+        b'0'..=b'9' => Some((byte - b'0') as i64 + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+/// 解码标准或URL安全的base64，忽略空白与填充
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(input.len() / 4 * 3);
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for byte in input.bytes().filter(|b| !b.is_ascii_whitespace() && *b != b'=') {
+        let value = match byte {
+            b'-' => 62,
+            b'_' => 63,
+            _ => base64_value(byte)?,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(decoded)
+}
+
+/// 解码 `%XX` 百分号转义
+fn decode_percent(input: &str) -> Option<Vec<u8>> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(decoded)
+}
+
+/// 查找打包产物末尾的 `//# <key>=<value>` 注释
+pub(crate) fn find_pragma<'a>(bundle: &'a str, key: &str) -> Option<&'a str> {
+    let index = LineIndex::new(bundle);
+    (0..index.len()).rev()
+        .filter_map(|i| index.line(i))
+        .filter_map(|line| {
+            let line = line.trim();
+            line.strip_prefix("//# ").or_else(|| line.strip_prefix("//@ "))
+        })
+        .find_map(|pragma| pragma.strip_prefix(key)?.strip_prefix('='))
+        .map(str::trim)
+}
+
+/// 从打包产物的 `//# sourceMappingURL=data:application/json;base64,...` 注释中取出内联source map JSON
+pub fn extract_inline_source_map(bundle: &str) -> Result<String, String> {
+    let url = find_pragma(bundle, "sourceMappingURL").ok_or("产物中没有sourceMappingURL注释")?;
+    let data = url.strip_prefix("data:").ok_or_else(|| format!("sourceMappingURL不是内联map: {}", url))?;
+    let (header, payload) = data.split_once(',').ok_or("data URI缺少数据部分")?;
+
+    let mut params = header.split(';');
+    let media_type = params.next().unwrap_or("");
+    if !media_type.is_empty() && !media_type.ends_with("json") {
+        return Err(format!("不支持的内联map类型: {}", media_type));
+    }
+
+    let bytes = if params.any(|param| param == "base64") {
+        decode_base64(payload).ok_or("内联map的base64数据不合法")?
+    } else {
+        decode_percent(payload).ok_or("内联map的转义数据不合法")?
+    };
+    String::from_utf8(bytes).map_err(|_| "内联map不是合法的UTF-8".to_string())
+}
+
+/// 依次解码segment中的VLQ数值交给 `push`，`push` 返回false时提前停止；格式非法时返回false
+fn decode_vlq_with(segment: &str, mut push: impl FnMut(i64) -> bool) -> bool {
+    let mut value: i64 = 0;
+    let mut shift = 0;
+
+    for byte in segment.bytes() {
+        let Some(digit) = base64_value(byte) else {
+            return false;
+        };
+        value += (digit & 31) << shift;
+        if digit & 32 != 0 {
+            shift += 5;
+            if shift > 60 {
+                return false;
+            }
+            continue;
+        }
+        // 最低位为符号位
+        if !push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 }) {
+            return false;
+        }
+        value = 0;
+        shift = 0;
+    }
+
+    shift == 0
+}
+
+/// 解码一个segment中的全部VLQ数值
+pub fn decode_vlq_segment(segment: &str) -> Option<Vec<i64>> {
+    let mut values = Vec::with_capacity(5);
+    decode_vlq_with(segment, |value| {
+        values.push(value);
+        true
+    }).then_some(values)
+}
+
+/// 不分配内存地解码一个映射segment，返回各字段与字段数；字段数不是1、4、5时返回None
+pub(crate) fn decode_segment_fields(segment: &str) -> Option<([i64; 5], usize)> {
+    let mut fields = [0i64; 5];
+    let mut count = 0;
+    let valid = decode_vlq_with(segment, |value| {
+        if count == fields.len() {
+            return false;
+        }
+        fields[count] = value;
+        count += 1;
+        true
+    });
+    (valid && matches!(count, 1 | 4 | 5)).then_some((fields, count))
+}
+
+/// source map 解析器：解码映射并把生成位置还原为原始位置
+#[wasm_bindgen]
+pub struct SourceMapConsumer {
+    file: Option<String>,
+    sources: Vec<String>,
+    names: Vec<String>,
+    // `ignoreList` 中的源文件下标，映射到这些文件的帧视为第三方
+    ignore_list: Vec<u32>,
+    // 按行、按块延迟解码的映射
+    mappings: MappingIndex,
+    // 索引map的各段，按偏移排序；普通map为空
+    sections: Vec<Section>,
+}
+
+/// 索引map中已解析的一段，偏移从0开始
+struct Section {
+    line: u32,
+    column: u32,
+    consumer: SourceMapConsumer,
+}
+
+#[wasm_bindgen]
+impl SourceMapConsumer {
+    /// 从source map JSON创建，格式不合法时返回undefined
+    pub fn from_json(json: &str) -> Option<SourceMapConsumer> {
+        SourceMapConsumer::parse(json).ok()
+    }
+
+    /// 从带内联source map的打包产物创建，没有内联map或格式不合法时返回undefined
+    pub fn from_bundle(bundle: &str) -> Option<SourceMapConsumer> {
+        SourceMapConsumer::parse_inline(bundle).ok()
+    }
+
+    /// map的file字段
+    #[wasm_bindgen(getter)]
+    pub fn file(&self) -> Option<String> {
+        self.file.clone()
+    }
+
+    /// 解码后的映射条数
+    pub fn mapping_count(&self) -> usize {
+        self.mappings.len() + self.sections.iter().map(|section| section.consumer.mapping_count()).sum::<usize>()
+    }
+
+    /// 将栈帧还原到原始源码位置，找不到映射时原样返回；源文件在 `ignoreList` 中时标记为第三方
+    pub fn map_frame(&self, frame: &StackFrame) -> StackFrame {
+        let mut mapped = frame.clone();
+        apply_location(&mut mapped, self.original_location(frame.line_number(), frame.column_number()));
+        mapped
+    }
+}
+
+impl SourceMapConsumer {
+    /// 解析source map JSON，支持带 `sections` 的索引map
+    pub fn parse(json: &str) -> Result<SourceMapConsumer, String> {
+        let raw: RawSourceMap = serde_json::from_str(json).map_err(|e| format!("source map解析失败: {}", e))?;
+        SourceMapConsumer::from_raw(raw)
+    }
+
+    /// 估算占用的字节数
+    pub fn approx_bytes(&self) -> usize {
+        std::mem::size_of::<SourceMapConsumer>()
+            + self.mappings.approx_bytes()
+            + self.sources.iter().chain(&self.names).map(String::len).sum::<usize>()
+            + self.sections.iter().map(|section| section.consumer.approx_bytes()).sum::<usize>()
+    }
+
+    /// 还原为可重新解析的source map JSON（`sourceRoot` 已并入 `sources`）
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(&self.to_exported()).unwrap_or_default()
+    }
+
+    fn to_exported(&self) -> ExportedSourceMap<'_> {
+        ExportedSourceMap {
+            version: 3,
+            file: self.file.as_deref(),
+            sources: &self.sources,
+            names: &self.names,
+            mappings: self.mappings.as_str(),
+            ignore_list: &self.ignore_list,
+            sections: self.sections.iter()
+                .map(|section| ExportedSection {
+                    offset: ExportedOffset { line: section.line, column: section.column },
+                    map: section.consumer.to_exported(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 解析打包产物中的内联source map
+    pub fn parse_inline(bundle: &str) -> Result<SourceMapConsumer, String> {
+        SourceMapConsumer::parse(&extract_inline_source_map(bundle)?)
+    }
+
+    fn from_raw(raw: RawSourceMap) -> Result<SourceMapConsumer, String> {
+        if raw.version != 3 {
+            return Err(format!("不支持的source map版本: {}", raw.version));
+        }
+
+        if !raw.sections.is_empty() {
+            let mut sections = Vec::with_capacity(raw.sections.len());
+            for (index, section) in raw.sections.into_iter().enumerate() {
+                let map = match (section.map, section.url) {
+                    (Some(map), _) => map,
+                    (None, Some(url)) => return Err(format!("第 {} 段引用外部map，暂不支持: {}", index + 1, url)),
+                    (None, None) => return Err(format!("第 {} 段缺少map", index + 1)),
+                };
+                sections.push(Section {
+                    line: section.offset.line,
+                    column: section.offset.column,
+                    consumer: SourceMapConsumer::from_raw(map)?,
+                });
+            }
+            // 规范要求各段按偏移递增且不重叠
+            if sections.windows(2).any(|pair| (pair[0].line, pair[0].column) > (pair[1].line, pair[1].column)) {
+                return Err("sections偏移未按顺序排列".to_string());
+            }
+
+            return Ok(SourceMapConsumer {
+                file: raw.file,
+                sources: Vec::new(),
+                names: Vec::new(),
+                ignore_list: Vec::new(),
+                mappings: MappingIndex::default(),
+                sections,
+            });
+        }
+
+        let mappings = MappingIndex::new(raw.mappings)?;
+
+        let root = raw.source_root.as_deref().unwrap_or("").trim_end_matches('/');
+        let sources = raw.sources.into_iter()
+            .map(|source| {
+                if root.is_empty() || source.contains("://") || source.starts_with('/') {
+                    source
+                } else {
+                    format!("{}/{}", root, source)
+                }
+            })
+            .collect();
+
+        Ok(SourceMapConsumer {
+            file: raw.file,
+            sources,
+            names: raw.names,
+            ignore_list: if raw.ignore_list.is_empty() { raw.legacy_ignore_list } else { raw.ignore_list },
+            mappings,
+            sections: Vec::new(),
+        })
+    }
+
+    /// 索引map先定位生成位置所在的段，返回该段的解析器与段内坐标
+    fn resolve_section(&self, generated_line: u32, generated_column: u32) -> Option<(&SourceMapConsumer, u32, u32)> {
+        if self.sections.is_empty() {
+            return Some((self, generated_line, generated_column));
+        }
+
+        let index = self.sections.partition_point(|section| (section.line, section.column) <= (generated_line, generated_column));
+        let section = self.sections.get(index.checked_sub(1)?)?;
+        // 列偏移只作用于段的第一行
+        let column = if generated_line == section.line { generated_column - section.column } else { generated_column };
+        section.consumer.resolve_section(generated_line - section.line, column)
+    }
+
+    /// 查找生成位置对应的映射（行列号从0开始）：同一行中列号不大于目标的最后一条
+    pub fn lookup(&self, generated_line: u32, generated_column: u32) -> Option<&Mapping> {
+        self.resolve_mapping(generated_line, generated_column).map(|(_, mapping)| mapping)
+    }
+
+    /// 查找映射并返回其所属的解析器，索引map中source与name下标都相对所属段
+    fn resolve_mapping(&self, generated_line: u32, generated_column: u32) -> Option<(&SourceMapConsumer, &Mapping)> {
+        let (consumer, generated_line, generated_column) = self.resolve_section(generated_line, generated_column)?;
+        consumer.mappings.lookup(generated_line as usize, generated_column).map(|mapping| (consumer, mapping))
+    }
+
+    /// 按栈帧约定（行列号从1开始）查询原始位置
+    pub fn original_location(&self, line: u32, column: u32) -> Option<OriginalLocation> {
+        let (consumer, mapping) = self.resolve_mapping(line.checked_sub(1)?, column.saturating_sub(1))?;
+        let source_index = mapping.source?;
+        let source = consumer.sources.get(source_index as usize)?;
+
+        Some(OriginalLocation {
+            source: source.clone(),
+            line: mapping.original_line + 1,
+            column: mapping.original_column + 1,
+            name: mapping.name.and_then(|name| consumer.names.get(name as usize).cloned()),
+            ignored: consumer.ignore_list.contains(&source_index),
+        })
+    }
+}
+
+/// 用还原出的原始位置替换帧的函数名与位置并注解还原程度，没有映射时只注解；
+/// 替换后按原始源文件重新分类来源、框架与所属包，`ignoreList` 中的源文件标记为第三方（与DevTools一致）
+pub(crate) fn apply_location(frame: &mut StackFrame, location: Option<OriginalLocation>) {
+    let Some(location) = location else {
+        annotate_symbolication(frame, Symbolication::None, Some(SymbolicationReason::MappingMiss));
+        return;
+    };
+    match location.name {
+        Some(name) => {
+            frame.set_location(name, location.source, location.line, location.column);
+            annotate_symbolication(frame, Symbolication::Full, None);
+        }
+        None => {
+            frame.set_location(frame.function_name(), location.source, location.line, location.column);
+            annotate_symbolication(frame, Symbolication::Partial, Some(SymbolicationReason::NameMissing));
+        }
+    }
+    frame.reclassify();
+    if location.ignored {
+        frame.set_origin(FrameOrigin::NodeModules);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 源码 src/cart.js:
+    //   function addItem(item) {
+    //     items.push(item);
+    //   }
+    // 压缩为单行 `function a(t){items.push(t)}`
+    const MAP: &str = r#"{
+        "version": 3,
+        "file": "cart.min.js",
+        "sourceRoot": "webpack://shop",
+        "sources": ["src/cart.js"],
+        "names": ["addItem", "item"],
+        "mappings": "AAAA,SAASA,EAAQC,GACf"
+    }"#;
+
+    #[test]
+    fn test_decode_vlq_segment() {
+        assert_eq!(decode_vlq_segment("AAAA"), Some(vec![0, 0, 0, 0]));
+        assert_eq!(decode_vlq_segment("SAASA"), Some(vec![9, 0, 0, 9, 0]));
+        assert_eq!(decode_vlq_segment("gB"), Some(vec![16]));
+        assert_eq!(decode_vlq_segment("D"), Some(vec![-1]));
+        assert_eq!(decode_vlq_segment("g"), None);
+        assert_eq!(decode_vlq_segment("A!"), None);
+    }
+
+    #[test]
+    fn test_decode_segment_fields() {
+        assert_eq!(decode_segment_fields("SAASA"), Some(([9, 0, 0, 9, 0], 5)));
+        assert_eq!(decode_segment_fields("AACA"), Some(([0, 0, 1, 0, 0], 4)));
+        assert_eq!(decode_segment_fields("AB"), None);
+        assert_eq!(decode_segment_fields("AAAAAA"), None);
+    }
+
+    #[test]
+    fn test_map_frame() {
+        let consumer = SourceMapConsumer::parse(MAP).unwrap();
+        assert_eq!(consumer.file().as_deref(), Some("cart.min.js"));
+        assert_eq!(consumer.mapping_count(), 4);
+
+        // 第1行第10列（从1开始）落在 `a` 上
+        let frame = StackFrame::new("a".to_string(), "/dist/cart.min.js".to_string(), 1, 10);
+        let mapped = consumer.map_frame(&frame);
+        assert_eq!(mapped.file_name(), "webpack://shop/src/cart.js");
+        assert_eq!(mapped.function_name(), "addItem");
+        assert_eq!((mapped.line_number(), mapped.column_number()), (1, 10));
+
+        // 映射之间的列取前一条
+        let mapped = consumer.map_frame(&StackFrame::new("t".to_string(), "/dist/cart.min.js".to_string(), 1, 21));
+        assert_eq!((mapped.line_number(), mapped.column_number()), (2, 3));
+        assert_eq!(mapped.function_name(), "t");
+        assert_eq!((mapped.get_annotation("symbolicated").as_deref(), mapped.get_annotation("symbolication_reason").as_deref()), (Some("partial"), Some("name_missing")));
+
+        // 超出映射范围时原样返回
+        let unmapped = StackFrame::new("x".to_string(), "/dist/cart.min.js".to_string(), 5, 1);
+        assert_eq!(consumer.map_frame(&unmapped).file_name(), "/dist/cart.min.js");
+        assert!(SourceMapConsumer::parse(r#"{"version": 2}"#).is_err());
+    }
+
+    #[test]
+    fn test_ignore_list() {
+        let map = r#"{"version": 3, "sources": ["src/app.js", "webpack/vendor.js"], "names": [], "mappings": "AAAA,UCAA", "ignoreList": [1]}"#;
+        let consumer = SourceMapConsumer::parse(map).unwrap();
+        let app = consumer.map_frame(&StackFrame::new("a".to_string(), "/dist/main.js".to_string(), 1, 1));
+        let vendor = consumer.map_frame(&StackFrame::new("b".to_string(), "/dist/main.js".to_string(), 1, 11));
+        assert_eq!((app.file_name(), app.is_in_app()), ("src/app.js".to_string(), true));
+        assert_eq!((vendor.file_name(), vendor.origin(), vendor.is_in_app()), ("webpack/vendor.js".to_string(), FrameOrigin::NodeModules, false));
+        assert_eq!(consumer.to_json()["ignoreList"], serde_json::json!([1]));
+
+        // 旧字段名
+        let legacy = SourceMapConsumer::parse(&map.replace("\"ignoreList\"", "\"x_google_ignoreList\"")).unwrap();
+        assert!(legacy.original_location(1, 11).unwrap().ignored);
+    }
+
+    #[test]
+    fn test_map_frame_reclassifies_original_source() {
+        let map = r#"{"version": 3, "sources": ["src/app.js", "webpack:///node_modules/react-dom/cjs/react-dom.js"], "names": [], "mappings": "AAAA,UCAA"}"#;
+        let consumer = SourceMapConsumer::parse(map).unwrap();
+        let mut frame = StackFrame::new("b".to_string(), "/dist/main.js".to_string(), 1, 11);
+        frame.set_origin(FrameOrigin::App);
+        let mapped = consumer.map_frame(&frame);
+        assert_eq!((mapped.origin(), mapped.framework().as_deref()), (FrameOrigin::NodeModules, Some("React")));
+        let app = consumer.map_frame(&StackFrame::new("a".to_string(), "/dist/main.js".to_string(), 1, 1));
+        assert_eq!((app.origin(), app.framework()), (FrameOrigin::App, None));
+    }
+
+    #[test]
+    fn test_indexed_source_map() {
+        let indexed = format!(r#"{{
+            "version": 3,
+            "file": "remote.js",
+            "sections": [
+                {{"offset": {{"line": 0, "column": 0}}, "map": {}}},
+                {{"offset": {{"line": 0, "column": 100}}, "map": {{"version": 3, "sources": ["src/b.js"], "names": [], "mappings": "AAAA;AACA"}}}}
+            ]
+        }}"#, MAP);
+        let consumer = SourceMapConsumer::parse(&indexed).unwrap();
+        assert_eq!(consumer.file().as_deref(), Some("remote.js"));
+        assert_eq!(consumer.mapping_count(), 6);
+
+        // 第一段
+        let location = consumer.original_location(1, 10).unwrap();
+        assert_eq!((location.source.as_str(), location.name.as_deref()), ("webpack://shop/src/cart.js", Some("addItem")));
+
+        // 第二段：首行扣除列偏移，之后的行不扣
+        let location = consumer.original_location(1, 101).unwrap();
+        assert_eq!((location.source.as_str(), location.line, location.column), ("src/b.js", 1, 1));
+        let location = consumer.original_location(2, 1).unwrap();
+        assert_eq!((location.source.as_str(), location.line), ("src/b.js", 2));
+
+        let external = r#"{"version": 3, "sections": [{"offset": {"line": 0, "column": 0}, "url": "a.map"}]}"#;
+        assert!(SourceMapConsumer::parse(external).is_err());
+    }
+
+    #[test]
+    fn test_inline_source_map() {
+        // encoded 为 map 的base64编码
+        let map = r#"{"version":3,"sources":["src/cart.js"],"names":["addItem"],"mappings":"AAAA,SAASA"}"#;
+        let encoded = "eyJ2ZXJzaW9uIjozLCJzb3VyY2VzIjpbInNyYy9jYXJ0LmpzIl0sIm5hbWVzIjpbImFkZEl0ZW0iXSwibWFwcGluZ3MiOiJBQUFBLFNBQVNBIn0=";
+        let bundle = format!("function a(t){{items.push(t)}}\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,{}\n", encoded);
+        assert_eq!(extract_inline_source_map(&bundle).unwrap(), map);
+
+        let consumer = SourceMapConsumer::from_bundle(&bundle).unwrap();
+        let location = consumer.original_location(1, 10).unwrap();
+        assert_eq!((location.source.as_str(), location.name.as_deref()), ("src/cart.js", Some("addItem")));
+
+        // 百分号转义的内联map
+        let bundle = "a();\n//# sourceMappingURL=data:application/json,%7B%22version%22%3A3%2C%22mappings%22%3A%22AAAA%22%7D";
+        assert_eq!(SourceMapConsumer::parse_inline(bundle).unwrap().mapping_count(), 1);
+
+        assert!(SourceMapConsumer::parse_inline("a();\n//# sourceMappingURL=app.js.map").is_err());
+        assert!(SourceMapConsumer::parse_inline("a();").is_err());
+    }
+}
@@ -0,0 +1,275 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use crate::parser::StackFrame;
+use crate::remap_cache;
+use crate::sourcemap::{self, SourceMapConsumer, Symbolication, SymbolicationReason};
+
+// 默认最多缓存的source map数量
+const DEFAULT_CAPACITY: usize = 16;
+
+/// 按打包产物URL缓存已解析的source map，超出容量时淘汰最久未使用的
+pub struct SourceMapRegistry {
+    maps: HashMap<String, SourceMapConsumer>,
+    // 使用顺序，队尾为最近使用
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+/// 去掉URL中的查询参数与片段，`app.js?v=3` 与 `app.js` 共用同一份map
+fn normalize_url(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+impl SourceMapRegistry {
+    pub fn new(capacity: usize) -> Self {
+        SourceMapRegistry {
+            maps: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// 解析并注册URL对应的source map，已存在时替换
+    pub fn register(&mut self, url: &str, map_json: &str) -> Result<(), String> {
+        let consumer = SourceMapConsumer::parse(map_json)?;
+        let url = normalize_url(url).to_string();
+        self.remove(&url);
+        if self.capacity == 0 {
+            return Ok(());
+        }
+
+        while self.maps.len() >= self.capacity {
+            if self.evict_oldest().is_none() {
+                break;
+            }
+        }
+        self.order.push_back(url.clone());
+        self.maps.insert(url, consumer);
+        Ok(())
+    }
+
+    /// 移除URL对应的map，并释放其映射缓存
+    pub fn remove(&mut self, url: &str) -> bool {
+        let url = normalize_url(url);
+        match self.maps.remove(url) {
+            Some(_) => {
+                self.order.retain(|u| u != url);
+                remap_cache::release_file(url);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 淘汰最久未使用的map，返回其URL
+    pub fn evict_oldest(&mut self) -> Option<String> {
+        let url = self.order.front()?.clone();
+        self.remove(&url);
+        Some(url)
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.maps.contains_key(normalize_url(url))
+    }
+
+    pub fn len(&self) -> usize {
+        self.maps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.maps.len() > capacity && self.evict_oldest().is_some() {}
+    }
+
+    pub fn clear(&mut self) {
+        for url in std::mem::take(&mut self.order) {
+            remap_cache::release_file(&url);
+        }
+        self.maps.clear();
+    }
+
+    /// 释放表的多余容量
+    pub fn compact(&mut self) {
+        self.maps.shrink_to_fit();
+        self.order.shrink_to_fit();
+    }
+
+    /// 各map的估算字节数之和，查询展开映射块后随之增长
+    pub fn approx_bytes(&self) -> usize {
+        self.maps.values().map(SourceMapConsumer::approx_bytes).sum()
+    }
+
+    /// 已注册的map，按使用顺序（最久未使用的在前），用于持久化后按序重新注册
+    pub fn entries(&self) -> Vec<(String, serde_json::Value)> {
+        self.order.iter()
+            .filter_map(|url| self.maps.get(url).map(|consumer| (url.clone(), consumer.to_json())))
+            .collect()
+    }
+
+    /// 用帧所在文件的map还原栈帧，未注册map或帧来自旧版产物时返回None；查询结果经过全局映射缓存
+    pub fn map_frame(&mut self, frame: &StackFrame) -> Option<StackFrame> {
+        if frame.is_stale_bundle() {
+            return None;
+        }
+        let url = normalize_url(frame.file_name_str());
+        let consumer = self.maps.get(url)?;
+
+        let (line, column) = (frame.line_number(), frame.column_number());
+        let location = remap_cache::get_or_insert_with(url, line, column, || consumer.original_location(line, column));
+        let mut mapped = frame.clone();
+        sourcemap::apply_location(&mut mapped, location);
+
+        // 标记为最近使用
+        if self.order.back().map(String::as_str) != Some(url) {
+            self.order.retain(|u| u != url);
+            self.order.push_back(url.to_string());
+        }
+        Some(mapped)
+    }
+}
+
+impl Default for SourceMapRegistry {
+    fn default() -> Self {
+        SourceMapRegistry::new(DEFAULT_CAPACITY)
+    }
+}
+
+thread_local! {
+    static REGISTRY: RefCell<SourceMapRegistry> = RefCell::new(SourceMapRegistry::default());
+}
+
+/// 注册URL对应的source map
+pub fn register(url: &str, map_json: &str) -> Result<(), String> {
+    REGISTRY.with(|registry| registry.borrow_mut().register(url, map_json))
+}
+
+pub fn unregister(url: &str) -> bool {
+    REGISTRY.with(|registry| registry.borrow_mut().remove(url))
+}
+
+/// 淘汰最久未使用的map（内存预算回收时调用）
+pub fn evict_oldest() -> Option<String> {
+    REGISTRY.with(|registry| registry.borrow_mut().evict_oldest())
+}
+
+pub fn set_capacity(capacity: usize) {
+    REGISTRY.with(|registry| registry.borrow_mut().set_capacity(capacity));
+}
+
+pub fn capacity() -> usize {
+    REGISTRY.with(|registry| registry.borrow().capacity())
+}
+
+/// URL（忽略查询参数）是否已注册map
+pub fn contains(url: &str) -> bool {
+    REGISTRY.with(|registry| registry.borrow().contains(url))
+}
+
+pub fn len() -> usize {
+    REGISTRY.with(|registry| registry.borrow().len())
+}
+
+pub fn clear() {
+    REGISTRY.with(|registry| registry.borrow_mut().clear());
+}
+
+/// 用一组map替换全局注册表，保留容量；任一map无效时不修改
+pub fn replace_all(entries: &[(String, String)]) -> Result<(), String> {
+    let capacity = REGISTRY.with(|registry| registry.borrow().capacity);
+    let mut replacement = SourceMapRegistry::new(capacity);
+    for (url, map_json) in entries {
+        replacement.register(url, map_json)?;
+    }
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        registry.clear();
+        *registry = replacement;
+    });
+    Ok(())
+}
+
+pub fn entries() -> Vec<(String, serde_json::Value)> {
+    REGISTRY.with(|registry| registry.borrow().entries())
+}
+
+pub fn compact() {
+    REGISTRY.with(|registry| registry.borrow_mut().compact());
+}
+
+pub fn approx_bytes() -> usize {
+    REGISTRY.with(|registry| registry.borrow().approx_bytes())
+}
+
+/// 用已注册的map还原栈帧
+pub fn map_frame(frame: &StackFrame) -> Option<StackFrame> {
+    REGISTRY.with(|registry| registry.borrow_mut().map_frame(frame))
+}
+
+/// 没有可用map的帧：复制并注解 `symbolicated: none` 与原因，没有源码位置的帧不注解
+pub fn unmapped(frame: &StackFrame) -> StackFrame {
+    let mut unmapped = frame.clone();
+    if frame.line_number() > 0 {
+        let reason = if frame.is_stale_bundle() { SymbolicationReason::StaleBundle } else { SymbolicationReason::NoMap };
+        sourcemap::annotate_symbolication(&mut unmapped, Symbolication::None, Some(reason));
+    }
+    unmapped
+}
+
+/// 用已注册的map还原栈帧，没有可用map时返回带原因注解的副本
+pub fn symbolicate(frame: &StackFrame) -> StackFrame {
+    map_frame(frame).unwrap_or_else(|| unmapped(frame))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r#"{"version":3,"sources":["src/cart.js"],"names":["addItem"],"mappings":"AAAA,SAASA"}"#;
+
+    fn frame(file: &str) -> StackFrame {
+        StackFrame::new("a".to_string(), file.to_string(), 1, 10)
+    }
+
+    #[test]
+    fn test_map_frame_by_url() {
+        let mut registry = SourceMapRegistry::default();
+        assert!(registry.register("https://cdn.test/cart.min.js", MAP).is_ok());
+        assert!(registry.register("https://cdn.test/bad.js", "{}").is_err());
+
+        let mapped = registry.map_frame(&frame("https://cdn.test/cart.min.js?v=3")).unwrap();
+        assert_eq!(mapped.file_name(), "src/cart.js");
+        assert_eq!(mapped.function_name(), "addItem");
+        assert!(registry.map_frame(&frame("https://cdn.test/other.js")).is_none());
+        assert!(registry.approx_bytes() > 0);
+
+        registry.clear();
+        assert_eq!(registry.approx_bytes(), 0);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut registry = SourceMapRegistry::new(2);
+        registry.register("/a.js", MAP).unwrap();
+        registry.register("/b.js", MAP).unwrap();
+        // 访问 /a.js 后，/b.js 成为最久未使用
+        registry.map_frame(&frame("/a.js"));
+        registry.register("/c.js", MAP).unwrap();
+
+        assert!(registry.contains("/a.js"));
+        assert!(!registry.contains("/b.js"));
+        assert_eq!(registry.len(), 2);
+
+        registry.set_capacity(1);
+        assert!(registry.contains("/c.js"));
+        assert_eq!(registry.evict_oldest().as_deref(), Some("/c.js"));
+        assert!(registry.is_empty());
+    }
+}
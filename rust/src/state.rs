@@ -0,0 +1,118 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use crate::aggregates::{self, Aggregates};
+use crate::hash::xxhash64;
+use crate::packages::{self, PackageInfo, PackageMap};
+use crate::parser;
+use crate::grouping;
+use crate::sourcemap_registry;
+use crate::storm::StormDetector;
+
+// 持久化格式：魔数 + 版本号 + 8字节xxhash64校验和 + JSON负载，与基线数据的封装一致
+const STATE_MAGIC: &[u8; 3] = b"PLS";
+const STATE_VERSION: u8 = 1;
+const STATE_HEADER_LEN: usize = STATE_MAGIC.len() + 1 + 8;
+
+/// 页面刷新或迁移到worker后需要恢复的全局状态。已知指纹的 `Baseline` 由调用方持有，
+/// 不属于全局状态，通过其自身的 `to_bytes`/`from_bytes` 持久化
+#[derive(Serialize, Deserialize)]
+struct StateSnapshot {
+    /// 已注册的source map（URL与map JSON），最久未使用的在前
+    source_maps: Vec<(String, serde_json::Value)>,
+    frameworks: BTreeMap<String, String>,
+    packages: BTreeMap<String, PackageInfo>,
+    /// 错误风暴检测的各指纹频率
+    storm: StormDetector,
+    aggregates: Aggregates,
+}
+
+/// 导出当前状态：已注册的source map、框架映射、包清单、风暴检测与聚合数据
+pub fn export_state() -> Vec<u8> {
+    let snapshot = StateSnapshot {
+        source_maps: sourcemap_registry::entries(),
+        frameworks: parser::framework_map().into_iter().collect(),
+        packages: packages::package_map().to_map().into_iter().collect(),
+        storm: grouping::storm_snapshot(),
+        aggregates: aggregates::snapshot(),
+    };
+    let payload = serde_json::to_vec(&snapshot).unwrap_or_default();
+
+    let mut bytes = Vec::with_capacity(STATE_HEADER_LEN + payload.len());
+    bytes.extend_from_slice(STATE_MAGIC);
+    bytes.push(STATE_VERSION);
+    bytes.extend_from_slice(&xxhash64(&payload, 0).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    bytes
+}
+
+/// 恢复 `export_state` 导出的状态，替换当前的对应状态；数据无效时不修改任何状态
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    if bytes.len() < STATE_HEADER_LEN || &bytes[..STATE_MAGIC.len()] != STATE_MAGIC {
+        return Err("不是PerfLite状态数据".to_string());
+    }
+    if bytes[STATE_MAGIC.len()] != STATE_VERSION {
+        return Err(format!("不支持的状态版本: {}", bytes[STATE_MAGIC.len()]));
+    }
+    let mut checksum = [0u8; 8];
+    checksum.copy_from_slice(&bytes[STATE_MAGIC.len() + 1..STATE_HEADER_LEN]);
+    let payload = &bytes[STATE_HEADER_LEN..];
+    if u64::from_le_bytes(checksum) != xxhash64(payload, 0) {
+        return Err("状态数据已损坏".to_string());
+    }
+    let snapshot: StateSnapshot = serde_json::from_slice(payload).map_err(|e| format!("状态数据解析失败: {}", e))?;
+
+    let source_maps: Vec<(String, String)> = snapshot.source_maps.iter().map(|(url, map)| (url.clone(), map.to_string())).collect();
+    sourcemap_registry::replace_all(&source_maps)?;
+    parser::set_framework_map(snapshot.frameworks.into_iter().collect());
+    packages::set_package_map(PackageMap::new(snapshot.packages.into_iter().collect()));
+    grouping::restore_storms(snapshot.storm);
+    aggregates::restore(snapshot.aggregates);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::parser::StackFrame;
+
+    #[test]
+    fn test_state_round_trip() {
+        let map = r#"{"version": 3, "file": "app.js", "sourceRoot": "src", "sources": ["a.ts"], "names": ["render"], "mappings": "AAAAA"}"#;
+        sourcemap_registry::register("https://cdn.test/app.js", map).unwrap();
+        parser::set_framework_map(HashMap::from([("/widgets/".to_string(), "Widgets".to_string())]));
+        packages::set_package_map(PackageMap::new(serde_json::from_str(r#"{"packages/ui/": {"name": "@shop/ui"}}"#).unwrap()));
+        aggregates::record("group-a");
+        grouping::record_key("fingerprint-a");
+        grouping::record_key("fingerprint-b");
+        grouping::record_key("fingerprint-c");
+        let frame = StackFrame::new("f".to_string(), "https://cdn.test/app.js".to_string(), 1, 1);
+        let mapped = sourcemap_registry::map_frame(&frame).unwrap();
+
+        let bytes = export_state();
+        sourcemap_registry::clear();
+        parser::set_framework_map(HashMap::new());
+        packages::set_package_map(PackageMap::default());
+        aggregates::restore(Aggregates::default());
+        grouping::clear_storms();
+
+        import_state(&bytes).unwrap();
+        assert_eq!(sourcemap_registry::map_frame(&frame).map(|frame| frame.file_name_str().to_string()), Some(mapped.file_name_str().to_string()));
+        assert_eq!(parser::framework_map().get("/widgets/").map(String::as_str), Some("Widgets"));
+        assert!(packages::package_map().package_for("packages/ui/button.tsx").is_some());
+        assert_eq!(aggregates::stats().groups, 1);
+        assert_eq!(aggregates::stats().approx_bytes, aggregates::snapshot().approx_bytes());
+        assert_eq!(export_state(), bytes);
+
+        let mut corrupted = bytes.clone();
+        corrupted[STATE_HEADER_LEN + 1] ^= 1;
+        assert!(import_state(&corrupted).is_err());
+        assert!(import_state(b"PLB").is_err());
+
+        sourcemap_registry::clear();
+        parser::set_framework_map(HashMap::new());
+        packages::set_package_map(PackageMap::default());
+        aggregates::restore(Aggregates::default());
+        grouping::clear_storms();
+    }
+}
@@ -0,0 +1,240 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+// 基线的指数滑动平均系数
+const BASELINE_ALPHA: f64 = 0.2;
+
+/// 错误风暴判定参数
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct StormPolicy {
+    /// 统计窗口长度
+    pub window_ms: f64,
+    /// 窗口内次数超过基线的倍数时判定为风暴
+    pub spike_factor: f64,
+    /// 窗口内至少达到该次数才可能是风暴，避免低频错误误判
+    pub min_events: u32,
+    /// 风暴期间每个窗口仍单独上报的事件数，超出部分只计数
+    pub report_limit: u32,
+}
+
+impl Default for StormPolicy {
+    fn default() -> Self {
+        StormPolicy {
+            window_ms: 10_000.0,
+            spike_factor: 10.0,
+            min_events: 20,
+            report_limit: 5,
+        }
+    }
+}
+
+/// 合成的错误风暴事件，风暴结束时产生
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StormEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub fingerprint: String,
+    pub started: f64,
+    pub ended: f64,
+    pub total_events: u64,
+    pub suppressed_events: u64,
+    /// 风暴前每个窗口的平均次数
+    pub baseline_per_window: f64,
+    pub peak_per_window: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct ActiveStorm {
+    started: f64,
+    total: u64,
+    suppressed: u64,
+    baseline: f64,
+    peak: u32,
+    // 当前窗口内已单独上报的事件数
+    reported: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct FingerprintRate {
+    window_start: f64,
+    count: u32,
+    // 风暴窗口不计入基线
+    baseline: f64,
+    storm: Option<ActiveStorm>,
+}
+
+/// 按指纹检测错误风暴：窗口内次数相对滚动基线突增时只上报少量事件，
+/// 其余事件计入风暴，风暴平息后合成一条汇总事件。由 `Grouper` 持有，按分组键检测
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct StormDetector {
+    policy: StormPolicy,
+    // 有序映射，序列化结果稳定，导出的状态字节可直接比较
+    rates: BTreeMap<String, FingerprintRate>,
+    finished: Vec<StormEvent>,
+}
+
+impl StormDetector {
+    pub fn new(policy: StormPolicy) -> Self {
+        StormDetector { policy, rates: BTreeMap::new(), finished: Vec::new() }
+    }
+
+    pub fn set_policy(&mut self, policy: StormPolicy) {
+        self.policy = policy;
+    }
+
+    fn threshold(policy: &StormPolicy, baseline: f64) -> f64 {
+        (baseline * policy.spike_factor).max(policy.min_events as f64)
+    }
+
+    /// 关闭已结束的窗口：更新基线，风暴窗口低于阈值时结束风暴
+    fn close_window(policy: &StormPolicy, fingerprint: &str, rate: &mut FingerprintRate, now: f64, finished: &mut Vec<StormEvent>) {
+        let window = policy.window_ms.max(1.0);
+        let elapsed = ((now - rate.window_start) / window).floor();
+        if elapsed < 1.0 {
+            return;
+        }
+
+        let stormy = rate.count as f64 > Self::threshold(policy, rate.baseline);
+        if !stormy {
+            rate.baseline += (rate.count as f64 - rate.baseline) * BASELINE_ALPHA;
+        }
+        // 中间没有事件的空窗口让基线衰减
+        rate.baseline *= (1.0 - BASELINE_ALPHA).powf(elapsed - 1.0);
+
+        if stormy && elapsed == 1.0 {
+            // 风暴延续到下一个窗口
+            if let Some(storm) = rate.storm.as_mut() {
+                storm.reported = 0;
+            }
+        } else if let Some(storm) = rate.storm.take() {
+            finished.push(StormEvent {
+                kind: "error_storm".to_string(),
+                fingerprint: fingerprint.to_string(),
+                started: storm.started,
+                ended: rate.window_start + window,
+                total_events: storm.total,
+                suppressed_events: storm.suppressed,
+                baseline_per_window: storm.baseline,
+                peak_per_window: storm.peak,
+            });
+        }
+        rate.window_start += elapsed * window;
+        rate.count = 0;
+    }
+
+    /// 记录一次错误，返回是否应单独上报（风暴中超出上报数量的事件返回false）
+    pub fn record_at(&mut self, fingerprint: &str, now: f64) -> bool {
+        let policy = self.policy;
+        let rate = self.rates.entry(fingerprint.to_string()).or_insert(FingerprintRate {
+            window_start: now,
+            count: 0,
+            baseline: 0.0,
+            storm: None,
+        });
+        Self::close_window(&policy, fingerprint, rate, now, &mut self.finished);
+
+        rate.count += 1;
+        if rate.storm.is_none() && rate.count as f64 > Self::threshold(&policy, rate.baseline) {
+            // 进入风暴前的事件已经单独上报，只计入总数
+            rate.storm = Some(ActiveStorm {
+                started: rate.window_start,
+                total: rate.count as u64 - 1,
+                suppressed: 0,
+                baseline: rate.baseline,
+                peak: 0,
+                reported: 0,
+            });
+        }
+
+        match rate.storm.as_mut() {
+            Some(storm) => {
+                storm.total += 1;
+                storm.peak = storm.peak.max(rate.count);
+                if storm.reported >= policy.report_limit {
+                    storm.suppressed += 1;
+                    return false;
+                }
+                storm.reported += 1;
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// 取出已结束的风暴事件，`now` 用于关闭已过期的窗口
+    pub fn take_events_at(&mut self, now: f64) -> Vec<StormEvent> {
+        let policy = self.policy;
+        for (fingerprint, rate) in self.rates.iter_mut() {
+            Self::close_window(&policy, fingerprint, rate, now, &mut self.finished);
+        }
+        // 长期没有事件、基线已衰减为零的指纹不再保留
+        self.rates.retain(|_, rate| rate.storm.is_some() || rate.count > 0 || rate.baseline > 0.01);
+        std::mem::take(&mut self.finished)
+    }
+
+    /// 指纹是否处于风暴中
+    pub fn in_storm(&self, fingerprint: &str) -> bool {
+        self.rates.get(fingerprint).is_some_and(|rate| rate.storm.is_some())
+    }
+
+    pub fn clear(&mut self) {
+        self.rates.clear();
+        self.finished.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> StormDetector {
+        StormDetector::new(StormPolicy { window_ms: 1000.0, spike_factor: 5.0, min_events: 10, report_limit: 2 })
+    }
+
+    #[test]
+    fn test_storm_suppresses_and_summarizes() {
+        let mut detector = detector();
+        // 平时每个窗口3次
+        for window in 0..5 {
+            for i in 0..3 {
+                assert!(detector.record_at("fp", window as f64 * 1000.0 + i as f64));
+            }
+        }
+        assert!(!detector.in_storm("fp"));
+
+        // 突增到100次：阈值内的事件与风暴开始后的2个事件上报，其余抑制
+        let reported = (0..100).filter(|i| detector.record_at("fp", 5000.0 + *i as f64)).count();
+        assert!(detector.in_storm("fp"));
+        assert_eq!(reported, 12);
+        assert!(detector.take_events_at(5500.0).is_empty());
+
+        // 风暴窗口之后的窗口恢复正常，风暴在该窗口关闭时结束
+        assert!(detector.record_at("fp", 6100.0));
+        assert!(detector.take_events_at(6500.0).is_empty());
+        let events = detector.take_events_at(7100.0);
+        assert_eq!(events.len(), 1);
+        let storm = &events[0];
+        assert_eq!((storm.total_events, storm.suppressed_events, storm.peak_per_window), (101, 88, 100));
+        assert_eq!((storm.started, storm.ended), (5000.0, 7000.0));
+        assert!(storm.baseline_per_window > 1.0 && storm.baseline_per_window < 3.0);
+        assert!(!detector.in_storm("fp"));
+    }
+
+    #[test]
+    fn test_fingerprints_are_independent() {
+        let mut detector = detector();
+        for i in 0..50 {
+            detector.record_at("noisy", i as f64);
+        }
+        assert!(detector.record_at("quiet", 60.0));
+        assert!(detector.in_storm("noisy") && !detector.in_storm("quiet"));
+
+        // 风暴之后长时间没有事件，在flush时结束
+        let events = detector.take_events_at(10_000.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].fingerprint, "noisy");
+        let json = serde_json::to_string(&events[0]).unwrap();
+        assert!(json.starts_with(r#"{"type":"error_storm","fingerprint":"noisy""#));
+    }
+}
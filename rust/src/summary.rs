@@ -0,0 +1,121 @@
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use crate::fingerprint::strip_query;
+use crate::parser::{ErrorParser, FrameOrigin, StackFrame};
+use crate::parser_options;
+
+/// 栈的紧凑统计，采样丢弃的错误附带它代替完整栈
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct StackSummary {
+    pub total: u32,
+    /// 业务代码与eval帧
+    pub app: u32,
+    /// 第三方依赖帧
+    pub vendor: u32,
+    /// 引擎内置函数与原生二进制帧
+    pub native: u32,
+    /// 浏览器扩展与运行时内部帧
+    pub extension: u32,
+    /// 离栈顶最远的业务代码帧下标，没有业务代码帧时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deepest_app_frame: Option<usize>,
+    /// 涉及的不同源码文件数（忽略查询参数，不含 `<anonymous>` 等没有源码的位置）
+    pub unique_files: u32,
+}
+
+/// 统计已解析的栈帧
+pub fn summarize_frames(frames: &[StackFrame]) -> StackSummary {
+    let mut summary = StackSummary { total: frames.len() as u32, ..StackSummary::default() };
+    let mut files: BTreeSet<&str> = BTreeSet::new();
+    for (index, frame) in frames.iter().enumerate() {
+        match frame.origin() {
+            FrameOrigin::App | FrameOrigin::Eval => {
+                summary.app += 1;
+                summary.deepest_app_frame = Some(index);
+            }
+            FrameOrigin::NodeModules => summary.vendor += 1,
+            FrameOrigin::Native | FrameOrigin::NativeBinary => summary.native += 1,
+            FrameOrigin::BrowserInternal => summary.extension += 1,
+        }
+        if !frame.has_no_source() {
+            files.insert(strip_query(frame.relative_file_name()));
+        }
+    }
+    summary.unique_files = files.len() as u32;
+    summary
+}
+
+/// 解析错误栈并统计
+pub fn summarize(stack: &str) -> StackSummary {
+    summarize_frames(&ErrorParser::new().parse_frames(stack))
+}
+
+/// 栈帧的源码位置，符号服务器据此预取source map
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// 一批错误栈中去重后的 `(文件, 行, 列)`，按首次出现的顺序，跳过没有位置的帧
+pub fn unique_locations<S: AsRef<str>>(stacks: &[S]) -> Vec<SourceLocation> {
+    let parser = ErrorParser::new();
+    let positions = parser_options::options();
+    let mut seen: HashSet<(String, u32, u32)> = HashSet::new();
+    let mut locations = Vec::new();
+    for stack in stacks {
+        for frame in parser.parse_frames(stack.as_ref()) {
+            if frame.line_number() == 0 {
+                continue;
+            }
+            let key = (frame.file_name_str().to_string(), frame.line_number(), frame.column_number());
+            if seen.contains(&key) {
+                continue;
+            }
+            locations.push(SourceLocation {
+                file: key.0.clone(),
+                line: positions.export_line(key.1),
+                column: positions.export_column(key.2),
+            });
+            seen.insert(key);
+        }
+    }
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let stack = "TypeError: x\n at render (https://cdn.test/app.js?v=1:10:15)\n at r (https://cdn.test/node_modules/react/index.js:1:1)\n at update (https://cdn.test/app.js?v=2:40:2)\n at Array.map (<anonymous>)\n at inject (chrome-extension://abc/content.js:1:1)";
+        assert_eq!(summarize(stack), StackSummary {
+            total: 5,
+            app: 2,
+            vendor: 1,
+            native: 1,
+            extension: 1,
+            deepest_app_frame: Some(2),
+            unique_files: 3,
+        });
+        assert_eq!(summarize("Error: boom"), StackSummary::default());
+    }
+
+    #[test]
+    fn test_unique_locations() {
+        let stacks = [
+            "TypeError: x\n at render (https://cdn.test/app.js:10:15)\n at Array.map (<anonymous>)\n at update (https://cdn.test/app.js:40:2)",
+            "RangeError: y\n at update (https://cdn.test/app.js:40:2)\n at render (https://cdn.test/app.js:10:16)",
+        ];
+        let locations = unique_locations(&stacks);
+        let triples: Vec<(&str, u32, u32)> = locations.iter().map(|location| (location.file.as_str(), location.line, location.column)).collect();
+        assert_eq!(triples, vec![
+            ("https://cdn.test/app.js", 10, 15),
+            ("https://cdn.test/app.js", 40, 2),
+            ("https://cdn.test/app.js", 10, 16),
+        ]);
+        assert!(unique_locations(&["Error: boom"]).is_empty());
+    }
+}
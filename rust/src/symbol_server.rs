@@ -0,0 +1,207 @@
+use wasm_bindgen::prelude::*;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+/// 符号服务器查询方式
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SymbolLookup {
+    /// 按调试ID查询
+    DebugId { debug_id: String },
+    /// 按版本号+文件路径查询
+    ReleasePath { release: String, path: String },
+}
+
+/// 交给JS fetch回调执行的请求描述
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SymbolRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub lookup: SymbolLookup,
+}
+
+/// 符号服务器响应的处理结果
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "status", content = "body", rename_all = "snake_case")]
+pub enum SymbolResponse {
+    Found(String),
+    NotFound,
+    Unauthorized,
+    Failed(u16),
+}
+
+/// 标准化调试ID：去掉空白与花括号，转小写，32位十六进制补齐连字符
+pub fn normalize_debug_id(debug_id: &str) -> Option<String> {
+    let hex: String = debug_id.trim()
+        .trim_matches(|c| c == '{' || c == '}')
+        .chars()
+        .filter(|c| *c != '-')
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    Some(format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32]))
+}
+
+/// 对URL路径片段做百分号编码
+fn encode_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// 私有符号服务器客户端，只负责构造请求和解释响应，实际网络请求由JS完成
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct SymbolServerClient {
+    base_url: String,
+    // 透传给fetch的认证头
+    auth_header: Option<(String, String)>,
+}
+
+#[wasm_bindgen]
+impl SymbolServerClient {
+    pub fn new(base_url: &str) -> Self {
+        SymbolServerClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            auth_header: None,
+        }
+    }
+
+    /// 设置认证头，例如 `Authorization: Bearer xxx`
+    pub fn set_auth_header(&mut self, name: &str, value: &str) {
+        self.auth_header = Some((name.to_string(), value.to_string()));
+    }
+
+    /// 按调试ID构造请求，返回JSON；ID非法时返回undefined
+    pub fn request_by_debug_id(&self, debug_id: &str) -> Option<String> {
+        self.debug_id_request(debug_id)
+            .and_then(|request| serde_json::to_string(&request).ok())
+    }
+
+    /// 按版本号与文件路径构造请求，返回JSON
+    pub fn request_by_release(&self, release: &str, path: &str) -> String {
+        serde_json::to_string(&self.release_request(release, path)).unwrap_or_default()
+    }
+
+    /// 调用JS fetch回调 `fetch(url, headersJson)`，返回回调的结果（通常是Promise）
+    pub fn fetch_by_debug_id(&self, debug_id: &str, fetch: &js_sys::Function) -> Result<JsValue, JsValue> {
+        let request = self.debug_id_request(debug_id)
+            .ok_or_else(|| JsValue::from_str("invalid debug id"))?;
+        Self::dispatch(&request, fetch)
+    }
+
+    /// 调用JS fetch回调按版本号与路径获取source map
+    pub fn fetch_by_release(&self, release: &str, path: &str, fetch: &js_sys::Function) -> Result<JsValue, JsValue> {
+        Self::dispatch(&self.release_request(release, path), fetch)
+    }
+
+    /// 解释HTTP响应，返回 `{"status": ..., "body": ...}` JSON
+    pub fn handle_response(&self, status: u16, body: &str) -> String {
+        serde_json::to_string(&interpret_response(status, body)).unwrap_or_default()
+    }
+}
+
+impl SymbolServerClient {
+    fn headers(&self) -> BTreeMap<String, String> {
+        let mut headers = BTreeMap::new();
+        headers.insert("Accept".to_string(), "application/json".to_string());
+        if let Some((name, value)) = &self.auth_header {
+            headers.insert(name.clone(), value.clone());
+        }
+        headers
+    }
+
+    /// 按调试ID构造请求：`GET {base}/maps/debug-id/{id}`
+    pub fn debug_id_request(&self, debug_id: &str) -> Option<SymbolRequest> {
+        let debug_id = normalize_debug_id(debug_id)?;
+        Some(SymbolRequest {
+            method: "GET".to_string(),
+            url: format!("{}/maps/debug-id/{}", self.base_url, debug_id),
+            headers: self.headers(),
+            lookup: SymbolLookup::DebugId { debug_id },
+        })
+    }
+
+    /// 按版本号与路径构造请求：`GET {base}/maps/releases/{release}/files/{path}`
+    pub fn release_request(&self, release: &str, path: &str) -> SymbolRequest {
+        SymbolRequest {
+            method: "GET".to_string(),
+            url: format!("{}/maps/releases/{}/files/{}", self.base_url, encode_segment(release), encode_segment(path)),
+            headers: self.headers(),
+            lookup: SymbolLookup::ReleasePath {
+                release: release.to_string(),
+                path: path.to_string(),
+            },
+        }
+    }
+
+    fn dispatch(request: &SymbolRequest, fetch: &js_sys::Function) -> Result<JsValue, JsValue> {
+        let headers = serde_json::to_string(&request.headers)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        fetch.call2(&JsValue::NULL, &JsValue::from_str(&request.url), &JsValue::from_str(&headers))
+    }
+}
+
+/// 根据HTTP状态码解释符号服务器响应
+pub fn interpret_response(status: u16, body: &str) -> SymbolResponse {
+    match status {
+        200..=299 => SymbolResponse::Found(body.to_string()),
+        404 | 410 => SymbolResponse::NotFound,
+        401 | 403 => SymbolResponse::Unauthorized,
+        _ => SymbolResponse::Failed(status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_debug_id() {
+        assert_eq!(
+            normalize_debug_id("{85314830-023F-4CF1-A267-535F4E37BB17}").as_deref(),
+            Some("85314830-023f-4cf1-a267-535f4e37bb17")
+        );
+        assert_eq!(
+            normalize_debug_id("85314830023f4cf1a267535f4e37bb17").as_deref(),
+            Some("85314830-023f-4cf1-a267-535f4e37bb17")
+        );
+        assert_eq!(normalize_debug_id("not-a-debug-id"), None);
+    }
+
+    #[test]
+    fn test_build_requests() {
+        let mut client = SymbolServerClient::new("https://symbols.example.com/");
+        client.set_auth_header("Authorization", "Bearer secret");
+
+        let request = client.debug_id_request("85314830023f4cf1a267535f4e37bb17").unwrap();
+        assert_eq!(request.url, "https://symbols.example.com/maps/debug-id/85314830-023f-4cf1-a267-535f4e37bb17");
+        assert_eq!(request.headers.get("Authorization").map(String::as_str), Some("Bearer secret"));
+
+        let request = client.release_request("web@1.2.0", "static/js/main.js");
+        assert_eq!(request.url, "https://symbols.example.com/maps/releases/web%401.2.0/files/static%2Fjs%2Fmain.js");
+        assert!(client.request_by_release("1.0", "a.js").contains("\"kind\":\"release_path\""));
+        assert_eq!(client.request_by_debug_id("bad"), None);
+    }
+
+    #[test]
+    fn test_interpret_response() {
+        assert_eq!(interpret_response(200, "{}"), SymbolResponse::Found("{}".to_string()));
+        assert_eq!(interpret_response(404, ""), SymbolResponse::NotFound);
+        assert_eq!(interpret_response(403, ""), SymbolResponse::Unauthorized);
+        assert_eq!(interpret_response(502, ""), SymbolResponse::Failed(502));
+
+        let client = SymbolServerClient::new("https://symbols.example.com");
+        assert_eq!(client.handle_response(404, ""), r#"{"status":"not_found"}"#);
+    }
+}
@@ -0,0 +1,285 @@
+use wasm_bindgen::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use crate::envelope::{self, ErrorEnvelope};
+use crate::fingerprint::strip_query;
+use crate::parser::{ErrorParser, FrameOrigin, StackFrame};
+use crate::sourcemap_registry;
+use crate::utils::console_log;
+use crate::wasm_symbols;
+
+// 默认保留的等待事件数
+const DEFAULT_CAPACITY: usize = 100;
+
+/// 等待source map的产物，`pending_bundles` 按等待的帧数降序排列，优先获取最能提高覆盖率的map
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PendingBundle {
+    pub url: String,
+    /// 等待该map的帧数
+    pub frames: u32,
+    /// 等待该map的事件数
+    pub events: u32,
+}
+
+// 等待map的事件：能还原的帧已还原，其余帧附带所等待的产物URL
+struct PendingEvent {
+    stack: String,
+    received: f64,
+    frames: Vec<(StackFrame, Option<String>)>,
+}
+
+impl PendingEvent {
+    fn is_complete(&self) -> bool {
+        self.frames.iter().all(|(_, waiting)| waiting.is_none())
+    }
+
+    /// 组装事件，仍在等待的帧按未还原处理
+    fn into_envelope(self) -> ErrorEnvelope {
+        let frames = self.frames.into_iter()
+            .map(|(frame, waiting)| if waiting.is_some() { sourcemap_registry::unmapped(&frame) } else { frame })
+            .collect();
+        envelope::build(&self.stack, frames, self.received, None)
+    }
+}
+
+/// 需要source map但还没有注册的帧所在的产物URL（去掉查询参数）
+fn missing_bundle(frame: &StackFrame) -> Option<String> {
+    if frame.is_wasm() || frame.line_number() == 0 || frame.is_stale_bundle() || !matches!(frame.origin(), FrameOrigin::App | FrameOrigin::NodeModules) {
+        return None;
+    }
+    let url = strip_query(frame.file_name_str());
+    (!sourcemap_registry::contains(url)).then(|| url.to_string())
+}
+
+/// 待还原帧的协调队列：暂存引用了未注册source map的事件，宿主按 `pending_bundles` 异步获取map后调用 `resolve`，
+/// 等待的map都到齐的事件补全还原后返回；超出容量时最早的事件按未还原处理，由 `take_evicted` 取出上报
+#[wasm_bindgen]
+pub struct SymbolicationQueue {
+    events: BTreeMap<u32, PendingEvent>,
+    next_id: u32,
+    capacity: usize,
+    dropped: u32,
+    evicted: Vec<ErrorEnvelope>,
+}
+
+#[wasm_bindgen]
+impl SymbolicationQueue {
+    /// `capacity` 为保留的等待事件数，0使用默认值100
+    pub fn new(capacity: usize) -> Self {
+        SymbolicationQueue {
+            events: BTreeMap::new(),
+            next_id: 0,
+            capacity: if capacity == 0 { DEFAULT_CAPACITY } else { capacity },
+            dropped: 0,
+            evicted: Vec::new(),
+        }
+    }
+
+    /// 解析并加入错误栈：所需的map都已注册时直接返回还原后的事件JSON，否则暂存并返回undefined
+    pub fn enqueue(&mut self, stack: &str) -> Option<String> {
+        self.push(stack, envelope::received_now()).map(|envelope| envelope_json(&envelope))
+    }
+
+    /// 等待中的产物URL（去重）及等待的帧数与事件数，按帧数降序的JSON数组
+    pub fn pending_bundles(&self) -> String {
+        crate::to_json_or(&self.bundles(), "[]")
+    }
+
+    /// 注册产物的source map并补全等待它的帧，返回因此完成的事件JSON数组；
+    /// map无效时这些帧按未还原处理，事件不再等待它。期间通过全局 `register_source_map` 注册的map一并补全
+    pub fn resolve(&mut self, url: &str, map_json: &str) -> String {
+        envelopes_json(&self.resolve_events(url, map_json))
+    }
+
+    /// 放弃等待，按当前已还原的程度返回全部事件的JSON数组并清空队列
+    pub fn flush(&mut self) -> String {
+        envelopes_json(&self.flush_events())
+    }
+
+    /// 等待中的事件数
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// 因超出容量被提前移出队列的事件数（累计）
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// 取出因超出容量被移出队列的事件（按未还原处理）的JSON数组，取出后清空
+    pub fn take_evicted(&mut self) -> String {
+        envelopes_json(&self.take_evicted_events())
+    }
+}
+
+impl SymbolicationQueue {
+    /// 加入错误栈，`received` 为接收时间（毫秒）
+    pub fn push(&mut self, stack: &str, received: f64) -> Option<ErrorEnvelope> {
+        let frames = ErrorParser::new().parse_frames(stack).into_iter()
+            .map(|frame| match missing_bundle(&frame) {
+                Some(url) => (frame, Some(url)),
+                None if frame.is_wasm() => (wasm_symbols::symbolize(&frame), None),
+                None => (sourcemap_registry::symbolicate(&frame), None),
+            })
+            .collect();
+        let event = PendingEvent { stack: stack.to_string(), received, frames };
+        if event.is_complete() {
+            return Some(event.into_envelope());
+        }
+
+        if self.events.len() >= self.capacity {
+            if let Some((_, oldest)) = self.events.pop_first() {
+                self.evicted.push(oldest.into_envelope());
+                self.dropped += 1;
+            }
+        }
+        self.next_id = self.next_id.wrapping_add(1);
+        self.events.insert(self.next_id, event);
+        None
+    }
+
+    /// 等待中的产物，帧数相同时按首次出现的顺序；已在全局注册表中的map不再列出
+    pub fn bundles(&self) -> Vec<PendingBundle> {
+        let mut bundles: Vec<PendingBundle> = Vec::new();
+        for event in self.events.values() {
+            let mut seen_in_event: Vec<&str> = Vec::new();
+            let waiting = event.frames.iter().filter_map(|(_, waiting)| waiting.as_deref());
+            for url in waiting.filter(|url| !sourcemap_registry::contains(url)) {
+                let index = match bundles.iter().position(|bundle| bundle.url == url) {
+                    Some(index) => index,
+                    None => {
+                        bundles.push(PendingBundle { url: url.to_string(), frames: 0, events: 0 });
+                        bundles.len() - 1
+                    }
+                };
+                bundles[index].frames += 1;
+                if !seen_in_event.contains(&url) {
+                    seen_in_event.push(url);
+                    bundles[index].events += 1;
+                }
+            }
+        }
+        bundles.sort_by_key(|bundle| std::cmp::Reverse(bundle.frames));
+        bundles
+    }
+
+    /// 注册map并补全等待它的帧，返回完成的事件
+    pub fn resolve_events(&mut self, url: &str, map_json: &str) -> Vec<ErrorEnvelope> {
+        if let Err(e) = sourcemap_registry::register(url, map_json) {
+            console_log(&e);
+        }
+        let url = strip_query(url);
+        for event in self.events.values_mut() {
+            // 给出的URL即使map无效也不再等待；其他URL的map可能已通过全局注册表注册
+            let ready = |waiting: &Option<String>| waiting.as_deref().is_some_and(|waiting| waiting == url || sourcemap_registry::contains(waiting));
+            for (frame, waiting) in event.frames.iter_mut().filter(|(_, waiting)| ready(waiting)) {
+                *frame = sourcemap_registry::symbolicate(frame);
+                *waiting = None;
+            }
+        }
+
+        let completed: Vec<u32> = self.events.iter().filter(|(_, event)| event.is_complete()).map(|(id, _)| *id).collect();
+        completed.into_iter()
+            .filter_map(|id| self.events.remove(&id))
+            .map(PendingEvent::into_envelope)
+            .collect()
+    }
+
+    /// 清空队列并返回全部事件
+    pub fn flush_events(&mut self) -> Vec<ErrorEnvelope> {
+        std::mem::take(&mut self.events).into_values().map(PendingEvent::into_envelope).collect()
+    }
+
+    /// 取出因超出容量被移出队列的事件
+    pub fn take_evicted_events(&mut self) -> Vec<ErrorEnvelope> {
+        std::mem::take(&mut self.evicted)
+    }
+}
+
+impl Default for SymbolicationQueue {
+    fn default() -> Self {
+        SymbolicationQueue::new(0)
+    }
+}
+
+fn envelope_json(envelope: &ErrorEnvelope) -> String {
+    crate::to_json_or(envelope, "{}")
+}
+
+fn envelopes_json(envelopes: &[ErrorEnvelope]) -> String {
+    crate::to_json_or(&envelopes, "[]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r#"{"version":3,"sources":["src/cart.js"],"names":["addItem"],"mappings":"AAAA,SAASA"}"#;
+
+    #[test]
+    fn test_resolve_pending_bundles() {
+        let mut queue = SymbolicationQueue::new(0);
+        let stack = "TypeError: x\n at a (https://queue.test/cart.min.js?v=2:1:10)\n at b (https://queue.test/cart.min.js:1:1)\n at c (https://queue.test/vendor.js:1:1)";
+        assert!(queue.push(stack, 0.0).is_none());
+        assert!(queue.push("Error: y\n at d (https://queue.test/vendor.js:2:1)", 0.0).is_none());
+        // 没有需要map的帧时直接完成
+        assert!(queue.push("Error: z\n at Array.map (<anonymous>)", 0.0).is_some());
+
+        assert_eq!(queue.bundles(), vec![
+            PendingBundle { url: "https://queue.test/cart.min.js".to_string(), frames: 2, events: 1 },
+            PendingBundle { url: "https://queue.test/vendor.js".to_string(), frames: 2, events: 2 },
+        ]);
+
+        // 第一个事件还在等待vendor.js
+        assert!(queue.resolve_events("https://queue.test/cart.min.js", MAP).is_empty());
+        assert_eq!(queue.bundles().len(), 1);
+
+        // 无效的map也不再等待，帧按未还原处理
+        let completed = queue.resolve_events("https://queue.test/vendor.js", "{}");
+        assert_eq!(completed.len(), 2);
+        let frames = &completed[0].error.frames;
+        assert_eq!((frames[0].function_name.as_str(), frames[0].file_name.as_str()), ("addItem", "src/cart.js"));
+        assert_eq!(frames[2].annotations.get("symbolication_reason").map(String::as_str), Some("no_map"));
+        assert!(queue.is_empty());
+        sourcemap_registry::unregister("https://queue.test/cart.min.js");
+    }
+
+    #[test]
+    fn test_capacity_and_flush() {
+        let mut queue = SymbolicationQueue::new(2);
+        for i in 0..3 {
+            queue.push(&format!("Error: {}\n at f (https://flush.test/app.js:1:1)", i), 0.0);
+        }
+        assert_eq!((queue.len(), queue.dropped()), (2, 1));
+
+        // 被移出的事件没有丢失，按未还原处理后取出
+        let evicted = queue.take_evicted_events();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].error.message, "Error: 0");
+        assert_eq!(evicted[0].error.frames[0].annotations.get("symbolication_reason").map(String::as_str), Some("no_map"));
+        assert!(queue.take_evicted_events().is_empty());
+
+        let flushed = queue.flush_events();
+        assert_eq!(flushed.iter().map(|envelope| envelope.error.message.as_str()).collect::<Vec<_>>(), vec!["Error: 1", "Error: 2"]);
+        assert!(queue.is_empty() && queue.bundles().is_empty());
+    }
+
+    #[test]
+    fn test_maps_registered_globally() {
+        let mut queue = SymbolicationQueue::new(0);
+        assert!(queue.push("Error: a\n at f (https://global.test/app.js:1:10)\n at g (https://global.test/lib.js:1:1)", 0.0).is_none());
+        assert_eq!(queue.bundles().len(), 2);
+
+        // 通过全局注册表注册的map不再列为等待，下一次resolve时一并补全
+        sourcemap_registry::register("https://global.test/app.js", MAP).unwrap();
+        assert_eq!(queue.bundles().iter().map(|bundle| bundle.url.as_str()).collect::<Vec<_>>(), vec!["https://global.test/lib.js"]);
+        let completed = queue.resolve_events("https://global.test/lib.js", "{}");
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].error.frames[0].function_name, "addItem");
+        sourcemap_registry::unregister("https://global.test/app.js");
+    }
+}
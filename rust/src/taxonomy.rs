@@ -0,0 +1,141 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+/// 需要单独分组的特殊错误形态，它们的栈帧与消息对分组没有意义
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// 跨域脚本的 `Script error.`，浏览器隐藏了全部细节
+    CrossOriginScript,
+    /// 内容安全策略（CSP）拦截
+    CspViolation,
+    /// 动态加载JS分块失败：webpack的 `ChunkLoadError`、vite的 `Failed to fetch dynamically imported module`
+    ChunkLoad,
+    /// 动态加载CSS分块失败
+    CssChunkLoad,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::CrossOriginScript => "cross_origin_script",
+            ErrorCategory::CspViolation => "csp_violation",
+            ErrorCategory::ChunkLoad => "chunk_load",
+            ErrorCategory::CssChunkLoad => "css_chunk_load",
+        }
+    }
+}
+
+/// 分类结果与专用的分组键
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Classification {
+    pub category: ErrorCategory,
+    /// 代替栈帧参与指纹的内容
+    pub key: String,
+}
+
+/// 文本中第一个URL的主机，没有绝对URL时为空字符串
+fn first_host(text: &str) -> &str {
+    let Some(at) = text.find("://") else {
+        return "";
+    };
+    let rest = &text[at + 3..];
+    let end = rest.find(['/', ' ', ')', '"', '\'', '?', '#']).unwrap_or(rest.len());
+    &rest[..end]
+}
+
+/// 被拦截资源的分组值：URL取主机，`inline`、`eval` 等关键字原样保留
+fn blocked_key(blocked: &str) -> String {
+    match first_host(blocked) {
+        "" => blocked.trim().to_string(),
+        host => host.to_string(),
+    }
+}
+
+/// 指令只取名称，如 `script-src 'self'` -> `script-src`
+fn directive_name(directive: &str) -> &str {
+    directive.split_whitespace().next().unwrap_or("")
+}
+
+fn csp_report(value: &Value) -> Option<Classification> {
+    let report = value.get("csp-report").unwrap_or(value);
+    let field = |keys: &[&str]| keys.iter().find_map(|key| report.get(*key).and_then(Value::as_str)).unwrap_or("");
+    let directive = field(&["effective-directive", "effectiveDirective", "violated-directive", "violatedDirective"]);
+    if directive.is_empty() {
+        return None;
+    }
+    let blocked = field(&["blocked-uri", "blockedURI", "blockedURL"]);
+    Some(Classification {
+        category: ErrorCategory::CspViolation,
+        key: format!("{}|{}", directive_name(directive), blocked_key(blocked)),
+    })
+}
+
+/// 按首行识别特殊错误形态
+pub fn classify(header: &str) -> Option<Classification> {
+    let line = header.trim();
+    let line = line.strip_prefix("Uncaught ").unwrap_or(line);
+    let classification = |category, key: &str| Some(Classification { category, key: key.to_string() });
+
+    if line.eq_ignore_ascii_case("Script error.") || line.eq_ignore_ascii_case("Script error") {
+        return classification(ErrorCategory::CrossOriginScript, "");
+    }
+    if line.contains("Loading CSS chunk") && line.contains("failed") {
+        return classification(ErrorCategory::CssChunkLoad, first_host(line));
+    }
+    if line.starts_with("ChunkLoadError")
+        || (line.contains("Loading chunk") && line.contains("failed"))
+        || line.contains("Failed to fetch dynamically imported module")
+        || line.contains("error loading dynamically imported module")
+    {
+        return classification(ErrorCategory::ChunkLoad, first_host(line));
+    }
+
+    // CSP违规报告（`report-uri` 的JSON或 `securitypolicyviolation` 事件）
+    if line.starts_with('{') {
+        return serde_json::from_str::<Value>(line).ok().as_ref().and_then(csp_report);
+    }
+    // 控制台文本：Refused to load the script 'URL' because it violates the following Content Security Policy directive: "script-src 'self'"
+    if let Some((before, after)) = line.split_once("Content Security Policy directive") {
+        let directive = after.split('"').nth(1).unwrap_or("");
+        let blocked = before.split('\'').nth(1).filter(|_| before.starts_with("Refused to load")).unwrap_or("inline");
+        return classification(ErrorCategory::CspViolation, &format!("{}|{}", directive_name(directive), blocked_key(blocked)));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(header: &str) -> Option<(ErrorCategory, String)> {
+        classify(header).map(|classification| (classification.category, classification.key))
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(category("Script error."), Some((ErrorCategory::CrossOriginScript, String::new())));
+        assert_eq!(category("Uncaught Script error"), Some((ErrorCategory::CrossOriginScript, String::new())));
+        assert_eq!(
+            category("ChunkLoadError: Loading chunk 842 failed. (error: https://cdn.test/static/js/842.3f2a.js)"),
+            Some((ErrorCategory::ChunkLoad, "cdn.test".to_string()))
+        );
+        assert_eq!(
+            category("TypeError: Failed to fetch dynamically imported module: https://app.test/assets/Page-B7x9.js"),
+            Some((ErrorCategory::ChunkLoad, "app.test".to_string()))
+        );
+        assert_eq!(category("Error: Loading CSS chunk 42 failed. (/static/css/42.css)"), Some((ErrorCategory::CssChunkLoad, String::new())));
+
+        let report = r#"{"csp-report": {"document-uri": "https://app.test/", "violated-directive": "script-src-elem", "blocked-uri": "https://ads.test/x.js?id=1"}}"#;
+        assert_eq!(category(report), Some((ErrorCategory::CspViolation, "script-src-elem|ads.test".to_string())));
+        let event = r#"{"effectiveDirective": "img-src", "blockedURI": "data"}"#;
+        assert_eq!(category(event), Some((ErrorCategory::CspViolation, "img-src|data".to_string())));
+        let console = r#"Refused to load the script 'https://evil.test/a.js' because it violates the following Content Security Policy directive: "script-src 'self'"."#;
+        assert_eq!(category(console), Some((ErrorCategory::CspViolation, "script-src|evil.test".to_string())));
+        let inline = r#"Refused to execute inline script because it violates the following Content Security Policy directive: "script-src 'self'"."#;
+        assert_eq!(category(inline), Some((ErrorCategory::CspViolation, "script-src|inline".to_string())));
+
+        assert_eq!(category("TypeError: x is undefined"), None);
+        assert_eq!(category(r#"{"message": "boom"}"#), None);
+    }
+}
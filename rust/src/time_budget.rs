@@ -0,0 +1,203 @@
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use crate::exported_frame::ExportedStackFrame;
+use crate::parser::{ErrorParser, StackFrame};
+use crate::simd::LineIndex;
+use crate::sourcemap_registry;
+use crate::utils::now_micros;
+use crate::wasm_symbols;
+
+// 同时保留的未完成任务上限，超出时丢弃最早的
+const MAX_PENDING: usize = 32;
+
+/// 处理截止时间，`max_micros` 为0表示不限时
+pub struct Deadline {
+    end: Option<f64>,
+}
+
+impl Deadline {
+    pub fn new(max_micros: u32) -> Self {
+        Deadline { end: (max_micros > 0).then(|| now_micros() + max_micros as f64) }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.end.is_some_and(|end| now_micros() >= end)
+    }
+}
+
+/// 限时处理的结果，超时时带继续处理的句柄
+#[derive(Serialize)]
+pub struct BudgetedFrames {
+    pub frames: Vec<ExportedStackFrame>,
+    pub budget_exceeded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation: Option<u32>,
+}
+
+// 超时未处理完的输入：原始栈按行继续，已处理格式的输入（JSON帧、`file:line:col|func`）按帧继续
+enum Remaining {
+    Lines { stack: String, newlines: Vec<usize>, next_line: usize },
+    Frames(std::vec::IntoIter<StackFrame>),
+}
+
+// 超时未处理完的任务，保留解析器与行索引，继续时不再重新编译正则、扫描全文
+struct Pending {
+    parser: ErrorParser,
+    remaining: Remaining,
+    symbolicate: bool,
+}
+
+#[derive(Default)]
+struct PendingStore {
+    next_handle: u32,
+    pending: BTreeMap<u32, Pending>,
+}
+
+thread_local! {
+    static PENDING: RefCell<PendingStore> = RefCell::new(PendingStore::default());
+}
+
+/// 还原单帧：wasm帧使用符号表，JS帧使用已注册的source map
+fn symbolicate_frame(frame: StackFrame) -> StackFrame {
+    if frame.is_wasm() {
+        return wasm_symbols::symbolize(&frame);
+    }
+    sourcemap_registry::symbolicate(&frame)
+}
+
+/// 在截止时间前处理已解析的帧，每次至少处理一帧
+fn process_frames(frames: &mut std::vec::IntoIter<StackFrame>, symbolicate: bool, deadline: &Deadline) -> Vec<StackFrame> {
+    let mut processed = Vec::new();
+    for frame in frames.by_ref() {
+        processed.push(if symbolicate { symbolicate_frame(frame) } else { frame });
+        if deadline.expired() {
+            break;
+        }
+    }
+    processed
+}
+
+fn run(mut pending: Pending, deadline: &Deadline) -> BudgetedFrames {
+    let symbolicate = pending.symbolicate;
+    let (frames, finished) = match &mut pending.remaining {
+        Remaining::Lines { stack, newlines, next_line } => {
+            let index = LineIndex::from_newlines(stack, std::mem::take(newlines));
+            let (frames, next) = if symbolicate {
+                pending.parser.parse_frames_until(&index, *next_line, deadline, symbolicate_frame)
+            } else {
+                pending.parser.parse_frames_until(&index, *next_line, deadline, |frame| frame)
+            };
+            *newlines = index.into_newlines();
+            if let Some(next) = next {
+                *next_line = next;
+            }
+            (frames, next.is_none())
+        }
+        Remaining::Frames(frames) => {
+            let processed = process_frames(frames, symbolicate, deadline);
+            (processed, frames.len() == 0)
+        }
+    };
+
+    let continuation = (!finished).then(|| {
+        PENDING.with(|store| {
+            let mut store = store.borrow_mut();
+            if store.pending.len() >= MAX_PENDING {
+                store.pending.pop_first();
+            }
+            store.next_handle = store.next_handle.wrapping_add(1);
+            let handle = store.next_handle;
+            store.pending.insert(handle, pending);
+            handle
+        })
+    });
+    BudgetedFrames {
+        frames: frames.into_iter().map(ExportedStackFrame::from).collect(),
+        budget_exceeded: continuation.is_some(),
+        continuation,
+    }
+}
+
+fn start(stack: &str, symbolicate: bool) -> Pending {
+    let remaining = match ErrorParser::reparse_processed(stack) {
+        Some(frames) => Remaining::Frames(frames.unwrap_or_default().into_iter()),
+        None => Remaining::Lines { stack: stack.to_string(), newlines: LineIndex::new(stack).into_newlines(), next_line: 0 },
+    };
+    Pending { parser: ErrorParser::new(), remaining, symbolicate }
+}
+
+/// 在 `max_micros` 微秒内解析（并按需还原）错误栈，超时时返回已处理的帧与继续句柄。
+/// 截止时间从调用开始计算，解析器与行索引的准备时间也计入预算
+pub fn parse(stack: &str, symbolicate: bool, max_micros: u32) -> BudgetedFrames {
+    let deadline = Deadline::new(max_micros);
+    run(start(stack, symbolicate), &deadline)
+}
+
+/// 用句柄继续处理剩余的部分，句柄只能使用一次，未知句柄返回None
+pub fn resume(handle: u32, max_micros: u32) -> Option<BudgetedFrames> {
+    let pending = PENDING.with(|store| store.borrow_mut().pending.remove(&handle))?;
+    Some(run(pending, &Deadline::new(max_micros)))
+}
+
+/// 放弃未完成的任务
+pub fn cancel(handle: u32) -> bool {
+    PENDING.with(|store| store.borrow_mut().pending.remove(&handle).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STACK: &str = "Error: x\n at a (/src/a.js:1:1)\n at b (/src/b.js:2:2)\n    at async c (/src/c.js:3:3)\n at d (/src/d.js:4:4)";
+
+    #[test]
+    fn test_budget_exceeded_and_resume() {
+        let complete = parse(STACK, false, 0);
+        assert_eq!((complete.frames.len(), complete.budget_exceeded, complete.continuation), (4, false, None));
+
+        // 截止时间已过：每次只处理一行，用句柄继续直到完成
+        let expired = Deadline { end: Some(0.0) };
+        let mut result = run(start(STACK, false), &expired);
+        let mut names: Vec<String> = Vec::new();
+        while let Some(handle) = result.continuation {
+            assert!(result.budget_exceeded);
+            names.extend(result.frames.iter().map(|frame| frame.function_name.clone()));
+            let pending = PENDING.with(|store| store.borrow_mut().pending.remove(&handle)).unwrap();
+            result = run(pending, &expired);
+        }
+        names.extend(result.frames.iter().map(|frame| frame.function_name.clone()));
+        assert_eq!(names, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_continuation_handle() {
+        let partial = run(start(STACK, false), &Deadline { end: Some(0.0) });
+        let handle = partial.continuation.unwrap();
+        assert!(partial.frames.is_empty());
+
+        let resumed = resume(handle, 0).unwrap();
+        assert_eq!((resumed.frames.len(), resumed.continuation), (4, None));
+        assert!(resume(handle, 0).is_none());
+
+        let handle = run(start(STACK, false), &Deadline { end: Some(0.0) }).continuation.unwrap();
+        assert!(cancel(handle));
+        assert!(!cancel(handle));
+    }
+
+    #[test]
+    fn test_processed_input_respects_deadline() {
+        // 已处理格式的输入同样按截止时间分段，每次至少处理一帧
+        let processed = "/src/a.js:1:1|a\n/src/b.js:2:2|b\n/src/c.js:3:3|c";
+        let expired = Deadline { end: Some(0.0) };
+        let first = run(start(processed, false), &expired);
+        assert_eq!(first.frames.len(), 1);
+        let handle = first.continuation.unwrap();
+
+        let pending = PENDING.with(|store| store.borrow_mut().pending.remove(&handle)).unwrap();
+        let second = run(pending, &expired);
+        assert_eq!(second.frames[0].function_name, "b");
+        let rest = resume(second.continuation.unwrap(), 0).unwrap();
+        assert_eq!((rest.frames.len(), rest.continuation), (1, None));
+    }
+}
@@ -0,0 +1,187 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use crate::fingerprint;
+use crate::hash;
+use crate::parser::{line_column_triples, ErrorParser, StackFrame};
+
+// 同时保留的分词结果上限，超出时丢弃最早的
+const MAX_TOKENIZED: usize = 32;
+
+/// 整个输入只扫描一遍得到的中间结果：行起始偏移与数字，栈帧在首次使用时按行偏移解析并缓存，
+/// 需要栈帧、数字与指纹的调用方不必让每个接口各自重新扫描
+pub struct Tokenized {
+    text: String,
+    line_starts: Vec<u32>,
+    numbers: Vec<u32>,
+    frames: Option<Vec<StackFrame>>,
+}
+
+impl Tokenized {
+    pub fn new(text: String) -> Self {
+        let mut line_starts = if text.is_empty() { Vec::new() } else { vec![0] };
+        let mut numbers = Vec::new();
+        // 当前数字串的值，超出u32范围时为None（与 `parse_numbers` 一样跳过）
+        let mut number: Option<Option<u32>> = None;
+        for (i, &byte) in text.as_bytes().iter().enumerate() {
+            if byte.is_ascii_digit() {
+                let digit = u32::from(byte - b'0');
+                let value = number.unwrap_or(Some(0));
+                number = Some(value.and_then(|value| value.checked_mul(10)).and_then(|value| value.checked_add(digit)));
+                continue;
+            }
+            if let Some(value) = number.take() {
+                numbers.extend(value);
+            }
+            if byte == b'\n' && i + 1 < text.len() {
+                line_starts.push(i as u32 + 1);
+            }
+        }
+        if let Some(value) = number {
+            numbers.extend(value);
+        }
+        Tokenized { text, line_starts, numbers, frames: None }
+    }
+
+    /// 行数
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// 输入中的所有十进制数字，与 `parse_numbers` 一致
+    pub fn numbers(&self) -> &[u32] {
+        &self.numbers
+    }
+
+    /// 栈帧，首次调用时解析
+    pub fn frames(&mut self) -> &[StackFrame] {
+        let (text, line_starts) = (&self.text, &self.line_starts);
+        self.frames.get_or_insert_with(|| ErrorParser::new().parse_pretokenized_bytes(text, line_starts, &[]))
+    }
+
+    /// 有位置的帧按 `[行, 列, 帧下标, ...]` 返回，与 `parse_line_column_simd` 一致
+    pub fn line_column(&mut self) -> Vec<u32> {
+        line_column_triples(self.frames())
+    }
+
+    /// 使用全局指纹算法计算指纹，与 `fingerprint` 一致
+    pub fn fingerprint(&mut self) -> String {
+        self.frames();
+        let header_end = self.line_starts.get(1).map_or(self.text.len(), |&start| start as usize);
+        let header = self.text[..header_end].trim_end_matches(['\n', '\r']);
+        let frames = self.frames.as_deref().unwrap_or_default();
+        fingerprint::fingerprint_parsed(&self.text, header, frames, hash::fingerprint_algorithm())
+    }
+}
+
+#[derive(Default)]
+struct TokenizedStore {
+    next_handle: u32,
+    entries: BTreeMap<u32, Tokenized>,
+    // 按创建顺序排列的句柄，句柄回绕后最小的不一定最早
+    order: VecDeque<u32>,
+}
+
+impl TokenizedStore {
+    /// 下一个未被占用的非零句柄，回绕时跳过仍在使用的句柄
+    fn allocate(&mut self) -> u32 {
+        loop {
+            self.next_handle = self.next_handle.wrapping_add(1);
+            if self.next_handle != 0 && !self.entries.contains_key(&self.next_handle) {
+                return self.next_handle;
+            }
+        }
+    }
+
+    fn insert(&mut self, tokenized: Tokenized) -> u32 {
+        if self.entries.len() >= MAX_TOKENIZED {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        let handle = self.allocate();
+        self.entries.insert(handle, tokenized);
+        self.order.push_back(handle);
+        handle
+    }
+
+    fn remove(&mut self, handle: u32) -> bool {
+        self.order.retain(|&entry| entry != handle);
+        self.entries.remove(&handle).is_some()
+    }
+}
+
+thread_local! {
+    static STORE: RefCell<TokenizedStore> = RefCell::new(TokenizedStore::default());
+}
+
+/// 分词并保存，返回之后传给其他接口的句柄
+pub fn tokenize(stack: &str) -> u32 {
+    let tokenized = Tokenized::new(stack.to_string());
+    STORE.with(|store| store.borrow_mut().insert(tokenized))
+}
+
+/// 对句柄对应的分词结果执行 `f`，未知或已释放的句柄返回None
+pub fn with<R>(handle: u32, f: impl FnOnce(&mut Tokenized) -> R) -> Option<R> {
+    STORE.with(|store| store.borrow_mut().entries.get_mut(&handle).map(f))
+}
+
+/// 释放分词结果
+pub fn release(handle: u32) -> bool {
+    STORE.with(|store| store.borrow_mut().remove(handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STACK: &str = "TypeError: x is undefined\n at render (https://cdn.test/app.js:10:15)\r\n at update (https://cdn.test/app.js:40:2)\n";
+
+    #[test]
+    fn test_matches_separate_scans() {
+        let parser = ErrorParser::new();
+        let mut tokenized = Tokenized::new(STACK.to_string());
+        assert_eq!(tokenized.line_count(), 3);
+        assert_eq!(tokenized.numbers(), parser.parse_numbers(STACK).as_slice());
+        assert_eq!(tokenized.line_column(), parser.parse_line_column_indexed(STACK));
+        assert_eq!(tokenized.fingerprint(), fingerprint::fingerprint(STACK));
+        assert_eq!(tokenized.frames().len(), parser.parse_frames(STACK).len());
+
+        // 超出u32范围的数字串被跳过
+        assert_eq!(Tokenized::new("a 99999999999 b 7".to_string()).numbers(), &[7]);
+        assert_eq!(Tokenized::new(String::new()).line_count(), 0);
+    }
+
+    #[test]
+    fn test_handles() {
+        let handle = tokenize(STACK);
+        assert_eq!(with(handle, |tokenized| tokenized.frames().len()), Some(2));
+        assert!(release(handle));
+        assert!(!release(handle));
+        assert!(with(handle, |tokenized| tokenized.line_count()).is_none());
+
+        let first = tokenize("Error: a");
+        for _ in 0..MAX_TOKENIZED {
+            tokenize("Error: b");
+        }
+        assert!(with(first, |_| ()).is_none());
+    }
+
+    #[test]
+    fn test_wrapping_handles_skip_live_entries() {
+        let mut store = TokenizedStore { next_handle: u32::MAX - 1, ..TokenizedStore::default() };
+        let live = store.insert(Tokenized::new("Error: a".to_string()));
+        assert_eq!(live, u32::MAX);
+        store.next_handle = live - 1;
+        // 回绕后跳过0与仍在使用的句柄
+        assert_eq!(store.insert(Tokenized::new("Error: b".to_string())), 1);
+        store.next_handle = live - 1;
+        assert_eq!(store.insert(Tokenized::new("Error: c".to_string())), 2);
+        assert_eq!(store.entries[&live].text, "Error: a");
+        // 按创建顺序淘汰
+        for _ in 0..MAX_TOKENIZED - 3 {
+            store.insert(Tokenized::new("Error: d".to_string()));
+        }
+        store.insert(Tokenized::new("Error: e".to_string()));
+        assert!(!store.entries.contains_key(&live) && store.entries.contains_key(&1));
+    }
+}
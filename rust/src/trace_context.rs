@@ -0,0 +1,203 @@
+use serde::{Serialize, Deserialize};
+use crate::uuid::random_bytes;
+
+// tracestate最多32个条目
+const MAX_TRACESTATE_ENTRIES: usize = 32;
+const FLAG_SAMPLED: u8 = 0x01;
+
+/// W3C `traceparent` 头：`00-<trace-id>-<parent-id>-<flags>`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TraceParent {
+    pub version: u8,
+    /// 32位十六进制
+    pub trace_id: String,
+    /// 16位十六进制，即当前span的ID
+    pub span_id: String,
+    pub flags: u8,
+}
+
+fn is_lower_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len / 2];
+    // 全零ID无效，概率极低但仍需排除
+    while bytes.iter().all(|b| *b == 0) {
+        random_bytes(&mut bytes);
+    }
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl TraceParent {
+    /// 按规范解析，版本00必须正好四段，更高版本允许后续扩展字段
+    pub fn parse(header: &str) -> Result<TraceParent, String> {
+        let parts: Vec<&str> = header.trim().split('-').collect();
+        if parts.len() < 4 {
+            return Err(format!("traceparent格式错误: {}", header));
+        }
+        let (version, trace_id, span_id, flags) = (parts[0], parts[1], parts[2], parts[3]);
+
+        if !is_lower_hex(version, 2) || version == "ff" {
+            return Err(format!("不支持的traceparent版本: {}", version));
+        }
+        if version == "00" && parts.len() != 4 {
+            return Err(format!("traceparent格式错误: {}", header));
+        }
+        if !is_lower_hex(trace_id, 32) || trace_id.bytes().all(|b| b == b'0') {
+            return Err(format!("无效的trace-id: {}", trace_id));
+        }
+        if !is_lower_hex(span_id, 16) || span_id.bytes().all(|b| b == b'0') {
+            return Err(format!("无效的parent-id: {}", span_id));
+        }
+        if !is_lower_hex(flags, 2) {
+            return Err(format!("无效的trace-flags: {}", flags));
+        }
+
+        Ok(TraceParent {
+            version: u8::from_str_radix(version, 16).unwrap_or(0),
+            trace_id: trace_id.to_string(),
+            span_id: span_id.to_string(),
+            flags: u8::from_str_radix(flags, 16).unwrap_or(0),
+        })
+    }
+
+    /// 生成新的trace
+    pub fn generate(sampled: bool) -> TraceParent {
+        TraceParent {
+            version: 0,
+            trace_id: random_hex(32),
+            span_id: random_hex(16),
+            flags: if sampled { FLAG_SAMPLED } else { 0 },
+        }
+    }
+
+    /// 同一trace下的子span，保留采样标志
+    pub fn child(&self) -> TraceParent {
+        TraceParent {
+            version: 0,
+            trace_id: self.trace_id.clone(),
+            span_id: random_hex(16),
+            flags: self.flags,
+        }
+    }
+
+    pub fn sampled(&self) -> bool {
+        self.flags & FLAG_SAMPLED != 0
+    }
+
+    /// 输出版本00的头部值
+    pub fn to_header(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.flags)
+    }
+}
+
+/// W3C `tracestate` 头：逗号分隔的 `key=value`，最近更新的条目在最前
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TraceState {
+    pub entries: Vec<(String, String)>,
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= 256
+        && key.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'_' | b'-' | b'*' | b'/' | b'@'))
+}
+
+fn is_valid_value(value: &str) -> bool {
+    !value.is_empty() && value.len() <= 256
+        && value.bytes().all(|b| (0x20..=0x7e).contains(&b) && b != b',' && b != b'=')
+        && !value.ends_with(' ')
+}
+
+impl TraceState {
+    /// 解析tracestate，跳过不合法的条目与重复的键，超过32个条目的部分丢弃
+    pub fn parse(header: &str) -> TraceState {
+        let mut state = TraceState::default();
+        for member in header.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+            let Some((key, value)) = member.split_once('=') else { continue };
+            if !is_valid_key(key) || !is_valid_value(value) || state.get(key).is_some() {
+                continue;
+            }
+            if state.entries.len() == MAX_TRACESTATE_ENTRIES {
+                break;
+            }
+            state.entries.push((key.to_string(), value.to_string()));
+        }
+        state
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// 设置条目并移到最前，超出上限时丢弃最后一个
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if !is_valid_key(key) || !is_valid_value(value) {
+            return Err(format!("无效的tracestate条目: {}={}", key, value));
+        }
+        self.entries.retain(|(k, _)| k != key);
+        self.entries.insert(0, (key.to_string(), value.to_string()));
+        self.entries.truncate(MAX_TRACESTATE_ENTRIES);
+        Ok(())
+    }
+
+    pub fn to_header(&self) -> String {
+        self.entries.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_traceparent_roundtrip() {
+        let parent = TraceParent::parse(HEADER).unwrap();
+        assert_eq!(parent.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(parent.span_id, "00f067aa0ba902b7");
+        assert!(parent.sampled());
+        assert_eq!(parent.to_header(), HEADER);
+
+        let child = parent.child();
+        assert_eq!(child.trace_id, parent.trace_id);
+        assert_ne!(child.span_id, parent.span_id);
+
+        let generated = TraceParent::generate(false);
+        assert!(!generated.sampled());
+        assert_eq!(TraceParent::parse(&generated.to_header()), Ok(generated));
+
+        // 更高版本允许追加字段
+        assert!(TraceParent::parse("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_traceparent() {
+        for header in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1",
+        ] {
+            assert!(TraceParent::parse(header).is_err(), "{}", header);
+        }
+    }
+
+    #[test]
+    fn test_tracestate() {
+        let mut state = TraceState::parse("congo=t61rcWkgMzE, rojo=00f067aa0ba902b7,BAD=x,rojo=dup,empty=");
+        assert_eq!(state.entries.len(), 2);
+        assert_eq!(state.get("rojo"), Some("00f067aa0ba902b7"));
+
+        state.set("rojo", "00f067aa0ba902b8").unwrap();
+        assert_eq!(state.to_header(), "rojo=00f067aa0ba902b8,congo=t61rcWkgMzE");
+        assert!(state.set("perf lite", "x").is_err());
+
+        let many: Vec<String> = (0..40).map(|i| format!("k{}=v", i)).collect();
+        assert_eq!(TraceState::parse(&many.join(",")).entries.len(), 32);
+    }
+}
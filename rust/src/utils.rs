@@ -1,5 +1,4 @@
 use wasm_bindgen::prelude::*;
-use std::fmt;
 
 #[wasm_bindgen]
 extern "C" {
@@ -13,15 +12,54 @@ extern "C" {
     fn error(s: &str);
 }
 
-/// 提取错误行号和列号的帮助函数
+/// 提取错误行号和列号的帮助函数，只有行号（旧版Safari、部分压缩工具）时列号为0
 pub fn extract_line_column(s: &str) -> Option<(u32, u32)> {
-    let parts: Vec<&str> = s.split(':').collect();
-    if parts.len() >= 2 {
-        let line = parts[parts.len() - 2].parse::<u32>().ok()?;
-        let col = parts[parts.len() - 1].parse::<u32>().ok()?;
-        return Some((line, col));
+    split_location_with(s, |part| part.parse::<u32>().ok()).map(|(_, line, col)| (line, col))
+}
+
+/// 拆分 `file:line:col` 或 `file:line`（列号为0），`parse` 决定行列号的解析方式
+pub fn split_location_with(s: &str, parse: impl Fn(&str) -> Option<u32>) -> Option<(&str, u32, u32)> {
+    let (rest, last) = s.rsplit_once(':')?;
+    let last = parse(last)?;
+    if let Some((file, line)) = rest.rsplit_once(':') {
+        if let Some(line) = parse(line) {
+            return Some((file, line, last));
+        }
+    }
+    match parse(rest) {
+        Some(line) => Some(("", line, last)),
+        None => Some((rest, last, 0)),
+    }
+}
+
+/// 宽松解析数字：折叠全角/阿拉伯-印度数字，去掉千分位分隔符
+///
+/// 用于本地化工具链输出的行列号，例如 `１２`、`1,024`、`1 024`。
+pub fn parse_number_lenient(s: &str) -> Option<u32> {
+    let mut value: u32 = 0;
+    let mut seen_digit = false;
+
+    for c in s.trim().chars() {
+        let digit = match c {
+            '0'..='9' => c as u32 - '0' as u32,
+            // 全角数字
+            '\u{FF10}'..='\u{FF19}' => c as u32 - 0xFF10,
+            // 阿拉伯-印度数字及扩展形式
+            '\u{0660}'..='\u{0669}' => c as u32 - 0x0660,
+            '\u{06F0}'..='\u{06F9}' => c as u32 - 0x06F0,
+            // 千分位分隔符：逗号、点、撇号、下划线、各类空格
+            ',' | '.' | '\'' | '_' | '\u{00A0}' | '\u{2009}' | '\u{202F}' | '\u{066C}' | '\u{FF0C}' | ' ' => continue,
+            _ => return None,
+        };
+        seen_digit = true;
+        value = value.checked_mul(10)?.checked_add(digit)?;
+    }
+
+    if seen_digit {
+        Some(value)
+    } else {
+        None
     }
-    None
 }
 
 /// 格式化错误栈信息
@@ -84,6 +122,7 @@ pub fn console_log(message: &str) {
 /**
  * 向JavaScript控制台输出警告信息
  */
+#[allow(dead_code)]
 pub fn console_warn(message: &str) {
     #[cfg(target_arch = "wasm32")]
     {
@@ -109,18 +148,108 @@ pub fn console_error(message: &str) {
     }
 }
 
+/**
+ * 当前时间戳（毫秒），非WASM环境下使用系统时钟
+ */
+pub fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/**
+ * 单调时钟（微秒），WASM环境下使用 `performance.now()`
+ */
+pub fn now_micros() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        performance_now() * 1000.0
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        thread_local! {
+            static ORIGIN: std::time::Instant = std::time::Instant::now();
+        }
+        ORIGIN.with(|origin| origin.elapsed().as_secs_f64() * 1_000_000.0)
+    }
+}
+
 /**
  * 生成一个带有时间戳的日志消息
  */
+#[allow(dead_code)]
 pub fn log_with_timestamp(message: &str) {
-    let timestamp = js_sys::Date::now();
+    let timestamp = now_ms();
     let timestamped_message = format!("[{}] {}", timestamp, message);
     console_log(&timestamped_message);
 }
 
+/**
+ * 还原Itanium C++ ABI的修饰名（`_ZN4node5AbortEv` -> `node::Abort`），只处理命名空间、
+ * 类名与构造/析构函数，参数列表省略；模板、替换引用等复杂形式返回None
+ */
+pub fn demangle(symbol: &str) -> Option<String> {
+    let mut rest = symbol.strip_prefix("_Z")?;
+    let nested = match rest.strip_prefix('N') {
+        Some(inner) => {
+            // 成员函数的cv限定符
+            rest = inner.trim_start_matches(['K', 'V', 'r']);
+            true
+        }
+        None => false,
+    };
+
+    let mut names: Vec<String> = Vec::new();
+    if let Some(inner) = rest.strip_prefix("St") {
+        names.push("std".to_string());
+        rest = inner;
+    }
+    loop {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        if digits > 0 {
+            let len: usize = rest[..digits].parse().ok()?;
+            let end = digits.checked_add(len)?;
+            let name = rest.get(digits..end)?;
+            names.push(name.to_string());
+            rest = &rest[end..];
+        } else if let Some(kind) = rest.strip_prefix(['C', 'D']).filter(|_| nested && !names.is_empty()) {
+            // 构造函数 C1/C2/C3，析构函数 D0/D1/D2
+            let class = names.last()?.clone();
+            names.push(if rest.starts_with('D') { format!("~{}", class) } else { class });
+            rest = kind.get(1..)?;
+        } else {
+            break;
+        }
+        if !nested {
+            break;
+        }
+    }
+
+    if names.is_empty() || (nested && !rest.starts_with('E')) {
+        return None;
+    }
+    Some(names.join("::"))
+}
+
 /**
  * 判断字符串是否包含有效的行列号信息
  */
+#[allow(dead_code)]
 pub fn has_line_column(s: &str) -> bool {
     extract_line_column(s).is_some()
 }
@@ -128,6 +257,7 @@ pub fn has_line_column(s: &str) -> bool {
 /**
  * 从JavaScript错误栈中提取文件名
  */
+#[allow(dead_code)]
 pub fn extract_file_name(stack_line: &str) -> Option<String> {
     // 检查常见的格式
     // 格式 1: at Function (file.js:line:column)
@@ -164,3 +294,43 @@ pub fn extract_file_name(stack_line: &str) -> Option<String> {
     
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number_lenient() {
+        assert_eq!(parse_number_lenient("42"), Some(42));
+        assert_eq!(parse_number_lenient("４２"), Some(42));
+        assert_eq!(parse_number_lenient("1,234,567"), Some(1234567));
+        assert_eq!(parse_number_lenient("1.234"), Some(1234));
+        assert_eq!(parse_number_lenient("12\u{202F}345"), Some(12345));
+        assert_eq!(parse_number_lenient("٣٤"), Some(34));
+        assert_eq!(parse_number_lenient(""), None);
+        assert_eq!(parse_number_lenient(",,"), None);
+        assert_eq!(parse_number_lenient("12a"), None);
+        assert_eq!(parse_number_lenient("99999999999"), None);
+    }
+
+    #[test]
+    fn test_extract_line_column() {
+        assert_eq!(extract_line_column("http://x/app.js:10:20"), Some((10, 20)));
+        assert_eq!(extract_line_column("http://x/app.js:10"), Some((10, 0)));
+        assert_eq!(extract_line_column("10:20"), Some((10, 20)));
+        assert_eq!(extract_line_column("app.js"), None);
+        assert_eq!(split_location_with("/src/a.js:3", |s| s.parse().ok()), Some(("/src/a.js", 3, 0)));
+    }
+
+    #[test]
+    fn test_demangle() {
+        assert_eq!(demangle("_ZN4node5AbortEv").as_deref(), Some("node::Abort"));
+        assert_eq!(demangle("_ZNK2v88internal7Isolate5ThrowEPNS0_6ObjectE").as_deref(), Some("v8::internal::Isolate::Throw"));
+        assert_eq!(demangle("_ZN4node11EnvironmentD2Ev").as_deref(), Some("node::Environment::~Environment"));
+        assert_eq!(demangle("_ZNSt6thread6_StateD0Ev").as_deref(), Some("std::thread::_State::~_State"));
+        assert_eq!(demangle("_Z5abortv").as_deref(), Some("abort"));
+        assert_eq!(demangle("node::Abort()"), None);
+        assert_eq!(demangle("_ZN4node"), None);
+        assert_eq!(demangle("_Z18446744073709551615x"), None);
+    }
+}
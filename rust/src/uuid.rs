@@ -0,0 +1,180 @@
+use std::cell::RefCell;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::hash::xxhash64;
+use crate::utils::now_ms;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    // 浏览器与Node 19+ 均提供 globalThis.crypto
+    #[wasm_bindgen(js_namespace = crypto, js_name = getRandomValues, catch)]
+    fn get_random_values(buf: &mut [u8]) -> Result<(), JsValue>;
+}
+
+/// 生成32字节随机种子：优先使用 crypto.getRandomValues，失败时用时钟与Math.random混合
+fn seed_bytes() -> [u8; 32] {
+    let mut seed = [0u8; 32];
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        if get_random_values(&mut seed).is_err() {
+            for chunk in seed.chunks_mut(4) {
+                let value = (js_sys::Math::random() * u32::MAX as f64) as u32;
+                chunk.copy_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+
+    // 非WASM环境没有crypto，混合系统时钟与栈地址
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        let address = &seed as *const _ as u64;
+        for (i, chunk) in seed.chunks_mut(8).enumerate() {
+            let mut input = [0u8; 16];
+            input[..8].copy_from_slice(&nanos.to_le_bytes());
+            input[8..].copy_from_slice(&address.to_le_bytes());
+            chunk.copy_from_slice(&xxhash64(&input, i as u64).to_le_bytes());
+        }
+    }
+
+    seed
+}
+
+/// xoshiro256** 伪随机数生成器，只在首次使用时从加密随机源取种子
+struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    fn from_seed(seed: [u8; 32]) -> Self {
+        let mut state = [0u64; 4];
+        for (i, word) in state.iter_mut().enumerate() {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&seed[i * 8..i * 8 + 8]);
+            *word = u64::from_le_bytes(bytes);
+        }
+        // 全零状态无法产生随机数
+        if state == [0; 4] {
+            state[0] = 0x9e3779b97f4a7c15;
+        }
+        Rng { state }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    fn fill(&mut self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(8) {
+            let value = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&value[..chunk.len()]);
+        }
+    }
+}
+
+thread_local! {
+    static RNG: RefCell<Option<Rng>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn random_bytes(bytes: &mut [u8]) {
+    RNG.with(|rng| {
+        rng.borrow_mut()
+            .get_or_insert_with(|| Rng::from_seed(seed_bytes()))
+            .fill(bytes);
+    });
+}
+
+/// [0, 1) 区间的随机数，用于退避抖动等非加密场景
+pub(crate) fn random_unit() -> f64 {
+    let mut bytes = [0u8; 8];
+    random_bytes(&mut bytes);
+    // 取高53位作为尾数
+    (u64::from_le_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// 重新从随机源取种子，或使用固定种子
+#[cfg(test)]
+fn reseed(seed: Option<[u8; 32]>) {
+    RNG.with(|rng| *rng.borrow_mut() = Some(Rng::from_seed(seed.unwrap_or_else(seed_bytes))));
+}
+
+/// 写入版本号与RFC 4122变体位
+fn set_version(bytes: &mut [u8; 16], version: u8) {
+    bytes[6] = (bytes[6] & 0x0f) | (version << 4);
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+}
+
+/// 随机UUID v4
+pub fn v4() -> String {
+    let mut bytes = [0u8; 16];
+    random_bytes(&mut bytes);
+    set_version(&mut bytes, 4);
+    format(&bytes)
+}
+
+/// 按时间排序的UUID v7：前48位为Unix毫秒时间戳
+pub fn v7_at(timestamp_ms: f64) -> String {
+    let mut bytes = [0u8; 16];
+    random_bytes(&mut bytes[6..]);
+    let timestamp = (timestamp_ms.max(0.0) as u64) & 0xffff_ffff_ffff;
+    bytes[..6].copy_from_slice(&timestamp.to_be_bytes()[2..]);
+    set_version(&mut bytes, 7);
+    format(&bytes)
+}
+
+/// 以当前时间生成UUID v7
+pub fn v7() -> String {
+    v7_at(now_ms())
+}
+
+/// 按 8-4-4-4-12 格式输出
+pub fn format(bytes: &[u8; 16]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v4_format() {
+        let id = v4();
+        assert_eq!(id.len(), 36);
+        assert_eq!(id.as_bytes()[14], b'4');
+        assert!(matches!(id.as_bytes()[19], b'8' | b'9' | b'a' | b'b'));
+        assert_ne!(v4(), id);
+    }
+
+    #[test]
+    fn test_v7_timestamp_prefix() {
+        let id = v7_at(1_700_000_000_000.0);
+        // 1700000000000 = 0x018bcfe56800
+        assert!(id.starts_with("018bcfe5-6800-7"));
+        assert!(v7_at(1.0) < v7_at(2.0));
+    }
+
+    #[test]
+    fn test_fixed_seed_is_deterministic() {
+        reseed(Some([7; 32]));
+        let first = v4();
+        reseed(Some([7; 32]));
+        assert_eq!(v4(), first);
+        reseed(None);
+    }
+}
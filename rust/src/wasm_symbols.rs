@@ -0,0 +1,147 @@
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::parser::StackFrame;
+
+/// 行号表中的一条记录：从 `offset` 开始的代码对应的源码位置（DWARF行表的精简导出）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WasmLineEntry {
+    pub offset: u32,
+    pub file: String,
+    pub line: u32,
+    #[serde(default)]
+    pub column: u32,
+}
+
+/// 宿主离线提取并注册的wasm符号表
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct WasmSymbolTable {
+    /// 名称段：函数索引 -> 函数名
+    pub functions: HashMap<u32, String>,
+    /// 按偏移递增排列的行号记录
+    pub lines: Vec<WasmLineEntry>,
+}
+
+impl WasmSymbolTable {
+    /// 解析符号表JSON：`{"functions": {...}, "lines": [...]}`，或简单的 `{"42": "name"}` 映射
+    pub fn parse(json: &str) -> Result<WasmSymbolTable, String> {
+        if let Ok(functions) = serde_json::from_str::<HashMap<u32, String>>(json) {
+            return Ok(WasmSymbolTable { functions, lines: Vec::new() });
+        }
+
+        let mut table: WasmSymbolTable = serde_json::from_str(json)
+            .map_err(|e| format!("wasm符号表解析失败: {}", e))?;
+        table.lines.sort_by_key(|entry| entry.offset);
+        Ok(table)
+    }
+
+    /// 字节偏移所在的行号记录
+    pub fn line_for_offset(&self, offset: u32) -> Option<&WasmLineEntry> {
+        let index = self.lines.partition_point(|entry| entry.offset <= offset);
+        self.lines[..index].last()
+    }
+
+    /// 用符号表还原wasm帧：没有真实名称时替换函数名，有行号表时还原源码位置
+    pub fn symbolize(&self, frame: &StackFrame) -> StackFrame {
+        let mut symbolized = frame.clone();
+        let name = frame.function_name();
+        let function_name = match frame.wasm_function().and_then(|index| self.functions.get(&index)) {
+            Some(resolved) if is_generic_name(&name) => resolved.clone(),
+            _ => name,
+        };
+
+        match frame.wasm_offset().and_then(|offset| self.line_for_offset(offset)) {
+            Some(entry) => symbolized.set_location(function_name, entry.file.clone(), entry.line, entry.column),
+            None => symbolized.set_location(function_name, frame.file_name(), frame.line_number(), frame.column_number()),
+        }
+        symbolized
+    }
+}
+
+/// 是否为引擎生成的占位函数名
+pub(crate) fn is_generic_name(name: &str) -> bool {
+    name.is_empty() || name == "<anonymous>" || name.contains("wasm-function[")
+}
+
+thread_local! {
+    // 模块URL -> 符号表，空URL的表用于未单独注册的模块
+    static TABLES: RefCell<HashMap<String, WasmSymbolTable>> = RefCell::new(HashMap::new());
+}
+
+/// 注册模块的符号表
+pub fn register(module_url: &str, table: WasmSymbolTable) {
+    TABLES.with(|tables| tables.borrow_mut().insert(module_url.to_string(), table));
+}
+
+/// 移除模块的符号表
+pub fn unregister(module_url: &str) -> bool {
+    TABLES.with(|tables| tables.borrow_mut().remove(module_url).is_some())
+}
+
+/// 清空全部符号表
+pub fn clear() {
+    TABLES.with(|tables| tables.borrow_mut().clear());
+}
+
+/// 按帧所在模块查找符号表并还原，非wasm帧或没有符号表时原样返回
+pub fn symbolize(frame: &StackFrame) -> StackFrame {
+    if !frame.is_wasm() {
+        return frame.clone();
+    }
+
+    TABLES.with(|tables| {
+        let tables = tables.borrow();
+        match tables.get(frame.file_name_str()).or_else(|| tables.get("")) {
+            Some(table) => table.symbolize(frame),
+            None => frame.clone(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wasm_frame(name: &str, index: u32, offset: u32) -> StackFrame {
+        let mut frame = StackFrame::new(name.to_string(), "https://cdn.test/app.wasm".to_string(), 0, 0);
+        frame.set_wasm_location(Some(index), Some(offset));
+        frame.set_address(Some(u64::from(offset)));
+        frame
+    }
+
+    #[test]
+    fn test_parse_table_forms() {
+        let table = WasmSymbolTable::parse(r#"{"3": "main"}"#).unwrap();
+        assert_eq!(table.functions.get(&3).map(String::as_str), Some("main"));
+
+        let table = WasmSymbolTable::parse(r#"{"functions": {"3": "main"}, "lines": [
+            {"offset": 200, "file": "src/lib.rs", "line": 20},
+            {"offset": 100, "file": "src/lib.rs", "line": 10, "column": 5}
+        ]}"#).unwrap();
+        assert_eq!(table.line_for_offset(150).map(|entry| entry.line), Some(10));
+        assert_eq!(table.line_for_offset(250).map(|entry| entry.line), Some(20));
+        assert!(table.line_for_offset(50).is_none());
+        assert!(WasmSymbolTable::parse("[1]").is_err());
+    }
+
+    #[test]
+    fn test_symbolize_registered_module() {
+        let table = WasmSymbolTable::parse(r#"{"functions": {"42": "app::render"},
+            "lines": [{"offset": 4096, "file": "src/render.rs", "line": 88, "column": 9}]}"#).unwrap();
+        register("https://cdn.test/app.wasm", table);
+
+        let symbolized = symbolize(&wasm_frame("wasm-function[42]", 42, 0x1a2b));
+        assert_eq!(symbolized.function_name(), "app::render");
+        assert_eq!(symbolized.file_name(), "src/render.rs");
+        assert_eq!((symbolized.line_number(), symbolized.column_number()), (88, 9));
+        assert_eq!(symbolized.wasm_offset(), Some(0x1a2b));
+
+        // 已有真实名称的帧只还原位置
+        assert_eq!(symbolize(&wasm_frame("$draw", 42, 0x1a2b)).function_name(), "$draw");
+
+        assert!(unregister("https://cdn.test/app.wasm"));
+        assert_eq!(symbolize(&wasm_frame("wasm-function[42]", 42, 0x1a2b)).function_name(), "wasm-function[42]");
+        clear();
+    }
+}
@@ -0,0 +1,193 @@
+use wasm_bindgen::prelude::*;
+use crate::fingerprint;
+use crate::parser::{line_column_triples, ErrorParser};
+use crate::parser_options;
+
+// 请求与响应共用的封装头：魔数 + 版本号 + 操作码 + 请求ID(u32)，数字均为小端
+const WORKER_MAGIC: &[u8; 3] = b"PLW";
+const WORKER_VERSION: u8 = 1;
+const WORKER_HEADER_LEN: usize = WORKER_MAGIC.len() + 1 + 1 + 4;
+
+// 响应头之后的状态字节
+const STATUS_OK: u8 = 0;
+const STATUS_ERROR: u8 = 1;
+
+/// worker请求的操作
+///
+/// 主线程用 `encode_request` 编码后把 `ArrayBuffer` 转移（transfer）给加载了WASM模块的worker，
+/// worker调用 `handle_request` 并把结果同样以转移方式传回，全程不经过JSON。请求体为
+/// `u32长度 + UTF-8错误栈`；响应为相同的封装头加状态字节（0成功、1失败），失败时响应体为错误消息，
+/// 成功时的响应体见各操作。字符串均编码为 `u32字节数 + UTF-8`
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkerOp {
+    /// 栈帧：`u32帧数`，每帧为 `函数名、文件名、u32行号、u32列号、u8来源`，行列号按全局约定导出
+    Parse = 1,
+    /// 指纹：一个字符串
+    Fingerprint = 2,
+    /// 输入中的所有数字：`u32个数 + u32数组`
+    Numbers = 3,
+    /// `[行, 列, 帧下标, ...]` 三元组：`u32个数 + u32数组`
+    LineColumn = 4,
+}
+
+impl WorkerOp {
+    fn from_code(code: u8) -> Option<WorkerOp> {
+        match code {
+            1 => Some(WorkerOp::Parse),
+            2 => Some(WorkerOp::Fingerprint),
+            3 => Some(WorkerOp::Numbers),
+            4 => Some(WorkerOp::LineColumn),
+            _ => None,
+        }
+    }
+}
+
+fn write_header(bytes: &mut Vec<u8>, op: u8, id: u32) {
+    bytes.extend_from_slice(WORKER_MAGIC);
+    bytes.push(WORKER_VERSION);
+    bytes.push(op);
+    bytes.extend_from_slice(&id.to_le_bytes());
+}
+
+fn write_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_str(bytes: &mut Vec<u8>, value: &str) {
+    write_u32(bytes, value.len() as u32);
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+fn write_u32s(bytes: &mut Vec<u8>, values: &[u32]) {
+    write_u32(bytes, values.len() as u32);
+    for value in values {
+        write_u32(bytes, *value);
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(bytes.get(at..at + 4)?);
+    Some(u32::from_le_bytes(value))
+}
+
+/// 编码请求
+pub fn encode_request(op: WorkerOp, id: u32, stack: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(WORKER_HEADER_LEN + 4 + stack.len());
+    write_header(&mut bytes, op as u8, id);
+    write_str(&mut bytes, stack);
+    bytes
+}
+
+/// 解码请求，返回操作码、请求ID与错误栈；封装头无效时返回Err，操作码未知时在响应中报错
+fn decode_request(bytes: &[u8]) -> Result<(u8, u32, &str), String> {
+    if bytes.len() < WORKER_HEADER_LEN || &bytes[..WORKER_MAGIC.len()] != WORKER_MAGIC {
+        return Err("不是PerfLite worker请求".to_string());
+    }
+    if bytes[WORKER_MAGIC.len()] != WORKER_VERSION {
+        return Err(format!("不支持的worker请求版本: {}", bytes[WORKER_MAGIC.len()]));
+    }
+    let op = bytes[WORKER_MAGIC.len() + 1];
+    let id = read_u32(bytes, WORKER_MAGIC.len() + 2).unwrap_or_default();
+    let len = read_u32(bytes, WORKER_HEADER_LEN).ok_or("worker请求缺少请求体")? as usize;
+    // 长度来自请求本身，32位目标上相加可能溢出
+    let end = (WORKER_HEADER_LEN + 4).checked_add(len).ok_or("worker请求体长度不符")?;
+    let body = bytes.get(WORKER_HEADER_LEN + 4..end).ok_or("worker请求体长度不符")?;
+    let stack = std::str::from_utf8(body).map_err(|e| format!("worker请求体不是UTF-8: {}", e))?;
+    Ok((op, id, stack))
+}
+
+/// 执行请求并编码响应；无法识别的请求返回操作码与请求ID为0的失败响应
+pub fn handle_request(bytes: &[u8]) -> Vec<u8> {
+    let (op, id, stack) = match decode_request(bytes) {
+        Ok(request) => request,
+        Err(e) => return error_response(0, 0, &e),
+    };
+    let Some(kind) = WorkerOp::from_code(op) else {
+        return error_response(op, id, &format!("未知的worker操作: {}", op));
+    };
+
+    let mut response = Vec::new();
+    write_header(&mut response, op, id);
+    response.push(STATUS_OK);
+    let parser = ErrorParser::new();
+    match kind {
+        WorkerOp::Parse => {
+            let frames = parser.parse_frames(stack);
+            let positions = parser_options::options();
+            write_u32(&mut response, frames.len() as u32);
+            for frame in &frames {
+                write_str(&mut response, frame.function_name_str());
+                write_str(&mut response, frame.file_name_str());
+                write_u32(&mut response, positions.export_line(frame.line_number()));
+                write_u32(&mut response, positions.export_column(frame.column_number()));
+                response.push(frame.origin() as u8);
+            }
+        }
+        WorkerOp::Fingerprint => write_str(&mut response, &fingerprint::fingerprint(stack)),
+        WorkerOp::Numbers => write_u32s(&mut response, &parser.parse_numbers(stack)),
+        WorkerOp::LineColumn => write_u32s(&mut response, &line_column_triples(&parser.parse_frames(stack))),
+    }
+    response
+}
+
+fn error_response(op: u8, id: u32, message: &str) -> Vec<u8> {
+    let mut response = Vec::with_capacity(WORKER_HEADER_LEN + 5 + message.len());
+    write_header(&mut response, op, id);
+    response.push(STATUS_ERROR);
+    write_str(&mut response, message);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STACK: &str = "TypeError: x\n at render (https://cdn.test/app.js:10:15)\n at Array.map (<anonymous>)";
+
+    /// 读取响应中的字符串，返回内容与下一个偏移
+    fn read_str(bytes: &[u8], at: usize) -> (&str, usize) {
+        let len = read_u32(bytes, at).unwrap() as usize;
+        (std::str::from_utf8(&bytes[at + 4..at + 4 + len]).unwrap(), at + 4 + len)
+    }
+
+    #[test]
+    fn test_handle_request() {
+        let response = handle_request(&encode_request(WorkerOp::Parse, 7, STACK));
+        assert_eq!(&response[..5], b"PLW\x01\x01");
+        assert_eq!((read_u32(&response, 5), response[WORKER_HEADER_LEN]), (Some(7), STATUS_OK));
+        let body = WORKER_HEADER_LEN + 1;
+        assert_eq!(read_u32(&response, body), Some(2));
+        let (function, at) = read_str(&response, body + 4);
+        let (file, at) = read_str(&response, at);
+        assert_eq!((function, file), ("render", "https://cdn.test/app.js"));
+        assert_eq!((read_u32(&response, at), read_u32(&response, at + 4)), (Some(10), Some(15)));
+        assert_eq!(response[at + 8], 0);
+
+        let response = handle_request(&encode_request(WorkerOp::Fingerprint, 8, STACK));
+        assert_eq!(read_str(&response, body).0, fingerprint::fingerprint(STACK));
+        let response = handle_request(&encode_request(WorkerOp::Numbers, 9, "a 1 b 22"));
+        assert_eq!(response[body..], [2, 0, 0, 0, 1, 0, 0, 0, 22, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_invalid_request() {
+        let response = handle_request(b"nope");
+        assert_eq!((response[WORKER_MAGIC.len() + 1], response[WORKER_HEADER_LEN]), (0, STATUS_ERROR));
+
+        let mut unknown = encode_request(WorkerOp::Parse, 3, STACK);
+        unknown[WORKER_MAGIC.len() + 1] = 99;
+        let response = handle_request(&unknown);
+        assert_eq!((read_u32(&response, 5), response[WORKER_HEADER_LEN]), (Some(3), STATUS_ERROR));
+
+        let mut truncated = encode_request(WorkerOp::Parse, 4, STACK);
+        truncated.truncate(truncated.len() - 1);
+        assert_eq!(handle_request(&truncated)[WORKER_HEADER_LEN], STATUS_ERROR);
+
+        // 超大的长度字段不会溢出
+        let mut oversized = encode_request(WorkerOp::Parse, 5, STACK);
+        oversized[WORKER_HEADER_LEN..WORKER_HEADER_LEN + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(decode_request(&oversized), Err("worker请求体长度不符".to_string()));
+    }
+}